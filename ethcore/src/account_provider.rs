@@ -17,12 +17,25 @@
 //! Account management.
 
 use std::fmt;
+use std::str::FromStr;
 use std::sync::RwLock;
 use std::collections::HashMap;
+use std::time::{Instant, Duration};
 use util::{Address as H160, H256, H520};
-use ethstore::{SecretStore, Error as SSError, SafeAccount, EthStore};
+use util::rlp::RlpStream;
+use transaction::Transaction;
+use ethstore::{SecretStore, Error as SSError, SafeAccount, EthStore, PresaleWallet};
 use ethstore::dir::{KeyDirectory};
-use ethstore::ethkey::{Address as SSAddress, Message as SSMessage, Secret as SSSecret, Random, Generator};
+use ethstore::ethkey::{Address as SSAddress, Message as SSMessage, Secret as SSSecret, Random, Brain, Generator, DerivationPath, ExtendedSecret, Mnemonic};
+
+/// Longest duration an account can be unlocked for (one year).
+pub const MAX_UNLOCK_DURATION_SECS: u64 = 365 * 24 * 60 * 60;
+
+fn unsigned_rlp(transaction: &Transaction) -> Vec<u8> {
+	let mut stream = RlpStream::new();
+	transaction.rlp_append_unsigned_transaction(&mut stream);
+	stream.out()
+}
 
 /// Type of unlock.
 #[derive(Clone)]
@@ -32,6 +45,8 @@ enum Unlock {
 	/// Account unlocked permantently can always sign message.
 	/// Use with caution.
 	Perm,
+	/// Account unlocked with a timeout; it's locked once the given instant passes.
+	Timed(Instant),
 }
 
 /// Data associated with account.
@@ -41,6 +56,17 @@ struct AccountData {
 	password: String,
 }
 
+/// Account metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountMeta {
+	/// The name of the account.
+	pub name: String,
+	/// Free-form metadata of the account (usually JSON).
+	pub meta: String,
+	/// UNIX time the account was created at, if known.
+	pub timestamp: Option<u64>,
+}
+
 /// `AccountProvider` errors.
 #[derive(Debug)]
 pub enum Error {
@@ -48,6 +74,8 @@ pub enum Error {
 	NotUnlocked,
 	/// Returned when signing fails.
 	SStore(SSError),
+	/// Returned when requested unlock duration exceeds `MAX_UNLOCK_DURATION_SECS`.
+	InvalidUnlockDuration,
 }
 
 impl fmt::Display for Error {
@@ -55,6 +83,7 @@ impl fmt::Display for Error {
 		match *self {
 			Error::NotUnlocked => write!(f, "Account is locked"),
 			Error::SStore(ref e) => write!(f, "{}", e),
+			Error::InvalidUnlockDuration => write!(f, "Unlock duration exceeds {} seconds", MAX_UNLOCK_DURATION_SECS),
 		}
 	}
 }
@@ -162,11 +191,97 @@ impl AccountProvider {
 		Ok(Address::from(address).into())
 	}
 
+	/// Creates new account from brain wallet phrase.
+	pub fn new_account_from_phrase(&self, phrase: String, password: &str) -> Result<H160, Error> {
+		let secret = try!(Brain::new(phrase).generate().map_err(SSError::from)).secret().clone();
+		let address = try!(self.sstore.insert_account(secret, password));
+		Ok(Address::from(address).into())
+	}
+
+	/// Imports presale wallet (JSON) and protects the account with the wallet password.
+	pub fn import_presale(&self, presale_json: &[u8], password: &str) -> Result<H160, Error> {
+		let wallet = try!(PresaleWallet::load(presale_json));
+		let keypair = try!(wallet.decrypt(password));
+		let address = try!(self.sstore.insert_account(keypair.secret().clone(), password));
+		Ok(Address::from(address).into())
+	}
+
+	/// Creates new account from BIP39 mnemonic `phrase` (protected with BIP39 `passphrase`) at given BIP32 path.
+	/// The account can be recovered from the phrase by any BIP39 wallet.
+	pub fn new_account_from_mnemonic(&self, phrase: &str, passphrase: &str, path: &DerivationPath, password: &str) -> Result<H160, Error> {
+		let mnemonic = try!(Mnemonic::from_str(phrase).map_err(SSError::from));
+		let master = try!(ExtendedSecret::from_seed(&mnemonic.seed(passphrase)).map_err(SSError::from));
+		let secret = try!(master.derive_path(path).map_err(SSError::from)).secret().clone();
+		let address = try!(self.sstore.insert_account(secret, password));
+		Ok(Address::from(address).into())
+	}
+
+	/// Derives new account from an unlocked seed account at given BIP32 path and stores it.
+	/// The secret of the seed account is used as the BIP32 seed. There is no mnemonic to recover
+	/// derived accounts from, use `new_account_from_mnemonic` for BIP39 wallets.
+	/// The new account is protected with the same password as the seed account.
+	pub fn derive_account_from_secret<A>(&self, account: A, path: &DerivationPath) -> Result<H160, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		let password = try!(self.unlocked_password(&account));
+		let address = try!(self.sstore.derive_account_from_secret(&account, &password, path));
+		Ok(Address::from(address).into())
+	}
+
+	/// Changes password of given account. Unlocked account stays unlocked.
+	pub fn change_password<A>(&self, account: A, password: String, new_password: String) -> Result<(), Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		try!(self.sstore.change_password(&account, &password, &new_password));
+		if let Some(data) = self.unlocked.write().unwrap().get_mut(&account) {
+			data.password = new_password;
+		}
+		Ok(())
+	}
+
+	/// Returns encrypted JSON key file of given account,
+	/// optionally re-encrypted with new password or number of KDF iterations.
+	pub fn export_account<A>(&self, account: A, password: String, new_password: Option<String>, iterations: Option<u32>) -> Result<String, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		let new_password = new_password.as_ref().map(String::as_str);
+		Ok(try!(self.sstore.export_account(&account, &password, new_password, iterations)))
+	}
+
 	/// Returns addresses of all accounts.
 	pub fn accounts(&self) -> Vec<H160> {
 		self.sstore.accounts().into_iter().map(|a| H160(a.into())).collect()
 	}
 
+	/// Returns metadata of all accounts.
+	pub fn accounts_info(&self) -> Result<HashMap<H160, AccountMeta>, Error> {
+		self.sstore.accounts()
+			.into_iter()
+			.map(|a| self.account_meta(a.clone()).map(|meta| (H160(a.into()), meta)))
+			.collect()
+	}
+
+	/// Returns metadata of given account.
+	pub fn account_meta<A>(&self, account: A) -> Result<AccountMeta, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		Ok(AccountMeta {
+			name: try!(self.sstore.name(&account)),
+			meta: try!(self.sstore.meta(&account)),
+			timestamp: try!(self.sstore.timestamp(&account)),
+		})
+	}
+
+	/// Sets name of given account.
+	pub fn set_account_name<A>(&self, account: A, name: String) -> Result<(), Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		try!(self.sstore.set_name(&account, name));
+		Ok(())
+	}
+
+	/// Sets metadata of given account.
+	pub fn set_account_meta<A>(&self, account: A, meta: String) -> Result<(), Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		try!(self.sstore.set_meta(&account, meta));
+		Ok(())
+	}
+
 	/// Helper method used for unlocking accounts.
 	fn unlock_account<A>(&self, account: A, password: String, unlock: Unlock) -> Result<(), Error> where Address: From<A> {
 		let a = Address::from(account);
@@ -205,22 +320,51 @@ impl AccountProvider {
 		self.unlock_account(account, password, Unlock::Temp)
 	}
 
-	/// Signs the message. Account must be unlocked.
-	pub fn sign<A, M>(&self, account: A, message: M) -> Result<H520, Error> where Address: From<A>, Message: From<M> {
+	/// Unlocks account for the given duration (at most `MAX_UNLOCK_DURATION_SECS`).
+	pub fn unlock_account_timed<A>(&self, account: A, password: String, duration: Duration) -> Result<(), Error> where Address: From<A> {
+		// `Instant` addition panics on overflow
+		if duration > Duration::from_secs(MAX_UNLOCK_DURATION_SECS) {
+			return Err(Error::InvalidUnlockDuration);
+		}
+		self.unlock_account(account, password, Unlock::Timed(Instant::now() + duration))
+	}
+
+	/// Locks account (no matter how it was unlocked). Returns `true` if the account was unlocked.
+	pub fn lock_account<A>(&self, account: A) -> bool where Address: From<A> {
 		let account = Address::from(account).into();
-		let message = Message::from(message).into();
+		self.unlocked.write().unwrap().remove(&account).is_some()
+	}
 
+	/// Returns password of an unlocked account.
+	/// Temporarily unlocked accounts are locked again.
+	fn unlocked_password(&self, account: &SSAddress) -> Result<String, Error> {
 		let data = {
 			let unlocked = self.unlocked.read().unwrap();
-			try!(unlocked.get(&account).ok_or(Error::NotUnlocked)).clone()
+			try!(unlocked.get(account).ok_or(Error::NotUnlocked)).clone()
 		};
 
-		if let Unlock::Temp = data.unlock {
-			let mut unlocked = self.unlocked.write().unwrap();
-			unlocked.remove(&account).expect("data exists: so key must exist: qed");
+		match data.unlock {
+			Unlock::Temp => {
+				let mut unlocked = self.unlocked.write().unwrap();
+				unlocked.remove(account).expect("data exists: so key must exist: qed");
+			},
+			Unlock::Timed(end) if Instant::now() > end => {
+				let mut unlocked = self.unlocked.write().unwrap();
+				unlocked.remove(account);
+				return Err(Error::NotUnlocked);
+			},
+			_ => {},
 		}
 
-		let signature = try!(self.sstore.sign(&account, &data.password, &message));
+		Ok(data.password)
+	}
+
+	/// Signs the message. Account must be unlocked.
+	pub fn sign<A, M>(&self, account: A, message: M) -> Result<H520, Error> where Address: From<A>, Message: From<M> {
+		let account = Address::from(account).into();
+		let message = Message::from(message).into();
+		let password = try!(self.unlocked_password(&account));
+		let signature = try!(self.sstore.sign(&account, &password, &message));
 		Ok(H520(signature.into()))
 	}
 
@@ -231,12 +375,109 @@ impl AccountProvider {
 		let signature = try!(self.sstore.sign(&account, &password, &message));
 		Ok(H520(signature.into()))
 	}
+
+	/// Signs the transaction. Account must be unlocked.
+	/// Remote signers get the whole unsigned transaction rather than just its hash.
+	pub fn sign_transaction<A>(&self, account: A, transaction: &Transaction) -> Result<H520, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		let password = try!(self.unlocked_password(&account));
+		let signature = try!(self.sstore.sign_transaction(&account, &password, &unsigned_rlp(transaction)));
+		Ok(H520(signature.into()))
+	}
+
+	/// Signs the transaction with given password.
+	pub fn sign_transaction_with_password<A>(&self, account: A, password: String, transaction: &Transaction) -> Result<H520, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		let signature = try!(self.sstore.sign_transaction(&account, &password, &unsigned_rlp(transaction)));
+		Ok(H520(signature.into()))
+	}
+
+	/// Decrypts an ECIES-encrypted message. Account must be unlocked.
+	pub fn decrypt<A>(&self, account: A, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		let password = try!(self.unlocked_password(&account));
+		Ok(try!(self.sstore.decrypt(&account, &password, shared_mac, message)))
+	}
+
+	/// Decrypts an ECIES-encrypted message using given password.
+	pub fn decrypt_with_password<A>(&self, account: A, password: String, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		Ok(try!(self.sstore.decrypt(&account, &password, shared_mac, message)))
+	}
+
+	/// Creates new vault and opens it.
+	pub fn create_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		Ok(try!(self.sstore.create_vault(name, password)))
+	}
+
+	/// Opens existing vault, making its accounts available.
+	pub fn open_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		Ok(try!(self.sstore.open_vault(name, password)))
+	}
+
+	/// Closes vault. Accounts stored in it are locked and hidden.
+	pub fn close_vault(&self, name: &str) -> Result<(), Error> {
+		try!(self.sstore.close_vault(name));
+		let accounts = self.sstore.accounts();
+		let mut unlocked = self.unlocked.write().unwrap();
+		let hidden: Vec<_> = unlocked.keys().filter(|a| !accounts.contains(a)).cloned().collect();
+		for address in hidden {
+			unlocked.remove(&address);
+		}
+		Ok(())
+	}
+
+	/// Returns names of all vaults.
+	pub fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		Ok(try!(self.sstore.list_vaults()))
+	}
+
+	/// Returns names of currently opened vaults.
+	pub fn list_opened_vaults(&self) -> Vec<String> {
+		self.sstore.list_opened_vaults()
+	}
+
+	/// Moves account to given opened vault or, if `None`, back to the main keys directory.
+	pub fn change_vault<A>(&self, account: A, vault: Option<&str>) -> Result<(), Error> where Address: From<A> {
+		let account = Address::from(account).into();
+		Ok(try!(self.sstore.change_account_vault(&account, vault)))
+	}
+
+	/// Returns metadata of opened vault.
+	pub fn vault_meta(&self, name: &str) -> Result<String, Error> {
+		Ok(try!(self.sstore.vault_meta(name)))
+	}
+
+	/// Sets metadata of opened vault.
+	pub fn set_vault_meta(&self, name: &str, meta: String) -> Result<(), Error> {
+		Ok(try!(self.sstore.set_vault_meta(name, meta)))
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::AccountProvider;
-	use ethstore::ethkey::{Generator, Random};
+	use std::str::FromStr;
+	use std::time::Duration;
+	use super::{AccountProvider, Error};
+	use transaction::Transaction;
+	use util::H160;
+	use ethstore::ethkey::{Generator, Random, ecies, DerivationPath};
+
+	#[test]
+	fn sign_transaction() {
+		// given
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		let t = Transaction::default();
+
+		// when
+		let signature = ap.sign_transaction_with_password(kp.address(), "test".into(), &t).unwrap();
+
+		// then
+		assert_eq!(signature, ap.sign_with_password(kp.address(), "test".into(), t.hash()).unwrap());
+		assert!(ap.sign_transaction(kp.address(), &t).is_err());
+	}
 
 	#[test]
 	fn unlock_account_temp() {
@@ -249,6 +490,141 @@ mod tests {
 		assert!(ap.sign(kp.address(), [0u8; 32]).is_err());
 	}
 
+	#[test]
+	fn set_account_name_and_meta() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.set_account_name(kp.address(), "Savings".into()).is_ok());
+		assert!(ap.set_account_meta(kp.address(), "{\"tags\":[]}".into()).is_ok());
+		let meta = ap.account_meta(kp.address()).unwrap();
+		assert_eq!(meta.name, "Savings");
+		assert_eq!(meta.meta, "{\"tags\":[]}");
+		assert!(meta.timestamp.is_some());
+		assert_eq!(ap.accounts_info().unwrap().len(), 1);
+	}
+
+	#[test]
+	fn unlock_account_timed() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.unlock_account_timed(kp.address(), "test1".into(), Duration::from_secs(60)).is_err());
+		assert!(ap.unlock_account_timed(kp.address(), "test".into(), Duration::from_millis(200)).is_ok());
+		assert!(ap.sign(kp.address(), [0u8; 32]).is_ok());
+		assert!(ap.sign(kp.address(), [0u8; 32]).is_ok());
+		::std::thread::sleep(Duration::from_millis(300));
+		assert!(ap.sign(kp.address(), [0u8; 32]).is_err());
+	}
+
+	#[test]
+	fn should_reject_too_long_unlock_duration() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		match ap.unlock_account_timed(kp.address(), "test".into(), Duration::from_secs(::std::u64::MAX)) {
+			Err(Error::InvalidUnlockDuration) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert!(ap.sign(kp.address(), [0u8; 32]).is_err());
+	}
+
+	#[test]
+	fn lock_account() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		assert!(ap.lock_account(kp.address()));
+		assert!(ap.sign(kp.address(), [0u8; 32]).is_err());
+		assert!(!ap.lock_account(kp.address()));
+	}
+
+	#[test]
+	fn decrypt_message() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		let encrypted = ecies::encrypt(kp.public(), &[], b"private data").unwrap();
+		assert!(ap.decrypt(kp.address(), &[], &encrypted).is_err());
+		assert!(ap.decrypt_with_password(kp.address(), "test1".into(), &[], &encrypted).is_err());
+		assert_eq!(ap.decrypt_with_password(kp.address(), "test".into(), &[], &encrypted).unwrap(), b"private data".to_vec());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test".into()).is_ok());
+		assert_eq!(ap.decrypt(kp.address(), &[], &encrypted).unwrap(), b"private data".to_vec());
+		assert!(ap.decrypt(kp.address(), &[], &encrypted).is_err());
+	}
+
+	#[test]
+	fn new_account_from_mnemonic() {
+		let ap = AccountProvider::transient_provider();
+		let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+		let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+		assert!(ap.new_account_from_mnemonic("abandon about", "", &path, "test").is_err());
+		let address = ap.new_account_from_mnemonic(phrase, "", &path, "test").unwrap();
+		assert_eq!(address, "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+		assert!(ap.sign_with_password(address, "test".into(), [0u8; 32]).is_ok());
+	}
+
+	#[test]
+	fn derive_account_from_secret() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+		assert!(ap.derive_account_from_secret(kp.address(), &path).is_err());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		let derived = ap.derive_account_from_secret(kp.address(), &path).unwrap();
+		assert_eq!(ap.accounts().len(), 2);
+		assert!(ap.sign_with_password(derived, "test".into(), [0u8; 32]).is_ok());
+	}
+
+	#[test]
+	fn new_account_from_phrase() {
+		let ap = AccountProvider::transient_provider();
+		let address = ap.new_account_from_phrase("this is sparta".into(), "test").unwrap();
+		assert_eq!(address, H160::from_str("26d1ec50b4e62c1d1a40d16e7cacc6a6580757d5").unwrap());
+		assert!(ap.sign_with_password(address, "test".into(), [0u8; 32]).is_ok());
+	}
+
+	#[test]
+	fn import_presale() {
+		let json = r#"
+		{
+			"encseed": "137103c28caeebbcea5d7f95edb97a289ded151b72159137cb7b2671f394f54cff8c121589dcb373e267225547b3c71cbdb54f6e48ec85cd549f96cf0dedb3bc0a9ac6c79b9c426c5878ca2c9d06ff42a23cb648312fc32ba83649de0928e066",
+			"ethaddr": "ede84640d1a1d3e06902048e67aa7db8d52c2ce1",
+			"email": "123@gmail.com",
+			"btcaddr": "1JvqEc6WLhg6GnyrLBe2ztPAU28KRfuseH"
+		} "#;
+
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.import_presale(json.as_bytes(), "124").is_err());
+		let address = ap.import_presale(json.as_bytes(), "123").unwrap();
+		assert_eq!(address, H160::from_str("ede84640d1a1d3e06902048e67aa7db8d52c2ce1").unwrap());
+		assert!(ap.sign_with_password(address, "123".into(), [0u8; 32]).is_ok());
+	}
+
+	#[test]
+	fn change_password_of_unlocked_account() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		assert!(ap.change_password(kp.address(), "wrong".into(), "new".into()).is_err());
+		assert!(ap.change_password(kp.address(), "test".into(), "new".into()).is_ok());
+		assert!(ap.sign(kp.address(), [0u8; 32]).is_ok());
+		assert!(ap.sign_with_password(kp.address(), "new".into(), [0u8; 32]).is_ok());
+		assert!(ap.export_account(kp.address(), "test".into(), None, None).is_err());
+		assert!(ap.export_account(kp.address(), "new".into(), None, Some(1024)).unwrap().contains(&format!("{:?}", kp.address())));
+	}
+
+	#[test]
+	fn vaults_require_disk_directory() {
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.create_vault("vault", "test").is_err());
+		assert!(ap.list_vaults().is_err());
+		assert!(ap.list_opened_vaults().is_empty());
+	}
+
 	#[test]
 	fn unlock_account_perm() {
 		let kp = Random.generate().unwrap();
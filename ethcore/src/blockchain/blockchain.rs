@@ -16,7 +16,7 @@
 
 //! Blockchain database.
 
-use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrder};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder};
 use bloomchain as bc;
 use util::*;
 use header::*;
@@ -141,6 +141,7 @@ pub struct BlockChain {
 	blooms_config: bc::Config,
 
 	best_block: RwLock<BestBlock>,
+	state_pending: AtomicBool,
 
 	// block cache
 	blocks: RwLock<HashMap<H256, Bytes>>,
@@ -281,6 +282,7 @@ impl BlockChain {
 				elements_per_index: LOG_BLOOMS_ELEMENTS_PER_INDEX,
 			},
 			best_block: RwLock::new(BestBlock::default()),
+			state_pending: AtomicBool::new(false),
 			blocks: RwLock::new(HashMap::new()),
 			block_details: RwLock::new(HashMap::new()),
 			block_hashes: RwLock::new(HashMap::new()),
@@ -329,6 +331,9 @@ impl BlockChain {
 			best_block.hash = best_block_hash;
 		}
 
+		let state_pending = bc.extras_db.get(b"state_pending").unwrap().is_some();
+		bc.state_pending.store(state_pending, AtomicOrder::Relaxed);
+
 		bc
 	}
 
@@ -726,6 +731,21 @@ impl BlockChain {
 		self.best_block.read().unwrap().total_difficulty
 	}
 
+	/// Returns true if the best block state is still being downloaded.
+	pub fn is_state_pending(&self) -> bool {
+		self.state_pending.load(AtomicOrder::Relaxed)
+	}
+
+	/// Mark the best block state as being downloaded or as complete. Persisted across restarts.
+	pub fn set_state_pending(&self, pending: bool) {
+		if pending {
+			self.extras_db.put(b"state_pending", &[1]).unwrap();
+		} else {
+			self.extras_db.delete(b"state_pending").unwrap();
+		}
+		self.state_pending.store(pending, AtomicOrder::Relaxed);
+	}
+
 	/// Get current cache size.
 	pub fn cache_size(&self) -> CacheSize {
 		CacheSize {
@@ -1037,6 +1057,32 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_reopen_with_state_pending() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let temp = RandomTempPath::new();
+		{
+			let bc = BlockChain::new(Config::default(), &genesis, temp.as_path());
+			assert!(!bc.is_state_pending());
+			bc.set_state_pending(true);
+			assert!(bc.is_state_pending());
+		}
+
+		{
+			let bc = BlockChain::new(Config::default(), &genesis, temp.as_path());
+			assert!(bc.is_state_pending());
+			bc.set_state_pending(false);
+		}
+
+		{
+			let bc = BlockChain::new(Config::default(), &genesis, temp.as_path());
+			assert!(!bc.is_state_pending());
+		}
+	}
+
 	#[test]
 	fn can_contain_arbitrary_block_sequence() {
 		let bc_result = generate_dummy_blockchain(50);
@@ -42,7 +42,7 @@ use client::{BlockID, TransactionID, UncleID, TraceId, ClientConfig, BlockChainC
 use client::Error as ClientError;
 use env_info::EnvInfo;
 use executive::{Executive, Executed, TransactOptions, contract_address};
-use receipt::LocalizedReceipt;
+use receipt::{Receipt, LocalizedReceipt};
 pub use blockchain::CacheSize as BlockChainCacheSize;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace;
@@ -376,9 +376,16 @@ impl<V> Client<V> where V: Verifier {
 	pub fn import_queued_transactions(&self, transactions: &[Bytes]) -> usize {
 		let _timer = PerfTimer::new("import_queued_transactions");
 		self.queue_transactions.fetch_sub(transactions.len(), AtomicOrdering::SeqCst);
+		let state = match self.state() {
+			Some(state) => state,
+			None => {
+				trace!(target: "client", "Dropping {} queued transactions while the best block state is pending", transactions.len());
+				return 0;
+			},
+		};
 		let fetch_account = |a: &Address| AccountDetails {
-			nonce: self.latest_nonce(a),
-			balance: self.latest_balance(a),
+			nonce: state.nonce(a),
+			balance: state.balance(a),
 		};
 		let tx = transactions.iter().filter_map(|bytes| UntrustedRlp::new(&bytes).as_val().ok()).collect();
 		let results = self.miner.import_transactions(self, tx, fetch_account);
@@ -387,12 +394,12 @@ impl<V> Client<V> where V: Verifier {
 
 	/// Attempt to get a copy of a specific block's state.
 	///
-	/// This will not fail if given BlockID::Latest.
+	/// This will not fail if given BlockID::Latest unless the best block state is still being downloaded.
 	/// Otherwise, this can fail (but may not) if the DB prunes state.
 	pub fn state_at(&self, id: BlockID) -> Option<State> {
 		// fast path for latest state.
 		if let BlockID::Latest = id.clone() {
-			return Some(self.state())
+			return self.state()
 		}
 
 		let block_number = match self.block_number(id.clone()) {
@@ -415,9 +422,13 @@ impl<V> Client<V> where V: Verifier {
 	}
 
 	/// Get a copy of the best block's state.
-	pub fn state(&self) -> State {
-		State::from_existing(self.state_db.lock().unwrap().boxed_clone(), HeaderView::new(&self.best_block_header()).state_root(), self.engine.account_start_nonce())
-			.expect("State root of best block header always valid.")
+	/// Returns `None` while the best block state is still being downloaded.
+	pub fn state(&self) -> Option<State> {
+		if self.chain.is_state_pending() {
+			return None;
+		}
+		Some(State::from_existing(self.state_db.lock().unwrap().boxed_clone(), HeaderView::new(&self.best_block_header()).state_root(), self.engine.account_start_nonce())
+			.expect("State root of best block header always valid once the best block state is not pending."))
 	}
 
 	/// Get info on the cache.
@@ -438,6 +449,11 @@ impl<V> Client<V> where V: Verifier {
 		self.block_queue.collect_garbage();
 	}
 
+	/// Reseal the pending block if miner's maximal reseal period has elapsed.
+	pub fn update_sealing_if_due(&self) {
+		self.miner.update_sealing_if_due(self);
+	}
+
 	/// Set up the cache behaviour.
 	pub fn configure_cache(&self, pref_cache_size: usize, max_cache_size: usize) {
 		self.chain.configure_cache(pref_cache_size, max_cache_size);
@@ -489,7 +505,8 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 			dao_rescue_block_gas_limit: self.dao_rescue_block_gas_limit(),
 		};
 		// that's just a copy of the state.
-		let mut state = self.state();
+		let mut state = try!(self.state().ok_or(ExecutionError::Internal));
+		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
 		let sender = try!(t.sender().map_err(|e| {
 			let message = format!("Transaction malformed: {:?}", e);
 			ExecutionError::TransactionMalformed(message)
@@ -504,9 +521,9 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 		let mut ret = Executive::new(&mut state, &env_info, self.engine.deref().deref(), &self.vm_factory).transact(t, options);
 
 		// TODO gav move this into Executive.
-		if analytics.state_diffing {
+		if let Some(original_state) = original_state {
 			if let Ok(ref mut x) = ret {
-				x.state_diff = Some(state.diff_from(self.state()));
+				x.state_diff = Some(state.diff_from(original_state));
 			}
 		}
 		ret
@@ -559,7 +576,7 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 	}
 
 	fn code(&self, address: &Address) -> Option<Bytes> {
-		self.state().code(address)
+		self.state().and_then(|s| s.code(address))
 	}
 
 	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
@@ -656,6 +673,53 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 		self.block_queue.import_block(bytes)
 	}
 
+	fn import_block_with_receipts(&self, bytes: Bytes, receipts: Bytes) -> ImportResult {
+		let _import_lock = self.import_lock.lock();
+		let header = BlockView::new(&bytes).header();
+		let hash = header.hash();
+		if self.chain.is_known(&hash) {
+			return Err(ImportError::AlreadyInChain.into());
+		}
+		if !self.chain.is_known(&header.parent_hash) {
+			return Err(BlockError::UnknownParent(header.parent_hash.clone()).into());
+		}
+		let engine = self.engine.deref().deref();
+		try!(verify_block_basic(&header, &bytes, engine));
+		try!(engine.verify_block_unordered(&header, Some(&bytes)));
+		try!(V::verify_block_family(&header, &bytes, engine, self.chain.deref()));
+
+		let receipts_rlp = UntrustedRlp::new(&receipts);
+		let receipts_root = ordered_trie_root(receipts_rlp.iter().map(|r| r.as_raw().to_vec()).collect());
+		if receipts_root != header.receipts_root {
+			return Err(From::from(BlockError::InvalidReceiptsRoot(Mismatch { expected: header.receipts_root.clone(), found: receipts_root })));
+		}
+		let receipts: Vec<Receipt> = try!(receipts_rlp.as_val());
+
+		// Mark the state as pending before the block becomes the best block, so that an interrupted import is resumed
+		self.chain.set_state_pending(true);
+		self.chain.insert_block(&bytes, receipts);
+		self.report.write().unwrap().blocks_imported += 1;
+		trace!(target: "client", "Imported #{} ({}) without execution", header.number(), hash);
+		Ok(hash)
+	}
+
+	fn import_state_data(&self, data: Vec<Bytes>) {
+		let mut state_db = self.state_db.lock().unwrap();
+		for node in &data {
+			state_db.insert(node);
+		}
+		// Downloaded nodes are never pruned, so they bypass the journal
+		state_db.inject().expect("State DB inject failed.");
+	}
+
+	fn is_state_pending(&self) -> bool {
+		self.chain.is_state_pending()
+	}
+
+	fn complete_state_import(&self) {
+		self.chain.set_state_pending(false);
+	}
+
 	fn queue_info(&self) -> BlockQueueInfo {
 		self.block_queue.queue_info()
 	}
@@ -768,9 +832,13 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 	}
 
 	fn import_transactions(&self, transactions: Vec<SignedTransaction>) -> Vec<Result<TransactionImportResult, Error>> {
+		let state = match self.state() {
+			Some(state) => state,
+			None => return transactions.into_iter().map(|_| Err(Error::Client(ClientError::StatePending))).collect(),
+		};
 		let fetch_account = |a: &Address| AccountDetails {
-			nonce: self.latest_nonce(a),
-			balance: self.latest_balance(a),
+			nonce: state.nonce(a),
+			balance: state.balance(a),
 		};
 		self.miner.import_transactions(self, transactions, fetch_account)
 	}
@@ -794,10 +862,14 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 	fn all_transactions(&self) -> Vec<SignedTransaction> {
 		self.miner.all_transactions()
 	}
+
+	fn local_transactions(&self) -> Vec<SignedTransaction> {
+		self.miner.local_transactions()
+	}
 }
 
 impl<V> MiningBlockChainClient for Client<V> where V: Verifier {
-	fn prepare_open_block(&self, author: Address, gas_floor_target: U256, extra_data: Bytes) -> OpenBlock {
+	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = self.engine.deref().deref();
 		let h = self.chain.best_block_hash();
 
@@ -810,7 +882,7 @@ impl<V> MiningBlockChainClient for Client<V> where V: Verifier {
 			self.build_last_hashes(h.clone()),
 			self.dao_rescue_block_gas_limit(),
 			author,
-			gas_floor_target,
+			gas_range_target,
 			extra_data,
 		).expect("OpenBlock::new only fails if parent state root invalid; state root of best block's header is never invalid; qed");
 
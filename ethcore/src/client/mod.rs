@@ -80,14 +80,13 @@ pub trait BlockChainClient : Sync + Send {
 	fn block_total_difficulty(&self, id: BlockID) -> Option<U256>;
 
 	/// Attempt to get address nonce at given block.
-	/// May not fail on BlockID::Latest.
+	/// May not fail on BlockID::Latest unless the latest block state is still being downloaded.
 	fn nonce(&self, address: &Address, id: BlockID) -> Option<U256>;
 
 	/// Get address nonce at the latest block's state.
-	fn latest_nonce(&self, address: &Address) -> U256 {
+	/// Returns None while the latest block state is still being downloaded.
+	fn latest_nonce(&self, address: &Address) -> Option<U256> {
 		self.nonce(address, BlockID::Latest)
-			.expect("nonce will return Some when given BlockID::Latest. nonce was given BlockID::Latest. \
-			Therefore nonce has returned Some; qed")
 	}
 
 	/// Get block hash.
@@ -98,28 +97,26 @@ pub trait BlockChainClient : Sync + Send {
 
 	/// Get address balance at the given block's state.
 	///
-	/// May not return None if given BlockID::Latest.
-	/// Returns None if and only if the block's root hash has been pruned from the DB.
+	/// May not return None if given BlockID::Latest unless the latest block state is still being downloaded.
+	/// Otherwise returns None if and only if the block's root hash has been pruned from the DB.
 	fn balance(&self, address: &Address, id: BlockID) -> Option<U256>;
 
 	/// Get address balance at the latest block's state.
-	fn latest_balance(&self, address: &Address) -> U256 {
+	/// Returns None while the latest block state is still being downloaded.
+	fn latest_balance(&self, address: &Address) -> Option<U256> {
 		self.balance(address, BlockID::Latest)
-			.expect("balance will return Some if given BlockID::Latest. balance was given BlockID::Latest \
-			Therefore balance has returned Some; qed")
 	}
 
 	/// Get value of the storage at given position at the given block's state.
 	///
-	/// May not return None if given BlockID::Latest.
-	/// Returns None if and only if the block's root hash has been pruned from the DB.
+	/// May not return None if given BlockID::Latest unless the latest block state is still being downloaded.
+	/// Otherwise returns None if and only if the block's root hash has been pruned from the DB.
 	fn storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Option<H256>;
 
 	/// Get value of the storage at given position at the latest block's state.
-	fn latest_storage_at(&self, address: &Address, position: &H256) -> H256 {
+	/// Returns None while the latest block state is still being downloaded.
+	fn latest_storage_at(&self, address: &Address, position: &H256) -> Option<H256> {
 		self.storage_at(address, position, BlockID::Latest)
-			.expect("storage_at will return Some if given BlockID::Latest. storage_at was given BlockID::Latest. \
-			Therefore storage_at has returned Some; qed")
 	}
 
 	/// Get transaction with given hash.
@@ -147,6 +144,20 @@ pub trait BlockChainClient : Sync + Send {
 	/// Import a block into the blockchain.
 	fn import_block(&self, bytes: Bytes) -> ImportResult;
 
+	/// Import a block with its receipts into the blockchain without executing it.
+	/// The block state is not available until it is downloaded with `import_state_data`.
+	fn import_block_with_receipts(&self, bytes: Bytes, receipts: Bytes) -> ImportResult;
+
+	/// Import state trie nodes for the best block.
+	fn import_state_data(&self, data: Vec<Bytes>);
+
+	/// Returns true if the best block was imported with `import_block_with_receipts`
+	/// and its state has not been completely downloaded yet.
+	fn is_state_pending(&self) -> bool;
+
+	/// Mark the state of the best block as completely downloaded.
+	fn complete_state_import(&self);
+
 	/// Get block queue information.
 	fn queue_info(&self) -> BlockQueueInfo;
 
@@ -199,6 +210,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// list all transactions
 	fn all_transactions(&self) -> Vec<SignedTransaction>;
 
+	/// list transactions submitted locally
+	fn local_transactions(&self) -> Vec<SignedTransaction>;
+
 	/// Get the gas price distribution.
 	fn gas_price_statistics(&self, sample_size: usize, distribution_size: usize) -> Result<Vec<U256>, ()> {
 		let mut h = self.chain_info().best_block_hash;
@@ -236,6 +250,7 @@ pub trait BlockChainClient : Sync + Send {
 /// Extended client interface used for mining
 pub trait MiningBlockChainClient : BlockChainClient {
 	/// Returns OpenBlock prepared for closing.
-	fn prepare_open_block(&self, author: Address, gas_floor_target: U256, extra_data: Bytes)
+	/// Must not be called while the best block state is pending.
+	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes)
 		-> OpenBlock;
 }
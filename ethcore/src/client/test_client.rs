@@ -16,7 +16,7 @@
 
 //! Test client.
 
-use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrder};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder};
 use util::*;
 use transaction::{Transaction, LocalizedTransaction, SignedTransaction, Action};
 use blockchain::TreeRoute;
@@ -63,10 +63,16 @@ pub struct TestBlockChainClient {
 	pub execution_result: RwLock<Option<Executed>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionID, LocalizedReceipt>>,
+	/// Logs returned by `logs` (filtered by address and topics).
+	pub logs: RwLock<Vec<LocalizedLogEntry>>,
 	/// Block queue size.
 	pub queue_size: AtomicUsize,
 	/// Miner
 	pub miner: Arc<Miner>,
+	/// Imported state trie nodes.
+	pub state_nodes: RwLock<HashMap<H256, Bytes>>,
+	/// Best block state is being downloaded.
+	pub state_pending: AtomicBool,
 }
 
 #[derive(Clone)]
@@ -104,8 +110,11 @@ impl TestBlockChainClient {
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
 			receipts: RwLock::new(HashMap::new()),
+			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
 			miner: Arc::new(Miner::default()),
+			state_nodes: RwLock::new(HashMap::new()),
+			state_pending: AtomicBool::new(false),
 		};
 		client.add_blocks(1, EachBlockWith::Nothing); // add genesis block
 		client.genesis_hash = client.last_hash.read().unwrap().clone();
@@ -117,6 +126,11 @@ impl TestBlockChainClient {
 		self.receipts.write().unwrap().insert(id, receipt);
 	}
 
+	/// Set logs returned by `logs`.
+	pub fn set_logs(&self, logs: Vec<LocalizedLogEntry>) {
+		*self.logs.write().unwrap() = logs;
+	}
+
 	/// Set the execution result.
 	pub fn set_execution_result(&self, result: Executed) {
 		*self.execution_result.write().unwrap() = Some(result);
@@ -240,7 +254,7 @@ impl TestBlockChainClient {
 }
 
 impl MiningBlockChainClient for TestBlockChainClient {
-	fn prepare_open_block(&self, _author: Address, _gas_floor_target: U256, _extra_data: Bytes) -> OpenBlock {
+	fn prepare_open_block(&self, _author: Address, _gas_range_target: (U256, U256), _extra_data: Bytes) -> OpenBlock {
 		unimplemented!();
 	}
 }
@@ -259,6 +273,9 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn nonce(&self, address: &Address, id: BlockID) -> Option<U256> {
+		if self.state_pending.load(AtomicOrder::Relaxed) {
+			return None;
+		}
 		match id {
 			BlockID::Latest => Some(self.nonces.read().unwrap().get(address).cloned().unwrap_or_else(U256::zero)),
 			_ => None,
@@ -270,6 +287,9 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
+		if self.state_pending.load(AtomicOrder::Relaxed) {
+			return None;
+		}
 		if let BlockID::Latest = id {
 			Some(self.balances.read().unwrap().get(address).cloned().unwrap_or_else(U256::zero))
 		} else {
@@ -278,6 +298,9 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Option<H256> {
+		if self.state_pending.load(AtomicOrder::Relaxed) {
+			return None;
+		}
 		if let BlockID::Latest = id {
 			Some(self.storage.read().unwrap().get(&(address.clone(), position.clone())).cloned().unwrap_or_else(H256::new))
 		} else {
@@ -301,8 +324,11 @@ impl BlockChainClient for TestBlockChainClient {
 		unimplemented!();
 	}
 
-	fn logs(&self, _filter: Filter) -> Vec<LocalizedLogEntry> {
-		unimplemented!();
+	fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry> {
+		self.logs.read().unwrap().iter()
+			.filter(|log| filter.matches(&log.entry))
+			.cloned()
+			.collect()
 	}
 
 	fn last_hashes(&self) -> LastHashes {
@@ -370,6 +396,9 @@ impl BlockChainClient for TestBlockChainClient {
 
 	// TODO: returns just hashes instead of node state rlp(?)
 	fn state_data(&self, hash: &H256) -> Option<Bytes> {
+		if let Some(node) = self.state_nodes.read().unwrap().get(hash) {
+			return Some(node.clone());
+		}
 		// starts with 'f' ?
 		if *hash > H256::from("f000000000000000000000000000000000000000000000000000000000000000") {
 			let mut rlp = RlpStream::new();
@@ -438,6 +467,26 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(h)
 	}
 
+	fn import_block_with_receipts(&self, b: Bytes, _receipts: Bytes) -> ImportResult {
+		self.state_pending.store(true, AtomicOrder::Relaxed);
+		self.import_block(b)
+	}
+
+	fn import_state_data(&self, data: Vec<Bytes>) {
+		let mut nodes = self.state_nodes.write().unwrap();
+		for node in data {
+			nodes.insert(node.sha3(), node);
+		}
+	}
+
+	fn is_state_pending(&self) -> bool {
+		self.state_pending.load(AtomicOrder::Relaxed)
+	}
+
+	fn complete_state_import(&self) {
+		self.state_pending.store(false, AtomicOrder::Relaxed);
+	}
+
 	fn queue_info(&self) -> BlockQueueInfo {
 		BlockQueueInfo {
 			verified_queue_size: self.queue_size.load(AtomicOrder::Relaxed),
@@ -502,4 +551,8 @@ impl BlockChainClient for TestBlockChainClient {
 	fn all_transactions(&self) -> Vec<SignedTransaction> {
 		self.miner.all_transactions()
 	}
+
+	fn local_transactions(&self) -> Vec<SignedTransaction> {
+		self.miner.local_transactions()
+	}
 }
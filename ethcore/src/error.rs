@@ -60,6 +60,15 @@ pub enum TransactionError {
 	InvalidGasLimit(OutOfBounds<U256>),
 	/// Transaction is invalid for some other reason.
 	DAORescue,
+	/// Sender is not allowed to import transactions by the queue policy.
+	SenderBanned,
+	/// Recipient is not allowed by the queue policy.
+	RecipientBanned,
+	/// Sender has reached the limit of transactions kept in the queue.
+	SenderLimitReached {
+		/// Maximal number of transactions from single sender
+		limit: usize,
+	},
 }
 
 impl fmt::Display for TransactionError {
@@ -79,6 +88,10 @@ impl fmt::Display for TransactionError {
 				format!("Gas limit exceeded. Limit={}, Given={}", limit, got),
 			InvalidGasLimit(ref err) => format!("Invalid gas limit. {}", err),
 			DAORescue => "Transaction is invalid due to the DAO rescue.".into(),
+			SenderBanned => "Sender is banned by the queue policy".into(),
+			RecipientBanned => "Recipient is banned by the queue policy".into(),
+			SenderLimitReached { limit } =>
+				format!("Too many transactions from sender in the queue. Limit={}", limit),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
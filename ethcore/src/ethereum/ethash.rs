@@ -98,6 +98,10 @@ impl Engine for Ethash {
 		hash_map!["nonce".to_owned() => format!("0x{}", header.nonce().hex()), "mixHash".to_owned() => format!("0x{}", header.mix_hash().hex())]
 	}
 
+	fn enacts_fork(&self, number: BlockNumber) -> bool {
+		number == self.ethash_params.frontier_compatibility_mode_limit
+	}
+
 	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
 		trace!(target: "client", "Creating schedule. fCML={}", self.ethash_params.frontier_compatibility_mode_limit);
 
@@ -112,15 +116,18 @@ impl Engine for Ethash {
 		}
 	}
 
-	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256) {
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
 		header.difficulty = self.calculate_difficuty(header, parent);
 		header.gas_limit = {
 			let gas_limit = parent.gas_limit;
 			let bound_divisor = self.ethash_params.gas_limit_bound_divisor;
 			if gas_limit < gas_floor_target {
 				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else if gas_limit > gas_ceil_target {
+				max(gas_ceil_target, gas_limit - gas_limit / bound_divisor + 1.into())
 			} else {
-				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into() + (header.gas_used * 6.into() / 5.into()) / bound_divisor)
+				min(gas_ceil_target,
+					max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into() + (header.gas_used * 6.into() / 5.into()) / bound_divisor))
 			}
 		};
 		header.note_dirty();
@@ -323,7 +330,7 @@ mod tests {
 		spec.ensure_db_good(db.as_hashdb_mut());
 		let last_hashes = vec![genesis_header.hash()];
 		let vm_factory = Default::default();
-		let b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), 3141562.into(), vec![]).unwrap();
+		let b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
 		let b = b.close();
 		assert_eq!(b.state().balance(&Address::zero()), U256::from_str("4563918244f40000").unwrap());
 	}
@@ -338,7 +345,7 @@ mod tests {
 		spec.ensure_db_good(db.as_hashdb_mut());
 		let last_hashes = vec![genesis_header.hash()];
 		let vm_factory = Default::default();
-		let mut b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), 3141562.into(), vec![]).unwrap();
+		let mut b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
 		let mut uncle = Header::new();
 		let uncle_author = address_from_hex("ef2d6d194084c2de36e0dabfce45d046b37d1106");
 		uncle.author = uncle_author.clone();
@@ -356,6 +363,13 @@ mod tests {
 		assert!(engine.version().major >= 1);
 	}
 
+	#[test]
+	fn enacts_homestead_fork_only() {
+		let engine = new_morden().engine;
+		assert!(engine.enacts_fork(0x789b0));
+		assert!(!engine.enacts_fork(1920000));
+	}
+
 	#[test]
 	fn can_return_schedule() {
 		let engine = new_morden().engine;
@@ -526,6 +540,27 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn should_move_gas_limit_towards_ceiling() {
+		let engine = new_morden().engine;
+		let mut parent_header: Header = Header::default();
+		parent_header.set_number(1);
+		parent_header.set_difficulty(U256::from(0x20000));
+		parent_header.set_gas_limit(U256::from(5_000_000));
+		let mut header: Header = Header::default();
+		header.set_number(2);
+		header.set_timestamp(10);
+
+		engine.populate_from_parent(&mut header, &parent_header, U256::from(3_000_000), U256::from(4_000_000));
+		assert_eq!(header.gas_limit(), &U256::from(5_000_000 - 5_000_000 / 1024 + 1));
+
+		engine.populate_from_parent(&mut header, &parent_header, U256::from(3_000_000), U256::from(4_999_000));
+		assert_eq!(header.gas_limit(), &U256::from(4_999_000));
+
+		engine.populate_from_parent(&mut header, &parent_header, U256::from(6_000_000), U256::from(7_000_000));
+		assert_eq!(header.gas_limit(), &U256::from(5_000_000 + 5_000_000 / 1024 - 1));
+	}
+
 	#[test]
 	fn test_difficulty_to_boundary() {
 		// result of f(0) is undefined, so do not assert the result
@@ -0,0 +1,115 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A blockchain engine that seals every block immediately, used for development chains.
+
+use std::collections::BTreeMap;
+use util::hash::Address;
+use util::numbers::U256;
+use util::Bytes;
+use builtin::Builtin;
+use engine::Engine;
+use spec::{CommonParams, Spec};
+use evm::Schedule;
+use env_info::EnvInfo;
+use header::Header;
+use block::ExecutedBlock;
+use account_provider::AccountProvider;
+
+/// An engine which seals every block as soon as it is prepared.
+///
+/// There is no proof of work and no signature, block timestamps are incremented by one
+/// second per block so the chain does not depend on the wall clock.
+pub struct InstantSeal {
+	params: CommonParams,
+	builtins: BTreeMap<Address, Builtin>,
+}
+
+impl InstantSeal {
+	/// Returns new instance of InstantSeal with default VM Factory
+	pub fn new(params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		InstantSeal {
+			params: params,
+			builtins: builtins,
+		}
+	}
+}
+
+impl Engine for InstantSeal {
+	fn name(&self) -> &str {
+		"InstantSeal"
+	}
+
+	fn params(&self) -> &CommonParams {
+		&self.params
+	}
+
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
+		&self.builtins
+	}
+
+	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
+		Schedule::new_homestead()
+	}
+
+	fn seals_internally(&self) -> bool { true }
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, _gas_floor_target: U256, _gas_ceil_target: U256) {
+		header.difficulty = parent.difficulty;
+		header.gas_limit = parent.gas_limit;
+		header.set_timestamp(parent.timestamp + 1);
+	}
+
+	fn generate_seal(&self, _block: &ExecutedBlock, _accounts: Option<&AccountProvider>) -> Option<Vec<Bytes>> {
+		Some(Vec::new())
+	}
+}
+
+/// Create a new development chain spec with `InstantSeal` consensus engine.
+/// Accounts with secrets `sha3("")`, `sha3("1")`, `sha3("2")` and `sha3("3")` are pre-funded.
+pub fn new_instant_seal() -> Spec { Spec::load(include_bytes!("../res/instant_seal.json")) }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use common::*;
+	use block::*;
+	use tests::helpers::*;
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = new_instant_seal().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.seals_internally());
+	}
+
+	#[test]
+	fn can_generate_seal() {
+		let spec = new_instant_seal();
+		let engine = &spec.engine;
+		let genesis_header = spec.genesis_header();
+		let mut db_result = get_temp_journal_db();
+		let mut db = db_result.take();
+		spec.ensure_db_good(db.as_hashdb_mut());
+		let last_hashes = vec![genesis_header.hash()];
+		let vm_factory = Default::default();
+		let b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		assert_eq!(b.block().header().timestamp(), genesis_header.timestamp() + 1);
+		let seal = engine.generate_seal(b.block(), None).unwrap();
+		assert!(b.try_seal(engine.deref(), seal).is_ok());
+	}
+}
@@ -98,6 +98,7 @@ pub extern crate ethstore;
 
 pub mod account_provider;
 pub mod basic_authority;
+pub mod instant_seal;
 pub mod block;
 pub mod block_queue;
 pub mod client;
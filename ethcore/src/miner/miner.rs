@@ -16,6 +16,7 @@
 
 use rayon::prelude::*;
 use std::sync::atomic::AtomicBool;
+use std::time::{Instant, Duration};
 
 use util::*;
 use account_provider::AccountProvider;
@@ -27,19 +28,70 @@ use transaction::SignedTransaction;
 use receipt::{Receipt};
 use spec::Spec;
 use engine::Engine;
-use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionImportResult, TransactionOrigin};
+use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionImportResult, TransactionOrigin, TransactionPolicy};
+use miner::{TransactionCondition, ScheduledTransaction, ScheduledTransactions};
+
+/// Strategy used to choose the gas limit of authored blocks.
+#[derive(Debug, PartialEq, Clone)]
+pub enum GasLimitStrategy {
+	/// Move the gas limit towards the range given by gas floor and ceiling targets.
+	Targets,
+	/// Move the gas limit within the gas floor and ceiling targets depending on how full
+	/// the given number of recent blocks were: up when they were more than 2/3 full,
+	/// down when less than 1/3 full.
+	Fullness(usize),
+}
+
+/// Configures the behaviour of the miner.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MinerOptions {
+	/// Force the miner to reseal, even when nobody has asked for work.
+	pub force_sealing: bool,
+	/// Reseal on receipt of new external transactions.
+	pub reseal_on_external_tx: bool,
+	/// Reseal on receipt of new local transactions.
+	pub reseal_on_own_tx: bool,
+	/// Minimum period between transaction-inspired reseals.
+	pub reseal_min_period: Duration,
+	/// Maximum period between reseals, even if there were no new transactions. `None` disables periodic resealing.
+	pub reseal_max_period: Option<Duration>,
+	/// Seal empty blocks (every `reseal_max_period`) when the engine is able to seal internally.
+	pub seal_empty_blocks: bool,
+	/// Strategy used to choose the gas limit of authored blocks.
+	pub gas_limit_strategy: GasLimitStrategy,
+}
+
+impl Default for MinerOptions {
+	fn default() -> Self {
+		MinerOptions {
+			force_sealing: false,
+			reseal_on_external_tx: true,
+			reseal_on_own_tx: true,
+			reseal_min_period: Duration::from_millis(0),
+			reseal_max_period: None,
+			seal_empty_blocks: false,
+			gas_limit_strategy: GasLimitStrategy::Targets,
+		}
+	}
+}
 
 /// Keeps track of transactions using priority queue and holds currently mined block.
 pub struct Miner {
 	// NOTE [ToDr]  When locking always lock in this order!
 	transaction_queue: Mutex<TransactionQueue>,
 	sealing_work: Mutex<UsingQueue<ClosedBlock>>,
+	scheduled_transactions: Mutex<ScheduledTransactions>,
 
 	// for sealing...
-	force_sealing: bool,
+	options: MinerOptions,
 	sealing_enabled: AtomicBool,
 	sealing_block_last_request: Mutex<u64>,
+	next_allowed_reseal: Mutex<Instant>,
+	next_mandatory_reseal: Mutex<Option<Instant>>,
+	/// New transactions arrived while resealing was throttled by `reseal_min_period`.
+	pending_tx_reseal: AtomicBool,
 	gas_floor_target: RwLock<U256>,
+	gas_ceil_target: RwLock<U256>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
 	spec: Spec,
@@ -49,52 +101,46 @@ pub struct Miner {
 
 impl Default for Miner {
 	fn default() -> Miner {
-		Miner {
-			transaction_queue: Mutex::new(TransactionQueue::new()),
-			force_sealing: false,
-			sealing_enabled: AtomicBool::new(false),
-			sealing_block_last_request: Mutex::new(0),
-			sealing_work: Mutex::new(UsingQueue::new(5)),
-			gas_floor_target: RwLock::new(U256::zero()),
-			author: RwLock::new(Address::default()),
-			extra_data: RwLock::new(Vec::new()),
-			accounts: None,
-			spec: Spec::new_test(),
-		}
+		Miner::new_raw(MinerOptions::default(), Spec::new_test(), None)
 	}
 }
 
 impl Miner {
 	/// Creates new instance of miner
-	pub fn new(force_sealing: bool, spec: Spec) -> Arc<Miner> {
-		Arc::new(Miner {
-			transaction_queue: Mutex::new(TransactionQueue::new()),
-			force_sealing: force_sealing,
-			sealing_enabled: AtomicBool::new(force_sealing),
-			sealing_block_last_request: Mutex::new(0),
-			sealing_work: Mutex::new(UsingQueue::new(5)),
-			gas_floor_target: RwLock::new(U256::zero()),
-			author: RwLock::new(Address::default()),
-			extra_data: RwLock::new(Vec::new()),
-			accounts: None,
-			spec: spec,
-		})
+	pub fn new(options: MinerOptions, spec: Spec) -> Arc<Miner> {
+		Arc::new(Miner::new_raw(options, spec, None))
 	}
 
 	/// Creates new instance of miner
-	pub fn with_accounts(force_sealing: bool, spec: Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
-		Arc::new(Miner {
+	pub fn with_accounts(options: MinerOptions, spec: Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
+		Arc::new(Miner::new_raw(options, spec, Some(accounts)))
+	}
+
+	fn new_raw(mut options: MinerOptions, spec: Spec, accounts: Option<Arc<AccountProvider>>) -> Miner {
+		options.force_sealing = options.force_sealing || spec.engine.seals_internally();
+		let now = Instant::now();
+		Miner {
 			transaction_queue: Mutex::new(TransactionQueue::new()),
-			force_sealing: force_sealing,
-			sealing_enabled: AtomicBool::new(force_sealing),
+			sealing_enabled: AtomicBool::new(options.force_sealing),
 			sealing_block_last_request: Mutex::new(0),
+			next_allowed_reseal: Mutex::new(now),
+			next_mandatory_reseal: Mutex::new(options.reseal_max_period.map(|period| now + period)),
+			pending_tx_reseal: AtomicBool::new(false),
 			sealing_work: Mutex::new(UsingQueue::new(5)),
+			scheduled_transactions: Mutex::new(ScheduledTransactions::new()),
 			gas_floor_target: RwLock::new(U256::zero()),
+			gas_ceil_target: RwLock::new(!U256::zero()),
 			author: RwLock::new(Address::default()),
 			extra_data: RwLock::new(Vec::new()),
-			accounts: Some(accounts),
+			options: options,
+			accounts: accounts,
 			spec: spec,
-		})
+		}
+	}
+
+	/// Sets the policy used to admit and prioritize transactions in the queue.
+	pub fn set_transaction_policy(&self, policy: Box<TransactionPolicy>) {
+		self.transaction_queue.lock().unwrap().set_policy(policy);
 	}
 
 	fn engine(&self) -> &Engine {
@@ -106,6 +152,20 @@ impl Miner {
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
 	fn prepare_sealing(&self, chain: &MiningBlockChainClient) {
 		trace!(target: "miner", "prepare_sealing: entering");
+		if chain.is_state_pending() {
+			trace!(target: "miner", "prepare_sealing: best block state is not downloaded yet");
+			return;
+		}
+
+		// Empty blocks are sealed internally only when the maximal reseal period has elapsed.
+		let reseal_due = {
+			let now = Instant::now();
+			let reseal_due = self.next_mandatory_reseal.lock().unwrap().map_or(false, |t| now >= t);
+			*self.next_allowed_reseal.lock().unwrap() = now + self.options.reseal_min_period;
+			*self.next_mandatory_reseal.lock().unwrap() = self.options.reseal_max_period.map(|period| now + period);
+			self.pending_tx_reseal.store(false, atomic::Ordering::Relaxed);
+			reseal_due
+		};
 
 		let (transactions, mut open_block) = {
 			let transactions = {self.transaction_queue.lock().unwrap().top_transactions()};
@@ -131,7 +191,7 @@ impl Miner {
 					trace!(target: "miner", "No existing work - making new block");
 					chain.prepare_open_block(
 						self.author(),
-						self.gas_floor_target(),
+						self.gas_range_target(chain),
 						self.extra_data()
 					)
 				}
@@ -167,8 +227,8 @@ impl Miner {
 		let block = open_block.close();
 
 		let fetch_account = |a: &Address| AccountDetails {
-			nonce: chain.latest_nonce(a),
-			balance: chain.latest_balance(a),
+			nonce: chain.latest_nonce(a).expect("prepare_sealing returns early while the best block state is pending; qed"),
+			balance: chain.latest_balance(a).expect("prepare_sealing returns early while the best block state is pending; qed"),
 		};
 
 		{
@@ -178,9 +238,9 @@ impl Miner {
 			}
 		}
 
-		if !block.transactions().is_empty() {
-			trace!(target: "miner", "prepare_sealing: block has transaction - attempting internal seal.");
-			// block with transactions - see if we can seal immediately.
+		if !block.transactions().is_empty() || (self.options.seal_empty_blocks && reseal_due) {
+			trace!(target: "miner", "prepare_sealing: block has transaction or empty block is due - attempting internal seal.");
+			// block with transactions (or empty block on schedule) - see if we can seal immediately.
 			let s = self.engine().generate_seal(block.block(), match self.accounts {
 				Some(ref x) => Some(&**x),
 				None => None,
@@ -211,12 +271,84 @@ impl Miner {
 		trace!(target: "miner", "prepare_sealing: leaving (last={:?})", sealing_work.peek_last_ref().map(|b| b.block().fields().header.hash()));
 	}
 
+	/// Imports scheduled transactions which conditions are met by the current best block.
+	fn import_ready_scheduled_transactions(&self, chain: &MiningBlockChainClient) {
+		let ready = {
+			let mut scheduled = self.scheduled_transactions.lock().unwrap();
+			if scheduled.is_empty() || chain.is_state_pending() {
+				return;
+			}
+			let header = chain.best_block_header();
+			let header = HeaderView::new(&header);
+			scheduled.drain_ready(header.number(), header.timestamp())
+		};
+
+		for scheduled in ready {
+			let hash = scheduled.transaction.hash();
+			trace!(target: "own_tx", "Scheduled transaction is ready: {:?}", hash);
+			if let Err(e) = self.import_own_transaction(chain, scheduled.transaction.clone(), |a: &Address| AccountDetails {
+				nonce: chain.latest_nonce(a).expect("Scheduled transactions are not released while the best block state is pending; qed"),
+				balance: chain.latest_balance(a).expect("Scheduled transactions are not released while the best block state is pending; qed"),
+			}) {
+				warn!(target: "own_tx", "Error importing scheduled transaction {:?}: {:?}", hash, e);
+				// keep it so the failure can be queried via `scheduled_transactions`
+				self.scheduled_transactions.lock().unwrap().fail(scheduled, format!("{:?}", e));
+			}
+		}
+	}
+
+	/// Returns the gas limit range that the next block should target.
+	fn gas_range_target(&self, chain: &MiningBlockChainClient) -> (U256, U256) {
+		let floor = self.gas_floor_target();
+		let ceil = max(floor, self.gas_ceil_target());
+		match self.options.gas_limit_strategy {
+			GasLimitStrategy::Targets => (floor, ceil),
+			GasLimitStrategy::Fullness(blocks) => {
+				let best_number = chain.chain_info().best_block_number;
+				let (used, limit) = (0..blocks as u64)
+					.take_while(|i| *i <= best_number)
+					.filter_map(|i| chain.block_header(BlockID::Number(best_number - i)))
+					.fold((U256::zero(), U256::zero()), |(used, limit), header| {
+						let header = HeaderView::new(&header);
+						(used + header.gas_used(), limit + header.gas_limit())
+					});
+				let current = HeaderView::new(&chain.best_block_header()).gas_limit();
+				let target = if used * 3.into() > limit * 2.into() {
+					ceil
+				} else if used * 3.into() < limit {
+					floor
+				} else {
+					min(ceil, max(floor, current))
+				};
+				trace!(target: "miner", "gas_range_target: recent blocks used {} of {} gas, targeting {}", used, limit, target);
+				(target, target)
+			}
+		}
+	}
+
 	fn update_gas_limit(&self, chain: &MiningBlockChainClient) {
 		let gas_limit = HeaderView::new(&chain.best_block_header()).gas_limit();
 		let mut queue = self.transaction_queue.lock().unwrap();
 		queue.set_gas_limit(gas_limit);
 	}
 
+	/// Checks if enough time has passed since the last reseal to reseal again because of new transactions.
+	fn tx_reseal_allowed(&self) -> bool {
+		Instant::now() >= *self.next_allowed_reseal.lock().unwrap()
+	}
+
+	/// Updates sealing if `reseal_max_period` has passed since the block was last prepared
+	/// or if a reseal for new transactions was throttled and `reseal_min_period` has passed since.
+	/// Should be called periodically.
+	pub fn update_sealing_if_due(&self, chain: &MiningBlockChainClient) {
+		let due = self.next_mandatory_reseal.lock().unwrap().map_or(false, |t| Instant::now() >= t);
+		let tx_reseal_due = self.pending_tx_reseal.load(atomic::Ordering::Relaxed) && self.tx_reseal_allowed();
+		if due || tx_reseal_due {
+			trace!(target: "miner", "update_sealing_if_due: reseal period elapsed (maximal={}, throttled={})", due, tx_reseal_due);
+			self.update_sealing(chain);
+		}
+	}
+
 	/// Returns true if we had to prepare new pending block
 	fn enable_and_prepare_sealing(&self, chain: &MiningBlockChainClient) -> bool {
 		trace!(target: "miner", "enable_and_prepare_sealing: entering");
@@ -305,25 +437,25 @@ impl MinerService for Miner {
 		}
 	}
 
-	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> U256 {
+	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
 		let sealing_work = self.sealing_work.lock().unwrap();
 		sealing_work.peek_last_ref().map_or_else(
 			|| chain.latest_balance(address),
-			|b| b.block().fields().state.balance(address)
+			|b| Some(b.block().fields().state.balance(address))
 		)
 	}
 
-	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> H256 {
+	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> Option<H256> {
 		let sealing_work = self.sealing_work.lock().unwrap();
 		sealing_work.peek_last_ref().map_or_else(
 			|| chain.latest_storage_at(address, position),
-			|b| b.block().fields().state.storage_at(address, position)
+			|b| Some(b.block().fields().state.storage_at(address, position))
 		)
 	}
 
-	fn nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> U256 {
+	fn nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
 		let sealing_work = self.sealing_work.lock().unwrap();
-		sealing_work.peek_last_ref().map_or_else(|| chain.latest_nonce(address), |b| b.block().fields().state.nonce(address))
+		sealing_work.peek_last_ref().map_or_else(|| chain.latest_nonce(address), |b| Some(b.block().fields().state.nonce(address)))
 	}
 
 	fn code(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<Bytes> {
@@ -344,6 +476,10 @@ impl MinerService for Miner {
 		*self.gas_floor_target.write().unwrap() = target;
 	}
 
+	fn set_gas_ceil_target(&self, target: U256) {
+		*self.gas_ceil_target.write().unwrap() = target;
+	}
+
 	fn set_minimal_gas_price(&self, min_gas_price: U256) {
 		self.transaction_queue.lock().unwrap().set_minimal_gas_price(min_gas_price);
 	}
@@ -384,6 +520,10 @@ impl MinerService for Miner {
 		*self.gas_floor_target.read().unwrap()
 	}
 
+	fn gas_ceil_target(&self) -> U256 {
+		*self.gas_ceil_target.read().unwrap()
+	}
+
 	fn import_transactions<T>(&self, chain: &MiningBlockChainClient, transactions: Vec<SignedTransaction>, fetch_account: T) ->
 		Vec<Result<TransactionImportResult, Error>>
 		where T: Fn(&Address) -> AccountDetails {
@@ -393,8 +533,13 @@ impl MinerService for Miner {
 				.map(|tx| transaction_queue.add(tx, &fetch_account, TransactionOrigin::External))
 				.collect()
 		};
-		if !results.is_empty() {
-			self.update_sealing(chain);
+		if !results.is_empty() && self.options.reseal_on_external_tx {
+			if self.tx_reseal_allowed() {
+				self.update_sealing(chain);
+			} else {
+				trace!(target: "miner", "import_transactions: reseal throttled, postponing");
+				self.pending_tx_reseal.store(true, atomic::Ordering::Relaxed);
+			}
 		}
 		results
 	}
@@ -428,20 +573,64 @@ impl MinerService for Miner {
 			import
 		};
 
-		if imported.is_ok() {
-			// Make sure to do it after transaction is imported and lock is droped.
-			// We need to create pending block and enable sealing
-			let prepared = self.enable_and_prepare_sealing(chain);
-			// If new block has not been prepared (means we already had one)
-			// we need to update sealing
-			if !prepared {
-				self.update_sealing(chain);
+		if imported.is_ok() && self.options.reseal_on_own_tx {
+			if self.tx_reseal_allowed() {
+				// Make sure to do it after transaction is imported and lock is droped.
+				// We need to create pending block and enable sealing
+				let prepared = self.enable_and_prepare_sealing(chain);
+				// If new block has not been prepared (means we already had one)
+				// we need to update sealing
+				if !prepared {
+					self.update_sealing(chain);
+				}
+			} else {
+				// Enable sealing now, the block is prepared by `update_sealing_if_due` once the period elapses.
+				trace!(target: "own_tx", "Reseal throttled, postponing");
+				self.sealing_enabled.store(true, atomic::Ordering::Relaxed);
+				self.pending_tx_reseal.store(true, atomic::Ordering::Relaxed);
 			}
 		}
 
 		imported
 	}
 
+	fn import_scheduled_transaction<T>(
+		&self,
+		chain: &MiningBlockChainClient,
+		transaction: SignedTransaction,
+		condition: TransactionCondition,
+		fetch_account: T
+	) -> Result<(), Error> where T: Fn(&Address) -> AccountDetails {
+
+		try!(transaction.sender());
+		try!(transaction.check_low_s());
+
+		let is_met = {
+			let header = chain.best_block_header();
+			let header = HeaderView::new(&header);
+			condition.is_met(header.number(), header.timestamp())
+		};
+
+		if is_met {
+			return self.import_own_transaction(chain, transaction, fetch_account).map(|_| ());
+		}
+
+		trace!(target: "own_tx", "Scheduling transaction {:?} with condition {:?}", transaction.hash(), condition);
+		if self.scheduled_transactions.lock().unwrap().add(transaction, condition) {
+			Ok(())
+		} else {
+			Err(Error::Transaction(TransactionError::AlreadyImported))
+		}
+	}
+
+	fn scheduled_transactions(&self) -> Vec<ScheduledTransaction> {
+		self.scheduled_transactions.lock().unwrap().all()
+	}
+
+	fn remove_scheduled_transaction(&self, hash: &H256) -> Option<ScheduledTransaction> {
+		self.scheduled_transactions.lock().unwrap().remove(hash)
+	}
+
 	fn pending_transactions_hashes(&self) -> Vec<H256> {
 		let queue = self.transaction_queue.lock().unwrap();
 		match (self.sealing_enabled.load(atomic::Ordering::Relaxed), self.sealing_work.lock().unwrap().peek_last_ref()) {
@@ -467,6 +656,11 @@ impl MinerService for Miner {
 		queue.top_transactions()
 	}
 
+	fn local_transactions(&self) -> Vec<SignedTransaction> {
+		let queue = self.transaction_queue.lock().unwrap();
+		queue.local_transactions()
+	}
+
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		let queue = self.transaction_queue.lock().unwrap();
 		// TODO: should only use the sealing_work when it's current (it could be an old block)
@@ -494,7 +688,12 @@ impl MinerService for Miner {
 	}
 
 	fn last_nonce(&self, address: &Address) -> Option<U256> {
-		self.transaction_queue.lock().unwrap().last_nonce(address)
+		let queued = self.transaction_queue.lock().unwrap().last_nonce(address);
+		let scheduled = self.scheduled_transactions.lock().unwrap().last_nonce(address);
+		match (queued, scheduled) {
+			(Some(queued), Some(scheduled)) => Some(max(queued, scheduled)),
+			(queued, scheduled) => queued.or(scheduled),
+		}
 	}
 
 	fn update_sealing(&self, chain: &MiningBlockChainClient) {
@@ -502,7 +701,7 @@ impl MinerService for Miner {
 			let current_no = chain.chain_info().best_block_number;
 			let has_local_transactions = self.transaction_queue.lock().unwrap().has_local_pending_transactions();
 			let last_request = *self.sealing_block_last_request.lock().unwrap();
-			let should_disable_sealing = !self.force_sealing
+			let should_disable_sealing = !self.options.force_sealing
 				&& !has_local_transactions
 				&& current_no > last_request
 				&& current_no - last_request > SEALING_TIMEOUT_IN_BLOCKS;
@@ -568,6 +767,12 @@ impl MinerService for Miner {
 		// First update gas limit in transaction queue
 		self.update_gas_limit(chain);
 
+		// Account details are not known until the best block state is downloaded
+		if chain.is_state_pending() {
+			trace!(target: "miner", "chain_new_blocks: best block state is not downloaded yet");
+			return;
+		}
+
 		// Then import all transactions...
 		{
 			let out_of_chain = retracted
@@ -579,8 +784,8 @@ impl MinerService for Miner {
 					let _sender = tx.sender();
 				}
 				let _ = self.import_transactions(chain, txs, |a| AccountDetails {
-					nonce: chain.latest_nonce(a),
-					balance: chain.latest_balance(a),
+					nonce: chain.latest_nonce(a).expect("Best block state is not pending, checked above; qed"),
+					balance: chain.latest_balance(a).expect("Best block state is not pending, checked above; qed"),
 				});
 			});
 		}
@@ -600,11 +805,14 @@ impl MinerService for Miner {
 						})
 						.collect::<HashSet<Address>>();
 				for sender in to_remove.into_iter() {
-					transaction_queue.remove_all(sender, chain.latest_nonce(&sender));
+					transaction_queue.remove_all(sender, chain.latest_nonce(&sender).expect("Best block state is not pending, checked above; qed"));
 				}
 			});
 		}
 
+		// Release scheduled transactions which became valid
+		self.import_ready_scheduled_transactions(chain);
+
 		self.update_sealing(chain);
 	}
 }
@@ -612,11 +820,258 @@ impl MinerService for Miner {
 #[cfg(test)]
 mod tests {
 
-	use super::super::MinerService;
-	use super::Miner;
+	use std::time::{Instant, Duration};
+	use super::super::{MinerService, AccountDetails};
+	use super::{Miner, MinerOptions, GasLimitStrategy};
 	use util::*;
 	use client::{TestBlockChainClient, EachBlockWith};
 	use block::*;
+	use transaction::{Transaction, SignedTransaction, Action};
+	use miner::TransactionCondition;
+	use spec::Spec;
+	use instant_seal::new_instant_seal;
+	use tests::helpers::generate_dummy_client;
+
+	fn transaction() -> SignedTransaction {
+		let keypair = KeyPair::create().unwrap();
+		Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret())
+	}
+
+	fn account_details(_address: &Address) -> AccountDetails {
+		AccountDetails {
+			nonce: U256::zero(),
+			balance: !U256::zero(),
+		}
+	}
+
+	#[test]
+	fn should_always_seal_with_instant_seal_engine() {
+		// given
+		let miner = Miner::new(MinerOptions::default(), new_instant_seal());
+
+		// then
+		assert!(miner.sealing_enabled.load(atomic::Ordering::Relaxed));
+	}
+
+	#[test]
+	fn should_reseal_on_external_transactions() {
+		// given
+		let client_result = generate_dummy_client(0);
+		let client = client_result.reference();
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			..MinerOptions::default()
+		}, Spec::new_test());
+
+		// when
+		let res = miner.import_transactions(&**client, vec![transaction()], account_details);
+
+		// then
+		assert!(res[0].is_ok());
+		assert_eq!(miner.status().transactions_in_pending_block, 1);
+	}
+
+	#[test]
+	fn should_not_reseal_on_external_transactions_if_disabled() {
+		// given
+		let client_result = generate_dummy_client(0);
+		let client = client_result.reference();
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			reseal_on_external_tx: false,
+			..MinerOptions::default()
+		}, Spec::new_test());
+
+		// when
+		let res = miner.import_transactions(&**client, vec![transaction()], account_details);
+
+		// then
+		assert!(res[0].is_ok());
+		assert_eq!(miner.status().transactions_in_pending_queue, 1);
+		assert!(miner.sealing_work.lock().unwrap().peek_last_ref().is_none());
+	}
+
+	#[test]
+	fn should_not_reseal_within_minimal_period() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			reseal_min_period: Duration::from_secs(3600),
+			..MinerOptions::default()
+		}, Spec::new_test());
+		// simulate recent reseal
+		*miner.next_allowed_reseal.lock().unwrap() = Instant::now() + miner.options.reseal_min_period;
+
+		// when
+		let res = miner.import_own_transaction(&client, transaction(), account_details);
+
+		// then
+		assert!(res.is_ok());
+		assert!(!miner.tx_reseal_allowed());
+		assert_eq!(miner.status().transactions_in_pending_queue, 1);
+	}
+
+	#[test]
+	fn should_seal_throttled_transaction_once_minimal_period_elapsed() {
+		// given
+		let client_result = generate_dummy_client(0);
+		let client = client_result.reference();
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			reseal_min_period: Duration::from_secs(3600),
+			..MinerOptions::default()
+		}, Spec::new_test());
+		*miner.next_allowed_reseal.lock().unwrap() = Instant::now() + miner.options.reseal_min_period;
+		miner.import_own_transaction(&**client, transaction(), account_details).unwrap();
+		miner.update_sealing_if_due(&**client);
+		assert!(miner.sealing_work.lock().unwrap().peek_last_ref().is_none());
+
+		// when
+		*miner.next_allowed_reseal.lock().unwrap() = Instant::now();
+		miner.update_sealing_if_due(&**client);
+
+		// then
+		assert_eq!(miner.status().transactions_in_pending_block, 1);
+		assert!(!miner.pending_tx_reseal.load(atomic::Ordering::Relaxed));
+	}
+
+	#[test]
+	fn should_update_sealing_only_after_maximal_period() {
+		// given
+		let client_result = generate_dummy_client(0);
+		let client = client_result.reference();
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			reseal_max_period: Some(Duration::from_secs(3600)),
+			..MinerOptions::default()
+		}, Spec::new_test());
+
+		// when
+		miner.update_sealing_if_due(&**client);
+		let sealed_before_period = miner.sealing_work.lock().unwrap().peek_last_ref().is_some();
+		*miner.next_mandatory_reseal.lock().unwrap() = Some(Instant::now());
+		miner.update_sealing_if_due(&**client);
+
+		// then
+		assert!(!sealed_before_period);
+		assert!(miner.sealing_work.lock().unwrap().peek_last_ref().is_some());
+	}
+
+	#[test]
+	fn should_not_update_sealing_if_maximal_period_disabled() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			..MinerOptions::default()
+		}, Spec::new_test());
+
+		// when
+		miner.update_sealing_if_due(&client);
+
+		// then
+		assert!(miner.next_mandatory_reseal.lock().unwrap().is_none());
+		assert!(miner.sealing_work.lock().unwrap().peek_last_ref().is_none());
+	}
+
+	#[test]
+	fn should_not_prepare_sealing_while_state_is_pending() {
+		// given
+		let client = TestBlockChainClient::default();
+		client.state_pending.store(true, atomic::Ordering::Relaxed);
+		let miner = Miner::new(MinerOptions {
+			force_sealing: true,
+			..MinerOptions::default()
+		}, Spec::new_test());
+
+		// when
+		let sealing_work = miner.map_sealing_work(&client, |_| ());
+
+		// then
+		assert!(sealing_work.is_none());
+	}
+
+	#[test]
+	fn should_include_scheduled_transactions_in_last_nonce() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::default();
+		let transaction = transaction();
+		let sender = transaction.sender().unwrap();
+		let condition = TransactionCondition { block: Some(100), timestamp: None };
+
+		// when
+		let res = miner.import_scheduled_transaction(&client, transaction, condition, account_details);
+
+		// then
+		assert!(res.is_ok());
+		assert_eq!(miner.status().transactions_in_pending_queue, 0);
+		assert_eq!(miner.last_nonce(&sender), Some(U256::zero()));
+	}
+
+	#[test]
+	fn should_keep_scheduled_transaction_that_failed_to_import() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::default();
+		let transaction = transaction();
+		let condition = TransactionCondition { block: Some(2), timestamp: None };
+		miner.import_scheduled_transaction(&client, transaction.clone(), condition, account_details).unwrap();
+		// the same transaction gets to the queue before its condition is met
+		miner.import_own_transaction(&client, transaction.clone(), account_details).unwrap();
+		client.add_blocks(1, EachBlockWith::Nothing);
+
+		// when
+		miner.import_ready_scheduled_transactions(&client);
+
+		// then
+		let scheduled = miner.scheduled_transactions();
+		assert_eq!(scheduled.len(), 1);
+		assert_eq!(scheduled[0].transaction, transaction);
+		assert!(scheduled[0].error.is_some());
+	}
+
+	#[test]
+	fn should_target_gas_range_between_floor_and_ceiling() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::default();
+		miner.set_gas_floor_target(U256::from(3_000_000));
+		miner.set_gas_ceil_target(U256::from(5_000_000));
+
+		// when
+		let range = miner.gas_range_target(&client);
+
+		// then
+		assert_eq!(range, (U256::from(3_000_000), U256::from(5_000_000)));
+	}
+
+	#[test]
+	fn should_target_gas_floor_when_recent_blocks_are_empty() {
+		// given
+		let client = TestBlockChainClient::default();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let miner = Miner::new(MinerOptions {
+			gas_limit_strategy: GasLimitStrategy::Fullness(5),
+			..MinerOptions::default()
+		}, Spec::new_test());
+		miner.set_gas_floor_target(U256::from(500_000));
+		miner.set_gas_ceil_target(U256::from(2_000_000));
+
+		// when
+		let range = miner.gas_range_target(&client);
+
+		// then
+		assert_eq!(range, (U256::from(500_000), U256::from(500_000)));
+	}
 
 	// TODO [ToDr] To uncomment` when TestBlockChainClient can actually return a ClosedBlock.
 	#[ignore]
@@ -44,9 +44,13 @@
 mod miner;
 mod external;
 mod transaction_queue;
+mod transaction_policy;
+mod scheduled_transactions;
 
 pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionImportResult, TransactionOrigin};
-pub use self::miner::{Miner};
+pub use self::transaction_policy::{TransactionPolicy, DefaultPolicy, ConfigurablePolicy, AccessList};
+pub use self::scheduled_transactions::{TransactionCondition, ScheduledTransaction, ScheduledTransactions};
+pub use self::miner::{Miner, MinerOptions, GasLimitStrategy};
 pub use self::external::{ExternalMiner, ExternalMinerService};
 
 use std::collections::BTreeMap;
@@ -87,6 +91,12 @@ pub trait MinerService : Send + Sync {
 	/// Set the gas limit we wish to target when sealing a new block.
 	fn set_gas_floor_target(&self, target: U256);
 
+	/// Get the maximal gas limit we wish to target when sealing a new block.
+	fn gas_ceil_target(&self) -> U256;
+
+	/// Set the maximal gas limit we wish to target when sealing a new block.
+	fn set_gas_ceil_target(&self, target: U256);
+
 	/// Get current transactions limit in queue.
 	fn transactions_limit(&self) -> usize;
 
@@ -103,6 +113,18 @@ pub trait MinerService : Send + Sync {
 		Result<TransactionImportResult, Error>
 		where T: Fn(&Address) -> AccountDetails, Self: Sized;
 
+	/// Holds own (node owner) transaction until `condition` is met and then imports it to queue.
+	/// If the condition is already met transaction is imported immediately.
+	fn import_scheduled_transaction<T>(&self, chain: &MiningBlockChainClient, transaction: SignedTransaction, condition: TransactionCondition, fetch_account: T) ->
+		Result<(), Error>
+		where T: Fn(&Address) -> AccountDetails, Self: Sized;
+
+	/// Returns transactions waiting for their conditions.
+	fn scheduled_transactions(&self) -> Vec<ScheduledTransaction>;
+
+	/// Cancels scheduled transaction. Returns the transaction if it was still waiting.
+	fn remove_scheduled_transaction(&self, hash: &H256) -> Option<ScheduledTransaction>;
+
 	/// Returns hashes of transactions currently in pending
 	fn pending_transactions_hashes(&self) -> Vec<H256>;
 
@@ -129,6 +151,9 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of all transactions.
 	fn all_transactions(&self) -> Vec<SignedTransaction>;
 
+	/// Get a list of transactions submitted locally (not received from the network).
+	fn local_transactions(&self) -> Vec<SignedTransaction>;
+
 	/// Get a list of all pending transactions.
 	fn pending_transactions(&self) -> Vec<SignedTransaction>;
 
@@ -136,6 +161,7 @@ pub trait MinerService : Send + Sync {
 	fn pending_receipts(&self) -> BTreeMap<H256, Receipt>;
 
 	/// Returns highest transaction nonce for given address.
+	/// Includes transactions that are scheduled and still waiting for their condition.
 	fn last_nonce(&self, address: &Address) -> Option<U256>;
 
 	/// Suggested gas price.
@@ -145,16 +171,19 @@ pub trait MinerService : Send + Sync {
 	fn sensible_gas_limit(&self) -> U256 { 21000.into() }
 
 	/// Latest account balance in pending state.
-	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> U256;
+	/// Returns None while the best block state is still being downloaded.
+	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256>;
 
 	/// Call into contract code using pending state.
 	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, ExecutionError>;
 
 	/// Get storage value in pending state.
-	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> H256;
+	/// Returns None while the best block state is still being downloaded.
+	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> Option<H256>;
 
 	/// Get account nonce in pending state.
-	fn nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> U256;
+	/// Returns None while the best block state is still being downloaded.
+	fn nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256>;
 
 	/// Get contract code in pending state.
 	fn code(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<Bytes>;
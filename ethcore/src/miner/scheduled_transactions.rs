@@ -0,0 +1,247 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scheduled transactions.
+//!
+//! Own transactions submitted with a `TransactionCondition` are held by the miner and
+//! inserted to `TransactionQueue` (and thus propagated) only once the condition is met.
+
+use std::collections::HashMap;
+use util::hash::{H256, Address};
+use util::numbers::U256;
+use header::BlockNumber;
+use transaction::SignedTransaction;
+
+/// Condition that has to be met before scheduled transaction is imported to the queue.
+///
+/// All specified constraints have to be satisfied.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransactionCondition {
+	/// Transaction is valid not before the block with given number.
+	pub block: Option<BlockNumber>,
+	/// Transaction is valid not before given UNIX timestamp (compared with timestamp of the best block).
+	pub timestamp: Option<u64>,
+}
+
+impl TransactionCondition {
+	/// Checks if the condition is met for the block with given number and timestamp
+	/// (so the transaction can be included in its child).
+	pub fn is_met(&self, number: BlockNumber, timestamp: u64) -> bool {
+		self.block.map_or(true, |block| number + 1 >= block)
+			&& self.timestamp.map_or(true, |time| timestamp >= time)
+	}
+}
+
+/// Transaction waiting for its condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledTransaction {
+	/// Signed transaction
+	pub transaction: SignedTransaction,
+	/// Condition to be met
+	pub condition: TransactionCondition,
+	/// Reason of the rejection if the condition was met but the transaction could not be imported to the queue
+	pub error: Option<String>,
+}
+
+/// Holding area for scheduled transactions.
+#[derive(Default)]
+pub struct ScheduledTransactions {
+	by_hash: HashMap<H256, ScheduledTransaction>,
+}
+
+impl ScheduledTransactions {
+	/// Creates new empty holding area.
+	pub fn new() -> Self {
+		ScheduledTransactions::default()
+	}
+
+	/// Holds given transaction until its condition is met.
+	/// Returns `false` if the transaction was already scheduled.
+	pub fn add(&mut self, transaction: SignedTransaction, condition: TransactionCondition) -> bool {
+		let hash = transaction.hash();
+		if self.by_hash.contains_key(&hash) {
+			return false;
+		}
+		self.by_hash.insert(hash, ScheduledTransaction {
+			transaction: transaction,
+			condition: condition,
+			error: None,
+		});
+		true
+	}
+
+	/// Keeps transaction which could not be imported once its condition was met, so the failure can be queried.
+	/// Failed transactions are never released again, they stay until removed.
+	pub fn fail(&mut self, mut scheduled: ScheduledTransaction, error: String) {
+		scheduled.error = Some(error);
+		self.by_hash.insert(scheduled.transaction.hash(), scheduled);
+	}
+
+	/// Cancels scheduled transaction.
+	pub fn remove(&mut self, hash: &H256) -> Option<ScheduledTransaction> {
+		self.by_hash.remove(hash)
+	}
+
+	/// Returns all scheduled transactions.
+	pub fn all(&self) -> Vec<ScheduledTransaction> {
+		self.by_hash.values().cloned().collect()
+	}
+
+	/// Returns number of scheduled transactions.
+	pub fn len(&self) -> usize {
+		self.by_hash.len()
+	}
+
+	/// Returns true if there are no scheduled transactions.
+	pub fn is_empty(&self) -> bool {
+		self.by_hash.is_empty()
+	}
+
+	/// Returns highest nonce of transactions from given sender that are still waiting for their condition.
+	pub fn last_nonce(&self, sender: &Address) -> Option<U256> {
+		self.by_hash.values()
+			.filter(|scheduled| scheduled.error.is_none())
+			.filter(|scheduled| scheduled.transaction.sender().ok().map_or(false, |s| &s == sender))
+			.map(|scheduled| scheduled.transaction.nonce)
+			.max()
+	}
+
+	/// Removes and returns transactions which condition is met by the block with given number and timestamp.
+	/// Failed transactions are not returned. Transactions are ordered by nonce.
+	pub fn drain_ready(&mut self, number: BlockNumber, timestamp: u64) -> Vec<ScheduledTransaction> {
+		let ready = self.by_hash.iter()
+			.filter(|&(_, scheduled)| scheduled.error.is_none() && scheduled.condition.is_met(number, timestamp))
+			.map(|(hash, _)| *hash)
+			.collect::<Vec<_>>();
+
+		let mut transactions = ready.into_iter()
+			.filter_map(|hash| self.by_hash.remove(&hash))
+			.collect::<Vec<_>>();
+		transactions.sort_by(|a, b| a.transaction.nonce.cmp(&b.transaction.nonce));
+		transactions
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use transaction::*;
+	use super::*;
+
+	fn new_tx(nonce: U256) -> SignedTransaction {
+		new_tx_from(&KeyPair::create().unwrap(), nonce)
+	}
+
+	fn new_tx_from(keypair: &KeyPair, nonce: U256) -> SignedTransaction {
+		Transaction {
+			action: Action::Create,
+			value: U256::from(100),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::one(),
+			nonce: nonce,
+		}.sign(keypair.secret())
+	}
+
+	#[test]
+	fn should_check_conditions() {
+		let by_block = TransactionCondition { block: Some(10), timestamp: None };
+		let by_time = TransactionCondition { block: None, timestamp: Some(1000) };
+		let both = TransactionCondition { block: Some(10), timestamp: Some(1000) };
+
+		assert!(!by_block.is_met(8, 2000));
+		assert!(by_block.is_met(9, 0));
+		assert!(!by_time.is_met(100, 999));
+		assert!(by_time.is_met(0, 1000));
+		assert!(!both.is_met(9, 999));
+		assert!(both.is_met(9, 1000));
+		assert!(TransactionCondition::default().is_met(0, 0));
+	}
+
+	#[test]
+	fn should_drain_only_ready_transactions() {
+		// given
+		let mut scheduled = ScheduledTransactions::new();
+		let tx1 = new_tx(U256::from(1));
+		let tx2 = new_tx(U256::from(0));
+		let tx3 = new_tx(U256::from(2));
+		assert!(scheduled.add(tx1.clone(), TransactionCondition { block: Some(5), timestamp: None }));
+		assert!(scheduled.add(tx2.clone(), TransactionCondition { block: None, timestamp: Some(100) }));
+		assert!(scheduled.add(tx3.clone(), TransactionCondition { block: Some(50), timestamp: None }));
+		assert!(!scheduled.add(tx3.clone(), TransactionCondition::default()));
+
+		// when
+		let ready = scheduled.drain_ready(10, 100);
+
+		// then
+		assert_eq!(ready.into_iter().map(|s| s.transaction).collect::<Vec<_>>(), vec![tx2, tx1]);
+		assert_eq!(scheduled.len(), 1);
+		assert_eq!(scheduled.all()[0].transaction, tx3);
+	}
+
+	#[test]
+	fn should_cancel_scheduled_transaction() {
+		// given
+		let mut scheduled = ScheduledTransactions::new();
+		let tx = new_tx(U256::zero());
+		scheduled.add(tx.clone(), TransactionCondition { block: Some(5), timestamp: None });
+
+		// when
+		let removed = scheduled.remove(&tx.hash());
+
+		// then
+		assert_eq!(removed.map(|s| s.transaction), Some(tx));
+		assert!(scheduled.is_empty());
+	}
+
+	#[test]
+	fn should_keep_failed_transactions_without_releasing_them() {
+		// given
+		let mut scheduled = ScheduledTransactions::new();
+		let tx = new_tx(U256::zero());
+		scheduled.add(tx.clone(), TransactionCondition { block: Some(5), timestamp: None });
+		let ready = scheduled.drain_ready(10, 0).pop().unwrap();
+
+		// when
+		scheduled.fail(ready, "AlreadyImported".into());
+
+		// then
+		assert_eq!(scheduled.len(), 1);
+		assert_eq!(scheduled.all()[0].error, Some("AlreadyImported".into()));
+		assert!(scheduled.drain_ready(20, 0).is_empty());
+	}
+
+	#[test]
+	fn should_return_highest_nonce_of_waiting_transactions() {
+		// given
+		let mut scheduled = ScheduledTransactions::new();
+		let keypair = KeyPair::create().unwrap();
+		let sender = keypair.address();
+		let tx4 = new_tx_from(&keypair, U256::from(4));
+		scheduled.add(new_tx_from(&keypair, U256::from(3)), TransactionCondition { block: Some(5), timestamp: None });
+		scheduled.add(tx4.clone(), TransactionCondition { block: Some(50), timestamp: None });
+		scheduled.add(new_tx(U256::from(10)), TransactionCondition { block: Some(50), timestamp: None });
+		assert_eq!(scheduled.last_nonce(&sender), Some(U256::from(4)));
+
+		// when
+		let failed = scheduled.drain_ready(10, 0).pop().unwrap();
+		scheduled.fail(failed, "Old".into());
+		scheduled.remove(&tx4.hash());
+
+		// then
+		assert_eq!(scheduled.last_nonce(&sender), None);
+	}
+}
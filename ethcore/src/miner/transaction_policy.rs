@@ -0,0 +1,235 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction admission and ordering policies.
+//!
+//! `TransactionQueue` always orders transactions by nonce height first (otherwise transactions
+//! from the same sender could not be included in a block) and always enforces minimal gas price,
+//! gas limit and sender balance. Everything else - which transactions are accepted and how
+//! transactions with equal nonce height are prioritized - is decided by a `TransactionPolicy`.
+
+use std::collections::HashSet;
+use util::numbers::U256;
+use util::hash::Address;
+use transaction::{Action, SignedTransaction};
+use error::TransactionError;
+use miner::TransactionOrigin;
+
+/// Decides which transactions are admitted to `TransactionQueue` and how they are scored.
+pub trait TransactionPolicy: Send + Sync {
+	/// Checks if transaction can be imported to the queue.
+	///
+	/// `queued` is the number of transactions from the same sender already in the queue
+	/// (not counting a transaction with the same nonce, which would be replaced).
+	fn check(&self, tx: &SignedTransaction, sender: &Address, queued: usize, origin: TransactionOrigin) -> Result<(), TransactionError>;
+
+	/// Returns `true` if transactions from given sender should be processed before other
+	/// transactions with the same nonce height.
+	fn is_priority(&self, _sender: &Address) -> bool {
+		false
+	}
+
+	/// Returns the score of the transaction.
+	/// Transactions with higher score (and the same nonce height and priority) are processed first.
+	fn score(&self, tx: &SignedTransaction) -> U256 {
+		tx.gas_price
+	}
+}
+
+/// Default policy: admits every transaction and scores transactions by gas price.
+#[derive(Debug, Default, Clone)]
+pub struct DefaultPolicy;
+
+impl TransactionPolicy for DefaultPolicy {
+	fn check(&self, _tx: &SignedTransaction, _sender: &Address, _queued: usize, _origin: TransactionOrigin) -> Result<(), TransactionError> {
+		Ok(())
+	}
+}
+
+/// List of addresses either allowed or denied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessList {
+	/// Every address is allowed.
+	All,
+	/// Only listed addresses are allowed.
+	Allow(HashSet<Address>),
+	/// All but listed addresses are allowed.
+	Deny(HashSet<Address>),
+}
+
+impl Default for AccessList {
+	fn default() -> Self {
+		AccessList::All
+	}
+}
+
+impl AccessList {
+	/// Checks if address is allowed by this list.
+	pub fn is_allowed(&self, address: &Address) -> bool {
+		match *self {
+			AccessList::All => true,
+			AccessList::Allow(ref list) => list.contains(address),
+			AccessList::Deny(ref list) => !list.contains(address),
+		}
+	}
+}
+
+/// Policy configured by the node operator (i.e. from CLI).
+#[derive(Debug, Default, Clone)]
+pub struct ConfigurablePolicy {
+	/// Maximal number of transactions from single sender kept in the queue.
+	pub max_per_sender: Option<usize>,
+	/// Maximal gas a single transaction may declare.
+	pub max_gas: Option<U256>,
+	/// Senders that are allowed to import transactions.
+	pub senders: AccessList,
+	/// Recipients that transactions are allowed to be sent to.
+	/// Contract creations are not affected by this list.
+	pub recipients: AccessList,
+	/// Senders which transactions are prioritized.
+	pub priority_senders: HashSet<Address>,
+}
+
+impl TransactionPolicy for ConfigurablePolicy {
+	fn check(&self, tx: &SignedTransaction, sender: &Address, queued: usize, origin: TransactionOrigin) -> Result<(), TransactionError> {
+		if !self.senders.is_allowed(sender) {
+			trace!(target: "miner", "Dropping transaction from not allowed sender: {:?} ({})", tx.hash(), sender);
+			return Err(TransactionError::SenderBanned);
+		}
+
+		if let Action::Call(ref to) = tx.action {
+			if !self.recipients.is_allowed(to) {
+				trace!(target: "miner", "Dropping transaction to not allowed recipient: {:?} ({})", tx.hash(), to);
+				return Err(TransactionError::RecipientBanned);
+			}
+		}
+
+		if let Some(max_gas) = self.max_gas {
+			if tx.gas > max_gas {
+				trace!(target: "miner", "Dropping transaction above per-transaction gas cap: {:?} ({} > {})", tx.hash(), tx.gas, max_gas);
+				return Err(TransactionError::GasLimitExceeded {
+					limit: max_gas,
+					got: tx.gas,
+				});
+			}
+		}
+
+		// Own transactions are never limited per sender.
+		if let (Some(limit), TransactionOrigin::External) = (self.max_per_sender, origin) {
+			if queued >= limit {
+				trace!(target: "miner", "Dropping transaction above per-sender limit: {:?} ({} >= {})", tx.hash(), queued, limit);
+				return Err(TransactionError::SenderLimitReached { limit: limit });
+			}
+		}
+
+		Ok(())
+	}
+
+	fn is_priority(&self, sender: &Address) -> bool {
+		self.priority_senders.contains(sender)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use transaction::*;
+	use error::TransactionError;
+	use miner::TransactionOrigin;
+	use super::*;
+
+	fn new_tx(action: Action, gas: U256) -> (SignedTransaction, Address) {
+		let keypair = KeyPair::create().unwrap();
+		let tx = Transaction {
+			action: action,
+			value: U256::from(100),
+			data: vec![],
+			gas: gas,
+			gas_price: U256::one(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret());
+		(tx, keypair.address())
+	}
+
+	#[test]
+	fn default_policy_should_accept_everything() {
+		// given
+		let policy = DefaultPolicy;
+		let (tx, sender) = new_tx(Action::Create, U256::from(100_000));
+
+		// when
+		let res = policy.check(&tx, &sender, 1_000_000, TransactionOrigin::External);
+
+		// then
+		assert_eq!(res, Ok(()));
+		assert_eq!(policy.score(&tx), U256::one());
+		assert!(!policy.is_priority(&sender));
+	}
+
+	#[test]
+	fn should_reject_not_allowed_senders_and_recipients() {
+		// given
+		let recipient = Address::from(5);
+		let (tx, sender) = new_tx(Action::Call(recipient), U256::from(100_000));
+		let mut policy = ConfigurablePolicy::default();
+
+		// when
+		policy.senders = AccessList::Deny(vec![sender].into_iter().collect());
+		let res1 = policy.check(&tx, &sender, 0, TransactionOrigin::External);
+		policy.senders = AccessList::All;
+		policy.recipients = AccessList::Allow(vec![Address::from(6)].into_iter().collect());
+		let res2 = policy.check(&tx, &sender, 0, TransactionOrigin::External);
+
+		// then
+		assert_eq!(res1, Err(TransactionError::SenderBanned));
+		assert_eq!(res2, Err(TransactionError::RecipientBanned));
+	}
+
+	#[test]
+	fn should_reject_transactions_above_gas_cap() {
+		// given
+		let (tx, sender) = new_tx(Action::Create, U256::from(100_000));
+		let mut policy = ConfigurablePolicy::default();
+		policy.max_gas = Some(U256::from(50_000));
+
+		// when
+		let res = policy.check(&tx, &sender, 0, TransactionOrigin::External);
+
+		// then
+		assert_eq!(res, Err(TransactionError::GasLimitExceeded {
+			limit: U256::from(50_000),
+			got: U256::from(100_000),
+		}));
+	}
+
+	#[test]
+	fn should_limit_external_transactions_per_sender() {
+		// given
+		let (tx, sender) = new_tx(Action::Create, U256::from(100_000));
+		let mut policy = ConfigurablePolicy::default();
+		policy.max_per_sender = Some(2);
+
+		// when
+		let res1 = policy.check(&tx, &sender, 1, TransactionOrigin::External);
+		let res2 = policy.check(&tx, &sender, 2, TransactionOrigin::External);
+		let res3 = policy.check(&tx, &sender, 2, TransactionOrigin::Local);
+
+		// then
+		assert_eq!(res1, Ok(()));
+		assert_eq!(res2, Err(TransactionError::SenderLimitReached { limit: 2 }));
+		assert_eq!(res3, Ok(()));
+	}
+}
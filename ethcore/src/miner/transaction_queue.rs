@@ -18,8 +18,9 @@
 //!
 //! `TransactionQueue` keeps track of all transactions seen by the node (received from other peers) and own transactions
 //! and orders them by priority. Top priority transactions are those with low nonce height (difference between
-//! transaction's nonce and next nonce expected from this sender). If nonces are equal transaction's gas price is used
-//! for comparison (higher gas price = higher priority).
+//! transaction's nonce and next nonce expected from this sender). If nonces are equal transaction's score is used
+//! for comparison (higher score = higher priority). Admission and scoring of transactions is delegated to
+//! a `TransactionPolicy` (by default transactions are scored by gas price).
 //!
 //! # Usage Example
 //!
@@ -90,6 +91,7 @@ use util::hash::{Address, H256};
 use util::table::*;
 use transaction::*;
 use error::{Error, TransactionError};
+use miner::transaction_policy::{TransactionPolicy, DefaultPolicy};
 
 /// Transaction origin
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -127,8 +129,13 @@ struct TransactionOrder {
 	/// (e.g. Tx(nonce:5), State(nonce:0) -> height: 5)
 	/// High nonce_height = Low priority (processed later)
 	nonce_height: U256,
+	/// Whether the sender is prioritized by the queue policy.
+	priority: bool,
+	/// Score of the transaction given by the queue policy.
+	/// Low score = Low priority (processed later)
+	score: U256,
 	/// Gas Price of the transaction.
-	/// Low gas price = Low priority (processed later)
+	/// Used to decide which transaction stays when (sender, nonce) collides.
 	gas_price: U256,
 	/// Hash to identify associated transaction
 	hash: H256,
@@ -141,6 +148,8 @@ impl TransactionOrder {
 	fn for_transaction(tx: &VerifiedTransaction, base_nonce: U256) -> Self {
 		TransactionOrder {
 			nonce_height: tx.nonce() - base_nonce,
+			priority: tx.priority,
+			score: tx.score,
 			gas_price: tx.transaction.gas_price,
 			hash: tx.hash(),
 			origin: tx.origin,
@@ -178,11 +187,16 @@ impl Ord for TransactionOrder {
 			return self.origin.cmp(&b.origin);
 		}
 
-		// Then compare gas_prices
-		let a_gas = self.gas_price;
-		let b_gas = b.gas_price;
-		if a_gas != b_gas {
-			return b_gas.cmp(&a_gas);
+		// Then senders prioritized by the policy
+		if self.priority != b.priority {
+			return b.priority.cmp(&self.priority);
+		}
+
+		// Then compare scores
+		let a_score = self.score;
+		let b_score = b.score;
+		if a_score != b_score {
+			return b_score.cmp(&a_score);
 		}
 
 		// Compare hashes
@@ -196,16 +210,29 @@ struct VerifiedTransaction {
 	transaction: SignedTransaction,
 	/// transaction origin
 	origin: TransactionOrigin,
+	/// Whether the sender is prioritized by the queue policy
+	priority: bool,
+	/// Transaction score (defaults to gas price)
+	score: U256,
 }
 impl VerifiedTransaction {
 	fn new(transaction: SignedTransaction, origin: TransactionOrigin) -> Result<Self, Error> {
 		try!(transaction.sender());
+		let score = transaction.gas_price;
 		Ok(VerifiedTransaction {
 			transaction: transaction,
 			origin: origin,
+			priority: false,
+			score: score,
 		})
 	}
 
+	/// Updates priority and score according to given policy.
+	fn apply_policy(&mut self, policy: &TransactionPolicy) {
+		self.priority = policy.is_priority(&self.sender());
+		self.score = policy.score(&self.transaction);
+	}
+
 	fn hash(&self) -> H256 {
 		self.transaction.hash()
 	}
@@ -341,6 +368,8 @@ pub struct TransactionQueue {
 	by_hash: HashMap<H256, VerifiedTransaction>,
 	/// Last nonce of transaction in current (to quickly check next expected transaction)
 	last_nonces: HashMap<Address, U256>,
+	/// Admission and scoring policy
+	policy: Box<TransactionPolicy>,
 }
 
 impl Default for TransactionQueue {
@@ -376,9 +405,16 @@ impl TransactionQueue {
 			future: future,
 			by_hash: HashMap::new(),
 			last_nonces: HashMap::new(),
+			policy: Box::new(DefaultPolicy),
 		}
 	}
 
+	/// Sets new admission and scoring policy.
+	/// Transactions already imported to the queue are not affected.
+	pub fn set_policy(&mut self, policy: Box<TransactionPolicy>) {
+		self.policy = policy;
+	}
+
 	/// Set the new limit for `current` and `future` queue.
 	pub fn set_limit(&mut self, limit: usize) {
 		self.current.set_limit(limit);
@@ -455,8 +491,9 @@ impl TransactionQueue {
 			}));
 		}
 
-		let vtx = try!(VerifiedTransaction::new(tx, origin));
-		let client_account = fetch_account(&vtx.sender());
+		let mut vtx = try!(VerifiedTransaction::new(tx, origin));
+		let sender = vtx.sender();
+		let client_account = fetch_account(&sender);
 
 		let cost = vtx.transaction.value + vtx.transaction.gas_price * vtx.transaction.gas;
 		if client_account.balance < cost {
@@ -468,9 +505,21 @@ impl TransactionQueue {
 			}));
 		}
 
+		let queued = self.queued_from_sender(&sender, &vtx.nonce());
+		try!(self.policy.check(&vtx.transaction, &sender, queued, origin));
+		vtx.apply_policy(&*self.policy);
+
 		self.import_tx(vtx, client_account.nonce).map_err(Error::Transaction)
 	}
 
+	/// Returns number of transactions from given sender (both in `current` and `future`)
+	/// excluding the transaction with given nonce (if any).
+	fn queued_from_sender(&self, sender: &Address, except_nonce: &U256) -> usize {
+		[&self.current, &self.future].iter()
+			.filter_map(|set| set.by_address.row(sender))
+			.fold(0, |count, row| count + row.len() - if row.contains_key(except_nonce) { 1 } else { 0 })
+	}
+
 	/// Removes all transactions from particular sender up to (excluding) given client (state) nonce.
 	/// Client (State) Nonce = next valid nonce for this sender.
 	pub fn remove_all(&mut self, sender: Address, client_nonce: U256) {
@@ -581,6 +630,16 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Returns local transactions from current, ordered by priority.
+	pub fn local_transactions(&self) -> Vec<SignedTransaction> {
+		self.current.by_priority
+			.iter()
+			.filter(|t| t.origin == TransactionOrigin::Local)
+			.map(|t| self.by_hash.get(&t.hash).expect("All transactions in `current` and `future` are always included in `by_hash`"))
+			.map(|t| t.transaction.clone())
+			.collect()
+	}
+
 	/// Returns true if there is at least one local transaction pending
 	pub fn has_local_pending_transactions(&self) -> bool {
 		self.current.by_priority.iter().any(|tx| tx.origin == TransactionOrigin::Local)
@@ -781,6 +840,7 @@ mod test {
 	use error::{Error, TransactionError};
 	use super::*;
 	use super::{TransactionSet, TransactionOrder, VerifiedTransaction};
+	use miner::transaction_policy::ConfigurablePolicy;
 
 	fn unwrap_tx_err(err: Result<TransactionImportResult, Error>) -> TransactionError {
 		match err.unwrap_err() {
@@ -1107,6 +1167,21 @@ mod test {
 		assert_eq!(top.len(), 2);
 	}
 
+	#[test]
+	fn should_return_only_local_transactions() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let (tx, tx2) = new_txs(U256::from(1));
+
+		// when
+		txq.add(tx.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+		txq.add(tx2.clone(), &default_nonce, TransactionOrigin::Local).unwrap();
+
+		// then
+		assert_eq!(txq.local_transactions(), vec![tx2]);
+		assert_eq!(txq.top_transactions().len(), 2);
+	}
+
 	#[test]
 	fn should_not_prioritize_local_transactions_with_different_nonce_height() {
 		// given
@@ -1626,4 +1701,46 @@ mod test {
 		assert_eq!(txq.current.by_priority.len(), 3);
 	}
 
+	#[test]
+	fn should_prioritize_senders_given_by_policy() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let tx1 = {
+			let mut tx = new_unsigned_tx(default_nonce_val());
+			tx.gas_price = U256::from(10);
+			tx.sign(KeyPair::create().unwrap().secret())
+		};
+		let tx2 = new_tx();
+		let mut policy = ConfigurablePolicy::default();
+		policy.priority_senders.insert(tx2.sender().unwrap());
+		txq.set_policy(Box::new(policy));
+
+		// when
+		txq.add(tx1.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+		txq.add(tx2.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+
+		// then
+		let top = txq.top_transactions();
+		assert_eq!(top[0], tx2);
+		assert_eq!(top[1], tx1);
+	}
+
+	#[test]
+	fn should_enforce_per_sender_limit_from_policy() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let (tx1, tx2) = new_txs(U256::from(1));
+		let mut policy = ConfigurablePolicy::default();
+		policy.max_per_sender = Some(1);
+		txq.set_policy(Box::new(policy));
+
+		// when
+		txq.add(tx1.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+		let res = txq.add(tx2, &default_nonce, TransactionOrigin::External);
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::SenderLimitReached { limit: 1 });
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.top_transactions()[0], tx1);
+	}
 }
@@ -119,15 +119,20 @@ struct ClientIoHandler {
 
 const CLIENT_TICK_TIMER: TimerToken = 0;
 const CLIENT_TICK_MS: u64 = 5000;
+const MINER_RESEAL_TIMER: TimerToken = 1;
+const MINER_RESEAL_MS: u64 = 500;
 
 impl IoHandler<NetSyncMessage> for ClientIoHandler {
 	fn initialize(&self, io: &IoContext<NetSyncMessage>) {
 		io.register_timer(CLIENT_TICK_TIMER, CLIENT_TICK_MS).expect("Error registering client timer");
+		io.register_timer(MINER_RESEAL_TIMER, MINER_RESEAL_MS).expect("Error registering miner reseal timer");
 	}
 
 	fn timeout(&self, _io: &IoContext<NetSyncMessage>, timer: TimerToken) {
-		if timer == CLIENT_TICK_TIMER {
-			self.client.tick();
+		match timer {
+			CLIENT_TICK_TIMER => self.client.tick(),
+			MINER_RESEAL_TIMER => self.client.update_sealing_if_due(),
+			_ => {}
 		}
 	}
 
@@ -25,6 +25,7 @@ use super::genesis::Genesis;
 use super::seal::Generic as GenericSeal;
 use ethereum;
 use basic_authority::BasicAuthority;
+use instant_seal::InstantSeal;
 use ethjson;
 
 /// Parameters common to all engines.
@@ -38,6 +39,8 @@ pub struct CommonParams {
 	pub network_id: U256,
 	/// Minimum gas limit.
 	pub min_gas_limit: U256,
+	/// Blocks expected on our side of a chain split.
+	pub fork_checkpoints: Vec<ForkCheckpoint>,
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -47,6 +50,37 @@ impl From<ethjson::spec::Params> for CommonParams {
 			maximum_extra_data_size: p.maximum_extra_data_size.into(),
 			network_id: p.network_id.into(),
 			min_gas_limit: p.min_gas_limit.into(),
+			fork_checkpoints: p.fork_checkpoints.map_or_else(Vec::new, |c| c.into_iter().map(From::from).collect()),
+		}
+	}
+}
+
+/// Block that distinguishes our chain from the other side of a contentious fork.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForkCheckpoint {
+	/// Block number.
+	pub number: BlockNumber,
+	/// Expected block hash, if known.
+	pub hash: Option<H256>,
+	/// Expected block extra data, if any.
+	pub extra_data: Option<Bytes>,
+}
+
+impl ForkCheckpoint {
+	/// Returns true if the header is the checkpoint block of our chain.
+	pub fn matches(&self, header: &Header) -> bool {
+		header.number() == self.number
+			&& self.hash.map_or(true, |h| h == header.hash())
+			&& self.extra_data.as_ref().map_or(true, |d| d == header.extra_data())
+	}
+}
+
+impl From<ethjson::spec::ForkCheckpoint> for ForkCheckpoint {
+	fn from(c: ethjson::spec::ForkCheckpoint) -> Self {
+		ForkCheckpoint {
+			number: c.number.into(),
+			hash: c.hash.map(Into::into),
+			extra_data: c.extra_data.map(Into::into),
 		}
 	}
 }
@@ -101,10 +135,15 @@ impl From<ethjson::spec::Spec> for Spec {
 		let g = Genesis::from(s.genesis);
 		let seal: GenericSeal = g.seal.into();
 		let params = CommonParams::from(s.params);
+		let engine = Spec::engine(s.engine, params.clone(), builtins);
+		for checkpoint in &params.fork_checkpoints {
+			// Peers on the chain we can actually validate would be dropped otherwise
+			assert!(engine.enacts_fork(checkpoint.number), "Fork checkpoint #{} is not enacted by the {} engine", checkpoint.number, engine.name());
+		}
 		Spec {
 			name: s.name.into(),
-			params: params.clone(),
-			engine: Spec::engine(s.engine, params, builtins),
+			params: params,
+			engine: engine,
 			nodes: s.nodes.unwrap_or_else(Vec::new),
 			parent_hash: g.parent_hash,
 			transactions_root: g.transactions_root,
@@ -129,6 +168,7 @@ impl Spec {
 	fn engine(engine_spec: ethjson::spec::Engine, params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Box<Engine> {
 		match engine_spec {
 			ethjson::spec::Engine::Null => Box::new(NullEngine::new(params, builtins)),
+			ethjson::spec::Engine::InstantSeal => Box::new(InstantSeal::new(params, builtins)),
 			ethjson::spec::Engine::Ethash(ethash) => Box::new(ethereum::Ethash::new(params, From::from(ethash.params), builtins)),
 			ethjson::spec::Engine::BasicAuthority(basic_authority) => Box::new(BasicAuthority::new(params, From::from(basic_authority.params), builtins)),
 		}
@@ -257,6 +297,7 @@ mod tests {
 	use util::hash::*;
 	use util::sha3::*;
 	use views::*;
+	use header::Header;
 	use super::*;
 
 	#[test]
@@ -267,4 +308,30 @@ mod tests {
 		let genesis = test_spec.genesis_block();
 		assert_eq!(BlockView::new(&genesis).header_view().sha3(), H256::from_str("0cd786a2425d16f152c658316c423e6ce1181e15c3295826d7c9904cba9ce303").unwrap());
 	}
+
+	#[test]
+	#[should_panic]
+	fn should_reject_fork_checkpoint_not_enacted_by_engine() {
+		let json = String::from_utf8(include_bytes!("../../res/null.json").to_vec()).unwrap()
+			.replace(r#""networkID" : "0x2""#, r#""networkID" : "0x2", "forkCheckpoints": [{ "number": "0x10", "extraData": "0x666f726b" }]"#);
+		Spec::load(json.as_bytes());
+	}
+
+	#[test]
+	fn fork_checkpoint_matches() {
+		// given
+		let mut header = Header::new();
+		header.set_number(10);
+		header.set_extra_data(b"fork".to_vec());
+		let by_hash = ForkCheckpoint { number: 10, hash: Some(header.hash()), extra_data: None };
+		let by_extra_data = ForkCheckpoint { number: 10, hash: None, extra_data: Some(b"fork".to_vec()) };
+		let other = ForkCheckpoint { number: 10, hash: None, extra_data: Some(b"other".to_vec()) };
+		let wrong_number = ForkCheckpoint { number: 11, hash: None, extra_data: None };
+
+		// then
+		assert!(by_hash.matches(&header));
+		assert!(by_extra_data.matches(&header));
+		assert!(!other.matches(&header));
+		assert!(!wrong_number.matches(&header));
+	}
 }
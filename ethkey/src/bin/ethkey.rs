@@ -23,7 +23,7 @@ use std::{env, fmt, process};
 use std::num::ParseIntError;
 use docopt::Docopt;
 use rustc_serialize::hex::{FromHex, FromHexError};
-use ethkey::{KeyPair, Random, Brain, Prefix, Error as EthkeyError, Generator, Secret, Message, Public, Signature, Address, sign, verify_public, verify_address};
+use ethkey::{KeyPair, Random, Brain, Prefix, Error as EthkeyError, Generator, Secret, Message, Public, Signature, Address, sign, verify_public, verify_address, Mnemonic, ExtendedSecret, DerivationPath};
 
 pub const USAGE: &'static str = r#"
 Ethereum keys generator.
@@ -34,6 +34,8 @@ Usage:
     ethkey generate random [options]
     ethkey generate prefix <prefix> <iterations> [options]
     ethkey generate brain <seed> [options]
+    ethkey generate mnemonic [--words N]
+    ethkey derive <phrase> <path> [--passphrase PASS] [options]
     ethkey sign <secret> <message>
     ethkey verify public <public> <signature> <message>
     ethkey verify address <address> <signature> <message>
@@ -44,6 +46,8 @@ Options:
     -s, --secret       Display only the secret.
     -p, --public       Display only the public.
     -a, --address      Display only the address.
+    --words N          Number of words in mnemonic phrase [default: 12].
+    --passphrase PASS  BIP39 passphrase protecting the mnemonic seed.
 
 Commands:
     info               Display public and address of the secret.
@@ -51,6 +55,8 @@ Commands:
     random             Random generation.
     prefix             Random generation, but address must start with a prefix
     brain              Generate new key from string seed.
+    mnemonic           Generate new BIP39 mnemonic phrase.
+    derive             Derive key at BIP32 path (e.g. m/44'/60'/0'/0/0) from mnemonic phrase.
     sign               Sign message using secret.
     verify             Verify signer of the signature.
 "#;
@@ -62,6 +68,8 @@ struct Args {
 	cmd_random: bool,
 	cmd_prefix: bool,
 	cmd_brain: bool,
+	cmd_mnemonic: bool,
+	cmd_derive: bool,
 	cmd_sign: bool,
 	cmd_verify: bool,
 	cmd_public: bool,
@@ -74,9 +82,13 @@ struct Args {
 	arg_public: String,
 	arg_address: String,
 	arg_signature: String,
+	arg_phrase: String,
+	arg_path: String,
 	flag_secret: bool,
 	flag_public: bool,
 	flag_address: bool,
+	flag_words: usize,
+	flag_passphrase: String,
 }
 
 #[derive(Debug)]
@@ -164,6 +176,9 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 		let secret = try!(Secret::from_str(&args.arg_secret));
 		let keypair = try!(KeyPair::from_secret(secret));
 		Ok(display(keypair, display_mode))
+	} else if args.cmd_generate && args.cmd_mnemonic {
+		let mnemonic = try!(Mnemonic::random(args.flag_words));
+		Ok(format!("{}", mnemonic))
 	} else if args.cmd_generate {
 		let display_mode = DisplayMode::new(&args);
 		let keypair = if args.cmd_random {
@@ -178,6 +193,13 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 			unreachable!();
 		};
 		Ok(display(try!(keypair), display_mode))
+	} else if args.cmd_derive {
+		let display_mode = DisplayMode::new(&args);
+		let mnemonic = try!(Mnemonic::from_str(&args.arg_phrase));
+		let path = try!(DerivationPath::from_str(&args.arg_path));
+		let master = try!(ExtendedSecret::from_seed(&mnemonic.seed(&args.flag_passphrase)));
+		let keypair = try!(try!(master.derive_path(&path)).keypair());
+		Ok(display(keypair, display_mode))
 	} else if args.cmd_sign {
 		let secret = try!(Secret::from_str(&args.arg_secret));
 		let message = try!(Message::from_str(&args.arg_message));
@@ -309,4 +331,26 @@ address: 26d1ec50b4e62c1d1a40d16e7cacc6a6580757d5".to_owned();
 		let expected = "false".to_owned();
 		assert_eq!(execute(command).unwrap(), expected);
 	}
+
+	#[test]
+	fn generate_mnemonic() {
+		let command = vec!["ethkey", "generate", "mnemonic", "--words", "24"]
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+
+		let phrase = execute(command).unwrap();
+		assert_eq!(phrase.split_whitespace().count(), 24);
+	}
+
+	#[test]
+	fn derive() {
+		let command = vec!["ethkey", "derive", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", "m/44'/60'/0'/0/0", "--address"]
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+
+		let expected = "9858effd232b4033e47d90003d41ec34ecaeda94".to_owned();
+		assert_eq!(execute(command).unwrap(), expected);
+	}
 }
@@ -0,0 +1,179 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Elliptic curve Diffie-Hellman and ECIES.
+
+/// ECDH key agreement.
+pub mod ecdh {
+	use secp256k1::{ecdh, key};
+	use {Secret, Public, Error, SECP256K1};
+
+	/// Agree on a shared secret
+	pub fn agree(secret: &Secret, public: &Public) -> Result<Secret, Error> {
+		let context = &SECP256K1;
+		let pdata = {
+			let mut temp = [4u8; 65];
+			(&mut temp[1..65]).copy_from_slice(&public[0..64]);
+			temp
+		};
+
+		let publ = try!(key::PublicKey::from_slice(context, &pdata));
+		let sec = try!(key::SecretKey::from_slice(context, &secret[..]));
+		let shared = ecdh::SharedSecret::new_raw(context, &publ, &sec);
+
+		let mut s = Secret::default();
+		s.copy_from_slice(&shared[0..32]);
+		Ok(s)
+	}
+}
+
+/// ECIES encryption (compatible with `util::crypto::ecies` used by devp2p).
+///
+/// Message layout: `0x04 || ephemeral public (64) || iv (16) || aes-128-ctr ciphertext || hmac-sha256 (32)`.
+pub mod ecies {
+	use rand::{Rng, OsRng};
+	use rcrypto::digest::Digest;
+	use rcrypto::sha2::Sha256;
+	use rcrypto::hmac::Hmac;
+	use rcrypto::mac::Mac;
+	use rcrypto::aes::{ctr, KeySize};
+	use {Secret, Public, Error, Generator, Random};
+	use super::ecdh;
+
+	/// Encrypt a message with a public key.
+	pub fn encrypt(public: &Public, shared_mac: &[u8], plain: &[u8]) -> Result<Vec<u8>, Error> {
+		let r = try!(Random.generate());
+		let z = try!(ecdh::agree(r.secret(), public));
+		let (ekey, mkey) = derive_keys(&z);
+
+		let mut iv = [0u8; 16];
+		try!(OsRng::new()).fill_bytes(&mut iv);
+
+		let mut msg = vec![0u8; 1 + 64 + 16 + plain.len() + 32];
+		msg[0] = 0x04u8;
+		{
+			let msgd = &mut msg[1..];
+			msgd[0..64].copy_from_slice(&r.public()[..]);
+			msgd[64..80].copy_from_slice(&iv);
+			ctr(KeySize::KeySize128, &ekey, &iv).process(plain, &mut msgd[80..(80 + plain.len())]);
+
+			let mut hmac = Hmac::new(Sha256::new(), &mkey);
+			hmac.input(&msgd[64..(80 + plain.len())]);
+			hmac.input(shared_mac);
+			hmac.raw_result(&mut msgd[(80 + plain.len())..]);
+		}
+		Ok(msg)
+	}
+
+	/// Decrypt a message with a secret key.
+	pub fn decrypt(secret: &Secret, shared_mac: &[u8], encrypted: &[u8]) -> Result<Vec<u8>, Error> {
+		let meta_len = 1 + 64 + 16 + 32;
+		if encrypted.len() < meta_len || encrypted[0] < 2 || encrypted[0] > 4 {
+			return Err(Error::InvalidMessage);
+		}
+
+		let e = &encrypted[1..];
+		let mut p = Public::default();
+		p.copy_from_slice(&e[0..64]);
+		let z = try!(ecdh::agree(secret, &p));
+		let (ekey, mkey) = derive_keys(&z);
+
+		let clen = encrypted.len() - meta_len;
+		let cipher_with_iv = &e[64..(80 + clen)];
+		let iv = &cipher_with_iv[0..16];
+		let cipher = &cipher_with_iv[16..];
+		let msg_mac = &e[(80 + clen)..];
+
+		// Verify tag
+		let mut hmac = Hmac::new(Sha256::new(), &mkey);
+		hmac.input(cipher_with_iv);
+		hmac.input(shared_mac);
+		let mut mac = [0u8; 32];
+		hmac.raw_result(&mut mac);
+		if &mac[..] != msg_mac {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut msg = vec![0u8; clen];
+		ctr(KeySize::KeySize128, &ekey, iv).process(cipher, &mut msg);
+		Ok(msg)
+	}
+
+	/// Derives encryption key and mac key from the shared secret.
+	fn derive_keys(z: &Secret) -> ([u8; 16], [u8; 32]) {
+		let mut key = [0u8; 32];
+		kdf(z, &[0u8; 0], &mut key);
+
+		let mut ekey = [0u8; 16];
+		ekey.copy_from_slice(&key[0..16]);
+
+		let mut mkey = [0u8; 32];
+		let mut hasher = Sha256::new();
+		hasher.input(&key[16..32]);
+		hasher.result(&mut mkey);
+
+		(ekey, mkey)
+	}
+
+	fn kdf(secret: &Secret, s1: &[u8], dest: &mut [u8]) {
+		let mut hasher = Sha256::new();
+		// SEC/ISO/Shoup specify counter size SHOULD be equivalent
+		// to size of hash output, however, it also notes that
+		// the 4 bytes is okay. NIST specifies 4 bytes.
+		let mut ctr = 1u32;
+		let mut written = 0usize;
+		while written < dest.len() {
+			let ctrs = [(ctr >> 24) as u8, (ctr >> 16) as u8, (ctr >> 8) as u8, ctr as u8];
+			hasher.input(&ctrs);
+			hasher.input(&secret[..]);
+			hasher.input(s1);
+			hasher.result(&mut dest[written..(written + 32)]);
+			hasher.reset();
+			written += 32;
+			ctr += 1;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {Generator, Random};
+	use super::ecies;
+
+	#[test]
+	fn ecies_shared() {
+		let kp = Random.generate().unwrap();
+		let message = b"So many books, so little time";
+
+		let shared = b"shared";
+		let wrong_shared = b"incorrect";
+		let encrypted = ecies::encrypt(kp.public(), shared, message).unwrap();
+		assert!(encrypted[..] != message[..]);
+		assert_eq!(encrypted[0], 0x04);
+
+		assert!(ecies::decrypt(kp.secret(), wrong_shared, &encrypted).is_err());
+		let decrypted = ecies::decrypt(kp.secret(), shared, &encrypted).unwrap();
+		assert_eq!(decrypted[..message.len()], message[..]);
+	}
+
+	#[test]
+	fn ecies_wrong_key() {
+		let kp = Random.generate().unwrap();
+		let other = Random.generate().unwrap();
+		let encrypted = ecies::encrypt(kp.public(), &[], b"secret").unwrap();
+		assert!(ecies::decrypt(other.secret(), &[], &encrypted).is_err());
+	}
+}
@@ -0,0 +1,296 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BIP32 hierarchical deterministic key derivation.
+
+use std::fmt;
+use std::str::FromStr;
+use secp256k1::key;
+use rcrypto::hmac::Hmac;
+use rcrypto::mac::Mac;
+use rcrypto::sha2::Sha512;
+use {Secret, Public, KeyPair, Error, SECP256K1};
+
+/// Index of the first hardened child key.
+pub const HARDENED_INDEX: u32 = 0x8000_0000;
+
+/// BIP32 derivation path, e.g. `m/44'/60'/0'/0/0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+	/// Returns child indices (hardened indices include `HARDENED_INDEX`).
+	pub fn indices(&self) -> &[u32] {
+		&self.0
+	}
+
+	/// Returns path extended with given child index.
+	pub fn child(&self, index: u32) -> Self {
+		let mut indices = self.0.clone();
+		indices.push(index);
+		DerivationPath(indices)
+	}
+}
+
+impl From<Vec<u32>> for DerivationPath {
+	fn from(indices: Vec<u32>) -> Self {
+		DerivationPath(indices)
+	}
+}
+
+impl FromStr for DerivationPath {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.split('/');
+		if parts.next() != Some("m") {
+			return Err(Error::InvalidDerivation);
+		}
+
+		parts.map(|part| {
+			let (number, hardened) = match part.chars().last() {
+				Some('\'') | Some('h') | Some('H') => (&part[..part.len() - 1], true),
+				_ => (part, false),
+			};
+			match u32::from_str(number) {
+				Ok(index) if index < HARDENED_INDEX => Ok(if hardened { index | HARDENED_INDEX } else { index }),
+				_ => Err(Error::InvalidDerivation),
+			}
+		}).collect::<Result<Vec<u32>, Error>>().map(DerivationPath)
+	}
+}
+
+impl fmt::Display for DerivationPath {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		try!(write!(f, "m"));
+		for index in &self.0 {
+			match *index >= HARDENED_INDEX {
+				true => try!(write!(f, "/{}'", index - HARDENED_INDEX)),
+				false => try!(write!(f, "/{}", index)),
+			}
+		}
+		Ok(())
+	}
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+	let mut hmac = Hmac::new(Sha512::new(), key);
+	let mut result = [0u8; 64];
+	hmac.input(data);
+	hmac.raw_result(&mut result);
+
+	let mut left = [0u8; 32];
+	let mut right = [0u8; 32];
+	left.copy_from_slice(&result[0..32]);
+	right.copy_from_slice(&result[32..64]);
+	(left, right)
+}
+
+fn to_public_key(public: &Public) -> Result<key::PublicKey, Error> {
+	let mut data = [4u8; 65];
+	data[1..65].copy_from_slice(&public[..]);
+	Ok(try!(key::PublicKey::from_slice(&SECP256K1, &data)))
+}
+
+fn compressed(public: &Public) -> Result<Vec<u8>, Error> {
+	let key = try!(to_public_key(public));
+	Ok(key.serialize_vec(&SECP256K1, true).to_vec())
+}
+
+fn index_bytes(index: u32) -> [u8; 4] {
+	[(index >> 24) as u8, (index >> 16) as u8, (index >> 8) as u8, index as u8]
+}
+
+/// Extended secret key: secret with a chain code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedSecret {
+	secret: Secret,
+	chain_code: [u8; 32],
+}
+
+impl ExtendedSecret {
+	/// Creates extended secret from its parts.
+	pub fn new(secret: Secret, chain_code: [u8; 32]) -> Self {
+		ExtendedSecret {
+			secret: secret,
+			chain_code: chain_code,
+		}
+	}
+
+	/// Creates master key from seed (e.g. BIP39 mnemonic seed).
+	pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+		let (secret, chain_code) = hmac_sha512(b"Bitcoin seed", seed);
+		// make sure the secret is valid
+		let _ = try!(key::SecretKey::from_slice(&SECP256K1, &secret));
+		Ok(ExtendedSecret::new(Secret::from(secret), chain_code))
+	}
+
+	/// Returns the secret.
+	pub fn secret(&self) -> &Secret {
+		&self.secret
+	}
+
+	/// Returns the chain code.
+	pub fn chain_code(&self) -> &[u8; 32] {
+		&self.chain_code
+	}
+
+	/// Returns keypair of this extended secret.
+	pub fn keypair(&self) -> Result<KeyPair, Error> {
+		KeyPair::from_secret(self.secret.clone())
+	}
+
+	/// Returns corresponding extended public key.
+	pub fn public(&self) -> Result<ExtendedPublic, Error> {
+		let keypair = try!(self.keypair());
+		Ok(ExtendedPublic::new(keypair.public().clone(), self.chain_code))
+	}
+
+	/// Derives child secret with given index.
+	/// Indices starting from `HARDENED_INDEX` are hardened.
+	pub fn derive(&self, index: u32) -> Result<Self, Error> {
+		let mut data = Vec::with_capacity(37);
+		if index >= HARDENED_INDEX {
+			data.push(0);
+			data.extend_from_slice(&self.secret[..]);
+		} else {
+			let keypair = try!(self.keypair());
+			data.extend_from_slice(&try!(compressed(keypair.public())));
+		}
+		data.extend_from_slice(&index_bytes(index));
+
+		let (tweak, chain_code) = hmac_sha512(&self.chain_code, &data);
+		let tweak = try!(key::SecretKey::from_slice(&SECP256K1, &tweak));
+		let mut child = try!(key::SecretKey::from_slice(&SECP256K1, &self.secret[..]));
+		try!(child.add_assign(&SECP256K1, &tweak));
+
+		let mut secret = Secret::default();
+		secret.copy_from_slice(&child[0..32]);
+		Ok(ExtendedSecret::new(secret, chain_code))
+	}
+
+	/// Derives child secret at given path.
+	pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Error> {
+		path.indices().iter().fold(Ok(self.clone()), |key, index| key.and_then(|key| key.derive(*index)))
+	}
+}
+
+/// Extended public key: public with a chain code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedPublic {
+	public: Public,
+	chain_code: [u8; 32],
+}
+
+impl ExtendedPublic {
+	/// Creates extended public from its parts.
+	pub fn new(public: Public, chain_code: [u8; 32]) -> Self {
+		ExtendedPublic {
+			public: public,
+			chain_code: chain_code,
+		}
+	}
+
+	/// Returns the public.
+	pub fn public(&self) -> &Public {
+		&self.public
+	}
+
+	/// Returns the chain code.
+	pub fn chain_code(&self) -> &[u8; 32] {
+		&self.chain_code
+	}
+
+	/// Derives child public with given (non-hardened) index.
+	pub fn derive(&self, index: u32) -> Result<Self, Error> {
+		if index >= HARDENED_INDEX {
+			return Err(Error::InvalidDerivation);
+		}
+
+		let mut data = try!(compressed(&self.public));
+		data.extend_from_slice(&index_bytes(index));
+
+		let (tweak, chain_code) = hmac_sha512(&self.chain_code, &data);
+		let tweak = try!(key::SecretKey::from_slice(&SECP256K1, &tweak));
+		let mut child = try!(to_public_key(&self.public));
+		try!(child.add_exp_assign(&SECP256K1, &tweak));
+
+		let serialized = child.serialize_vec(&SECP256K1, false);
+		let mut public = Public::default();
+		public.copy_from_slice(&serialized[1..65]);
+		Ok(ExtendedPublic::new(public, chain_code))
+	}
+
+	/// Derives child public at given path (hardened indices are not allowed).
+	pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Error> {
+		path.indices().iter().fold(Ok(self.clone()), |key, index| key.and_then(|key| key.derive(*index)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use rustc_serialize::hex::{FromHex, ToHex};
+	use Mnemonic;
+	use super::{ExtendedSecret, DerivationPath, HARDENED_INDEX};
+
+	#[test]
+	fn derivation_path() {
+		let path = DerivationPath::from_str("m/44'/60'/0'/0/1").unwrap();
+		assert_eq!(path.indices(), &[44 | HARDENED_INDEX, 60 | HARDENED_INDEX, HARDENED_INDEX, 0, 1]);
+		assert_eq!(format!("{}", path), "m/44'/60'/0'/0/1");
+		assert_eq!(DerivationPath::from_str("m").unwrap().indices().len(), 0);
+		assert!(DerivationPath::from_str("44'/60'").is_err());
+		assert!(DerivationPath::from_str("m/2147483648").is_err());
+		assert!(DerivationPath::from_str("m/x").is_err());
+	}
+
+	#[test]
+	fn bip32_test_vector() {
+		let seed = "000102030405060708090a0b0c0d0e0f".from_hex().unwrap();
+		let master = ExtendedSecret::from_seed(&seed).unwrap();
+		assert_eq!(master.secret().to_hex(), "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35");
+		assert_eq!(master.chain_code().to_hex(), "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508");
+
+		let child = master.derive(HARDENED_INDEX).unwrap();
+		assert_eq!(child.secret().to_hex(), "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea");
+		assert_eq!(child.chain_code().to_hex(), "47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141");
+
+		let child = child.derive(1).unwrap();
+		assert_eq!(child.secret().to_hex(), "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368");
+		assert_eq!(child.chain_code().to_hex(), "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19");
+	}
+
+	#[test]
+	fn public_derivation_matches_secret_derivation() {
+		let seed = "000102030405060708090a0b0c0d0e0f".from_hex().unwrap();
+		let master = ExtendedSecret::from_seed(&seed).unwrap().derive(HARDENED_INDEX).unwrap();
+		let path = DerivationPath::from_str("m/1/2").unwrap();
+		let from_secret = master.derive_path(&path).unwrap().public().unwrap();
+		let from_public = master.public().unwrap().derive_path(&path).unwrap();
+		assert_eq!(from_secret, from_public);
+		assert!(master.public().unwrap().derive(HARDENED_INDEX).is_err());
+	}
+
+	#[test]
+	fn ethereum_account_from_mnemonic() {
+		let mnemonic = Mnemonic::from_str("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+		let master = ExtendedSecret::from_seed(&mnemonic.seed("")).unwrap();
+		let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+		let keypair = master.derive_path(&path).unwrap().keypair().unwrap();
+		assert_eq!(keypair.address().to_hex(), "9858effd232b4033e47d90003d41ec34ecaeda94");
+	}
+}
@@ -20,11 +20,15 @@ extern crate lazy_static;
 extern crate tiny_keccak;
 extern crate secp256k1;
 extern crate rustc_serialize;
+extern crate crypto as rcrypto;
 
 mod brain;
+mod crypto;
 mod error;
+mod extended;
 mod keypair;
 mod keccak;
+mod mnemonic;
 mod prefix;
 mod primitive;
 mod random;
@@ -41,8 +45,11 @@ pub trait Generator {
 }
 
 pub use self::brain::Brain;
+pub use self::crypto::{ecdh, ecies};
 pub use self::error::Error;
+pub use self::extended::{ExtendedSecret, ExtendedPublic, DerivationPath, HARDENED_INDEX};
 pub use self::keypair::{KeyPair, public_to_address};
+pub use self::mnemonic::Mnemonic;
 pub use self::primitive::{Secret, Public, Address, Message};
 pub use self::prefix::Prefix;
 pub use self::random::Random;
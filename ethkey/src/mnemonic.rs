@@ -0,0 +1,194 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BIP39 mnemonic phrases.
+
+use std::fmt;
+use std::str::FromStr;
+use rand::{Rng, OsRng};
+use rcrypto::digest::Digest;
+use rcrypto::sha2::{Sha256, Sha512};
+use rcrypto::hmac::Hmac;
+use rcrypto::pbkdf2::pbkdf2;
+use Error;
+
+const WORDLIST: &'static str = include_str!("../res/bip39-english.txt");
+const SEED_ITERATIONS: u32 = 2048;
+
+lazy_static! {
+	static ref WORDS: Vec<&'static str> = WORDLIST.lines().collect();
+}
+
+/// Returns the bit at given position (most significant bit first).
+fn bit(data: &[u8], position: usize) -> bool {
+	data[position / 8] & (0x80 >> (position % 8)) != 0
+}
+
+fn checksum(entropy: &[u8]) -> u8 {
+	let mut hasher = Sha256::new();
+	let mut hash = [0u8; 32];
+	hasher.input(entropy);
+	hasher.result(&mut hash);
+	hash[0]
+}
+
+/// BIP39 mnemonic phrase (english wordlist).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mnemonic {
+	entropy: Vec<u8>,
+	words: Vec<&'static str>,
+}
+
+impl Mnemonic {
+	/// Generates new random mnemonic with given number of words (12, 15, 18, 21 or 24).
+	pub fn random(words: usize) -> Result<Self, Error> {
+		if words % 3 != 0 || words < 12 || words > 24 {
+			return Err(Error::InvalidMnemonic);
+		}
+		let mut entropy = vec![0u8; words * 4 / 3];
+		try!(OsRng::new()).fill_bytes(&mut entropy);
+		Mnemonic::from_entropy(&entropy)
+	}
+
+	/// Creates mnemonic encoding given entropy (16, 20, 24, 28 or 32 bytes).
+	pub fn from_entropy(entropy: &[u8]) -> Result<Self, Error> {
+		if entropy.len() % 4 != 0 || entropy.len() < 16 || entropy.len() > 32 {
+			return Err(Error::InvalidMnemonic);
+		}
+
+		let mut data = entropy.to_vec();
+		data.push(checksum(entropy));
+
+		let count = (entropy.len() * 8 + entropy.len() / 4) / 11;
+		let words = (0..count)
+			.map(|i| (0..11).fold(0usize, |index, j| (index << 1) | bit(&data, i * 11 + j) as usize))
+			.map(|index| WORDS[index])
+			.collect();
+
+		Ok(Mnemonic {
+			entropy: entropy.to_vec(),
+			words: words,
+		})
+	}
+
+	/// Returns entropy encoded by this mnemonic.
+	pub fn entropy(&self) -> &[u8] {
+		&self.entropy
+	}
+
+	/// Returns the phrase.
+	pub fn phrase(&self) -> String {
+		self.words.join(" ")
+	}
+
+	/// Computes 64-byte seed protected with given passphrase (may be empty).
+	/// Passphrase is expected to be already NFKD-normalized.
+	pub fn seed(&self, passphrase: &str) -> Vec<u8> {
+		let mut mac = Hmac::new(Sha512::new(), self.phrase().as_bytes());
+		let salt = format!("mnemonic{}", passphrase);
+		let mut seed = vec![0u8; 64];
+		pbkdf2(&mut mac, salt.as_bytes(), SEED_ITERATIONS, &mut seed);
+		seed
+	}
+}
+
+impl FromStr for Mnemonic {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let indices = try!(s.split_whitespace()
+			.map(|word| WORDS.binary_search(&word).map_err(|_| Error::InvalidMnemonic))
+			.collect::<Result<Vec<usize>, Error>>());
+
+		if indices.len() % 3 != 0 || indices.len() < 12 || indices.len() > 24 {
+			return Err(Error::InvalidMnemonic);
+		}
+
+		let mut data = vec![0u8; indices.len() * 4 / 3 + 1];
+		for (i, index) in indices.into_iter().enumerate() {
+			for j in 0..11 {
+				if index & (1 << (10 - j)) != 0 {
+					let position = i * 11 + j;
+					data[position / 8] |= 0x80 >> (position % 8);
+				}
+			}
+		}
+
+		let checksum_byte = data.pop().expect("data is not empty; qed");
+		let mnemonic = try!(Mnemonic::from_entropy(&data));
+		let checksum_bits = data.len() / 4;
+		let mask = (0xff00u16 >> checksum_bits) as u8;
+		if checksum(&data) & mask != checksum_byte {
+			return Err(Error::InvalidMnemonic);
+		}
+
+		Ok(mnemonic)
+	}
+}
+
+impl fmt::Display for Mnemonic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		write!(f, "{}", self.phrase())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use rustc_serialize::hex::{FromHex, ToHex};
+	use super::{Mnemonic, WORDS};
+
+	#[test]
+	fn wordlist_is_sorted() {
+		assert_eq!(WORDS.len(), 2048);
+		assert!(WORDS.windows(2).all(|w| w[0] < w[1]));
+	}
+
+	#[test]
+	fn mnemonic_from_entropy() {
+		let entropy = "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f".from_hex().unwrap();
+		let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+		assert_eq!(mnemonic.phrase(), "legal winner thank year wave sausage worth useful legal winner thank yellow");
+
+		let entropy = "8080808080808080808080808080808080808080808080808080808080808080".from_hex().unwrap();
+		let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+		assert_eq!(mnemonic.phrase(), "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless");
+	}
+
+	#[test]
+	fn mnemonic_seed() {
+		let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+		assert_eq!(mnemonic.phrase(), "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+		assert_eq!(mnemonic.seed("TREZOR").to_hex(), "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04");
+	}
+
+	#[test]
+	fn mnemonic_from_phrase() {
+		let mnemonic = Mnemonic::from_str("legal winner thank year wave sausage worth useful legal winner thank yellow").unwrap();
+		assert_eq!(mnemonic.entropy().to_hex(), "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f");
+		assert!(Mnemonic::from_str("legal winner thank year wave sausage worth useful legal winner thank year").is_err());
+		assert!(Mnemonic::from_str("legal winner thank year wave sausage worth useful legal winner thank").is_err());
+		assert!(Mnemonic::from_str("legal winner thank year wave sausage worth useful legal winner thank yellowish").is_err());
+	}
+
+	#[test]
+	fn random_mnemonic() {
+		let mnemonic = Mnemonic::random(24).unwrap();
+		assert_eq!(mnemonic.entropy().len(), 32);
+		assert_eq!(Mnemonic::from_str(&mnemonic.phrase()).unwrap(), mnemonic);
+		assert!(Mnemonic::random(13).is_err());
+	}
+}
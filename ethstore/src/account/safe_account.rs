@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ops::{Deref, DerefMut};
-use ethkey::{KeyPair, sign, Address, Secret, Signature, Message};
+use ethkey::{KeyPair, sign, Address, Secret, Signature, Message, ecies};
 use {json, Error, crypto};
 use crypto::Keccak256;
 use random::Random;
@@ -35,6 +35,10 @@ pub struct SafeAccount {
 	pub version: Version,
 	pub address: Address,
 	pub crypto: Crypto,
+	pub name: String,
+	pub meta: String,
+	/// UNIX time the account was created at, if known.
+	pub timestamp: Option<u64>,
 }
 
 impl From<json::Crypto> for Crypto {
@@ -66,6 +70,9 @@ impl From<json::KeyFile> for SafeAccount {
 			version: From::from(json.version),
 			address: From::from(json.address), //json.address.into(),
 			crypto: From::from(json.crypto),
+			name: json.name.unwrap_or_else(String::new),
+			meta: json.meta.unwrap_or_else(|| "{}".to_owned()),
+			timestamp: json.timestamp,
 		}
 	}
 }
@@ -77,6 +84,9 @@ impl Into<json::KeyFile> for SafeAccount {
 			version: self.version.into(),
 			address: self.address.into(), //From::from(self.address),
 			crypto: self.crypto.into(),
+			name: Some(self.name),
+			meta: Some(self.meta),
+			timestamp: self.timestamp,
 		}
 	}
 }
@@ -138,12 +148,15 @@ impl Crypto {
 }
 
 impl SafeAccount {
-	pub fn create(keypair: &KeyPair, id: [u8; 16], password: &str, iterations: u32) -> Self {
+	pub fn create(keypair: &KeyPair, id: [u8; 16], password: &str, iterations: u32, name: String, meta: String) -> Self {
 		SafeAccount {
 			id: id,
 			version: Version::V3,
 			crypto: Crypto::create(keypair.secret(), password, iterations),
 			address: keypair.address(),
+			name: name,
+			meta: meta,
+			timestamp: None,
 		}
 	}
 
@@ -152,6 +165,11 @@ impl SafeAccount {
 		sign(&secret, message).map_err(From::from)
 	}
 
+	pub fn decrypt(&self, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+		let secret = try!(self.crypto.secret(password));
+		ecies::decrypt(&secret, shared_mac, message).map_err(From::from)
+	}
+
 	pub fn change_password(&self, old_password: &str, new_password: &str, iterations: u32) -> Result<Self, Error> {
 		let secret = try!(self.crypto.secret(old_password));
 		let result = SafeAccount {
@@ -159,6 +177,9 @@ impl SafeAccount {
 			version: self.version.clone(),
 			crypto: Crypto::create(&secret, new_password, iterations),
 			address: self.address.clone(),
+			name: self.name.clone(),
+			meta: self.meta.clone(),
+			timestamp: self.timestamp,
 		};
 		Ok(result)
 	}
@@ -170,7 +191,7 @@ impl SafeAccount {
 
 #[cfg(test)]
 mod tests {
-	use ethkey::{Generator, Random, verify_public, Message};
+	use ethkey::{Generator, Random, verify_public, Message, ecies};
 	use super::{Crypto, SafeAccount};
 
 	#[test]
@@ -194,11 +215,21 @@ mod tests {
 		let keypair = Random.generate().unwrap();
 		let password = "hello world";
 		let message = Message::default();
-		let account = SafeAccount::create(&keypair, [0u8; 16], password, 10240);
+		let account = SafeAccount::create(&keypair, [0u8; 16], password, 10240, "Test".to_owned(), "{}".to_owned());
 		let signature = account.sign(password, &message).unwrap();
 		assert!(verify_public(keypair.public(), &signature, &message).unwrap());
 	}
 
+	#[test]
+	fn decrypt_message() {
+		let keypair = Random.generate().unwrap();
+		let password = "hello world";
+		let account = SafeAccount::create(&keypair, [0u8; 16], password, 10240, "Test".to_owned(), "{}".to_owned());
+		let encrypted = ecies::encrypt(keypair.public(), &[], b"secret message").unwrap();
+		assert_eq!(account.decrypt(password, &[], &encrypted).unwrap(), b"secret message".to_vec());
+		assert!(account.decrypt("wrong password", &[], &encrypted).is_err());
+	}
+
 	#[test]
 	fn change_password() {
 		let keypair = Random.generate().unwrap();
@@ -206,11 +237,13 @@ mod tests {
 		let sec_password = "this is sparta";
 		let i = 10240;
 		let message = Message::default();
-		let account = SafeAccount::create(&keypair, [0u8; 16], first_password, i);
+		let account = SafeAccount::create(&keypair, [0u8; 16], first_password, i, "Test".to_owned(), "{}".to_owned());
 		let new_account = account.change_password(first_password, sec_password, i).unwrap();
 		assert!(account.sign(first_password, &message).is_ok());
 		assert!(account.sign(sec_password, &message).is_err());
 		assert!(new_account.sign(first_password, &message).is_err());
 		assert!(new_account.sign(sec_password, &message).is_ok());
+		assert_eq!(new_account.name, account.name);
+		assert_eq!(new_account.timestamp, account.timestamp);
 	}
 }
@@ -22,7 +22,7 @@ use std::{env, process};
 use std::ops::Deref;
 use std::str::FromStr;
 use docopt::Docopt;
-use ethstore::ethkey::{Secret, Address, Message};
+use ethstore::ethkey::{Secret, Address, Message, Mnemonic, ExtendedSecret, DerivationPath};
 use ethstore::dir::{KeyDirectory, ParityDirectory, DiskDirectory, GethDirectory, DirectoryType};
 use ethstore::{EthStore, SecretStore, import_accounts, Error};
 
@@ -32,8 +32,12 @@ Ethereum key management.
 
 Usage:
     ethstore insert <secret> <password> [--dir DIR]
+    ethstore insert-mnemonic <phrase> <path> <password> [--passphrase PASS] [--dir DIR]
+    ethstore derive-from-secret <address> <password> <path> [--dir DIR]
     ethstore change-pwd <address> <old-pwd> <new-pwd> [--dir DIR]
     ethstore list [--dir DIR]
+    ethstore set-name <address> <name> [--dir DIR]
+    ethstore set-meta <address> <meta> [--dir DIR]
     ethstore import [--src DIR] [--dir DIR]
     ethstore remove <address> <password> [--dir DIR]
     ethstore sign <address> <password> <message> [--dir DIR]
@@ -47,11 +51,21 @@ Options:
     --src DIR          Specify import source. It may be either
                        parity, parity-test, get, geth-test
                        or a path [default: geth].
+    --passphrase PASS  BIP39 passphrase protecting the mnemonic seed.
 
 Commands:
     insert             Save account with password.
+    insert-mnemonic    Save account derived from BIP39 mnemonic phrase
+                       at BIP32 path (e.g. m/44'/60'/0'/0/0).
+    derive-from-secret Save new account derived from existing seed account
+                       at BIP32 path. Uses the same password. The seed
+                       account secret is used as BIP32 seed, so there is
+                       no mnemonic to recover derived accounts from (see
+                       insert-mnemonic for BIP39 wallets).
     change-pwd         Change password.
-    list               List accounts.
+    list               List accounts with their names.
+    set-name           Set account name.
+    set-meta           Set account metadata (free-form JSON).
     import             Import accounts from src.
     remove             Remove account.
     sign               Sign message.
@@ -60,8 +74,12 @@ Commands:
 #[derive(Debug, RustcDecodable)]
 struct Args {
 	cmd_insert: bool,
+	cmd_insert_mnemonic: bool,
+	cmd_derive_from_secret: bool,
 	cmd_change_pwd: bool,
 	cmd_list: bool,
+	cmd_set_name: bool,
+	cmd_set_meta: bool,
 	cmd_import: bool,
 	cmd_remove: bool,
 	cmd_sign: bool,
@@ -71,8 +89,13 @@ struct Args {
 	arg_new_pwd: String,
 	arg_address: String,
 	arg_message: String,
+	arg_name: String,
+	arg_meta: String,
+	arg_phrase: String,
+	arg_path: String,
 	flag_src: String,
 	flag_dir: String,
+	flag_passphrase: String,
 }
 
 fn main() {
@@ -105,6 +128,14 @@ fn format_accounts(accounts: &[Address]) -> String {
 		.join("\n")
 }
 
+fn format_accounts_with_names(store: &SecretStore, accounts: &[Address]) -> String {
+	accounts.iter()
+		.enumerate()
+		.map(|(i, a)| format!("{:2}: {} {}", i, a, store.name(a).unwrap_or_else(|_| String::new())))
+		.collect::<Vec<String>>()
+		.join("\n")
+}
+
 fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item=S>, S: AsRef<str> {
 	let args: Args = Docopt::new(USAGE)
 		.and_then(|d| d.argv(command).decode())
@@ -116,13 +147,33 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 		let secret = try!(Secret::from_str(&args.arg_secret));
 		let address = try!(store.insert_account(secret, &args.arg_password));
 		Ok(format!("{}", address))
+	} else if args.cmd_insert_mnemonic {
+		let mnemonic = try!(Mnemonic::from_str(&args.arg_phrase));
+		let path = try!(DerivationPath::from_str(&args.arg_path));
+		let master = try!(ExtendedSecret::from_seed(&mnemonic.seed(&args.flag_passphrase)));
+		let secret = try!(master.derive_path(&path)).secret().clone();
+		let address = try!(store.insert_account(secret, &args.arg_password));
+		Ok(format!("{}", address))
+	} else if args.cmd_derive_from_secret {
+		let address = try!(Address::from_str(&args.arg_address));
+		let path = try!(DerivationPath::from_str(&args.arg_path));
+		let derived = try!(store.derive_account_from_secret(&address, &args.arg_password, &path));
+		Ok(format!("{}", derived))
 	} else if args.cmd_change_pwd {
 		let address = try!(Address::from_str(&args.arg_address));
 		let ok = store.change_password(&address, &args.arg_old_pwd, &args.arg_new_pwd).is_ok();
 		Ok(format!("{}", ok))
 	} else if args.cmd_list {
 		let accounts = store.accounts();
-		Ok(format_accounts(&accounts))
+		Ok(format_accounts_with_names(&store, &accounts))
+	} else if args.cmd_set_name {
+		let address = try!(Address::from_str(&args.arg_address));
+		let ok = store.set_name(&address, args.arg_name).is_ok();
+		Ok(format!("{}", ok))
+	} else if args.cmd_set_meta {
+		let address = try!(Address::from_str(&args.arg_address));
+		let ok = store.set_meta(&address, args.arg_meta).is_ok();
+		Ok(format!("{}", ok))
 	} else if args.cmd_import {
 		let src = try!(key_dir(&args.flag_src));
 		let dst = try!(key_dir(&args.flag_dir));
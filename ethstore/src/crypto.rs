@@ -22,6 +22,8 @@ use rcrypto::hmac::Hmac;
 
 pub const KEY_LENGTH: usize = 32;
 pub const KEY_ITERATIONS: usize = 10240;
+/// Upper bound of PBKDF2 iterations accepted when re-encrypting keys on request.
+pub const MAX_KEY_ITERATIONS: u32 = 10 * KEY_ITERATIONS as u32;
 pub const KEY_LENGTH_AES: usize = KEY_LENGTH / 2;
 
 pub fn derive_key_iterations(password: &str, salt: &[u8; 32], c: u32) -> (Vec<u8>, Vec<u8>) {
@@ -92,4 +92,8 @@ impl KeyDirectory for GethDirectory {
 	fn remove(&self, address: &Address) -> Result<(), Error> {
 		self.dir.remove(address)
 	}
+
+	fn path(&self) -> Option<&PathBuf> {
+		self.dir.path()
+	}
 }
@@ -14,12 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::path::PathBuf;
 use ethkey::Address;
 use {SafeAccount, Error};
 
 mod disk;
 mod geth;
 mod parity;
+mod vault;
 
 pub enum DirectoryType {
 	Testnet,
@@ -30,8 +32,11 @@ pub trait KeyDirectory: Send + Sync {
 	fn load(&self) -> Result<Vec<SafeAccount>, Error>;
 	fn insert(&self, account: SafeAccount) -> Result<(), Error>;
 	fn remove(&self, address: &Address) -> Result<(), Error>;
+	/// Root path of the directory, if it is backed by the filesystem.
+	fn path(&self) -> Option<&PathBuf> { None }
 }
 
 pub use self::disk::DiskDirectory;
 pub use self::geth::GethDirectory;
 pub use self::parity::ParityDirectory;
+pub use self::vault::{VaultDiskDirectory, list_vaults};
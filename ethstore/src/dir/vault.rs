@@ -0,0 +1,175 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fs, io};
+use std::path::{PathBuf, Path};
+use std::sync::RwLock;
+use ethkey::{Address, Secret};
+use account::Crypto;
+use random::Random;
+use {crypto, json, SafeAccount, Error};
+use super::{KeyDirectory, DiskDirectory};
+
+/// Name of the file describing the vault.
+pub const VAULT_FILE_NAME: &'static str = "vault.json";
+
+fn vault_path<P>(root: P, name: &str) -> Result<PathBuf, Error> where P: AsRef<Path> {
+	let valid = !name.is_empty() && name != "." && name != ".." && !name.contains(|c: char| c == '/' || c == '\\');
+	if !valid {
+		return Err(Error::InvalidVaultName);
+	}
+
+	let mut path = root.as_ref().to_path_buf();
+	path.push(name);
+	Ok(path)
+}
+
+fn vault_file_path<P>(path: P) -> PathBuf where P: AsRef<Path> {
+	let mut file_path = path.as_ref().to_path_buf();
+	file_path.push(VAULT_FILE_NAME);
+	file_path
+}
+
+fn write_vault_file(path: &Path, crypto: Crypto, meta: String) -> Result<(), Error> {
+	let vault_file = json::VaultFile {
+		crypto: crypto.into(),
+		meta: meta,
+	};
+	let mut file = try!(fs::File::create(vault_file_path(path)));
+	vault_file.write(&mut file).map_err(|e| Error::Custom(format!("{:?}", e)))
+}
+
+fn read_vault_file(path: &Path) -> Result<(Crypto, String), Error> {
+	let file = try!(fs::File::open(vault_file_path(path)));
+	let vault_file = try!(json::VaultFile::load(file).map_err(|e| Error::Custom(format!("{:?}", e))));
+	Ok((vault_file.crypto.into(), vault_file.meta))
+}
+
+/// Returns names of all vaults found in given keys directory.
+pub fn list_vaults<P>(root: P) -> Result<Vec<String>, Error> where P: AsRef<Path> {
+	let vaults = try!(fs::read_dir(root))
+		.flat_map(Result::ok)
+		.filter(|entry| entry.metadata().map(|m| m.is_dir()).unwrap_or(false))
+		.filter(|entry| vault_file_path(entry.path()).is_file())
+		.filter_map(|entry| entry.file_name().into_string().ok())
+		.collect();
+	Ok(vaults)
+}
+
+/// Keys directory protected with its own vault password.
+/// Vault is a subdirectory of the main keys directory.
+pub struct VaultDiskDirectory {
+	dir: DiskDirectory,
+	path: PathBuf,
+	name: String,
+	crypto: Crypto,
+	key: Secret,
+	meta: RwLock<String>,
+}
+
+impl VaultDiskDirectory {
+	/// Creates new vault in given keys directory.
+	pub fn create<P>(root: P, name: &str, password: &str, iterations: u32) -> Result<Self, Error> where P: AsRef<Path> {
+		let path = try!(vault_path(root, name));
+		if path.exists() {
+			return Err(Error::VaultExists);
+		}
+
+		// random vault key, encrypted with the vault password
+		let secret: [u8; 32] = Random::random();
+		let key = Secret::from(secret);
+		let crypto = Crypto::create(&key, password, iterations);
+		let dir = try!(DiskDirectory::create(&path));
+		if let Err(err) = write_vault_file(&path, crypto.clone(), "{}".to_owned()) {
+			let _ = fs::remove_dir_all(&path);
+			return Err(err);
+		}
+
+		Ok(VaultDiskDirectory {
+			dir: dir,
+			path: path,
+			name: name.to_owned(),
+			crypto: crypto,
+			key: key,
+			meta: RwLock::new("{}".to_owned()),
+		})
+	}
+
+	/// Opens existing vault. Fails if the password is invalid.
+	pub fn at<P>(root: P, name: &str, password: &str) -> Result<Self, Error> where P: AsRef<Path> {
+		let path = try!(vault_path(root, name));
+		let (crypto, meta) = try!(read_vault_file(&path).map_err(|err| match err {
+			Error::Io(ref e) if e.kind() == io::ErrorKind::NotFound => Error::InvalidVault,
+			err => err,
+		}));
+		let key = try!(crypto.secret(password));
+
+		Ok(VaultDiskDirectory {
+			dir: DiskDirectory::at(&path),
+			path: path,
+			name: name.to_owned(),
+			crypto: crypto,
+			key: key,
+			meta: RwLock::new(meta),
+		})
+	}
+
+	/// Returns vault name.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Returns vault metadata.
+	pub fn meta(&self) -> String {
+		self.meta.read().unwrap().clone()
+	}
+
+	/// Updates vault metadata.
+	pub fn set_meta(&self, meta: String) -> Result<(), Error> {
+		try!(write_vault_file(&self.path, self.crypto.clone(), meta.clone()));
+		*self.meta.write().unwrap() = meta;
+		Ok(())
+	}
+
+	/// Applies the vault key to account ciphertext.
+	/// AES-128-CTR is symmetric, so the same call both seals the account before it is
+	/// written to disk and opens it after it is read back.
+	fn apply_key(&self, mut account: SafeAccount) -> SafeAccount {
+		let mut ciphertext = [0u8; 32];
+		crypto::aes::encrypt(&self.key[0..16], &account.id, &account.crypto.ciphertext, &mut ciphertext);
+		account.crypto.ciphertext = ciphertext;
+		account
+	}
+}
+
+impl KeyDirectory for VaultDiskDirectory {
+	fn load(&self) -> Result<Vec<SafeAccount>, Error> {
+		let accounts = try!(self.dir.load());
+		Ok(accounts.into_iter().map(|account| self.apply_key(account)).collect())
+	}
+
+	fn insert(&self, account: SafeAccount) -> Result<(), Error> {
+		self.dir.insert(self.apply_key(account))
+	}
+
+	fn remove(&self, address: &Address) -> Result<(), Error> {
+		self.dir.remove(address)
+	}
+
+	fn path(&self) -> Option<&PathBuf> {
+		Some(&self.path)
+	}
+}
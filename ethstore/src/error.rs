@@ -24,7 +24,13 @@ pub enum Error {
 	InvalidPassword,
 	InvalidSecret,
 	InvalidAccount,
+	InvalidKeyIterations,
 	CreationFailed,
+	InvalidVault,
+	InvalidVaultName,
+	VaultExists,
+	VaultsNotSupported,
+	RemoteSigner(String),
 	EthKey(EthKeyError),
 	Custom(String),
 }
@@ -36,7 +42,13 @@ impl fmt::Display for Error {
 			Error::InvalidPassword => "Invalid password".into(),
 			Error::InvalidSecret => "Invalid secret".into(),
 			Error::InvalidAccount => "Invalid account".into(),
+			Error::InvalidKeyIterations => "Invalid number of key derivation iterations".into(),
 			Error::CreationFailed => "Account creation failed".into(),
+			Error::InvalidVault => "Invalid vault".into(),
+			Error::InvalidVaultName => "Invalid vault name".into(),
+			Error::VaultExists => "Vault already exists".into(),
+			Error::VaultsNotSupported => "Vaults are not supported by this key directory".into(),
+			Error::RemoteSigner(ref s) => format!("Remote signer error: {}", s),
 			Error::EthKey(ref err) => format!("{}", err),
 			Error::Custom(ref s) => s.clone(),
 		};
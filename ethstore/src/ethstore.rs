@@ -14,20 +14,31 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 use ethkey::KeyPair;
-use crypto::KEY_ITERATIONS;
+use crypto::{KEY_ITERATIONS, MAX_KEY_ITERATIONS, Keccak256};
 use random::Random;
-use ethkey::{Signature, Address, Message, Secret};
-use dir::KeyDirectory;
+use ethkey::{Signature, Address, Message, Secret, ExtendedSecret, DerivationPath};
+use dir::{KeyDirectory, VaultDiskDirectory, list_vaults};
 use account::SafeAccount;
-use {Error, SecretStore};
+use {json, Error, SecretStore};
+
+/// Current UNIX time in seconds.
+fn timestamp() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
 
 pub struct EthStore {
 	dir: Box<KeyDirectory>,
 	iterations: u32,
 	cache: RwLock<BTreeMap<Address, SafeAccount>>,
+	/// Opened vaults by name.
+	vaults: RwLock<HashMap<String, VaultDiskDirectory>>,
+	/// Vault of each account loaded from an opened vault.
+	vault_accounts: RwLock<HashMap<Address, String>>,
 }
 
 impl EthStore {
@@ -42,13 +53,67 @@ impl EthStore {
 			dir: directory,
 			iterations: iterations,
 			cache: RwLock::new(cache),
+			vaults: RwLock::new(HashMap::new()),
+			vault_accounts: RwLock::new(HashMap::new()),
 		};
 		Ok(store)
 	}
 
+	fn get(&self, address: &Address) -> Result<SafeAccount, Error> {
+		let cache = self.cache.read().unwrap();
+		cache.get(address).cloned().ok_or(Error::InvalidAccount)
+	}
+
+	fn vault_of(&self, address: &Address) -> Option<String> {
+		self.vault_accounts.read().unwrap().get(address).cloned()
+	}
+
+	fn insert_into(&self, vault: Option<&str>, account: SafeAccount) -> Result<(), Error> {
+		match vault {
+			Some(name) => {
+				let vaults = self.vaults.read().unwrap();
+				let vault = try!(vaults.get(name).ok_or(Error::InvalidVault));
+				vault.insert(account)
+			},
+			None => self.dir.insert(account),
+		}
+	}
+
+	fn remove_from(&self, vault: Option<&str>, address: &Address) -> Result<(), Error> {
+		match vault {
+			Some(name) => {
+				let vaults = self.vaults.read().unwrap();
+				let vault = try!(vaults.get(name).ok_or(Error::InvalidVault));
+				vault.remove(address)
+			},
+			None => self.dir.remove(address),
+		}
+	}
+
+	fn vaults_root(&self) -> Result<PathBuf, Error> {
+		self.dir.path().cloned().ok_or(Error::VaultsNotSupported)
+	}
+
+	/// Creates a new account in given vault, or in the main directory if `vault` is `None`.
+	fn create_account(&self, secret: Secret, password: &str, vault: Option<String>) -> Result<Address, Error> {
+		let keypair = try!(KeyPair::from_secret(secret).map_err(|_| Error::CreationFailed));
+		let id: [u8; 16] = Random::random();
+		let mut account = SafeAccount::create(&keypair, id, password, self.iterations, "".to_owned(), "{}".to_owned());
+		account.timestamp = Some(timestamp());
+		let address = account.address.clone();
+		let vault = vault.or_else(|| self.vault_of(&address));
+		try!(self.insert_into(vault.as_ref().map(String::as_str), account.clone()));
+		if let Some(vault) = vault {
+			self.vault_accounts.write().unwrap().insert(address.clone(), vault);
+		}
+		self.cache.write().unwrap().insert(address.clone(), account);
+		Ok(address)
+	}
+
 	fn save(&self, account: SafeAccount) -> Result<(), Error> {
 		// save to file
-		try!(self.dir.insert(account.clone()));
+		let vault = self.vault_of(&account.address);
+		try!(self.insert_into(vault.as_ref().map(String::as_str), account.clone()));
 
 		// update cache
 		let mut cache = self.cache.write().unwrap();
@@ -59,12 +124,7 @@ impl EthStore {
 
 impl SecretStore for EthStore {
 	fn insert_account(&self, secret: Secret, password: &str) -> Result<Address, Error> {
-		let keypair = try!(KeyPair::from_secret(secret).map_err(|_| Error::CreationFailed));
-		let id: [u8; 16] = Random::random();
-		let account = SafeAccount::create(&keypair, id, password, self.iterations);
-		let address = account.address.clone();
-		try!(self.save(account));
-		Ok(address)
+		self.create_account(secret, password, None)
 	}
 
 	fn accounts(&self) -> Vec<Address> {
@@ -91,7 +151,9 @@ impl SecretStore for EthStore {
 		};
 
 		if can_remove {
-			try!(self.dir.remove(address));
+			let vault = self.vault_of(address);
+			try!(self.remove_from(vault.as_ref().map(String::as_str), address));
+			self.vault_accounts.write().unwrap().remove(address);
 			let mut cache = self.cache.write().unwrap();
 			cache.remove(address);
 			Ok(())
@@ -100,9 +162,170 @@ impl SecretStore for EthStore {
 		}
 	}
 
+	fn export_account(&self, address: &Address, password: &str, new_password: Option<&str>, iterations: Option<u32>) -> Result<String, Error> {
+		// zero iterations make PBKDF2 panic, too many block the caller for ages
+		if iterations.map_or(false, |i| i == 0 || i > MAX_KEY_ITERATIONS) {
+			return Err(Error::InvalidKeyIterations);
+		}
+		let account = try!(self.get(address));
+		let account = match (new_password, iterations) {
+			(None, None) if account.check_password(password) => account,
+			(None, None) => return Err(Error::InvalidPassword),
+			(new_password, iterations) => try!(account.change_password(
+				password,
+				new_password.unwrap_or(password),
+				iterations.unwrap_or(self.iterations),
+			)),
+		};
+
+		let keyfile: json::KeyFile = account.into();
+		let mut result = Vec::new();
+		try!(keyfile.write(&mut result).map_err(|e| Error::Custom(format!("{:?}", e))));
+		Ok(String::from_utf8(result).expect("serde_json produces valid utf8; qed"))
+	}
+
 	fn sign(&self, account: &Address, password: &str, message: &Message) -> Result<Signature, Error> {
 		let cache = self.cache.read().unwrap();
 		let account = try!(cache.get(account).ok_or(Error::InvalidAccount));
 		account.sign(password, message)
 	}
+
+	fn sign_transaction(&self, account: &Address, password: &str, transaction: &[u8]) -> Result<Signature, Error> {
+		let hash: [u8; 32] = transaction.keccak256();
+		self.sign(account, password, &hash.into())
+	}
+
+	fn decrypt(&self, account: &Address, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+		let cache = self.cache.read().unwrap();
+		let account = try!(cache.get(account).ok_or(Error::InvalidAccount));
+		account.decrypt(password, shared_mac, message)
+	}
+
+	fn derive_account_from_secret(&self, account: &Address, password: &str, path: &DerivationPath) -> Result<Address, Error> {
+		// The raw 32-byte secret of the seed account is used as BIP32 seed. This is not BIP39:
+		// derived addresses only match wallets using the same scheme. Keys compatible with
+		// mnemonic based wallets are created from the mnemonic phrase instead.
+		let seed = try!(try!(self.get(account)).crypto.secret(password));
+		let master = try!(ExtendedSecret::from_seed(&seed[..]));
+		let derived = try!(master.derive_path(path));
+		// derived account is kept next to its seed account
+		self.create_account(derived.secret().clone(), password, self.vault_of(account))
+	}
+
+	fn name(&self, account: &Address) -> Result<String, Error> {
+		self.get(account).map(|account| account.name)
+	}
+
+	fn meta(&self, account: &Address) -> Result<String, Error> {
+		self.get(account).map(|account| account.meta)
+	}
+
+	fn timestamp(&self, account: &Address) -> Result<Option<u64>, Error> {
+		self.get(account).map(|account| account.timestamp)
+	}
+
+	fn set_name(&self, account: &Address, name: String) -> Result<(), Error> {
+		let mut account = try!(self.get(account));
+		account.name = name;
+		self.save(account)
+	}
+
+	fn set_meta(&self, account: &Address, meta: String) -> Result<(), Error> {
+		let mut account = try!(self.get(account));
+		account.meta = meta;
+		self.save(account)
+	}
+
+	fn create_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		let root = try!(self.vaults_root());
+		let vault = try!(VaultDiskDirectory::create(root, name, password, self.iterations));
+		self.vaults.write().unwrap().insert(name.to_owned(), vault);
+		Ok(())
+	}
+
+	fn open_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		let root = try!(self.vaults_root());
+		let vault = try!(VaultDiskDirectory::at(root, name, password));
+		if self.vaults.read().unwrap().contains_key(name) {
+			return Ok(());
+		}
+
+		let accounts = try!(vault.load());
+		{
+			// accounts already known (e.g. the same key in the root directory) take precedence,
+			// so that closing the vault later never drops them from the cache
+			let mut cache = self.cache.write().unwrap();
+			let mut vault_accounts = self.vault_accounts.write().unwrap();
+			for account in accounts {
+				if cache.contains_key(&account.address) {
+					continue;
+				}
+				vault_accounts.insert(account.address.clone(), name.to_owned());
+				cache.insert(account.address.clone(), account);
+			}
+		}
+		self.vaults.write().unwrap().insert(name.to_owned(), vault);
+		Ok(())
+	}
+
+	fn close_vault(&self, name: &str) -> Result<(), Error> {
+		try!(self.vaults.write().unwrap().remove(name).ok_or(Error::InvalidVault));
+
+		let addresses: Vec<Address> = {
+			let mut vault_accounts = self.vault_accounts.write().unwrap();
+			let addresses = vault_accounts.iter()
+				.filter(|&(_, vault)| vault == name)
+				.map(|(address, _)| address.clone())
+				.collect::<Vec<_>>();
+			for address in &addresses {
+				vault_accounts.remove(address);
+			}
+			addresses
+		};
+
+		let mut cache = self.cache.write().unwrap();
+		for address in &addresses {
+			cache.remove(address);
+		}
+		Ok(())
+	}
+
+	fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		let root = try!(self.vaults_root());
+		list_vaults(root)
+	}
+
+	fn list_opened_vaults(&self) -> Vec<String> {
+		self.vaults.read().unwrap().keys().cloned().collect()
+	}
+
+	fn change_account_vault(&self, address: &Address, vault: Option<&str>) -> Result<(), Error> {
+		let account = try!(self.get(address));
+		let current = self.vault_of(address);
+		if current.as_ref().map(String::as_str) == vault {
+			return Ok(());
+		}
+
+		// write to the new location first, so the key is never lost
+		try!(self.insert_into(vault, account));
+		try!(self.remove_from(current.as_ref().map(String::as_str), address));
+
+		let mut vault_accounts = self.vault_accounts.write().unwrap();
+		match vault {
+			Some(name) => vault_accounts.insert(address.clone(), name.to_owned()),
+			None => vault_accounts.remove(address),
+		};
+		Ok(())
+	}
+
+	fn vault_meta(&self, name: &str) -> Result<String, Error> {
+		let vaults = self.vaults.read().unwrap();
+		vaults.get(name).map(VaultDiskDirectory::meta).ok_or(Error::InvalidVault)
+	}
+
+	fn set_vault_meta(&self, name: &str, meta: String) -> Result<(), Error> {
+		let vaults = self.vaults.read().unwrap();
+		let vault = try!(vaults.get(name).ok_or(Error::InvalidVault));
+		vault.set_meta(meta)
+	}
 }
@@ -26,6 +26,9 @@ pub struct KeyFile {
 	pub version: Version,
 	pub crypto: Crypto,
 	pub address: H160,
+	pub name: Option<String>,
+	pub meta: Option<String>,
+	pub timestamp: Option<u64>,
 }
 
 enum KeyFileField {
@@ -33,6 +36,9 @@ enum KeyFileField {
 	Version,
 	Crypto,
 	Address,
+	Name,
+	Meta,
+	Timestamp,
 }
 
 impl Deserialize for KeyFileField {
@@ -57,6 +63,9 @@ impl Visitor for KeyFileFieldVisitor {
 			"crypto" => Ok(KeyFileField::Crypto),
 			"Crypto" => Ok(KeyFileField::Crypto),
 			"address" => Ok(KeyFileField::Address),
+			"name" => Ok(KeyFileField::Name),
+			"meta" => Ok(KeyFileField::Meta),
+			"timestamp" => Ok(KeyFileField::Timestamp),
 			_ => Err(Error::custom(format!("Unknown field: '{}'", value))),
 		}
 	}
@@ -66,7 +75,7 @@ impl Deserialize for KeyFile {
 	fn deserialize<D>(deserializer: &mut D) -> Result<KeyFile, D::Error>
 		where D: Deserializer
 	{
-		static FIELDS: &'static [&'static str] = &["id", "version", "crypto", "Crypto", "address"];
+		static FIELDS: &'static [&'static str] = &["id", "version", "crypto", "Crypto", "address", "name", "meta", "timestamp"];
 		deserializer.deserialize_struct("KeyFile", FIELDS, KeyFileVisitor)
 	}
 }
@@ -83,6 +92,9 @@ impl Visitor for KeyFileVisitor {
 		let mut version = None;
 		let mut crypto = None;
 		let mut address = None;
+		let mut name = None;
+		let mut meta = None;
+		let mut timestamp = None;
 
 		loop {
 			match try!(visitor.visit_key()) {
@@ -90,6 +102,9 @@ impl Visitor for KeyFileVisitor {
 				Some(KeyFileField::Version) => { version = Some(try!(visitor.visit_value())); }
 				Some(KeyFileField::Crypto) => { crypto = Some(try!(visitor.visit_value())); }
 				Some(KeyFileField::Address) => { address = Some(try!(visitor.visit_value())); }
+				Some(KeyFileField::Name) => { name = try!(visitor.visit_value()); }
+				Some(KeyFileField::Meta) => { meta = try!(visitor.visit_value()); }
+				Some(KeyFileField::Timestamp) => { timestamp = try!(visitor.visit_value()); }
 				None => { break; }
 			}
 		}
@@ -121,6 +136,9 @@ impl Visitor for KeyFileVisitor {
 			version: version,
 			crypto: crypto,
 			address: address,
+			name: name,
+			meta: meta,
+			timestamp: timestamp,
 		};
 
 		Ok(result)
@@ -186,6 +204,9 @@ mod tests {
 				}),
 				mac: H256::from_str("46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f").unwrap(),
 			},
+			name: None,
+			meta: None,
+			timestamp: None,
 		};
 
 		let keyfile: KeyFile = serde_json::from_str(json).unwrap();
@@ -235,6 +256,9 @@ mod tests {
 				}),
 				mac: H256::from_str("46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f").unwrap(),
 			},
+			name: None,
+			meta: None,
+			timestamp: None,
 		};
 
 		let keyfile: KeyFile = serde_json::from_str(json).unwrap();
@@ -261,6 +285,9 @@ mod tests {
 				}),
 				mac: H256::from_str("46325c5d4e8c991ad2683d525c7854da387138b6ca45068985aa4959fa2b8c8f").unwrap(),
 			},
+			name: Some("Test".to_owned()),
+			meta: Some("{}".to_owned()),
+			timestamp: Some(1469000000),
 		};
 
 		let serialized = serde_json::to_string(&file).unwrap();
@@ -0,0 +1,78 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{H160, H256, H520};
+
+/// Request sent to remote signer. Each request is a single line of JSON.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteRequest {
+	/// Either `accounts`, `sign` or `signTransaction`.
+	pub method: String,
+	pub address: Option<H160>,
+	pub hash: Option<H256>,
+	/// Hex-encoded RLP of the unsigned transaction.
+	pub transaction: Option<String>,
+	pub password: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteAccountsResponse {
+	pub result: Option<Vec<H160>>,
+	pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSignResponse {
+	pub result: Option<H520>,
+	pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use serde_json;
+	use json::{RemoteRequest, RemoteAccountsResponse, H160, H256};
+
+	#[test]
+	fn remote_request() {
+		let json = r#"{"method":"sign","address":"ede84640d1a1d3e06902048e67aa7db8d52c2ce1","hash":"0000000000000000000000000000000000000000000000000000000000000001","transaction":null,"password":"123"}"#;
+
+		let expected = RemoteRequest {
+			method: "sign".to_owned(),
+			address: Some(H160::from_str("ede84640d1a1d3e06902048e67aa7db8d52c2ce1").unwrap()),
+			hash: Some(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap()),
+			transaction: None,
+			password: Some("123".to_owned()),
+		};
+
+		let request: RemoteRequest = serde_json::from_str(json).unwrap();
+		assert_eq!(expected, request);
+		assert_eq!(serde_json::to_string(&request).unwrap(), json);
+	}
+
+	#[test]
+	fn remote_error_response() {
+		let json = r#"{"error":"Invalid password"}"#;
+
+		let expected = RemoteAccountsResponse {
+			result: None,
+			error: Some("Invalid password".to_owned()),
+		};
+
+		let response: RemoteAccountsResponse = serde_json::from_str(json).unwrap();
+		assert_eq!(expected, response);
+	}
+}
@@ -39,6 +39,7 @@ mod ethstore;
 mod import;
 mod presale;
 mod random;
+mod remote;
 mod secret_store;
 
 pub use self::account::SafeAccount;
@@ -46,5 +47,6 @@ pub use self::error::Error;
 pub use self::ethstore::EthStore;
 pub use self::import::import_accounts;
 pub use self::presale::PresaleWallet;
+pub use self::remote::{Endpoint, RemoteSigner, RemoteStore, serve as serve_remote_signer};
 pub use self::secret_store::SecretStore;
 
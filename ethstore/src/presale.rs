@@ -1,4 +1,5 @@
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 use rcrypto::pbkdf2::pbkdf2;
 use rcrypto::sha2::Sha256;
@@ -33,7 +34,12 @@ impl From<json::PresaleWallet> for PresaleWallet {
 impl PresaleWallet {
 	pub fn open<P>(path: P) -> Result<Self, Error> where P: AsRef<Path> {
 		let file = try!(fs::File::open(path));
-		let presale = json::PresaleWallet::load(file).unwrap();
+		PresaleWallet::load(file)
+	}
+
+	pub fn load<R>(reader: R) -> Result<Self, Error> where R: Read {
+		let presale = try!(json::PresaleWallet::load(reader)
+			.map_err(|e| Error::Custom(format!("Invalid presale wallet: {}", e))));
 		Ok(PresaleWallet::from(presale))
 	}
 
@@ -0,0 +1,423 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signing with keys held by an external process.
+//!
+//! Remote signer is reached over a Unix socket or a loopback TCP address;
+//! passwords are sent in cleartext, so other hosts are rejected.
+//! Each request and response is a single line of JSON:
+//!
+//! `{"method":"accounts"}` -> `{"result":["<address>", ...]}`
+//!
+//! `{"method":"sign","address":"<address>","hash":"<hash>","password":"<password>"}` -> `{"result":"<signature>"}`
+//!
+//! `{"method":"signTransaction","address":"<address>","transaction":"<rlp>","password":"<password>"}` -> `{"result":"<signature>"}`
+//!
+//! `<rlp>` is the hex-encoded RLP of the unsigned transaction. The signer signs its Keccak-256 hash,
+//! so it's able to inspect what it signs.
+//!
+//! Failures are reported as `{"error":"<message>"}`.
+
+use std::io::{self, Read, Write, BufRead, BufReader};
+use std::net::{SocketAddr, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use serde_json;
+use rustc_serialize::hex::{ToHex, FromHex};
+use ethkey::{Address, Message, Signature, Secret, DerivationPath};
+use {json, Error, SecretStore};
+
+/// Read and write timeout of a single remote signer request.
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// How long the list of remote accounts is cached for.
+const ACCOUNTS_CACHE_SECS: u64 = 10;
+
+/// Remote signer address.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Endpoint {
+	/// Unix domain socket.
+	#[cfg(unix)]
+	Unix(PathBuf),
+	/// TCP socket on a loopback address, e.g. `127.0.0.1:8181`.
+	Tcp(SocketAddr),
+}
+
+fn is_loopback(addr: &SocketAddr) -> bool {
+	match *addr {
+		SocketAddr::V4(ref addr) => addr.ip().is_loopback(),
+		SocketAddr::V6(ref addr) => addr.ip().is_loopback(),
+	}
+}
+
+fn tcp_endpoint(addr: SocketAddr) -> Result<Endpoint, Error> {
+	match is_loopback(&addr) {
+		true => Ok(Endpoint::Tcp(addr)),
+		false => Err(Error::RemoteSigner(format!("Remote signer must listen on a loopback address: {}", addr))),
+	}
+}
+
+impl FromStr for Endpoint {
+	type Err = Error;
+
+	#[cfg(unix)]
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.parse() {
+			Ok(addr) => tcp_endpoint(addr),
+			Err(_) => Ok(Endpoint::Unix(PathBuf::from(s))),
+		}
+	}
+
+	#[cfg(not(unix))]
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let addr = try!(s.parse().map_err(|_| Error::RemoteSigner(format!("Invalid endpoint: {}", s))));
+		tcp_endpoint(addr)
+	}
+}
+
+/// Writes single line request and reads single line response.
+/// Stream should have read and write timeouts set.
+fn exchange<S>(mut stream: S, request: &str) -> io::Result<String> where S: Read + Write {
+	try!(stream.write_all(request.as_bytes()));
+	try!(stream.write_all(b"\n"));
+	try!(stream.flush());
+	let mut response = String::new();
+	try!(BufReader::new(stream).read_line(&mut response));
+	Ok(response)
+}
+
+fn remote_error<E>(err: E) -> Error where E: ::std::fmt::Display {
+	Error::RemoteSigner(format!("{}", err))
+}
+
+/// Client of the remote signer.
+pub struct RemoteSigner {
+	endpoint: Endpoint,
+}
+
+impl RemoteSigner {
+	/// Creates client of the signer listening on `endpoint`.
+	/// Fails if `endpoint` is a TCP address other than loopback.
+	pub fn new(endpoint: Endpoint) -> Result<Self, Error> {
+		if let Endpoint::Tcp(ref addr) = endpoint {
+			try!(tcp_endpoint(addr.clone()));
+		}
+
+		Ok(RemoteSigner {
+			endpoint: endpoint,
+		})
+	}
+
+	fn call(&self, request: &json::RemoteRequest) -> Result<String, Error> {
+		let request = try!(serde_json::to_string(request).map_err(remote_error));
+		let timeout = Some(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+		let response = match self.endpoint {
+			#[cfg(unix)]
+			Endpoint::Unix(ref path) => UnixStream::connect(path).and_then(|stream| {
+				try!(stream.set_read_timeout(timeout));
+				try!(stream.set_write_timeout(timeout));
+				exchange(stream, &request)
+			}),
+			Endpoint::Tcp(ref addr) => TcpStream::connect(addr).and_then(|stream| {
+				try!(stream.set_read_timeout(timeout));
+				try!(stream.set_write_timeout(timeout));
+				exchange(stream, &request)
+			}),
+		};
+		response.map_err(remote_error)
+	}
+
+	fn signature(response: &str) -> Result<Signature, Error> {
+		let response: json::RemoteSignResponse = try!(serde_json::from_str(response).map_err(remote_error));
+		match (response.result, response.error) {
+			(Some(signature), None) => {
+				let signature: [u8; 65] = signature.into();
+				Ok(signature.into())
+			},
+			(_, Some(err)) => Err(Error::RemoteSigner(err)),
+			(None, None) => Err(Error::RemoteSigner("Empty response".into())),
+		}
+	}
+
+	/// Returns accounts managed by remote signer.
+	pub fn accounts(&self) -> Result<Vec<Address>, Error> {
+		let response = try!(self.call(&json::RemoteRequest {
+			method: "accounts".to_owned(),
+			address: None,
+			hash: None,
+			transaction: None,
+			password: None,
+		}));
+
+		let response: json::RemoteAccountsResponse = try!(serde_json::from_str(&response).map_err(remote_error));
+		match (response.result, response.error) {
+			(Some(accounts), None) => Ok(accounts.into_iter().map(Into::into).collect()),
+			(_, Some(err)) => Err(Error::RemoteSigner(err)),
+			(None, None) => Err(Error::RemoteSigner("Empty response".into())),
+		}
+	}
+
+	/// Asks remote signer to sign given hash.
+	pub fn sign(&self, account: &Address, password: &str, message: &Message) -> Result<Signature, Error> {
+		let hash: [u8; 32] = message.clone().into();
+		let response = try!(self.call(&json::RemoteRequest {
+			method: "sign".to_owned(),
+			address: Some(account.clone().into()),
+			hash: Some(hash.into()),
+			transaction: None,
+			password: Some(password.to_owned()),
+		}));
+
+		Self::signature(&response)
+	}
+
+	/// Asks remote signer to sign given RLP-encoded unsigned transaction.
+	pub fn sign_transaction(&self, account: &Address, password: &str, transaction: &[u8]) -> Result<Signature, Error> {
+		let response = try!(self.call(&json::RemoteRequest {
+			method: "signTransaction".to_owned(),
+			address: Some(account.clone().into()),
+			hash: None,
+			transaction: Some(transaction.to_hex()),
+			password: Some(password.to_owned()),
+		}));
+
+		Self::signature(&response)
+	}
+}
+
+fn sign_response(signature: Result<Signature, Error>) -> json::RemoteSignResponse {
+	match signature {
+		Ok(signature) => {
+			let signature: [u8; 65] = signature.into();
+			json::RemoteSignResponse { result: Some(signature.into()), error: None }
+		},
+		Err(err) => json::RemoteSignResponse { result: None, error: Some(format!("{}", err)) },
+	}
+}
+
+/// Handles single remote signer request using given store.
+/// Can be used to run a stand-in remote signer.
+pub fn serve<S>(mut stream: S, store: &SecretStore) -> Result<(), Error> where S: Read + Write {
+	let mut request = String::new();
+	try!(BufReader::new(&mut stream).read_line(&mut request));
+	let request: json::RemoteRequest = try!(serde_json::from_str(&request).map_err(remote_error));
+
+	let response = match (request.method.as_str(), request.address, request.hash, request.transaction, request.password) {
+		("accounts", _, _, _, _) => serde_json::to_string(&json::RemoteAccountsResponse {
+			result: Some(store.accounts().into_iter().map(Into::into).collect()),
+			error: None,
+		}),
+		("sign", Some(address), Some(hash), None, Some(password)) => {
+			let hash: [u8; 32] = hash.into();
+			serde_json::to_string(&sign_response(store.sign(&address.into(), &password, &hash.into())))
+		},
+		("signTransaction", Some(address), None, Some(transaction), Some(password)) => {
+			let signature = transaction.from_hex()
+				.map_err(|_| Error::RemoteSigner("Invalid transaction".into()))
+				.and_then(|transaction| store.sign_transaction(&address.into(), &password, &transaction));
+			serde_json::to_string(&sign_response(signature))
+		},
+		_ => serde_json::to_string(&json::RemoteSignResponse {
+			result: None,
+			error: Some("Invalid request".to_owned()),
+		}),
+	};
+
+	let response = try!(response.map_err(remote_error));
+	try!(stream.write_all(response.as_bytes()));
+	try!(stream.write_all(b"\n"));
+	try!(stream.flush());
+	Ok(())
+}
+
+fn unsupported() -> Error {
+	Error::RemoteSigner("Operation is not supported for remote accounts".into())
+}
+
+/// Secret store which delegates signing of remote accounts to `RemoteSigner`.
+/// All other accounts are handled by the local store.
+pub struct RemoteStore {
+	local: Box<SecretStore>,
+	signer: RemoteSigner,
+	remote_accounts: RwLock<Vec<Address>>,
+	accounts_refreshed: RwLock<Instant>,
+}
+
+impl RemoteStore {
+	/// Creates new store. Fails if remote signer is not reachable.
+	pub fn open(local: Box<SecretStore>, signer: RemoteSigner) -> Result<Self, Error> {
+		let remote_accounts = try!(signer.accounts());
+		let store = RemoteStore {
+			local: local,
+			signer: signer,
+			remote_accounts: RwLock::new(remote_accounts),
+			accounts_refreshed: RwLock::new(Instant::now()),
+		};
+		Ok(store)
+	}
+
+	fn is_remote(&self, account: &Address) -> bool {
+		self.remote_accounts.read().unwrap().contains(account)
+	}
+}
+
+impl SecretStore for RemoteStore {
+	fn insert_account(&self, secret: Secret, password: &str) -> Result<Address, Error> {
+		self.local.insert_account(secret, password)
+	}
+
+	fn accounts(&self) -> Vec<Address> {
+		// refresh cached remote accounts at most every `ACCOUNTS_CACHE_SECS`,
+		// keep last known ones if the signer is temporarily unavailable
+		let expired = self.accounts_refreshed.read().unwrap().elapsed() >= Duration::from_secs(ACCOUNTS_CACHE_SECS);
+		if expired {
+			*self.accounts_refreshed.write().unwrap() = Instant::now();
+			if let Ok(accounts) = self.signer.accounts() {
+				*self.remote_accounts.write().unwrap() = accounts;
+			}
+		}
+
+		let mut accounts = self.local.accounts();
+		for account in self.remote_accounts.read().unwrap().iter() {
+			if !accounts.contains(account) {
+				accounts.push(account.clone());
+			}
+		}
+		accounts
+	}
+
+	fn change_password(&self, account: &Address, old_password: &str, new_password: &str) -> Result<(), Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.change_password(account, old_password, new_password),
+		}
+	}
+
+	fn remove_account(&self, account: &Address, password: &str) -> Result<(), Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.remove_account(account, password),
+		}
+	}
+
+	fn export_account(&self, account: &Address, password: &str, new_password: Option<&str>, iterations: Option<u32>) -> Result<String, Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.export_account(account, password, new_password, iterations),
+		}
+	}
+
+	fn sign(&self, account: &Address, password: &str, message: &Message) -> Result<Signature, Error> {
+		match self.is_remote(account) {
+			true => self.signer.sign(account, password, message),
+			false => self.local.sign(account, password, message),
+		}
+	}
+
+	fn sign_transaction(&self, account: &Address, password: &str, transaction: &[u8]) -> Result<Signature, Error> {
+		match self.is_remote(account) {
+			true => self.signer.sign_transaction(account, password, transaction),
+			false => self.local.sign_transaction(account, password, transaction),
+		}
+	}
+
+	fn decrypt(&self, account: &Address, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.decrypt(account, password, shared_mac, message),
+		}
+	}
+
+	fn derive_account_from_secret(&self, account: &Address, password: &str, path: &DerivationPath) -> Result<Address, Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.derive_account_from_secret(account, password, path),
+		}
+	}
+
+	fn name(&self, account: &Address) -> Result<String, Error> {
+		match self.is_remote(account) {
+			true => Ok(String::new()),
+			false => self.local.name(account),
+		}
+	}
+
+	fn meta(&self, account: &Address) -> Result<String, Error> {
+		match self.is_remote(account) {
+			true => Ok("{\"remote\":true}".to_owned()),
+			false => self.local.meta(account),
+		}
+	}
+
+	fn timestamp(&self, account: &Address) -> Result<Option<u64>, Error> {
+		match self.is_remote(account) {
+			true => Ok(None),
+			false => self.local.timestamp(account),
+		}
+	}
+
+	fn set_name(&self, account: &Address, name: String) -> Result<(), Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.set_name(account, name),
+		}
+	}
+
+	fn set_meta(&self, account: &Address, meta: String) -> Result<(), Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.set_meta(account, meta),
+		}
+	}
+
+	fn create_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		self.local.create_vault(name, password)
+	}
+
+	fn open_vault(&self, name: &str, password: &str) -> Result<(), Error> {
+		self.local.open_vault(name, password)
+	}
+
+	fn close_vault(&self, name: &str) -> Result<(), Error> {
+		self.local.close_vault(name)
+	}
+
+	fn list_vaults(&self) -> Result<Vec<String>, Error> {
+		self.local.list_vaults()
+	}
+
+	fn list_opened_vaults(&self) -> Vec<String> {
+		self.local.list_opened_vaults()
+	}
+
+	fn change_account_vault(&self, account: &Address, vault: Option<&str>) -> Result<(), Error> {
+		match self.is_remote(account) {
+			true => Err(unsupported()),
+			false => self.local.change_account_vault(account, vault),
+		}
+	}
+
+	fn vault_meta(&self, name: &str) -> Result<String, Error> {
+		self.local.vault_meta(name)
+	}
+
+	fn set_vault_meta(&self, name: &str, meta: String) -> Result<(), Error> {
+		self.local.set_vault_meta(name, meta)
+	}
+}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethkey::{Address, Message, Signature, Secret};
+use ethkey::{Address, Message, Signature, Secret, DerivationPath};
 use Error;
 
 pub trait SecretStore: Send + Sync {
@@ -26,6 +26,50 @@ pub trait SecretStore: Send + Sync {
 
 	fn remove_account(&self, account: &Address, password: &str) -> Result<(), Error>;
 
+	/// Returns JSON key file of given account.
+	/// The key is re-encrypted if either `new_password` or `iterations` is given.
+	/// `iterations` has to be between 1 and 10 times the default number of iterations.
+	fn export_account(&self, account: &Address, password: &str, new_password: Option<&str>, iterations: Option<u32>) -> Result<String, Error>;
+
 	fn sign(&self, account: &Address, password: &str, message: &Message) -> Result<Signature, Error>;
+
+	/// Signs Keccak-256 hash of given RLP-encoded unsigned transaction.
+	fn sign_transaction(&self, account: &Address, password: &str, transaction: &[u8]) -> Result<Signature, Error>;
+
+	fn decrypt(&self, account: &Address, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error>;
+
+	/// Derives new account at BIP32 `path` using the secret of `account` as the BIP32 seed.
+	/// This is not an HD wallet: no mnemonic backs the derived keys, so they can't be recovered
+	/// from a BIP39 phrase and have to be backed up like any other key.
+	/// The new account is stored in the vault of `account`.
+	fn derive_account_from_secret(&self, account: &Address, password: &str, path: &DerivationPath) -> Result<Address, Error>;
+
+	fn name(&self, account: &Address) -> Result<String, Error>;
+
+	fn meta(&self, account: &Address) -> Result<String, Error>;
+
+	/// Returns UNIX time the account was created at, if known.
+	fn timestamp(&self, account: &Address) -> Result<Option<u64>, Error>;
+
+	fn set_name(&self, account: &Address, name: String) -> Result<(), Error>;
+
+	fn set_meta(&self, account: &Address, meta: String) -> Result<(), Error>;
+
+	fn create_vault(&self, name: &str, password: &str) -> Result<(), Error>;
+
+	fn open_vault(&self, name: &str, password: &str) -> Result<(), Error>;
+
+	fn close_vault(&self, name: &str) -> Result<(), Error>;
+
+	fn list_vaults(&self) -> Result<Vec<String>, Error>;
+
+	fn list_opened_vaults(&self) -> Vec<String>;
+
+	/// Moves account to given vault. `None` moves it back to the main keys directory.
+	fn change_account_vault(&self, account: &Address, vault: Option<&str>) -> Result<(), Error>;
+
+	fn vault_meta(&self, name: &str) -> Result<String, Error>;
+
+	fn set_vault_meta(&self, name: &str, meta: String) -> Result<(), Error>;
 }
 
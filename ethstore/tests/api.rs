@@ -20,9 +20,11 @@ extern crate ethstore;
 mod util;
 
 use std::str::FromStr;
+use std::fs::File;
+use std::io::Write;
 use ethstore::{SecretStore, EthStore};
-use ethstore::ethkey::{Random, Generator, Secret, Address};
-use ethstore::dir::DiskDirectory;
+use ethstore::ethkey::{Random, Generator, Secret, Address, DerivationPath};
+use ethstore::dir::{DiskDirectory, KeyDirectory};
 use util::TransientDir;
 
 #[test]
@@ -77,6 +79,39 @@ fn secret_store_change_password() {
 	assert!(store.sign(&accounts[0], "1", &Default::default()).is_ok());
 }
 
+#[test]
+fn secret_store_export_account() {
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let address = store.insert_account(random_secret(), "old").unwrap();
+	assert!(store.export_account(&address, "wrong", None, None).is_err());
+	assert!(store.export_account(&address, "old", None, None).is_ok());
+
+	// re-encrypt the exported key and load it into another store
+	let exported = store.export_account(&address, "old", Some("new"), Some(1024)).unwrap();
+	let other = TransientDir::create().unwrap();
+	let mut path = other.path().clone();
+	path.push("exported");
+	File::create(&path).and_then(|mut f| f.write_all(exported.as_bytes())).unwrap();
+	let other = EthStore::open(Box::new(other)).unwrap();
+	assert_eq!(other.accounts(), vec![address.clone()]);
+	assert!(other.sign(&address, "new", &Default::default()).is_ok());
+	assert!(other.sign(&address, "old", &Default::default()).is_err());
+
+	// original key is untouched
+	assert!(store.sign(&address, "old", &Default::default()).is_ok());
+}
+
+#[test]
+fn secret_store_export_account_with_invalid_iterations() {
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let address = store.insert_account(random_secret(), "old").unwrap();
+	assert!(store.export_account(&address, "old", None, Some(0)).is_err());
+	assert!(store.export_account(&address, "old", Some("new"), Some(::std::u32::MAX)).is_err());
+	assert!(store.sign(&address, "old", &Default::default()).is_ok());
+}
+
 #[test]
 fn secret_store_remove_account() {
 	let dir = TransientDir::create().unwrap();
@@ -89,6 +124,69 @@ fn secret_store_remove_account() {
 	assert!(store.remove_account(&accounts[0], "").is_err());
 }
 
+#[test]
+fn secret_store_account_name_and_meta() {
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	assert!(store.insert_account(random_secret(), "").is_ok());
+	let accounts = store.accounts();
+	assert_eq!(store.name(&accounts[0]).unwrap(), "");
+	assert_eq!(store.meta(&accounts[0]).unwrap(), "{}");
+	let created = store.timestamp(&accounts[0]).unwrap();
+	assert!(created.is_some());
+	assert!(store.set_name(&accounts[0], "Savings".to_owned()).is_ok());
+	assert!(store.set_meta(&accounts[0], "{\"tags\":[\"cold\"]}".to_owned()).is_ok());
+	assert_eq!(store.name(&accounts[0]).unwrap(), "Savings");
+	assert_eq!(store.meta(&accounts[0]).unwrap(), "{\"tags\":[\"cold\"]}");
+	assert_eq!(store.timestamp(&accounts[0]).unwrap(), created);
+	assert!(store.set_name(&Address::default(), "Nobody".to_owned()).is_err());
+}
+
+#[test]
+fn secret_store_persists_account_name() {
+	let dir = TransientDir::create().unwrap();
+	let path = dir.path().to_owned();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let address = store.insert_account(random_secret(), "").unwrap();
+	assert!(store.set_name(&address, "Savings".to_owned()).is_ok());
+
+	let reopened = EthStore::open(Box::new(DiskDirectory::at(&path))).unwrap();
+	assert_eq!(reopened.name(&address).unwrap(), "Savings");
+}
+
+#[test]
+fn secret_store_derive_account_from_secret() {
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let seed = store.insert_account(random_secret(), "pass").unwrap();
+	let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+	assert!(store.derive_account_from_secret(&seed, "wrong", &path).is_err());
+	let first = store.derive_account_from_secret(&seed, "pass", &path).unwrap();
+	let second = store.derive_account_from_secret(&seed, "pass", &DerivationPath::from_str("m/44'/60'/0'/0/1").unwrap()).unwrap();
+	assert!(first != second);
+	assert_eq!(store.accounts().len(), 3);
+	assert!(store.sign(&first, "pass", &Default::default()).is_ok());
+}
+
+#[test]
+fn secret_store_derive_account_from_secret_in_vault() {
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let seed = store.insert_account(random_secret(), "pass").unwrap();
+	store.create_vault("vault1", "vault password").unwrap();
+	store.change_account_vault(&seed, Some("vault1")).unwrap();
+	let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+
+	let derived = store.derive_account_from_secret(&seed, "pass", &path).unwrap();
+	assert_eq!(store.accounts().len(), 2);
+
+	// derived account is hidden together with the seed account
+	store.close_vault("vault1").unwrap();
+	assert!(store.accounts().is_empty());
+	store.open_vault("vault1", "vault password").unwrap();
+	assert!(store.sign(&derived, "pass", &Default::default()).is_ok());
+}
+
 fn test_path() -> &'static str {
 	match ::std::fs::metadata("ethstore") {
 		Ok(_) => "ethstore/tests/res/geth_keystore",
@@ -96,6 +194,143 @@ fn test_path() -> &'static str {
 	}
 }
 
+#[test]
+fn secret_store_vaults() {
+	let dir = TransientDir::create().unwrap();
+	let root = dir.path().clone();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let address = store.insert_account(random_secret(), "account").unwrap();
+
+	// create vault and move account into it
+	store.create_vault("vault1", "vault password").unwrap();
+	assert!(store.create_vault("vault1", "other").is_err());
+	assert!(store.create_vault("../vault2", "other").is_err());
+	store.change_account_vault(&address, Some("vault1")).unwrap();
+	assert_eq!(store.list_vaults().unwrap(), vec!["vault1".to_owned()]);
+	assert_eq!(store.list_opened_vaults(), vec!["vault1".to_owned()]);
+	assert_eq!(store.accounts(), vec![address.clone()]);
+
+	// closed vault hides its accounts
+	store.close_vault("vault1").unwrap();
+	assert!(store.list_opened_vaults().is_empty());
+	assert!(store.accounts().is_empty());
+
+	// reopening the store doesn't expose vault accounts either
+	let store = EthStore::open(Box::new(DiskDirectory::at(&root))).unwrap();
+	assert!(store.accounts().is_empty());
+	assert!(store.open_vault("vault1", "wrong password").is_err());
+	store.open_vault("vault1", "vault password").unwrap();
+	assert_eq!(store.accounts(), vec![address.clone()]);
+	assert!(store.sign(&address, "account", &Default::default()).is_ok());
+
+	// metadata
+	assert_eq!(store.vault_meta("vault1").unwrap(), "{}".to_owned());
+	store.set_vault_meta("vault1", "{\"name\":\"Savings\"}".to_owned()).unwrap();
+	assert_eq!(store.vault_meta("vault1").unwrap(), "{\"name\":\"Savings\"}".to_owned());
+
+	// move account back to the main directory
+	store.change_account_vault(&address, None).unwrap();
+	store.close_vault("vault1").unwrap();
+	assert_eq!(store.accounts(), vec![address]);
+}
+
+#[test]
+fn secret_store_vault_keys_need_vault_password() {
+	// given
+	let dir = TransientDir::create().unwrap();
+	let root = dir.path().clone();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	store.create_vault("vault1", "vault password").unwrap();
+	let address = store.insert_account(random_secret(), "account").unwrap();
+
+	// when
+	store.change_account_vault(&address, Some("vault1")).unwrap();
+
+	// then
+	let on_disk = DiskDirectory::at(root.join("vault1")).load().unwrap();
+	assert_eq!(on_disk.len(), 1);
+	assert_eq!(on_disk[0].address, address);
+	assert!(!on_disk[0].check_password("account"));
+	assert!(store.sign(&address, "account", &Default::default()).is_ok());
+}
+
+#[test]
+fn secret_store_vault_keeps_root_account_with_same_address() {
+	// given
+	let dir = TransientDir::create().unwrap();
+	let store = EthStore::open(Box::new(dir)).unwrap();
+	let secret = random_secret();
+	store.create_vault("vault1", "vault password").unwrap();
+	let address = store.insert_account(secret.clone(), "account").unwrap();
+	store.change_account_vault(&address, Some("vault1")).unwrap();
+	store.close_vault("vault1").unwrap();
+	assert_eq!(store.insert_account(secret, "root").unwrap(), address);
+
+	// when
+	store.open_vault("vault1", "vault password").unwrap();
+	store.close_vault("vault1").unwrap();
+
+	// then
+	assert_eq!(store.accounts(), vec![address.clone()]);
+	assert!(store.sign(&address, "root", &Default::default()).is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn secret_store_remote_signer() {
+	use std::os::unix::net::UnixListener;
+	use std::sync::Arc;
+	use std::thread;
+	use ethstore::{RemoteSigner, RemoteStore, Endpoint, serve_remote_signer};
+	use ethstore::ethkey::verify_address;
+
+	// stand-in signer process holding its own keys
+	let remote = Arc::new(EthStore::open(Box::new(TransientDir::create().unwrap())).unwrap());
+	let remote_account = remote.insert_account(random_secret(), "remote").unwrap();
+	let socket = util::random_dir();
+	let listener = UnixListener::bind(&socket).unwrap();
+	let server = remote.clone();
+	thread::spawn(move || {
+		for stream in listener.incoming() {
+			serve_remote_signer(stream.unwrap(), &*server).unwrap();
+		}
+	});
+
+	let local = EthStore::open(Box::new(TransientDir::create().unwrap())).unwrap();
+	let local_account = local.insert_account(random_secret(), "local").unwrap();
+	let signer = RemoteSigner::new(Endpoint::Unix(socket.clone())).unwrap();
+	let store = RemoteStore::open(Box::new(local), signer).unwrap();
+
+	let accounts = store.accounts();
+	assert_eq!(accounts.len(), 2);
+	assert!(accounts.contains(&local_account));
+	assert!(accounts.contains(&remote_account));
+
+	let message = Default::default();
+	let signature = store.sign(&remote_account, "remote", &message).unwrap();
+	assert!(verify_address(&remote_account, &signature, &message).unwrap());
+	assert!(store.sign(&remote_account, "wrong", &message).is_err());
+	assert!(store.sign(&local_account, "local", &message).is_ok());
+	assert!(store.change_password(&remote_account, "remote", "new").is_err());
+
+	// transactions are signed by their hash
+	let transaction = vec![0xc0u8];
+	let signature = store.sign_transaction(&remote_account, "remote", &transaction).unwrap();
+	assert_eq!(signature, remote.sign_transaction(&remote_account, "remote", &transaction).unwrap());
+	assert!(store.sign_transaction(&remote_account, "wrong", &transaction).is_err());
+	::std::fs::remove_file(socket).unwrap();
+}
+
+#[test]
+fn remote_signer_accepts_only_local_endpoints() {
+	use ethstore::{RemoteSigner, Endpoint};
+
+	assert!("127.0.0.1:8545".parse::<Endpoint>().is_ok());
+	assert!("[::1]:8545".parse::<Endpoint>().is_ok());
+	assert!("10.0.0.1:8545".parse::<Endpoint>().is_err());
+	assert!(RemoteSigner::new(Endpoint::Tcp("8.8.8.8:8545".parse().unwrap())).is_err());
+}
+
 fn pat_path() -> &'static str {
 	match ::std::fs::metadata("ethstore") {
 		Ok(_) => "ethstore/tests/res/pat",
@@ -16,4 +16,4 @@
 
 mod transient_dir;
 
-pub use self::transient_dir::TransientDir;
+pub use self::transient_dir::{TransientDir, random_dir};
@@ -44,6 +44,10 @@ impl TransientDir {
 		Ok(result)
 	}
 
+	pub fn path(&self) -> &PathBuf {
+		&self.path
+	}
+
 	pub fn open() -> Self {
 		let path = random_dir();
 		TransientDir {
@@ -71,4 +75,8 @@ impl KeyDirectory for TransientDir {
 	fn remove(&self, address: &Address) -> Result<(), Error> {
 		self.dir.remove(address)
 	}
+
+	fn path(&self) -> Option<&PathBuf> {
+		self.dir.path()
+	}
 }
@@ -0,0 +1,56 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Spec fork checkpoint deserialization.
+
+use uint::Uint;
+use hash::H256;
+use bytes::Bytes;
+
+/// Block every peer on our side of a chain split is expected to have.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ForkCheckpoint {
+	/// Block number.
+	pub number: Uint,
+	/// Expected block hash.
+	pub hash: Option<H256>,
+	/// Expected block extra data.
+	#[serde(rename="extraData")]
+	pub extra_data: Option<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use spec::fork::ForkCheckpoint;
+
+	#[test]
+	fn fork_checkpoint_deserialization() {
+		let s = r#"[{
+			"number": "0x1d4c00",
+			"hash": "0x4985f5ca3d2afbec36529aa96f74de3cc10a2a4a6c44f2157a57d2c6059a11bb"
+		}, {
+			"number": "0x1d4c00",
+			"extraData": "0x64616f2d686172642d666f726b"
+		}]"#;
+
+		let deserialized: Vec<ForkCheckpoint> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.len(), 2);
+		assert!(deserialized[0].hash.is_some() && deserialized[0].extra_data.is_none());
+		assert!(deserialized[1].hash.is_none() && deserialized[1].extra_data.is_some());
+	}
+}
@@ -17,6 +17,7 @@
 //! Spec params deserialization.
 
 use uint::Uint;
+use spec::ForkCheckpoint;
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -33,6 +34,9 @@ pub struct Params {
 	/// Minimum gas limit.
 	#[serde(rename="minGasLimit")]
 	pub min_gas_limit: Uint,
+	/// Blocks expected on our side of a chain split.
+	#[serde(rename="forkCheckpoints")]
+	pub fork_checkpoints: Option<Vec<ForkCheckpoint>>,
 }
 
 #[cfg(test)]
@@ -25,6 +25,8 @@ Usage:
   parity daemon <pid-file> [options]
   parity account (new | list ) [options]
   parity account import <path>... [options]
+  parity account export <address> [options]
+  parity wallet import <path> --password FILE [options]
   parity import [ <file> ] [options]
   parity export [ <file> ] [options]
   parity signer new-token [options]
@@ -34,7 +36,8 @@ Usage:
 Protocol Options:
   --chain CHAIN            Specify the blockchain type. CHAIN may be either a
                            JSON chain specification file or olympic, frontier,
-                           homestead, mainnet, morden, or testnet
+                           homestead, mainnet, morden, testnet or dev
+                           (instantly sealing development chain)
                            [default: homestead].
   -d --db-path PATH        Specify the database & configuration directory path
                            [default: $HOME/.parity].
@@ -59,6 +62,12 @@ Account Options:
                            deriving key from the password (bigger is more
                            secure) [default: 10240].
   --no-import-keys         Do not import keys from legacy clients.
+  --remote-signer ENDPOINT
+                           Delegate signing of accounts held by an external
+                           signer process listening on ENDPOINT. ENDPOINT is
+                           either a path to a Unix socket or a loopback
+                           HOST:PORT address; other hosts are rejected as
+                           passwords are sent in cleartext.
 
 Networking Options:
   --no-network             Disable p2p networking.
@@ -79,6 +88,25 @@ Networking Options:
                            These nodes will always have a reserved slot on top
                            of the normal maximum peers.
   --reserved-only          Connect only to reserved nodes.
+  --allow-ips FILTER       Filter outbound and inbound connections by IP.
+                           FILTER is a space-delimited list of: all, public,
+                           private, none, CIDR networks to allow and
+                           -CIDR networks to deny [default: all].
+  --max-upload KBPS        Limit total upload rate to KBPS kilobytes per
+                           second. 0 means no limit [default: 0].
+  --max-download KBPS      Limit total download rate to KBPS kilobytes per
+                           second. 0 means no limit [default: 0].
+  --max-peer-upload KBPS   Limit upload rate to each peer to KBPS kilobytes
+                           per second. 0 means no limit [default: 0].
+  --max-peer-download KBPS
+                           Limit download rate from each peer to KBPS
+                           kilobytes per second. 0 means no limit
+                           [default: 0].
+  --fast-sync              Download the state of a recent block instead of
+                           executing all blocks from genesis. An interrupted
+                           download resumes on restart.
+  --whisper                Enable the Whisper messaging protocol. Use the shh
+                           JSON-RPC API to send and receive messages.
 
 API and Console Options:
   --jsonrpc-off            Disable the JSON-RPC API server.
@@ -91,7 +119,7 @@ API and Console Options:
   --jsonrpc-apis APIS      Specify the APIs available through the JSONRPC
                            interface. APIS is a comma-delimited list of API
                            name. Possible name are web3, eth, net, personal,
-                           ethcore, ethcore_set, traces.
+                           ethcore, ethcore_set, traces, shh.
                            [default: web3,eth,net,ethcore,personal,traces].
 
   --ipc-off                Disable JSON-RPC over IPC service.
@@ -127,6 +155,23 @@ API and Console Options:
 Sealing/Mining Options:
   --force-sealing          Force the node to author new blocks as if it were
                            always sealing/mining.
+  --reseal-on-txs SET      Specify which transactions should force the node
+                           to reseal a block. SET is one of:
+                           none - never reseal on new transactions;
+                           own - reseal only on a new local transaction;
+                           ext - reseal only on a new external transaction;
+                           all - reseal on all new transactions [default: all].
+  --reseal-min-period MS   Specify the minimum time between reseals from
+                           incoming transactions. MS is time measured in
+                           milliseconds [default: 0].
+  --reseal-max-period MS   Specify the maximum time between reseals, even if
+                           there were no new transactions. MS is time measured
+                           in milliseconds, 0 disables periodic resealing
+                           [default: 0].
+  --seal-empty-blocks      Keep sealing empty blocks every --reseal-max-period
+                           when the chain's engine seals internally (e.g.
+                           authority or dev chains). Requires a non-zero
+                           --reseal-max-period.
   --usd-per-tx USD         Amount of USD to be paid for a basic transaction
                            [default: 0.005]. The minimum gas price is set
                            accordingly.
@@ -136,6 +181,12 @@ Sealing/Mining Options:
                            good value [default: auto].
   --gas-floor-target GAS   Amount of gas per block to target when sealing a new
                            block [default: 3141592].
+  --gas-cap GAS            A cap on how large we will raise the gas limit per
+                           block due to transaction volume. By default there
+                           is no cap.
+  --gas-fullness BLOCKS    Move the gas limit target between the floor and
+                           the cap depending on how full the last BLOCKS
+                           blocks were.
   --author ADDRESS         Specify the block author (aka "coinbase") address
                            for sending block rewards from sealed blocks
                            [default: 0037a6b811ffeb6e072da21179d11b1406371c63].
@@ -143,6 +194,18 @@ Sealing/Mining Options:
                            more than 32 characters.
   --tx-limit LIMIT         Limit of transactions kept in the queue (waiting to
                            be included in next block) [default: 1024].
+  --tx-sender-limit LIMIT  Limit of transactions from a single sender kept in
+                           the queue. Own transactions are not limited.
+  --tx-gas-limit GAS       Maximal amount of gas a single transaction may
+                           declare to be accepted to the queue.
+  --tx-allow-senders ADDRESSES  Accept transactions only from ADDRESSES.
+                           ADDRESSES is a comma-delimited list of addresses.
+  --tx-deny-senders ADDRESSES   Reject transactions from ADDRESSES.
+  --tx-allow-recipients ADDRESSES  Accept transactions only to ADDRESSES
+                           (contract creations are always accepted).
+  --tx-deny-recipients ADDRESSES   Reject transactions to ADDRESSES.
+  --tx-priority-senders ADDRESSES  Include transactions from ADDRESSES before
+                           other transactions with higher gas price.
 
 Footprint Options:
   --tracing BOOL           Indicates if full transaction tracing should be
@@ -223,6 +286,7 @@ Miscellaneous Options:
 pub struct Args {
 	pub cmd_daemon: bool,
 	pub cmd_account: bool,
+	pub cmd_wallet: bool,
 	pub cmd_new: bool,
 	pub cmd_list: bool,
 	pub cmd_export: bool,
@@ -233,6 +297,7 @@ pub struct Args {
 	pub arg_pid_file: String,
 	pub arg_file: Option<String>,
 	pub arg_path: Vec<String>,
+	pub arg_address: String,
 	pub flag_chain: String,
 	pub flag_db_path: String,
 	pub flag_identity: String,
@@ -244,6 +309,7 @@ pub struct Args {
 	pub flag_keys_path: String,
 	pub flag_keys_iterations: u32,
 	pub flag_no_import_keys: bool,
+	pub flag_remote_signer: Option<String>,
 	pub flag_bootnodes: Option<String>,
 	pub flag_network_id: Option<String>,
 	pub flag_pruning: String,
@@ -255,6 +321,13 @@ pub struct Args {
 	pub flag_node_key: Option<String>,
 	pub flag_reserved_peers: Option<String>,
 	pub flag_reserved_only: bool,
+	pub flag_allow_ips: String,
+	pub flag_max_upload: usize,
+	pub flag_max_download: usize,
+	pub flag_max_peer_upload: usize,
+	pub flag_max_peer_download: usize,
+	pub flag_fast_sync: bool,
+	pub flag_whisper: bool,
 	pub flag_cache_pref_size: usize,
 	pub flag_cache_max_size: usize,
 	pub flag_queue_max_size: usize,
@@ -277,12 +350,25 @@ pub struct Args {
 	pub flag_signer_path: String,
 	pub flag_no_token: bool,
 	pub flag_force_sealing: bool,
+	pub flag_reseal_on_txs: String,
+	pub flag_reseal_min_period: u64,
+	pub flag_reseal_max_period: u64,
+	pub flag_seal_empty_blocks: bool,
 	pub flag_author: String,
 	pub flag_usd_per_tx: String,
 	pub flag_usd_per_eth: String,
 	pub flag_gas_floor_target: String,
+	pub flag_gas_cap: Option<String>,
+	pub flag_gas_fullness: Option<usize>,
 	pub flag_extra_data: Option<String>,
 	pub flag_tx_limit: usize,
+	pub flag_tx_sender_limit: Option<usize>,
+	pub flag_tx_gas_limit: Option<String>,
+	pub flag_tx_allow_senders: Option<String>,
+	pub flag_tx_deny_senders: Option<String>,
+	pub flag_tx_allow_recipients: Option<String>,
+	pub flag_tx_deny_recipients: Option<String>,
+	pub flag_tx_priority_senders: Option<String>,
 	pub flag_logging: Option<String>,
 	pub flag_version: bool,
 	pub flag_from: String,
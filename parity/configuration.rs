@@ -19,16 +19,20 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::net::{SocketAddr, IpAddr};
 use std::path::PathBuf;
+use std::time::Duration;
 use cli::{USAGE, Args};
 use docopt::Docopt;
 
 use die::*;
 use util::*;
 use ethcore::account_provider::AccountProvider;
+use ethcore::ethstore::Endpoint;
 use util::network_settings::NetworkSettings;
 use ethcore::client::{append_path, get_db_path, ClientConfig, Switch, VMType};
 use ethcore::ethereum;
+use ethcore::instant_seal;
 use ethcore::spec::Spec;
+use ethcore::miner::{MinerOptions, GasLimitStrategy, ConfigurablePolicy, AccessList};
 use ethsync::SyncConfig;
 use price_info::PriceInfo;
 use rpc::IpcConfiguration;
@@ -85,6 +89,15 @@ impl Configuration {
 		}
 	}
 
+	pub fn gas_ceil_target(&self) -> U256 {
+		match self.args.flag_gas_cap.as_ref() {
+			Some(d) => U256::from_dec_str(d).unwrap_or_else(|_| {
+				die!("{}: Invalid target gas cap given. Must be a decimal unsigned 256-bit number.", d)
+			}),
+			None => !U256::zero(),
+		}
+	}
+
 
 
 	pub fn gas_price(&self) -> U256 {
@@ -120,6 +133,66 @@ impl Configuration {
 		}
 	}
 
+	fn addresses(list: &str, flag: &str) -> HashSet<Address> {
+		list.split(',').map(|d| {
+			Address::from_str(clean_0x(d)).unwrap_or_else(|_| {
+				die!("{}: Invalid address for {}. Must be 40 hex characters, with or without the 0x at the beginning.", d, flag)
+			})
+		}).collect()
+	}
+
+	fn access_list(allow: &Option<String>, deny: &Option<String>, flags: (&str, &str)) -> AccessList {
+		match (allow.as_ref(), deny.as_ref()) {
+			(Some(_), Some(_)) => die!("{} and {} cannot be used together.", flags.0, flags.1),
+			(Some(list), None) => AccessList::Allow(Self::addresses(list, flags.0)),
+			(None, Some(list)) => AccessList::Deny(Self::addresses(list, flags.1)),
+			(None, None) => AccessList::All,
+		}
+	}
+
+	pub fn transaction_policy(&self) -> ConfigurablePolicy {
+		ConfigurablePolicy {
+			max_per_sender: self.args.flag_tx_sender_limit,
+			max_gas: self.args.flag_tx_gas_limit.as_ref().map(|d| U256::from_dec_str(d).unwrap_or_else(|_| {
+				die!("{}: Invalid transaction gas limit given. Must be a decimal unsigned 256-bit number.", d)
+			})),
+			senders: Self::access_list(
+				&self.args.flag_tx_allow_senders,
+				&self.args.flag_tx_deny_senders,
+				("--tx-allow-senders", "--tx-deny-senders")
+			),
+			recipients: Self::access_list(
+				&self.args.flag_tx_allow_recipients,
+				&self.args.flag_tx_deny_recipients,
+				("--tx-allow-recipients", "--tx-deny-recipients")
+			),
+			priority_senders: self.args.flag_tx_priority_senders.as_ref()
+				.map_or_else(HashSet::new, |list| Self::addresses(list, "--tx-priority-senders")),
+		}
+	}
+
+	pub fn miner_options(&self) -> MinerOptions {
+		let (own, ext) = match self.args.flag_reseal_on_txs.as_str() {
+			"none" => (false, false),
+			"own" => (true, false),
+			"ext" => (false, true),
+			"all" => (true, true),
+			x => die!("{}: Invalid value for --reseal-on-txs. Must be one of: none, own, ext, all.", x),
+		};
+		MinerOptions {
+			force_sealing: self.args.flag_force_sealing,
+			reseal_on_external_tx: ext,
+			reseal_on_own_tx: own,
+			reseal_min_period: Duration::from_millis(self.args.flag_reseal_min_period),
+			reseal_max_period: match self.args.flag_reseal_max_period {
+				0 => None,
+				ms => Some(Duration::from_millis(ms)),
+			},
+			seal_empty_blocks: self.args.flag_seal_empty_blocks,
+			gas_limit_strategy: self.args.flag_gas_fullness.map_or(GasLimitStrategy::Targets, GasLimitStrategy::Fullness),
+		}
+	}
+
 	pub fn extra_data(&self) -> Bytes {
 		if !self.args.flag_dont_help_rescue_dao {
 			(b"rescuedao"[..]).to_owned()
@@ -137,6 +210,7 @@ impl Configuration {
 			"frontier" | "homestead" | "mainnet" => ethereum::new_frontier(!self.args.flag_dogmatic),
 			"morden" | "testnet" => ethereum::new_morden(),
 			"olympic" => ethereum::new_olympic(),
+			"dev" => instant_seal::new_instant_seal(),
 			f => Spec::load(contents(f).unwrap_or_else(|_| {
 				die!("{}: Couldn't read chain specification file. Sure it exists?", f)
 			}).as_ref()),
@@ -195,6 +269,28 @@ impl Configuration {
 		(listen_address, public_address)
 	}
 
+	pub fn ip_filter(&self) -> IpFilter {
+		IpFilter::parse(&self.args.flag_allow_ips).unwrap_or_else(|| {
+			die!("{}: Invalid IP filter given. Use all, public, private, none or CIDR networks optionally prefixed with `-`.", self.args.flag_allow_ips)
+		})
+	}
+
+	fn bandwidth_limit(upload_kbps: usize, download_kbps: usize) -> BandwidthLimit {
+		let limit = |kbps: usize| if kbps == 0 { None } else { Some(kbps * 1024) };
+		BandwidthLimit {
+			upload: limit(upload_kbps),
+			download: limit(download_kbps),
+		}
+	}
+
+	pub fn global_bandwidth(&self) -> BandwidthLimit {
+		Configuration::bandwidth_limit(self.args.flag_max_upload, self.args.flag_max_download)
+	}
+
+	pub fn peer_bandwidth(&self) -> BandwidthLimit {
+		Configuration::bandwidth_limit(self.args.flag_max_peer_upload, self.args.flag_max_peer_download)
+	}
+
 	pub fn net_settings(&self, spec: &Spec) -> NetworkConfiguration {
 		let mut ret = NetworkConfiguration::new();
 		ret.nat_enabled = self.args.flag_nat == "any" || self.args.flag_nat == "upnp";
@@ -209,6 +305,9 @@ impl Configuration {
 		net_path.push("network");
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = self.init_reserved_nodes();
+		ret.ip_filter = self.ip_filter();
+		ret.global_bandwidth = self.global_bandwidth();
+		ret.peer_bandwidth = self.peer_bandwidth();
 
 		if self.args.flag_reserved_only {
 			ret.non_reserved_mode = ::util::network::NonReservedPeerMode::Deny;
@@ -287,21 +386,28 @@ impl Configuration {
 		sync_config.network_id = self.args.flag_network_id.as_ref().or(self.args.flag_networkid.as_ref()).map_or(spec.network_id(), |id| {
 			U256::from_str(id).unwrap_or_else(|_| die!("{}: Invalid index given with --network-id/--networkid", id))
 		});
+		sync_config.fast_sync = self.args.flag_fast_sync;
+		sync_config.fork_checkpoints = spec.params.fork_checkpoints.clone();
 		sync_config
 	}
 
-	pub fn account_service(&self) -> AccountProvider {
-		use ethcore::ethstore::{import_accounts, EthStore};
-		use ethcore::ethstore::dir::{GethDirectory, DirectoryType, DiskDirectory};
-
-		// Secret Store
-		let passwords = self.args.flag_password.iter().flat_map(|filename| {
+	/// Returns passwords read from all `--password` files (one per line).
+	pub fn passwords(&self) -> Vec<String> {
+		self.args.flag_password.iter().flat_map(|filename| {
 			BufReader::new(&File::open(filename).unwrap_or_else(|_| die!("{} Unable to read password file. Ensure it exists and permissions are correct.", filename)))
 				.lines()
 				.map(|l| l.unwrap())
 				.collect::<Vec<_>>()
 				.into_iter()
-		}).collect::<Vec<_>>();
+		}).collect()
+	}
+
+	pub fn account_service(&self) -> AccountProvider {
+		use ethcore::ethstore::{import_accounts, EthStore, RemoteStore, RemoteSigner};
+		use ethcore::ethstore::dir::{GethDirectory, DirectoryType, DiskDirectory};
+
+		// Secret Store
+		let passwords = self.passwords();
 
 		if !self.args.flag_no_import_keys {
 			let dir_type = if self.args.flag_testnet {
@@ -318,7 +424,17 @@ impl Configuration {
 
 		let dir = Box::new(DiskDirectory::create(self.keys_path()).unwrap());
 		let iterations = self.keys_iterations();
-		let account_service = AccountProvider::new(Box::new(EthStore::open_with_iterations(dir, iterations).unwrap()));
+		let store = EthStore::open_with_iterations(dir, iterations).unwrap();
+		let account_service = match self.remote_signer() {
+			Some(endpoint) => {
+				let signer = RemoteSigner::new(endpoint)
+					.unwrap_or_else(|e| die!("Invalid remote signer endpoint: {}", e));
+				let store = RemoteStore::open(Box::new(store), signer)
+					.unwrap_or_else(|e| die!("Unable to connect to remote signer: {}", e));
+				AccountProvider::new(Box::new(store))
+			},
+			None => AccountProvider::new(Box::new(store)),
+		};
 
 		if let Some(ref unlocks) = self.args.flag_unlock {
 			for d in unlocks.split(',') {
@@ -353,6 +469,12 @@ impl Configuration {
 		}
 	}
 
+	pub fn remote_signer(&self) -> Option<Endpoint> {
+		self.args.flag_remote_signer.as_ref().map(|endpoint| {
+			endpoint.parse().unwrap_or_else(|e| die!("{}: Invalid remote signer endpoint: {}", endpoint, e))
+		})
+	}
+
 	pub fn keys_iterations(&self) -> u32 {
 		self.args.flag_keys_iterations
 	}
@@ -446,6 +568,10 @@ mod tests {
 	use cli::USAGE;
 	use docopt::Docopt;
 	use util::network_settings::NetworkSettings;
+	use util::{Address, U256, FromStr};
+	use ethcore::miner::AccessList;
+	use ethcore::ethstore::Endpoint;
+	use util::network::{IpFilter, IpNetwork, AllowIP, BandwidthLimit};
 
 	fn parse(args: &[&str]) -> Configuration {
 		Configuration {
@@ -502,5 +628,127 @@ mod tests {
 		assert(conf1);
 		assert(conf2);
 	}
-}
 
+	#[test]
+	fn should_parse_transaction_policy() {
+		// given
+		let allowed = "0x0037a6b811ffeb6e072da21179d11b1406371c63";
+
+		// when
+		let default = parse(&["parity"]).transaction_policy();
+		let conf = parse(&["parity",
+						 "--tx-sender-limit", "16",
+						 "--tx-gas-limit", "100000",
+						 "--tx-allow-senders", allowed,
+						 "--tx-priority-senders", allowed
+						 ]).transaction_policy();
+
+		// then
+		let address = Address::from_str(&allowed[2..]).unwrap();
+		assert_eq!(default.max_per_sender, None);
+		assert_eq!(default.senders, AccessList::All);
+		assert_eq!(conf.max_per_sender, Some(16));
+		assert_eq!(conf.max_gas, Some(U256::from(100_000)));
+		assert_eq!(conf.senders, AccessList::Allow(vec![address].into_iter().collect()));
+		assert_eq!(conf.recipients, AccessList::All);
+		assert!(conf.priority_senders.contains(&address));
+	}
+
+	#[test]
+	fn should_parse_miner_options() {
+		// when
+		let default = parse(&["parity"]).miner_options();
+		let conf = parse(&["parity",
+						 "--reseal-on-txs", "own",
+						 "--reseal-min-period", "4000",
+						 "--reseal-max-period", "60000",
+						 "--seal-empty-blocks"
+						 ]).miner_options();
+
+		// then
+		assert_eq!(default, MinerOptions::default());
+		assert!(conf.reseal_on_own_tx);
+		assert!(!conf.reseal_on_external_tx);
+		assert_eq!(conf.reseal_min_period, Duration::from_millis(4000));
+		assert_eq!(conf.reseal_max_period, Some(Duration::from_millis(60000)));
+		assert!(conf.seal_empty_blocks);
+	}
+
+	#[test]
+	fn should_parse_gas_targets() {
+		// when
+		let default = parse(&["parity"]);
+		let conf = parse(&["parity", "--gas-cap", "6283184", "--gas-fullness", "10"]);
+
+		// then
+		assert_eq!(default.gas_ceil_target(), !U256::zero());
+		assert_eq!(default.miner_options().gas_limit_strategy, GasLimitStrategy::Targets);
+		assert_eq!(conf.gas_ceil_target(), U256::from(6_283_184));
+		assert_eq!(conf.miner_options().gas_limit_strategy, GasLimitStrategy::Fullness(10));
+	}
+
+	#[test]
+	fn should_parse_account_export() {
+		// when
+		let conf = parse(&["parity", "account", "export", "0x0000000000000000000000000000000000000001"]);
+
+		// then
+		assert!(conf.args.cmd_account);
+		assert!(conf.args.cmd_export);
+		assert_eq!(conf.args.arg_address, "0x0000000000000000000000000000000000000001".to_owned());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn should_parse_remote_signer_endpoint() {
+		// given
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--remote-signer", "127.0.0.1:8545"]);
+		let conf2 = parse(&["parity", "--remote-signer", "/tmp/signer.ipc"]);
+
+		// then
+		assert_eq!(conf0.remote_signer(), None);
+		assert_eq!(conf1.remote_signer(), Some(Endpoint::Tcp("127.0.0.1:8545".parse().unwrap())));
+		assert_eq!(conf2.remote_signer(), Some(Endpoint::Unix("/tmp/signer.ipc".into())));
+	}
+
+	#[test]
+	fn should_parse_ip_filter() {
+		// given
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--allow-ips", "public 10.0.0.0/8 -10.1.0.0/16"]);
+
+		// then
+		assert_eq!(conf0.ip_filter(), IpFilter::default());
+		assert_eq!(conf1.ip_filter(), IpFilter {
+			predefined: AllowIP::Public,
+			custom_allow: vec![IpNetwork::V4("10.0.0.0".parse().unwrap(), 8)],
+			custom_block: vec![IpNetwork::V4("10.1.0.0".parse().unwrap(), 16)],
+		});
+	}
+
+	#[test]
+	fn should_parse_bandwidth_limits() {
+		// given
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--max-upload", "100", "--max-peer-download", "10"]);
+
+		// then
+		assert_eq!(conf0.global_bandwidth(), BandwidthLimit::default());
+		assert_eq!(conf0.peer_bandwidth(), BandwidthLimit::default());
+		assert_eq!(conf1.global_bandwidth(), BandwidthLimit { upload: Some(100 * 1024), download: None });
+		assert_eq!(conf1.peer_bandwidth(), BandwidthLimit { upload: None, download: Some(10 * 1024) });
+	}
+
+	#[test]
+	fn should_parse_wallet_import() {
+		// when
+		let conf = parse(&["parity", "wallet", "import", "presale.json", "--password", "pwd.txt"]);
+
+		// then
+		assert!(conf.args.cmd_wallet);
+		assert!(conf.args.cmd_import);
+		assert_eq!(conf.args.arg_path, vec!["presale.json".to_owned()]);
+		assert_eq!(conf.args.flag_password, vec!["pwd.txt".to_owned()]);
+	}
+}
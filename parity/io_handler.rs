@@ -18,6 +18,7 @@ use std::sync::{Arc, Weak};
 use ethcore::client::Client;
 use ethcore::service::{NetSyncMessage, SyncMessage};
 use ethsync::EthSync;
+use ethcore_whisper::Whisper;
 use ethcore::account_provider::AccountProvider;
 use util::{TimerToken, IoHandler, IoContext, NetworkService, NetworkIoMessage};
 
@@ -31,6 +32,7 @@ pub struct ClientIoHandler {
 	pub accounts: Arc<AccountProvider>,
 	pub info: Informant,
 	pub network: Weak<NetworkService<SyncMessage>>,
+	pub whisper: Option<Arc<Whisper>>,
 }
 
 impl IoHandler<NetSyncMessage> for ClientIoHandler {
@@ -51,6 +53,9 @@ impl IoHandler<NetSyncMessage> for ClientIoHandler {
 				if let Some(network) = self.network.upgrade() {
 					network.start().unwrap_or_else(|e| warn!("Error starting network: {:?}", e));
 					EthSync::register(&*network, self.sync.clone()).unwrap_or_else(|e| warn!("Error registering eth protocol handler: {}", e));
+					if let Some(ref whisper) = self.whisper {
+						Whisper::register(&*network, whisper.clone()).unwrap_or_else(|e| warn!("Error registering whisper protocol handler: {}", e));
+					}
 				}
 			},
 			NetworkIoMessage::User(SyncMessage::StopNetwork) => {
@@ -27,6 +27,7 @@ extern crate rustc_serialize;
 extern crate ethcore_util as util;
 extern crate ethcore;
 extern crate ethsync;
+extern crate ethcore_whisper;
 #[macro_use]
 extern crate log as rlog;
 extern crate env_logger;
@@ -87,6 +88,7 @@ use ethcore::error::{Error, ImportError};
 use ethcore::service::ClientService;
 use ethcore::spec::Spec;
 use ethsync::EthSync;
+use ethcore_whisper::{Whisper, WhisperConfig};
 use ethcore::miner::{Miner, MinerService, ExternalMiner};
 use migration::migrate;
 use informant::Informant;
@@ -129,6 +131,11 @@ fn execute(conf: Configuration) {
 		return;
 	}
 
+	if conf.args.cmd_wallet {
+		execute_wallet_cli(conf);
+		return;
+	}
+
 	if conf.args.cmd_export {
 		execute_export(conf);
 		return;
@@ -197,12 +204,14 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 	let account_service = Arc::new(conf.account_service());
 
 	// Miner
-	let miner = Miner::with_accounts(conf.args.flag_force_sealing, conf.spec(), account_service.clone());
+	let miner = Miner::with_accounts(conf.miner_options(), conf.spec(), account_service.clone());
 	miner.set_author(conf.author());
 	miner.set_gas_floor_target(conf.gas_floor_target());
+	miner.set_gas_ceil_target(conf.gas_ceil_target());
 	miner.set_extra_data(conf.extra_data());
 	miner.set_minimal_gas_price(conf.gas_price());
 	miner.set_transactions_limit(conf.args.flag_tx_limit);
+	miner.set_transaction_policy(Box::new(conf.transaction_policy()));
 
 	// Build client
 	let mut service = ClientService::start(
@@ -219,6 +228,15 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 	let sync = EthSync::new(sync_config, client.clone());
 	EthSync::register(&*service.network(), sync.clone()).unwrap_or_else(|e| die_with_error("Error registering eth protocol handler", UtilError::from(e).into()));
 
+	// Whisper
+	let whisper = if conf.args.flag_whisper {
+		let whisper = Whisper::new(WhisperConfig::default());
+		Whisper::register(&*service.network(), whisper.clone()).unwrap_or_else(|e| die_with_error("Error registering whisper protocol handler", UtilError::from(e).into()));
+		Some(whisper)
+	} else {
+		None
+	};
+
 	let deps_for_rpc_apis = Arc::new(rpc_apis::Dependencies {
 		signer_port: conf.signer_port(),
 		signer_queue: Arc::new(rpc_apis::ConfirmationsQueue::default()),
@@ -231,6 +249,7 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 		settings: network_settings.clone(),
 		allow_pending_receipt_query: !conf.args.flag_geth,
 		net_service: service.network(),
+		whisper: whisper.clone(),
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -281,6 +300,7 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 		sync: sync.clone(),
 		accounts: account_service.clone(),
 		network: Arc::downgrade(&service.network()),
+		whisper: whisper,
 	});
 	service.register_io_handler(io_handler).expect("Error registering IO handler");
 
@@ -321,6 +341,10 @@ fn execute_export(conf: Configuration) {
 		ideal_peers: 0,
 		reserved_nodes: Vec::new(),
 		non_reserved_mode: ::util::network::NonReservedPeerMode::Accept,
+		ip_filter: ::util::network::IpFilter::default(),
+		global_bandwidth: ::util::network::BandwidthLimit::default(),
+		peer_bandwidth: ::util::network::BandwidthLimit::default(),
+		compression: true,
 	};
 	let client_config = conf.client_config(&spec);
 
@@ -393,6 +417,10 @@ fn execute_import(conf: Configuration) {
 		ideal_peers: 0,
 		reserved_nodes: Vec::new(),
 		non_reserved_mode: ::util::network::NonReservedPeerMode::Accept,
+		ip_filter: ::util::network::IpFilter::default(),
+		global_bandwidth: ::util::network::BandwidthLimit::default(),
+		peer_bandwidth: ::util::network::BandwidthLimit::default(),
+		compression: true,
 	};
 	let client_config = conf.client_config(&spec);
 
@@ -532,6 +560,59 @@ fn execute_account_cli(conf: Configuration) {
 		}
 		println!("Imported {} keys", imported);
 	}
+
+	if conf.args.cmd_export {
+		use std::io::stderr;
+		use util::Address;
+
+		let address = conf.args.arg_address.trim_left_matches("0x");
+		let address = Address::from_str(address).unwrap_or_else(|_| die!("{}: Invalid address", conf.args.arg_address));
+		// prompts go to stderr, so the key file itself can be redirected
+		let mut passwords = conf.passwords();
+		if passwords.is_empty() {
+			let _ = write!(stderr(), "Type password: ");
+			passwords.push(read_password().unwrap());
+		}
+		let password = passwords.into_iter()
+			.find(|password| secret_store.sign_with_password(address, password.clone(), H256::default()).is_ok())
+			.unwrap_or_else(|| die!("Unable to unlock account {:?}. Pass the correct password using `--password`.", address));
+
+		let mut new_password = None;
+		if conf.args.flag_password.is_empty() {
+			let _ = write!(stderr(), "Type new password (leave empty to keep the current one): ");
+			let password = read_password().unwrap();
+			if !password.is_empty() {
+				new_password = Some(password);
+			}
+		}
+
+		let json = secret_store.export_account(address, password, new_password, Some(iterations))
+			.unwrap_or_else(|e| die!("Unable to export account: {:?}", e));
+		println!("{}", json);
+	}
+}
+
+fn execute_wallet_cli(conf: Configuration) {
+	use ethcore::ethstore::EthStore;
+	use ethcore::ethstore::dir::DiskDirectory;
+	use ethcore::account_provider::AccountProvider;
+
+	let dir = Box::new(DiskDirectory::create(conf.keys_path()).unwrap());
+	let iterations = conf.keys_iterations();
+	let secret_store = AccountProvider::new(Box::new(EthStore::open_with_iterations(dir, iterations).unwrap()));
+
+	let wallet_path = &conf.args.arg_path[0];
+	let mut wallet = Vec::new();
+	File::open(wallet_path)
+		.and_then(|mut file| file.read_to_end(&mut wallet))
+		.unwrap_or_else(|e| die!("{}: Unable to read presale wallet: {}", wallet_path, e));
+
+	let address = conf.passwords().iter()
+		.filter_map(|password| secret_store.import_presale(&wallet, password).ok())
+		.next()
+		.unwrap_or_else(|| die!("Unable to decrypt presale wallet. Pass the correct password using `--password`."));
+	println!("Imported account:");
+	println!("{:?}", address);
 }
 
 fn wait_for_exit(
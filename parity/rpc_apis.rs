@@ -20,6 +20,7 @@ use std::sync::Arc;
 
 use die::*;
 use ethsync::EthSync;
+use ethcore_whisper::Whisper;
 use ethcore::miner::{Miner, ExternalMiner};
 use ethcore::client::Client;
 use util::RotatingLogger;
@@ -46,6 +47,7 @@ pub enum Api {
 	EthcoreSet,
 	Traces,
 	Rpc,
+	Shh,
 }
 
 pub enum ApiError {
@@ -74,6 +76,7 @@ impl FromStr for Api {
 			"ethcore_set" => Ok(EthcoreSet),
 			"traces" => Ok(Traces),
 			"rpc" => Ok(Rpc),
+			"shh" => Ok(Shh),
 			e => Err(ApiError::UnknownApi(e.into())),
 		}
 	}
@@ -91,6 +94,7 @@ pub struct Dependencies {
 	pub settings: Arc<NetworkSettings>,
 	pub allow_pending_receipt_query: bool,
 	pub net_service: Arc<NetworkService<::ethcore::service::SyncMessage>>,
+	pub whisper: Option<Arc<Whisper>>,
 }
 
 fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
@@ -106,6 +110,7 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 			Api::EthcoreSet => ("ethcore_set", "1.0"),
 			Api::Traces => ("traces", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
+			Api::Shh => ("shh", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
 	}
@@ -156,14 +161,15 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 				}
 			},
 			Api::Personal => {
-				server.add_delegate(PersonalClient::new(&deps.secret_store, &deps.client, &deps.miner, deps.signer_port).to_delegate());
+				let queue = deps.signer_port.map(|_| deps.signer_queue.clone());
+				server.add_delegate(PersonalClient::new(&deps.secret_store, &deps.client, &deps.miner, deps.signer_port, queue).to_delegate());
 			},
 			Api::Signer => {
 				server.add_delegate(SignerClient::new(&deps.secret_store, &deps.client, &deps.miner, &deps.signer_queue).to_delegate());
 			},
 			Api::Ethcore => {
 				let queue = deps.signer_port.map(|_| deps.signer_queue.clone());
-				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, deps.logger.clone(), deps.settings.clone(), queue).to_delegate())
+				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, &deps.sync, &deps.net_service, deps.logger.clone(), deps.settings.clone(), queue).to_delegate())
 			},
 			Api::EthcoreSet => {
 				server.add_delegate(EthcoreSetClient::new(&deps.miner, &deps.net_service).to_delegate())
@@ -174,6 +180,12 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 			Api::Rpc => {
 				let modules = to_modules(&apis);
 				server.add_delegate(RpcClient::new(modules).to_delegate());
+			},
+			Api::Shh => {
+				match deps.whisper {
+					Some(ref whisper) => server.add_delegate(ShhClient::new(whisper).to_delegate()),
+					None => warn!("Whisper is disabled, shh API is not available. Use --whisper to enable it."),
+				}
 			}
 		}
 	}
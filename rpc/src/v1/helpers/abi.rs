@@ -0,0 +1,178 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal contract ABI decoding.
+//!
+//! Supports only types needed to decode calls to known contracts.
+
+use util::{Address, H256, U256, FixedHash, Hashable};
+
+/// Type of a function (or event) parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamType {
+	/// `address`
+	Address,
+	/// `uint256`
+	Uint,
+	/// `bytes32`
+	FixedBytes,
+	/// `bytes`
+	Bytes,
+}
+
+/// Decoded parameter value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+	/// `address`
+	Address(Address),
+	/// `uint256`
+	Uint(U256),
+	/// `bytes32`
+	FixedBytes(H256),
+	/// `bytes`
+	Bytes(Vec<u8>),
+}
+
+/// Data could not be decoded with given types.
+#[derive(Debug, PartialEq)]
+pub struct InvalidData;
+
+/// Returns first 4 bytes of function signature hash, e.g. `confirm(bytes32)`.
+pub fn method_id(signature: &str) -> [u8; 4] {
+	let hash = signature.as_bytes().sha3();
+	let mut id = [0u8; 4];
+	id.copy_from_slice(&hash[0..4]);
+	id
+}
+
+/// Returns topic of event with given signature.
+pub fn event_topic(signature: &str) -> H256 {
+	signature.as_bytes().sha3()
+}
+
+fn word(data: &[u8], offset: usize) -> Result<&[u8], InvalidData> {
+	match offset.checked_add(32) {
+		Some(end) if end <= data.len() => Ok(&data[offset..end]),
+		_ => Err(InvalidData),
+	}
+}
+
+fn as_usize(word: &[u8]) -> Result<usize, InvalidData> {
+	// offsets and lengths bigger than 4 bytes can't point into the data anyway
+	if word[..28].iter().any(|b| *b != 0) {
+		return Err(InvalidData);
+	}
+	Ok(word[28..].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize))
+}
+
+fn decode_param(param: ParamType, data: &[u8], offset: usize) -> Result<Token, InvalidData> {
+	let head = try!(word(data, offset));
+	match param {
+		ParamType::Address => {
+			if head[..12].iter().any(|b| *b != 0) {
+				return Err(InvalidData);
+			}
+			Ok(Token::Address(Address::from_slice(&head[12..])))
+		},
+		ParamType::Uint => Ok(Token::Uint(U256::from(head))),
+		ParamType::FixedBytes => Ok(Token::FixedBytes(H256::from_slice(head))),
+		ParamType::Bytes => {
+			let start = try!(as_usize(head));
+			let len = try!(as_usize(try!(word(data, start))));
+			let start = start + 32;
+			match start.checked_add(len) {
+				Some(end) if end <= data.len() => Ok(Token::Bytes(data[start..end].to_vec())),
+				_ => Err(InvalidData),
+			}
+		},
+	}
+}
+
+/// Decodes ABI-encoded parameters.
+pub fn decode(params: &[ParamType], data: &[u8]) -> Result<Vec<Token>, InvalidData> {
+	params.iter()
+		.enumerate()
+		.map(|(i, param)| decode_param(*param, data, i * 32))
+		.collect()
+}
+
+/// Decodes parameters of a call to function with given signature.
+/// Fails if the call data is for a different function.
+pub fn decode_call(signature: &str, params: &[ParamType], data: &[u8]) -> Result<Vec<Token>, InvalidData> {
+	if data.len() < 4 || &data[0..4] != &method_id(signature)[..] {
+		return Err(InvalidData);
+	}
+	decode(params, &data[4..])
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use rustc_serialize::hex::FromHex;
+	use util::{Address, H256, U256};
+	use super::*;
+
+	#[test]
+	fn should_compute_method_id() {
+		assert_eq!(method_id("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+	}
+
+	#[test]
+	fn should_decode_static_params() {
+		// given
+		let data = ("000000000000000000000000000000000000000000000000000000000000000a".to_owned() +
+			"0000000000000000000000001111111111111111111111111111111111111111" +
+			"2222222222222222222222222222222222222222222222222222222222222222").from_hex().unwrap();
+
+		// when
+		let tokens = decode(&[ParamType::Uint, ParamType::Address, ParamType::FixedBytes], &data).unwrap();
+
+		// then
+		assert_eq!(tokens, vec![
+			Token::Uint(U256::from(10)),
+			Token::Address(Address::from_str("1111111111111111111111111111111111111111").unwrap()),
+			Token::FixedBytes(H256::from_str("2222222222222222222222222222222222222222222222222222222222222222").unwrap()),
+		]);
+	}
+
+	#[test]
+	fn should_decode_dynamic_bytes() {
+		// given
+		let data = ("0000000000000000000000000000000000000000000000000000000000000001".to_owned() +
+			"0000000000000000000000000000000000000000000000000000000000000040" +
+			"0000000000000000000000000000000000000000000000000000000000000003" +
+			"1234560000000000000000000000000000000000000000000000000000000000").from_hex().unwrap();
+
+		// when
+		let tokens = decode(&[ParamType::Uint, ParamType::Bytes], &data).unwrap();
+
+		// then
+		assert_eq!(tokens, vec![Token::Uint(U256::from(1)), Token::Bytes(vec![0x12, 0x34, 0x56])]);
+	}
+
+	#[test]
+	fn should_reject_invalid_data() {
+		// too short
+		assert_eq!(decode(&[ParamType::Uint], &[0u8; 31]), Err(InvalidData));
+		// dirty address padding
+		assert_eq!(decode(&[ParamType::Address], &[1u8; 32]), Err(InvalidData));
+		// bytes pointing outside of the data
+		let data = "00000000000000000000000000000000000000000000000000000000000000ff".from_hex().unwrap();
+		assert_eq!(decode(&[ParamType::Bytes], &data), Err(InvalidData));
+		// different method
+		assert_eq!(decode_call("confirm(bytes32)", &[ParamType::FixedBytes], &[0u8; 36]), Err(InvalidData));
+	}
+}
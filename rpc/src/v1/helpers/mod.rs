@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod abi;
+pub mod multisig;
 mod poll_manager;
 mod poll_filter;
 mod signing_queue;
@@ -0,0 +1,272 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Multisig wallet (`wallet.sol`) awareness.
+//!
+//! Recognizes calls to the wallet contract and tracks pending operations
+//! using the events emitted by the wallet.
+
+use std::collections::HashMap;
+use util::{Address, H256, U256};
+use ethcore::log_entry::LogEntry;
+use v1::helpers::abi::{self, ParamType, Token};
+
+const EXECUTE: &'static str = "execute(address,uint256,bytes)";
+const CONFIRM: &'static str = "confirm(bytes32)";
+const REVOKE: &'static str = "revoke(bytes32)";
+
+const CONFIRMATION_EVENT: &'static str = "Confirmation(address,bytes32)";
+const REVOKE_EVENT: &'static str = "Revoke(address,bytes32)";
+const CONFIRMATION_NEEDED_EVENT: &'static str = "ConfirmationNeeded(bytes32,address,uint256,address,bytes)";
+const MULTI_TRANSACT_EVENT: &'static str = "MultiTransact(address,bytes32,uint256,address,bytes)";
+
+/// Decoded call to the multisig wallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WalletCall {
+	/// Submits new transaction. Above the daily limit it needs to be confirmed by other owners.
+	Submit {
+		/// Recipient
+		to: Address,
+		/// Value
+		value: U256,
+		/// Call data
+		data: Vec<u8>,
+	},
+	/// Confirms pending operation.
+	Confirm(H256),
+	/// Revokes confirmation of pending operation.
+	Revoke(H256),
+}
+
+impl WalletCall {
+	/// Decodes wallet call from transaction data.
+	pub fn decode(data: &[u8]) -> Option<Self> {
+		if let Ok(tokens) = abi::decode_call(EXECUTE, &[ParamType::Address, ParamType::Uint, ParamType::Bytes], data) {
+			return match (&tokens[0], &tokens[1], &tokens[2]) {
+				(&Token::Address(to), &Token::Uint(value), &Token::Bytes(ref data)) => Some(WalletCall::Submit {
+					to: to,
+					value: value,
+					data: data.clone(),
+				}),
+				_ => None,
+			};
+		}
+
+		let operation = |signature| match abi::decode_call(signature, &[ParamType::FixedBytes], data) {
+			Ok(ref tokens) => match tokens[0] {
+				Token::FixedBytes(operation) => Some(operation),
+				_ => None,
+			},
+			Err(_) => None,
+		};
+
+		operation(CONFIRM).map(WalletCall::Confirm)
+			.or_else(|| operation(REVOKE).map(WalletCall::Revoke))
+	}
+}
+
+/// Wallet operation waiting for confirmations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingOperation {
+	/// Operation hash
+	pub operation: H256,
+	/// Owner who submitted the operation
+	pub initiator: Address,
+	/// Value
+	pub value: U256,
+	/// Recipient
+	pub to: Address,
+	/// Call data
+	pub data: Vec<u8>,
+	/// Owners who confirmed the operation
+	pub confirmed_by: Vec<Address>,
+}
+
+/// Topics of the wallet events needed to track pending operations.
+pub fn event_topics() -> Vec<H256> {
+	vec![
+		abi::event_topic(CONFIRMATION_EVENT),
+		abi::event_topic(REVOKE_EVENT),
+		abi::event_topic(CONFIRMATION_NEEDED_EVENT),
+		abi::event_topic(MULTI_TRANSACT_EVENT),
+	]
+}
+
+fn owner_and_operation(log: &LogEntry) -> Option<(Address, H256)> {
+	match abi::decode(&[ParamType::Address, ParamType::FixedBytes], &log.data) {
+		Ok(ref tokens) => match (&tokens[0], &tokens[1]) {
+			(&Token::Address(owner), &Token::FixedBytes(operation)) => Some((owner, operation)),
+			_ => None,
+		},
+		Err(_) => None,
+	}
+}
+
+/// Returns operations which were submitted but not executed yet.
+/// Logs have to be given in the order they were created.
+pub fn pending_operations(logs: &[LogEntry]) -> Vec<PendingOperation> {
+	let confirmation = abi::event_topic(CONFIRMATION_EVENT);
+	let revoke = abi::event_topic(REVOKE_EVENT);
+	let confirmation_needed = abi::event_topic(CONFIRMATION_NEEDED_EVENT);
+	let multi_transact = abi::event_topic(MULTI_TRANSACT_EVENT);
+
+	let mut pending: Vec<PendingOperation> = Vec::new();
+	let mut confirmations: HashMap<H256, Vec<Address>> = HashMap::new();
+
+	for log in logs {
+		let topic = match log.topics.first() {
+			Some(topic) => *topic,
+			None => continue,
+		};
+
+		if topic == confirmation {
+			if let Some((owner, operation)) = owner_and_operation(log) {
+				let owners = confirmations.entry(operation).or_insert_with(Vec::new);
+				if !owners.contains(&owner) {
+					owners.push(owner);
+				}
+			}
+		} else if topic == revoke {
+			if let Some((owner, operation)) = owner_and_operation(log) {
+				if let Some(owners) = confirmations.get_mut(&operation) {
+					owners.retain(|o| o != &owner);
+				}
+			}
+		} else if topic == confirmation_needed {
+			let params = [ParamType::FixedBytes, ParamType::Address, ParamType::Uint, ParamType::Address, ParamType::Bytes];
+			if let Ok(tokens) = abi::decode(&params, &log.data) {
+				if let (&Token::FixedBytes(operation), &Token::Address(initiator), &Token::Uint(value), &Token::Address(to), &Token::Bytes(ref data)) =
+					(&tokens[0], &tokens[1], &tokens[2], &tokens[3], &tokens[4]) {
+					pending.push(PendingOperation {
+						operation: operation,
+						initiator: initiator,
+						value: value,
+						to: to,
+						data: data.clone(),
+						confirmed_by: Vec::new(),
+					});
+				}
+			}
+		} else if topic == multi_transact {
+			if let Some((_, operation)) = owner_and_operation(log) {
+				pending.retain(|op| op.operation != operation);
+				confirmations.remove(&operation);
+			}
+		}
+	}
+
+	for op in &mut pending {
+		op.confirmed_by = confirmations.get(&op.operation).cloned().unwrap_or_else(Vec::new);
+	}
+	pending
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use rustc_serialize::hex::FromHex;
+	use util::{Address, H256, U256};
+	use ethcore::log_entry::LogEntry;
+	use v1::helpers::abi;
+	use super::{WalletCall, PendingOperation, pending_operations};
+	use super::{CONFIRMATION_EVENT, REVOKE_EVENT, CONFIRMATION_NEEDED_EVENT, MULTI_TRANSACT_EVENT};
+
+	fn log(event: &str, data: &str) -> LogEntry {
+		LogEntry {
+			address: Address::from(0x10),
+			topics: vec![abi::event_topic(event)],
+			data: data.from_hex().unwrap(),
+		}
+	}
+
+	const OWNER1: &'static str = "0000000000000000000000000000000000000000000000000000000000000001";
+	const OWNER2: &'static str = "0000000000000000000000000000000000000000000000000000000000000002";
+	const OPERATION: &'static str = "1111111111111111111111111111111111111111111111111111111111111111";
+
+	fn submitted() -> String {
+		// operation, initiator, value, to, data offset, data length
+		OPERATION.to_owned() + OWNER1 +
+			"0000000000000000000000000000000000000000000000000000000000000005" +
+			"0000000000000000000000000000000000000000000000000000000000000003" +
+			"00000000000000000000000000000000000000000000000000000000000000a0" +
+			"0000000000000000000000000000000000000000000000000000000000000000"
+	}
+
+	#[test]
+	fn should_decode_wallet_calls() {
+		// given
+		let confirm = abi::method_id("confirm(bytes32)").to_vec().into_iter().chain(OPERATION.from_hex().unwrap()).collect::<Vec<_>>();
+		let revoke = abi::method_id("revoke(bytes32)").to_vec().into_iter().chain(OPERATION.from_hex().unwrap()).collect::<Vec<_>>();
+		let execute = abi::method_id("execute(address,uint256,bytes)").to_vec().into_iter().chain((OWNER2.to_owned() +
+			"0000000000000000000000000000000000000000000000000000000000000005" +
+			"0000000000000000000000000000000000000000000000000000000000000060" +
+			"0000000000000000000000000000000000000000000000000000000000000001" +
+			"ff00000000000000000000000000000000000000000000000000000000000000").from_hex().unwrap()).collect::<Vec<_>>();
+		let operation = H256::from_str(OPERATION).unwrap();
+
+		// then
+		assert_eq!(WalletCall::decode(&confirm), Some(WalletCall::Confirm(operation)));
+		assert_eq!(WalletCall::decode(&revoke), Some(WalletCall::Revoke(operation)));
+		assert_eq!(WalletCall::decode(&execute), Some(WalletCall::Submit {
+			to: Address::from(2),
+			value: U256::from(5),
+			data: vec![0xff],
+		}));
+		assert_eq!(WalletCall::decode(&[1, 2, 3, 4]), None);
+	}
+
+	#[test]
+	fn should_track_pending_operations() {
+		// given
+		let logs = vec![
+			log(CONFIRMATION_EVENT, &(OWNER1.to_owned() + OPERATION)),
+			log(CONFIRMATION_NEEDED_EVENT, &submitted()),
+			log(CONFIRMATION_EVENT, &(OWNER2.to_owned() + OPERATION)),
+			log(REVOKE_EVENT, &(OWNER2.to_owned() + OPERATION)),
+		];
+
+		// when
+		let pending = pending_operations(&logs);
+
+		// then
+		assert_eq!(pending, vec![PendingOperation {
+			operation: H256::from_str(OPERATION).unwrap(),
+			initiator: Address::from(1),
+			value: U256::from(5),
+			to: Address::from(3),
+			data: vec![],
+			confirmed_by: vec![Address::from(1)],
+		}]);
+	}
+
+	#[test]
+	fn should_not_return_executed_operations() {
+		// given
+		let logs = vec![
+			log(CONFIRMATION_EVENT, &(OWNER1.to_owned() + OPERATION)),
+			log(CONFIRMATION_NEEDED_EVENT, &submitted()),
+			log(CONFIRMATION_EVENT, &(OWNER2.to_owned() + OPERATION)),
+			log(MULTI_TRANSACT_EVENT, &(OWNER2.to_owned() + OPERATION +
+				"0000000000000000000000000000000000000000000000000000000000000005" +
+				"0000000000000000000000000000000000000000000000000000000000000003" +
+				"00000000000000000000000000000000000000000000000000000000000000a0" +
+				"0000000000000000000000000000000000000000000000000000000000000000")),
+		];
+
+		// then
+		assert!(pending_operations(&logs).is_empty());
+	}
+}
@@ -18,9 +18,10 @@ use std::thread;
 use std::time::{Instant, Duration};
 use std::sync::{mpsc, Mutex, RwLock, Arc};
 use std::collections::HashMap;
-use v1::types::{TransactionRequest, TransactionConfirmation};
+use v1::types::{ConfirmationRequest, ConfirmationPayload, QueueNotification};
 use util::U256;
 use jsonrpc_core;
+use serde_json;
 
 /// Result that can be returned from JSON RPC.
 pub type RpcResult = Result<jsonrpc_core::Value, jsonrpc_core::Error>;
@@ -36,6 +37,8 @@ pub enum QueueEvent {
 	RequestRejected(U256),
 	/// Request resolved.
 	RequestConfirmed(U256),
+	/// Request modified before confirmation.
+	RequestModified(U256),
 }
 
 /// Defines possible errors returned from queue receiving method.
@@ -50,30 +53,34 @@ pub enum QueueError {
 /// Message Receiver type
 pub type QueueEventReceiver = mpsc::Receiver<QueueEvent>;
 
-/// A queue of transactions awaiting to be confirmed and signed.
+/// A queue of requests (transactions, decryptions) awaiting to be confirmed.
 pub trait SigningQueue: Send + Sync {
 	/// Add new request to the queue.
 	/// Returns a `ConfirmationPromise` that can be used to await for resolution of given request.
-	fn add_request(&self, transaction: TransactionRequest) -> ConfirmationPromise;
+	fn add_request(&self, payload: ConfirmationPayload) -> ConfirmationPromise;
 
 	/// Removes a request from the queue.
-	/// Notifies possible token holders that transaction was rejected.
-	fn request_rejected(&self, id: U256) -> Option<TransactionConfirmation>;
+	/// Notifies possible token holders that request was rejected.
+	fn request_rejected(&self, id: U256) -> Option<ConfirmationRequest>;
 
 	/// Removes a request from the queue.
-	/// Notifies possible token holders that transaction was confirmed and given hash was assigned.
-	fn request_confirmed(&self, id: U256, result: RpcResult) -> Option<TransactionConfirmation>;
+	/// Notifies possible token holders that request was confirmed and given result was assigned.
+	fn request_confirmed(&self, id: U256, result: RpcResult) -> Option<ConfirmationRequest>;
+
+	/// Replaces payload of the request, e.g. with transaction modified in `SignerUI`.
+	/// Returns updated request if it is contained in the queue.
+	fn request_modified(&self, id: &U256, payload: ConfirmationPayload) -> Option<ConfirmationRequest>;
 
 	/// Returns a request if it is contained in the queue.
-	fn peek(&self, id: &U256) -> Option<TransactionConfirmation>;
+	fn peek(&self, id: &U256) -> Option<ConfirmationRequest>;
 
 	/// Return copy of all the requests in the queue.
-	fn requests(&self) -> Vec<TransactionConfirmation>;
+	fn requests(&self) -> Vec<ConfirmationRequest>;
 
-	/// Returns number of transactions awaiting confirmation.
+	/// Returns number of requests awaiting confirmation.
 	fn len(&self) -> usize;
 
-	/// Returns true if there are no transactions awaiting confirmation.
+	/// Returns true if there are no requests awaiting confirmation.
 	fn is_empty(&self) -> bool;
 }
 
@@ -99,7 +106,7 @@ const QUEUE_TIMEOUT_DURATION_SEC : u64 = 20;
 pub struct ConfirmationToken {
 	result: Arc<Mutex<ConfirmationResult>>,
 	handle: thread::Thread,
-	request: TransactionConfirmation,
+	request: ConfirmationRequest,
 }
 
 pub struct ConfirmationPromise {
@@ -156,7 +163,7 @@ impl ConfirmationPromise {
 	}
 }
 
-/// Queue for all unconfirmed transactions.
+/// Queue for all unconfirmed requests.
 pub struct ConfirmationsQueue {
 	id: Mutex<U256>,
 	queue: RwLock<HashMap<U256, ConfirmationToken>>,
@@ -199,6 +206,28 @@ impl ConfirmationsQueue {
 		}
 	}
 
+	/// Returns `personal_signerPending` JSON-RPC notification describing given event
+	/// together with all requests currently awaiting confirmation.
+	pub fn notification(&self, event: &QueueEvent) -> Option<String> {
+		let (event, id) = match *event {
+			QueueEvent::Finish => return None,
+			QueueEvent::NewRequest(id) => ("newRequest", id),
+			QueueEvent::RequestRejected(id) => ("requestRejected", id),
+			QueueEvent::RequestConfirmed(id) => ("requestConfirmed", id),
+			QueueEvent::RequestModified(id) => ("requestModified", id),
+		};
+
+		let params = QueueNotification {
+			event: event.into(),
+			id: id,
+			pending: self.requests(),
+		};
+
+		serde_json::to_string(&params).ok().map(|params| {
+			format!(r#"{{"jsonrpc":"2.0","method":"personal_signerPending","params":[{}]}}"#, params)
+		})
+	}
+
 	/// Notifies consumer that the communcation is over.
 	/// No more events will be sent after this function is invoked.
 	pub fn finish(&self) {
@@ -213,7 +242,7 @@ impl ConfirmationsQueue {
 
 	/// Removes transaction from this queue and notifies `ConfirmationPromise` holders about the result.
 	/// Notifies also a receiver about that event.
-	fn remove(&self, id: U256, result: Option<RpcResult>) -> Option<TransactionConfirmation> {
+	fn remove(&self, id: U256, result: Option<RpcResult>) -> Option<ConfirmationRequest> {
 		let token = self.queue.write().unwrap().remove(&id);
 
 		if let Some(token) = token {
@@ -238,7 +267,7 @@ impl Drop for ConfirmationsQueue {
 }
 
 impl SigningQueue for  ConfirmationsQueue {
-	fn add_request(&self, transaction: TransactionRequest) -> ConfirmationPromise {
+	fn add_request(&self, payload: ConfirmationPayload) -> ConfirmationPromise {
 		// Increment id
 		let id = {
 			let mut last_id = self.id.lock().unwrap();
@@ -251,12 +280,12 @@ impl SigningQueue for  ConfirmationsQueue {
 			queue.insert(id, ConfirmationToken {
 				result: Arc::new(Mutex::new(ConfirmationResult::Waiting)),
 				handle: thread::current(),
-				request: TransactionConfirmation {
+				request: ConfirmationRequest {
 					id: id,
-					transaction: transaction,
+					payload: payload,
 				},
 			});
-			debug!(target: "own_tx", "Signer: New transaction ({:?}) in confirmation queue.", id);
+			debug!(target: "own_tx", "Signer: New request ({:?}) in confirmation queue.", id);
 			queue.get(&id).map(|token| token.as_promise()).expect("Token was just inserted.")
 		};
 		// Notify listeners
@@ -265,21 +294,37 @@ impl SigningQueue for  ConfirmationsQueue {
 
 	}
 
-	fn peek(&self, id: &U256) -> Option<TransactionConfirmation> {
+	fn peek(&self, id: &U256) -> Option<ConfirmationRequest> {
 		self.queue.read().unwrap().get(id).map(|token| token.request.clone())
 	}
 
-	fn request_rejected(&self, id: U256) -> Option<TransactionConfirmation> {
-		debug!(target: "own_tx", "Signer: Transaction rejected ({:?}).", id);
+	fn request_modified(&self, id: &U256, payload: ConfirmationPayload) -> Option<ConfirmationRequest> {
+		let request = {
+			let mut queue = self.queue.write().unwrap();
+			queue.get_mut(id).map(|token| {
+				token.request.payload = payload;
+				token.request.clone()
+			})
+		};
+
+		if request.is_some() {
+			debug!(target: "own_tx", "Signer: Request modified ({:?}).", id);
+			self.notify(QueueEvent::RequestModified(*id));
+		}
+		request
+	}
+
+	fn request_rejected(&self, id: U256) -> Option<ConfirmationRequest> {
+		debug!(target: "own_tx", "Signer: Request rejected ({:?}).", id);
 		self.remove(id, None)
 	}
 
-	fn request_confirmed(&self, id: U256, result: RpcResult) -> Option<TransactionConfirmation> {
-		debug!(target: "own_tx", "Signer: Transaction confirmed ({:?}).", id);
+	fn request_confirmed(&self, id: U256, result: RpcResult) -> Option<ConfirmationRequest> {
+		debug!(target: "own_tx", "Signer: Request confirmed ({:?}).", id);
 		self.remove(id, Some(result))
 	}
 
-	fn requests(&self) -> Vec<TransactionConfirmation> {
+	fn requests(&self) -> Vec<ConfirmationRequest> {
 		let queue = self.queue.read().unwrap();
 		queue.values().map(|token| token.request.clone()).collect()
 	}
@@ -303,7 +348,7 @@ mod test {
 	use std::sync::{Arc, Mutex};
 	use util::hash::Address;
 	use util::numbers::{U256, H256};
-	use v1::types::TransactionRequest;
+	use v1::types::{TransactionRequest, ConfirmationPayload};
 	use super::*;
 	use jsonrpc_core::to_value;
 
@@ -316,6 +361,7 @@ mod test {
 			value: Some(U256::from(10_000_000)),
 			data: None,
 			nonce: None,
+			condition: None,
 		}
 	}
 
@@ -328,7 +374,7 @@ mod test {
 		// when
 		let q = queue.clone();
 		let handle = thread::spawn(move || {
-			let v = q.add_request(request);
+			let v = q.add_request(request.into());
 			v.wait_with_timeout().expect("Should return hash")
 		});
 
@@ -359,7 +405,7 @@ mod test {
 				*v = Some(notification);
 			}).expect("Should be closed nicely.")
 		});
-		queue.add_request(request);
+		queue.add_request(request.into());
 		queue.finish();
 
 		// then
@@ -368,6 +414,37 @@ mod test {
 		assert_eq!(r, Some(QueueEvent::NewRequest(U256::from(1))));
 	}
 
+	#[test]
+	fn should_modify_request() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		queue.add_request(request().into());
+		let mut modified = request();
+		modified.gas_price = Some(U256::from(1));
+
+		// when
+		let updated = queue.request_modified(&U256::from(1), modified.clone().into());
+
+		// then
+		assert_eq!(updated.unwrap().payload, ConfirmationPayload::Transaction(modified.clone()));
+		assert_eq!(queue.peek(&U256::from(1)).unwrap().payload, ConfirmationPayload::Transaction(modified.clone()));
+		assert!(queue.request_modified(&U256::from(2), modified.into()).is_none());
+	}
+
+	#[test]
+	fn should_create_notification() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		queue.add_request(request().into());
+
+		// when
+		let notification = queue.notification(&QueueEvent::NewRequest(U256::from(1))).unwrap();
+
+		// then
+		assert!(notification.starts_with(r#"{"jsonrpc":"2.0","method":"personal_signerPending","params":[{"event":"newRequest","id":"0x01","pending":[{"id":"0x01","transaction":"#));
+		assert_eq!(queue.notification(&QueueEvent::Finish), None);
+	}
+
 	#[test]
 	fn should_add_transactions() {
 		// given
@@ -375,13 +452,13 @@ mod test {
 		let request = request();
 
 		// when
-		queue.add_request(request.clone());
+		queue.add_request(request.clone().into());
 		let all = queue.requests();
 
 		// then
 		assert_eq!(all.len(), 1);
 		let el = all.get(0).unwrap();
 		assert_eq!(el.id, U256::from(1));
-		assert_eq!(el.transaction, request);
+		assert_eq!(el.payload, ConfirmationPayload::Transaction(request));
 	}
 }
@@ -37,7 +37,7 @@ use ethcore::filter::Filter as EthcoreFilter;
 use self::ethash::SeedHashCompute;
 use v1::traits::Eth;
 use v1::types::{Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, Transaction, CallRequest, OptionalValue, Index, Filter, Log, Receipt};
-use v1::impls::{default_gas_price, dispatch_transaction, error_codes};
+use v1::impls::{default_gas_price, dispatch_transaction, state_syncing_error, error_codes};
 use serde;
 
 /// Eth rpc implementation.
@@ -156,8 +156,12 @@ impl<C, S, M, EM> EthClient<C, S, M, EM> where
 	fn sign_call(&self, request: CallRequest) -> Result<SignedTransaction, Error> {
 		let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
 		let from = request.from.unwrap_or(Address::zero());
+		let nonce = match request.nonce {
+			Some(nonce) => nonce,
+			None => try!(client.latest_nonce(&from).ok_or_else(state_syncing_error)),
+		};
 		Ok(EthTransaction {
-			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
+			nonce: nonce,
 			action: request.to.map_or(Action::Create, Action::Call),
 			gas: request.gas.unwrap_or(U256::from(50_000_000)),
 			gas_price: request.gas_price.unwrap_or_else(|| default_gas_price(&*client, &*miner)),
@@ -244,7 +248,7 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 				let status = take_weak!(self.sync).status();
 				let res = match status.state {
 					SyncState::Idle => SyncStatus::None,
-					SyncState::Waiting | SyncState::Blocks | SyncState::NewBlocks | SyncState::ChainHead => {
+					SyncState::Waiting | SyncState::Blocks | SyncState::NewBlocks | SyncState::ChainHead | SyncState::StateDownload => {
 						let current_block = U256::from(take_weak!(self.client).chain_info().best_block_number);
 
 						let info = SyncInfo {
@@ -310,7 +314,7 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 	fn balance(&self, params: Params) -> Result<Value, Error> {
 		from_params_default_second(params)
 			.and_then(|(address, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&take_weak!(self.miner).balance(take_weak!(self.client).deref(), &address)),
+				BlockNumber::Pending => to_value(&try!(take_weak!(self.miner).balance(take_weak!(self.client).deref(), &address).ok_or_else(state_syncing_error))),
 				id => to_value(&try!(take_weak!(self.client).balance(&address, id.into()).ok_or_else(make_unsupported_err))),
 			})
 	}
@@ -318,7 +322,10 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 	fn storage_at(&self, params: Params) -> Result<Value, Error> {
 		from_params_default_third::<Address, U256>(params)
 			.and_then(|(address, position, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&U256::from(take_weak!(self.miner).storage_at(&*take_weak!(self.client), &address, &H256::from(position)))),
+				BlockNumber::Pending => match take_weak!(self.miner).storage_at(&*take_weak!(self.client), &address, &H256::from(position)) {
+					Some(s) => to_value(&U256::from(s)),
+					None => Err(state_syncing_error()),
+				},
 				id => match take_weak!(self.client).storage_at(&address, &H256::from(position), id.into()) {
 					Some(s) => to_value(&U256::from(s)),
 					None => Err(make_unsupported_err()), // None is only returned on unsupported requests.
@@ -329,7 +336,7 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 	fn transaction_count(&self, params: Params) -> Result<Value, Error> {
 		from_params_default_second(params)
 			.and_then(|(address, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&take_weak!(self.miner).nonce(take_weak!(self.client).deref(), &address)),
+				BlockNumber::Pending => to_value(&try!(take_weak!(self.miner).nonce(take_weak!(self.client).deref(), &address).ok_or_else(state_syncing_error))),
 				id => to_value(&take_weak!(self.client).nonce(&address, id.into())),
 			})
 	}
@@ -508,7 +515,8 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 			.and_then(|(raw_transaction, )| {
 				let raw_transaction = raw_transaction.to_vec();
 				match UntrustedRlp::new(&raw_transaction).as_val() {
-					Ok(signed_transaction) => dispatch_transaction(&*take_weak!(self.client), &*take_weak!(self.miner), signed_transaction),
+					Ok(signed_transaction) => dispatch_transaction(&*take_weak!(self.client), &*take_weak!(self.miner), signed_transaction, None)
+						.and_then(|hash| to_value(&hash)),
 					Err(_) => to_value(&H256::zero()),
 				}
 		})
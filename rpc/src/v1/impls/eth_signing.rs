@@ -24,21 +24,8 @@ use util::numbers::*;
 use ethcore::account_provider::AccountProvider;
 use v1::helpers::{SigningQueue, ConfirmationsQueue};
 use v1::traits::EthSigning;
-use v1::types::{TransactionRequest, Bytes};
-use v1::impls::{default_gas_price, sign_and_dispatch};
-
-fn fill_optional_fields<C, M>(request: &mut TransactionRequest, client: &C, miner: &M)
-	where C: MiningBlockChainClient, M: MinerService {
-	if request.gas.is_none() {
-		request.gas = Some(miner.sensible_gas_limit());
-	}
-	if request.gas_price.is_none() {
-		request.gas_price = Some(default_gas_price(client, miner));
-	}
-	if request.data.is_none() {
-		request.data = Some(Bytes::new(Vec::new()));
-	}
-}
+use v1::types::TransactionRequest;
+use v1::impls::{sign_and_dispatch, fill_optional_fields};
 
 /// Implementation of functions that require signing when no trusted signer is used.
 pub struct EthSigningQueueClient<C, M> where C: MiningBlockChainClient, M: MinerService {
@@ -75,7 +62,7 @@ impl<C, M> EthSigning for EthSigningQueueClient<C, M>
 				let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
 
 				fill_optional_fields(&mut request, &*client, &*miner);
-				let id = queue.add_request(request);
+				let id = queue.add_request(request.into());
 				let result = id.wait_with_timeout();
 				result.unwrap_or_else(|| to_value(&H256::new()))
 		})
@@ -18,35 +18,45 @@
 use util::{RotatingLogger};
 use util::network_settings::NetworkSettings;
 use util::misc::version_data;
+use util::hash::H512;
+use util::network::NetworkService;
 use std::sync::{Arc, Weak};
 use std::ops::Deref;
 use std::collections::{BTreeMap};
 use ethcore::client::{MiningBlockChainClient};
+use ethcore::service::SyncMessage;
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
+use ethsync::SyncProvider;
+use ethcore::ethstore::ethkey::{ecies, Public};
 use v1::traits::Ethcore;
-use v1::types::{Bytes};
+use v1::types::{Bytes, ScheduledTransaction, Peers, PeerInfo, BannedPeer};
 use v1::helpers::{SigningQueue, ConfirmationsQueue};
 use v1::impls::error_codes;
 
 /// Ethcore implementation.
-pub struct EthcoreClient<C, M> where
+pub struct EthcoreClient<C, M, S> where
 	C: MiningBlockChainClient,
-	M: MinerService {
+	M: MinerService,
+	S: SyncProvider {
 
 	client: Weak<C>,
 	miner: Weak<M>,
+	sync: Weak<S>,
+	net: Weak<NetworkService<SyncMessage>>,
 	logger: Arc<RotatingLogger>,
 	settings: Arc<NetworkSettings>,
 	confirmations_queue: Option<Arc<ConfirmationsQueue>>,
 }
 
-impl<C, M> EthcoreClient<C, M> where C: MiningBlockChainClient, M: MinerService {
+impl<C, M, S> EthcoreClient<C, M, S> where C: MiningBlockChainClient, M: MinerService, S: SyncProvider {
 	/// Creates new `EthcoreClient`.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>, logger: Arc<RotatingLogger>, settings: Arc<NetworkSettings>, queue: Option<Arc<ConfirmationsQueue>>) -> Self {
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, sync: &Arc<S>, net: &Arc<NetworkService<SyncMessage>>, logger: Arc<RotatingLogger>, settings: Arc<NetworkSettings>, queue: Option<Arc<ConfirmationsQueue>>) -> Self {
 		EthcoreClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			sync: Arc::downgrade(sync),
+			net: Arc::downgrade(net),
 			logger: logger,
 			settings: settings,
 			confirmations_queue: queue,
@@ -54,7 +64,7 @@ impl<C, M> EthcoreClient<C, M> where C: MiningBlockChainClient, M: MinerService
 	}
 }
 
-impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: MiningBlockChainClient + 'static {
+impl<C, M, S> Ethcore for EthcoreClient<C, M, S> where M: MinerService + 'static, C: MiningBlockChainClient + 'static, S: SyncProvider + 'static {
 
 	fn transactions_limit(&self, _: Params) -> Result<Value, Error> {
 		to_value(&take_weak!(self.miner).transactions_limit())
@@ -72,6 +82,10 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 		to_value(&take_weak!(self.miner).gas_floor_target())
 	}
 
+	fn gas_ceil_target(&self, _: Params) -> Result<Value, Error> {
+		to_value(&take_weak!(self.miner).gas_ceil_target())
+	}
+
 	fn dev_logs(&self, _params: Params) -> Result<Value, Error> {
 		let logs = self.logger.logs();
 		to_value(&logs.deref().as_slice())
@@ -89,6 +103,28 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 		to_value(&self.settings.max_peers)
 	}
 
+	fn net_peers(&self, _params: Params) -> Result<Value, Error> {
+		let sync = take_weak!(self.sync);
+		let mut eth_peers = sync.peers().into_iter().map(|p| (p.id, p)).collect::<BTreeMap<_, _>>();
+		let peers = take_weak!(self.net).sessions_info().into_iter().map(|(id, session)| {
+			let mut info = PeerInfo::from(session);
+			info.protocols.eth = eth_peers.remove(&id).map(Into::into);
+			info
+		}).collect::<Vec<_>>();
+
+		to_value(&Peers {
+			active: sync.status().num_active_peers,
+			connected: peers.len(),
+			max: self.settings.max_peers,
+			peers: peers,
+		})
+	}
+
+	fn banned_peers(&self, _params: Params) -> Result<Value, Error> {
+		let banned = take_weak!(self.net).banned_peers().into_iter().map(BannedPeer::from).collect::<Vec<_>>();
+		to_value(&banned)
+	}
+
 	fn net_port(&self, _params: Params) -> Result<Value, Error> {
 		to_value(&self.settings.network_port)
 	}
@@ -135,4 +171,19 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 			Some(ref queue) => to_value(&queue.len()),
 		}
 	}
+
+	fn scheduled_transactions(&self, _params: Params) -> Result<Value, Error> {
+		to_value(&take_weak!(self.miner).scheduled_transactions()
+			.into_iter()
+			.map(ScheduledTransaction::from)
+			.collect::<Vec<_>>())
+	}
+
+	fn encrypt_message(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H512, Bytes)>(params).and_then(|(key, message)| {
+			ecies::encrypt(&Public::from(key.0), &[], &message.0)
+				.map_err(|_| Error::invalid_params())
+				.and_then(|encrypted| to_value(&Bytes::new(encrypted)))
+		})
+	}
 }
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 /// Ethcore-specific rpc interface for operations altering the settings.
-use util::{U256, Address};
+use util::{U256, H256, H512, Address};
 use util::network::{NetworkService, NonReservedPeerMode};
 use std::sync::{Arc, Weak};
 use jsonrpc_core::*;
@@ -58,6 +58,13 @@ impl<M> EthcoreSet for EthcoreSetClient<M> where M: MinerService + 'static {
 		})
 	}
 
+	fn set_gas_ceil_target(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(U256,)>(params).and_then(|(gas_ceil_target,)| {
+			take_weak!(self.miner).set_gas_ceil_target(gas_ceil_target);
+			to_value(&true)
+		})
+	}
+
 	fn set_extra_data(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(Bytes,)>(params).and_then(|(extra_data,)| {
 			take_weak!(self.miner).set_extra_data(extra_data.to_vec());
@@ -79,6 +86,12 @@ impl<M> EthcoreSet for EthcoreSetClient<M> where M: MinerService + 'static {
 		})
 	}
 
+	fn remove_scheduled_transaction(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H256,)>(params).and_then(|(hash,)| {
+			to_value(&take_weak!(self.miner).remove_scheduled_transaction(&hash).is_some())
+		})
+	}
+
 	fn add_reserved_peer(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(String,)>(params).and_then(|(peer,)| {
 			match take_weak!(self.net).add_reserved_peer(&peer) {
@@ -106,4 +119,16 @@ impl<M> EthcoreSet for EthcoreSetClient<M> where M: MinerService + 'static {
 		take_weak!(self.net).set_non_reserved_mode(NonReservedPeerMode::Accept);
 		to_value(&true)
 	}
+
+	fn ban_peer(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H512,)>(params).and_then(|(id,)| {
+			to_value(&take_weak!(self.net).ban_peer(&id))
+		})
+	}
+
+	fn unban_peer(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H512,)>(params).and_then(|(id,)| {
+			to_value(&take_weak!(self.net).unban_peer(&id))
+		})
+	}
 }
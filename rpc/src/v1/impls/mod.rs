@@ -40,6 +40,7 @@ mod ethcore;
 mod ethcore_set;
 mod traces;
 mod rpc;
+mod shh;
 
 pub use self::web3::Web3Client;
 pub use self::eth::EthClient;
@@ -52,8 +53,9 @@ pub use self::ethcore::EthcoreClient;
 pub use self::ethcore_set::EthcoreSetClient;
 pub use self::traces::TracesClient;
 pub use self::rpc::RpcClient;
+pub use self::shh::ShhClient;
 
-use v1::types::TransactionRequest;
+use v1::types::{TransactionRequest, TransactionCondition, Bytes};
 use ethcore::error::Error as EthcoreError;
 use ethcore::miner::{AccountDetails, MinerService};
 use ethcore::client::MiningBlockChainClient;
@@ -69,69 +71,97 @@ mod error_codes {
 	pub const UNSUPPORTED_REQUEST_CODE: i64 = -32000;
 	pub const NO_WORK_CODE: i64 = -32001;
 	pub const UNKNOWN_ERROR: i64 = -32002;
+	pub const STATE_SYNCING: i64 = -32003;
 	pub const TRANSACTION_ERROR: i64 = -32010;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
+	pub const ACCOUNT_ERROR: i64 = -32021;
 	pub const SIGNER_DISABLED: i64 = -32030;
+	pub const REQUEST_REJECTED: i64 = -32040;
 }
 
-fn dispatch_transaction<C, M>(client: &C, miner: &M, signed_transaction: SignedTransaction) -> Result<Value, Error>
+fn dispatch_transaction<C, M>(client: &C, miner: &M, signed_transaction: SignedTransaction, condition: Option<TransactionCondition>) -> Result<H256, Error>
 	where C: MiningBlockChainClient, M: MinerService {
 	let hash = signed_transaction.hash();
 
-	let import = miner.import_own_transaction(client, signed_transaction, |a: &Address| {
+	if client.is_state_pending() {
+		return Err(state_syncing_error());
+	}
+
+	let fetch_account = |a: &Address| {
 		AccountDetails {
-			nonce: client.latest_nonce(&a),
-			balance: client.latest_balance(&a),
+			nonce: client.latest_nonce(&a).expect("Best block state is not pending, checked above; qed"),
+			balance: client.latest_balance(&a).expect("Best block state is not pending, checked above; qed"),
 		}
-	});
+	};
+
+	let import = match condition {
+		Some(condition) => miner.import_scheduled_transaction(client, signed_transaction, condition.into(), fetch_account),
+		None => miner.import_own_transaction(client, signed_transaction, fetch_account).map(|_| ()),
+	};
 
 	import
 		.map_err(transaction_error)
-		.and_then(|_| to_value(&hash))
+		.map(|_| hash)
+}
+
+/// Fills missing gas, gas price and data of the request with defaults.
+/// Nonce is left empty, it's computed right before signing.
+fn fill_optional_fields<C, M>(request: &mut TransactionRequest, client: &C, miner: &M)
+	where C: MiningBlockChainClient, M: MinerService {
+	if request.gas.is_none() {
+		request.gas = Some(miner.sensible_gas_limit());
+	}
+	if request.gas_price.is_none() {
+		request.gas_price = Some(default_gas_price(client, miner));
+	}
+	if request.data.is_none() {
+		request.data = Some(Bytes::new(Vec::new()));
+	}
 }
 
-fn prepare_transaction<C, M>(client: &C, miner: &M, request: TransactionRequest) -> Transaction where C: MiningBlockChainClient, M: MinerService {
-	Transaction {
-		nonce: request.nonce
-			.or_else(|| miner
-					 .last_nonce(&request.from)
-					 .map(|nonce| nonce + U256::one()))
-			.unwrap_or_else(|| client.latest_nonce(&request.from)),
+fn prepare_transaction<C, M>(client: &C, miner: &M, request: TransactionRequest) -> Result<Transaction, Error> where C: MiningBlockChainClient, M: MinerService {
+	let nonce = match request.nonce.or_else(|| miner.last_nonce(&request.from).map(|nonce| nonce + U256::one())) {
+		Some(nonce) => nonce,
+		None => try!(client.latest_nonce(&request.from).ok_or_else(state_syncing_error)),
+	};
+
+	Ok(Transaction {
+		nonce: nonce,
 
 		action: request.to.map_or(Action::Create, Action::Call),
 		gas: request.gas.unwrap_or_else(|| miner.sensible_gas_limit()),
 		gas_price: request.gas_price.unwrap_or_else(|| default_gas_price(client, miner)),
 		value: request.value.unwrap_or_else(U256::zero),
 		data: request.data.map_or_else(Vec::new, |b| b.to_vec()),
-	}
+	})
 }
 
-fn unlock_sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionRequest, account_provider: &AccountProvider, address: Address, password: String) -> Result<Value, Error>
+fn unlock_sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionRequest, account_provider: &AccountProvider, address: Address, password: String) -> Result<H256, Error>
 	where C: MiningBlockChainClient, M: MinerService {
 
+	let condition = request.condition.clone();
 	let signed_transaction = {
-		let t = prepare_transaction(client, miner, request);
-		let hash = t.hash();
-		let signature = try!(account_provider.sign_with_password(address, password, hash).map_err(signing_error));
+		let t = try!(prepare_transaction(client, miner, request));
+		let signature = try!(account_provider.sign_transaction_with_password(address, password, &t).map_err(signing_error));
 		t.with_signature(signature)
 	};
 
 	trace!(target: "miner", "send_transaction: dispatching tx: {}", encode(&signed_transaction).to_vec().pretty());
-	dispatch_transaction(&*client, &*miner, signed_transaction)
+	dispatch_transaction(&*client, &*miner, signed_transaction, condition)
 }
 
-fn sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionRequest, account_provider: &AccountProvider, address: Address) -> Result<Value, Error>
+fn sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionRequest, account_provider: &AccountProvider, address: Address) -> Result<H256, Error>
 	where C: MiningBlockChainClient, M: MinerService {
 
+	let condition = request.condition.clone();
 	let signed_transaction = {
-		let t = prepare_transaction(client, miner, request);
-		let hash = t.hash();
-		let signature = try!(account_provider.sign(address, hash).map_err(signing_error));
+		let t = try!(prepare_transaction(client, miner, request));
+		let signature = try!(account_provider.sign_transaction(address, &t).map_err(signing_error));
 		t.with_signature(signature)
 	};
 
 	trace!(target: "miner", "send_transaction: dispatching tx: {}", encode(&signed_transaction).to_vec().pretty());
-	dispatch_transaction(&*client, &*miner, signed_transaction)
+	dispatch_transaction(&*client, &*miner, signed_transaction, condition)
 }
 
 fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where C: MiningBlockChainClient, M: MinerService {
@@ -150,6 +180,30 @@ fn signing_error(error: AccountError) -> Error {
 	}
 }
 
+fn account_error(error: AccountError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::ACCOUNT_ERROR),
+		message: "Account management error.".into(),
+		data: Some(Value::String(format!("{}", error))),
+	}
+}
+
+fn state_syncing_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::STATE_SYNCING),
+		message: "State of the latest block is still being downloaded. Try again once the node is synced.".into(),
+		data: None,
+	}
+}
+
+fn request_rejected_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::REQUEST_REJECTED),
+		message: "Request has been rejected in Trusted Signer or the confirmation timed out.".into(),
+		data: None,
+	}
+}
+
 fn transaction_error(error: EthcoreError) -> Error {
 	use ethcore::error::TransactionError::*;
 
@@ -174,6 +228,11 @@ fn transaction_error(error: EthcoreError) -> Error {
 			},
 			InvalidGasLimit(_) => "Supplied gas is beyond limit.".into(),
 			DAORescue => "Transaction removes funds from a DAO.".into(),
+			SenderBanned => "Sender is not allowed to send transactions to this node.".into(),
+			RecipientBanned => "Recipient of the transaction is not allowed by this node.".into(),
+			SenderLimitReached { limit } => {
+				format!("There are too many transactions from your account in the queue (limit: {}). Wait until some of them are mined.", limit)
+			},
 		};
 		Error {
 			code: ErrorCode::ServerError(error_codes::TRANSACTION_ERROR),
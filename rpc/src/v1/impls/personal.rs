@@ -16,31 +16,45 @@
 
 //! Account management (personal) rpc implementation
 use std::sync::{Arc, Weak};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use serde_json;
 use jsonrpc_core::*;
 use v1::traits::Personal;
-use v1::types::TransactionRequest;
-use v1::impls::unlock_sign_and_dispatch;
-use ethcore::account_provider::AccountProvider;
+use v1::types::{TransactionRequest, AccountInfo, Bytes, DecryptRequest};
+use v1::helpers::{SigningQueue, ConfirmationsQueue};
+use v1::impls::{unlock_sign_and_dispatch, account_error, signing_error, request_rejected_error};
+use ethcore::account_provider::{AccountProvider, Error as AccountError};
+use ethcore::ethstore::Error as SSError;
 use util::numbers::*;
 use ethcore::client::MiningBlockChainClient;
 use ethcore::miner::MinerService;
 
+fn params_len(params: &Params) -> usize {
+	match params {
+		&Params::Array(ref vec) => vec.len(),
+		_ => 0,
+	}
+}
+
 /// Account management (personal) rpc implementation.
 pub struct PersonalClient<C, M> where C: MiningBlockChainClient, M: MinerService {
 	accounts: Weak<AccountProvider>,
 	client: Weak<C>,
 	miner: Weak<M>,
 	signer_port: Option<u16>,
+	confirmations_queue: Option<Arc<ConfirmationsQueue>>,
 }
 
 impl<C, M> PersonalClient<C, M> where C: MiningBlockChainClient, M: MinerService {
 	/// Creates new PersonalClient
-	pub fn new(store: &Arc<AccountProvider>, client: &Arc<C>, miner: &Arc<M>, signer_port: Option<u16>) -> Self {
+	pub fn new(store: &Arc<AccountProvider>, client: &Arc<C>, miner: &Arc<M>, signer_port: Option<u16>, queue: Option<Arc<ConfirmationsQueue>>) -> Self {
 		PersonalClient {
 			accounts: Arc::downgrade(store),
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			signer_port: signer_port,
+			confirmations_queue: queue,
 		}
 	}
 }
@@ -70,17 +84,201 @@ impl<C: 'static, M: 'static> Personal for PersonalClient<C, M> where C: MiningBl
 		)
 	}
 
+	fn new_account_from_wallet(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String, String)>(params).and_then(|(json, pass)| {
+			let store = take_weak!(self.accounts);
+			store.import_presale(json.as_bytes(), &pass)
+				.map_err(account_error)
+				.and_then(|address| to_value(&address))
+		})
+	}
+
+	fn new_account_from_secret(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H256, String)>(params).and_then(|(secret, pass)| {
+			let store = take_weak!(self.accounts);
+			store.insert_account(secret, &pass)
+				.map_err(account_error)
+				.and_then(|address| to_value(&address))
+		})
+	}
+
+	fn new_account_from_phrase(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String, String)>(params).and_then(|(phrase, pass)| {
+			let store = take_weak!(self.accounts);
+			store.new_account_from_phrase(phrase, &pass)
+				.map_err(account_error)
+				.and_then(|address| to_value(&address))
+		})
+	}
+
+	fn change_password(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, String, String)>(params).and_then(|(account, password, new_password)| {
+			let store = take_weak!(self.accounts);
+			store.change_password(account, password, new_password)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn export_account(&self, params: Params) -> Result<Value, Error> {
+		let params = match params_len(&params) {
+			2 => from_params::<(Address, String)>(params).map(|(a, p)| (a, p, None, None)),
+			3 => from_params::<(Address, String, Option<String>)>(params).map(|(a, p, n)| (a, p, n, None)),
+			_ => from_params::<(Address, String, Option<String>, Option<u32>)>(params),
+		};
+
+		params.and_then(|(account, password, new_password, iterations)| {
+			let store = take_weak!(self.accounts);
+			let json = try!(store.export_account(account, password, new_password, iterations).map_err(|e| match e {
+				AccountError::SStore(SSError::InvalidKeyIterations) => Error::invalid_params(),
+				e => account_error(e),
+			}));
+			serde_json::from_str(&json).map_err(|_| Error::internal_error())
+		})
+	}
+
 	fn unlock_account(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(Address, String, u64)>(params).and_then(
-			|(account, account_pass, _)|{
+			|(account, account_pass, duration)|{
 				let store = take_weak!(self.accounts);
-				match store.unlock_account_temporarily(account, account_pass) {
+				// Zero duration unlocks the account for a single signature only.
+				let result = match duration {
+					0 => store.unlock_account_temporarily(account, account_pass),
+					secs => store.unlock_account_timed(account, account_pass, Duration::from_secs(secs)),
+				};
+				match result {
 					Ok(_) => Ok(Value::Bool(true)),
+					Err(AccountError::InvalidUnlockDuration) => Err(Error::invalid_params()),
 					Err(_) => Ok(Value::Bool(false)),
 				}
 			})
 	}
 
+	fn lock_account(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, )>(params).and_then(
+			|(account, )| {
+				let store = take_weak!(self.accounts);
+				to_value(&store.lock_account(account))
+			})
+	}
+
+	fn set_account_name(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, String)>(params).and_then(|(account, name)| {
+			let store = take_weak!(self.accounts);
+			store.set_account_name(account, name)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn set_account_meta(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, String)>(params).and_then(|(account, meta)| {
+			let store = take_weak!(self.accounts);
+			store.set_account_meta(account, meta)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn accounts_info(&self, _: Params) -> Result<Value, Error> {
+		let store = take_weak!(self.accounts);
+		let info = try!(store.accounts_info().map_err(account_error));
+		let info = info.into_iter()
+			.map(|(address, meta)| (address, AccountInfo::from(meta)))
+			.collect::<BTreeMap<Address, AccountInfo>>();
+		to_value(&info)
+	}
+
+	fn decrypt(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, Bytes)>(params).and_then(|(address, msg)| {
+			match self.confirmations_queue {
+				// Decryption has to be confirmed in Trusted Signer.
+				Some(ref queue) => {
+					let promise = queue.add_request(DecryptRequest {
+						address: address,
+						msg: msg,
+					}.into());
+					promise.wait_with_timeout().unwrap_or_else(|| Err(request_rejected_error()))
+				},
+				None => {
+					let store = take_weak!(self.accounts);
+					store.decrypt(address, &[], &msg.0)
+						.map_err(signing_error)
+						.and_then(|plain| to_value(&Bytes::new(plain)))
+				},
+			}
+		})
+	}
+
+	fn new_vault(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String, String)>(params).and_then(|(name, password)| {
+			let store = take_weak!(self.accounts);
+			store.create_vault(&name, &password)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn open_vault(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String, String)>(params).and_then(|(name, password)| {
+			let store = take_weak!(self.accounts);
+			store.open_vault(&name, &password)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn close_vault(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String,)>(params).and_then(|(name,)| {
+			let store = take_weak!(self.accounts);
+			store.close_vault(&name)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn list_vaults(&self, _: Params) -> Result<Value, Error> {
+		let store = take_weak!(self.accounts);
+		let vaults = try!(store.list_vaults().map_err(account_error));
+		to_value(&vaults)
+	}
+
+	fn list_opened_vaults(&self, _: Params) -> Result<Value, Error> {
+		let store = take_weak!(self.accounts);
+		to_value(&store.list_opened_vaults())
+	}
+
+	fn change_vault(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, String)>(params).and_then(|(account, vault)| {
+			let store = take_weak!(self.accounts);
+			let vault = match vault.is_empty() {
+				true => None,
+				false => Some(vault.as_str()),
+			};
+			store.change_vault(account, vault)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
+	fn get_vault_meta(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String,)>(params).and_then(|(name,)| {
+			let store = take_weak!(self.accounts);
+			store.vault_meta(&name)
+				.map_err(account_error)
+				.and_then(|meta| to_value(&meta))
+		})
+	}
+
+	fn set_vault_meta(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String, String)>(params).and_then(|(name, meta)| {
+			let store = take_weak!(self.accounts);
+			store.set_vault_meta(&name, meta)
+				.map_err(account_error)
+				.and_then(|_| to_value(&true))
+		})
+	}
+
 	fn sign_and_send_transaction(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(TransactionRequest, String)>(params)
 			.and_then(|(request, password)| {
@@ -19,12 +19,14 @@
 use std::sync::{Arc, Weak};
 use jsonrpc_core::*;
 use v1::traits::PersonalSigner;
-use v1::types::TransactionModification;
-use v1::impls::unlock_sign_and_dispatch;
+use v1::types::{TransactionModification, ConfirmationPayload, ConfirmedTransaction, Bytes, MultisigOperation};
+use v1::impls::{unlock_sign_and_dispatch, fill_optional_fields, account_error, state_syncing_error};
 use v1::helpers::{SigningQueue, ConfirmationsQueue};
+use v1::helpers::multisig;
 use ethcore::account_provider::AccountProvider;
+use ethcore::filter::Filter;
 use util::numbers::*;
-use ethcore::client::MiningBlockChainClient;
+use ethcore::client::{MiningBlockChainClient, BlockID};
 use ethcore::miner::MinerService;
 
 /// Transactions confirmation (personal) rpc implementation.
@@ -63,18 +65,57 @@ impl<C: 'static, M: 'static> PersonalSigner for SignerClient<C, M> where C: Mini
 				let client = take_weak!(self.client);
 				let miner = take_weak!(self.miner);
 				queue.peek(&id).and_then(|confirmation| {
-						let mut request = confirmation.transaction;
-						// apply modification
-						if let Some(gas_price) = modification.gas_price {
-							request.gas_price = Some(gas_price);
-						}
+						// `result` is passed to the requester, `response` is returned to `SignerUI`
+						let result = match confirmation.payload {
+							ConfirmationPayload::Transaction(mut request) => {
+								// don't touch the queued request unless the password is valid
+								let sender = request.from;
+								if accounts.sign_with_password(sender, pass.clone(), H256::default()).is_err() {
+									return None;
+								}
+
+								// apply modification
+								modification.apply(&mut request);
+								fill_optional_fields(&mut request, &*client, &*miner);
+
+								// reject nonces that are already used
+								if let Some(nonce) = request.nonce {
+									match client.latest_nonce(&request.from) {
+										Some(latest) if nonce < latest => return Some(Err(Error::invalid_params())),
+										None => return Some(Err(state_syncing_error())),
+										_ => {},
+									}
+								}
 
-						let sender = request.from;
+								let modification = match modification.is_empty() {
+									true => None,
+									false => {
+										queue.request_modified(&id, request.clone().into());
+										Some(modification)
+									},
+								};
+
+								unlock_sign_and_dispatch(&*client, &*miner, request, &*accounts, sender, pass)
+									.and_then(|hash| {
+										let response = try!(to_value(&ConfirmedTransaction {
+											hash: hash,
+											modification: modification,
+										}));
+										Ok((try!(to_value(&hash)), response))
+									})
+							},
+							ConfirmationPayload::Decrypt(request) => {
+								accounts.decrypt_with_password(request.address, pass, &[], &request.msg.0)
+									.map_err(account_error)
+									.and_then(|plain| to_value(&Bytes::new(plain)))
+									.map(|value| (value.clone(), value))
+							},
+						};
 
-						match unlock_sign_and_dispatch(&*client, &*miner, request, &*accounts, sender, pass) {
-							Ok(hash) => {
-								queue.request_confirmed(id, Ok(hash.clone()));
-								Some(to_value(&hash))
+						match result {
+							Ok((value, response)) => {
+								queue.request_confirmed(id, Ok(value));
+								Some(Ok(response))
 							},
 							_ => None
 						}
@@ -86,6 +127,28 @@ impl<C: 'static, M: 'static> PersonalSigner for SignerClient<C, M> where C: Mini
 		)
 	}
 
+	fn multisig_pending(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Address, )>(params).and_then(
+			|(wallet, )| {
+				let client = take_weak!(self.client);
+				let accounts = take_weak!(self.accounts).accounts();
+				let filter = Filter {
+					from_block: BlockID::Earliest,
+					to_block: BlockID::Latest,
+					address: Some(vec![wallet]),
+					topics: [Some(multisig::event_topics()), None, None, None],
+				};
+
+				let logs = client.logs(filter).into_iter().map(|log| log.entry).collect::<Vec<_>>();
+				let pending = multisig::pending_operations(&logs).into_iter()
+					.filter(|op| !op.confirmed_by.iter().any(|owner| accounts.contains(owner)))
+					.map(MultisigOperation::from)
+					.collect::<Vec<_>>();
+				to_value(&pending)
+			}
+		)
+	}
+
 	fn reject_transaction(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(U256, )>(params).and_then(
 			|(id, )| {
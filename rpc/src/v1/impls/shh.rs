@@ -0,0 +1,135 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper rpc implementation.
+use std::sync::{Arc, Weak};
+use jsonrpc_core::*;
+use util::numbers::*;
+use ethcore_whisper::{self, Whisper, Post, Filter, Encryption, PostError, PROTOCOL_VERSION, MAX_WORK_MS, MAX_TTL};
+use v1::traits::Shh;
+use v1::types::{Bytes, Index, WhisperPost, WhisperFilter, WhisperMessage};
+
+/// Whisper rpc implementation.
+pub struct ShhClient {
+	whisper: Weak<Whisper>,
+}
+
+impl ShhClient {
+	/// Creates new ShhClient.
+	pub fn new(whisper: &Arc<Whisper>) -> Self {
+		ShhClient {
+			whisper: Arc::downgrade(whisper),
+		}
+	}
+}
+
+fn topics(topics: Vec<Bytes>) -> Vec<ethcore_whisper::Topic> {
+	topics.into_iter().map(|t| ethcore_whisper::topic(&t.to_vec())).collect()
+}
+
+fn post_error(error: PostError) -> Error {
+	match error {
+		PostError::UnknownIdentity => Error::invalid_params(),
+		e => Error {
+			code: ErrorCode::InternalError,
+			message: "Error posting whisper message.".into(),
+			data: Some(Value::String(format!("{:?}", e))),
+		},
+	}
+}
+
+impl Shh for ShhClient {
+	fn version(&self, _: Params) -> Result<Value, Error> {
+		Ok(Value::String(format!("{}", PROTOCOL_VERSION)))
+	}
+
+	fn post(&self, params: Params) -> Result<Value, Error> {
+		let whisper = take_weak!(self.whisper);
+		from_params::<(WhisperPost,)>(params)
+			.and_then(|(post,)| {
+				if post.ttl > U256::from(MAX_TTL) {
+					return Err(Error::invalid_params());
+				}
+				let work_ms = if post.priority > U256::from(MAX_WORK_MS) { MAX_WORK_MS } else { post.priority.low_u64() };
+				let encryption = match (post.key, post.to) {
+					(Some(key), _) => Encryption::Symmetric(key),
+					(None, Some(to)) => Encryption::Asymmetric(to),
+					(None, None) => Encryption::None,
+				};
+				whisper.post(Post {
+					from: post.from,
+					encryption: encryption,
+					topics: topics(post.topics),
+					payload: post.payload.to_vec(),
+					ttl: post.ttl.low_u32(),
+					work_ms: work_ms,
+				}).map_err(post_error).and_then(|_| to_value(&true))
+			})
+	}
+
+	fn new_identity(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => take_weak!(self.whisper).new_identity()
+				.map_err(|_| Error::internal_error())
+				.and_then(|public| to_value(&public)),
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn has_identity(&self, params: Params) -> Result<Value, Error> {
+		let whisper = take_weak!(self.whisper);
+		from_params::<(H512,)>(params)
+			.and_then(|(identity,)| to_value(&whisper.has_identity(&identity)))
+	}
+
+	fn new_filter(&self, params: Params) -> Result<Value, Error> {
+		let whisper = take_weak!(self.whisper);
+		from_params::<(WhisperFilter,)>(params)
+			.and_then(|(filter,)| {
+				let id = whisper.add_filter(Filter {
+					topics: topics(filter.topics.unwrap_or_else(Vec::new)),
+					to: filter.to,
+					from: filter.from,
+					key: filter.key,
+				});
+				to_value(&U256::from(id))
+			})
+	}
+
+	fn uninstall_filter(&self, params: Params) -> Result<Value, Error> {
+		let whisper = take_weak!(self.whisper);
+		from_params::<(Index,)>(params)
+			.and_then(|(index,)| to_value(&whisper.remove_filter(index.value())))
+	}
+
+	fn filter_changes(&self, params: Params) -> Result<Value, Error> {
+		let whisper = take_weak!(self.whisper);
+		from_params::<(Index,)>(params)
+			.and_then(|(index,)| {
+				let messages = whisper.poll_filter(index.value()).unwrap_or_else(Vec::new);
+				to_value(&messages.into_iter().map(WhisperMessage::from).collect::<Vec<_>>())
+			})
+	}
+
+	fn messages(&self, params: Params) -> Result<Value, Error> {
+		let whisper = take_weak!(self.whisper);
+		from_params::<(Index,)>(params)
+			.and_then(|(index,)| {
+				let messages = whisper.filter_messages(index.value()).unwrap_or_else(Vec::new);
+				to_value(&messages.into_iter().map(WhisperMessage::from).collect::<Vec<_>>())
+			})
+	}
+}
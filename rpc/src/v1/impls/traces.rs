@@ -24,6 +24,7 @@ use ethcore::client::{BlockChainClient, CallAnalytics, TransactionID, TraceId};
 use ethcore::miner::MinerService;
 use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
 use v1::traits::Traces;
+use v1::impls::state_syncing_error;
 use v1::types::{TraceFilter, LocalizedTrace, Trace, BlockNumber, Index, CallRequest, Bytes, StateDiff, VMTrace};
 
 /// Traces api implementation.
@@ -46,8 +47,12 @@ impl<C, M> TracesClient<C, M> where C: BlockChainClient, M: MinerService {
 		let client = take_weak!(self.client);
 		let miner = take_weak!(self.miner);
 		let from = request.from.unwrap_or(0.into());
+		let nonce = match request.nonce {
+			Some(nonce) => nonce,
+			None => try!(client.latest_nonce(&from).ok_or_else(state_syncing_error)),
+		};
 		Ok(EthTransaction {
-			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
+			nonce: nonce,
 			action: request.to.map_or(Action::Create, Action::Call),
 			gas: request.gas.unwrap_or(50_000_000.into()),
 			gas_price: request.gas_price.unwrap_or_else(|| miner.sensible_gas_price()),
@@ -23,7 +23,7 @@ use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics};
 use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::transaction::SignedTransaction;
 use ethcore::receipt::Receipt;
-use ethcore::miner::{MinerService, MinerStatus, AccountDetails, TransactionImportResult};
+use ethcore::miner::{MinerService, MinerStatus, AccountDetails, TransactionImportResult, TransactionCondition, ScheduledTransaction};
 
 /// Test miner service.
 pub struct TestMinerService {
@@ -37,9 +37,12 @@ pub struct TestMinerService {
 	pub pending_receipts: Mutex<BTreeMap<H256, Receipt>>,
 	/// Last nonces.
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
+	/// Scheduled transactions.
+	pub scheduled_transactions: Mutex<HashMap<H256, ScheduledTransaction>>,
 
 	min_gas_price: RwLock<U256>,
 	gas_floor_target: RwLock<U256>,
+	gas_ceil_target: RwLock<U256>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
 	limit: RwLock<usize>,
@@ -53,8 +56,10 @@ impl Default for TestMinerService {
 			pending_transactions: Mutex::new(HashMap::new()),
 			pending_receipts: Mutex::new(BTreeMap::new()),
 			last_nonces: RwLock::new(HashMap::new()),
+			scheduled_transactions: Mutex::new(HashMap::new()),
 			min_gas_price: RwLock::new(U256::from(20_000_000)),
 			gas_floor_target: RwLock::new(U256::from(12345)),
+			gas_ceil_target: RwLock::new(U256::from(54321)),
 			author: RwLock::new(Address::zero()),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
 			limit: RwLock::new(1024),
@@ -86,6 +91,11 @@ impl MinerService for TestMinerService {
 		*self.gas_floor_target.write().unwrap() = target;
 	}
 
+	/// Set the maximal gas limit we wish to target when sealing a new block.
+	fn set_gas_ceil_target(&self, target: U256) {
+		*self.gas_ceil_target.write().unwrap() = target;
+	}
+
 	fn set_minimal_gas_price(&self, min_gas_price: U256) {
 		*self.min_gas_price.write().unwrap() = min_gas_price;
 	}
@@ -114,6 +124,10 @@ impl MinerService for TestMinerService {
 		*self.gas_floor_target.read().unwrap()
 	}
 
+	fn gas_ceil_target(&self) -> U256 {
+		*self.gas_ceil_target.read().unwrap()
+	}
+
 	/// Imports transactions to transaction queue.
 	fn import_transactions<T>(&self, _chain: &MiningBlockChainClient, transactions: Vec<SignedTransaction>, fetch_account: T) ->
 		Vec<Result<TransactionImportResult, Error>>
@@ -138,7 +152,7 @@ impl MinerService for TestMinerService {
 
 		// keep the pending nonces up to date
 		if let Ok(ref sender) = transaction.sender() {
-			let nonce = self.last_nonce(sender).unwrap_or(chain.latest_nonce(sender));
+			let nonce = self.last_nonce(sender).or_else(|| chain.latest_nonce(sender)).unwrap_or_else(U256::zero);
 			self.last_nonces.write().unwrap().insert(sender.clone(), nonce + U256::from(1));
 		}
 
@@ -148,6 +162,27 @@ impl MinerService for TestMinerService {
 		Ok(TransactionImportResult::Current)
 	}
 
+	/// Schedules transaction (never imports it to the queue).
+	fn import_scheduled_transaction<T>(&self, _chain: &MiningBlockChainClient, transaction: SignedTransaction, condition: TransactionCondition, _fetch_account: T) ->
+		Result<(), Error>
+		where T: Fn(&Address) -> AccountDetails {
+
+		self.scheduled_transactions.lock().unwrap().insert(transaction.hash(), ScheduledTransaction {
+			transaction: transaction,
+			condition: condition,
+			error: None,
+		});
+		Ok(())
+	}
+
+	fn scheduled_transactions(&self) -> Vec<ScheduledTransaction> {
+		self.scheduled_transactions.lock().unwrap().values().cloned().collect()
+	}
+
+	fn remove_scheduled_transaction(&self, hash: &H256) -> Option<ScheduledTransaction> {
+		self.scheduled_transactions.lock().unwrap().remove(hash)
+	}
+
 	/// Returns hashes of transactions currently in pending
 	fn pending_transactions_hashes(&self) -> Vec<H256> {
 		vec![]
@@ -180,6 +215,10 @@ impl MinerService for TestMinerService {
 		self.pending_transactions.lock().unwrap().values().cloned().collect()
 	}
 
+	fn local_transactions(&self) -> Vec<SignedTransaction> {
+		self.pending_transactions.lock().unwrap().values().cloned().collect()
+	}
+
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		self.pending_transactions.lock().unwrap().values().cloned().collect()
 	}
@@ -189,7 +228,14 @@ impl MinerService for TestMinerService {
 	}
 
 	fn last_nonce(&self, address: &Address) -> Option<U256> {
-		self.last_nonces.read().unwrap().get(address).cloned()
+		let scheduled = self.scheduled_transactions.lock().unwrap().values()
+			.filter(|s| s.transaction.sender().ok().as_ref() == Some(address))
+			.map(|s| s.transaction.nonce)
+			.max();
+		match (self.last_nonces.read().unwrap().get(address).cloned(), scheduled) {
+			(Some(queued), Some(scheduled)) => Some(max(queued, scheduled)),
+			(queued, scheduled) => queued.or(scheduled),
+		}
 	}
 
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
@@ -198,22 +244,22 @@ impl MinerService for TestMinerService {
 		unimplemented!();
 	}
 
-	fn balance(&self, _chain: &MiningBlockChainClient, address: &Address) -> U256 {
-		self.latest_closed_block.lock().unwrap().as_ref().map_or_else(U256::zero, |b| b.block().fields().state.balance(address).clone())
+	fn balance(&self, _chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
+		Some(self.latest_closed_block.lock().unwrap().as_ref().map_or_else(U256::zero, |b| b.block().fields().state.balance(address).clone()))
 	}
 
 	fn call(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction, _analytics: CallAnalytics) -> Result<Executed, ExecutionError> {
 		unimplemented!();
 	}
 
-	fn storage_at(&self, _chain: &MiningBlockChainClient, address: &Address, position: &H256) -> H256 {
-		self.latest_closed_block.lock().unwrap().as_ref().map_or_else(H256::default, |b| b.block().fields().state.storage_at(address, position).clone())
+	fn storage_at(&self, _chain: &MiningBlockChainClient, address: &Address, position: &H256) -> Option<H256> {
+		Some(self.latest_closed_block.lock().unwrap().as_ref().map_or_else(H256::default, |b| b.block().fields().state.storage_at(address, position).clone()))
 	}
 
-	fn nonce(&self, _chain: &MiningBlockChainClient, address: &Address) -> U256 {
+	fn nonce(&self, _chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
 		// we assume all transactions are in a pending block, ignoring the
 		// reality of gas limits.
-		self.last_nonce(address).unwrap_or(U256::zero())
+		Some(self.last_nonce(address).unwrap_or(U256::zero()))
 	}
 
 	fn code(&self, _chain: &MiningBlockChainClient, address: &Address) -> Option<Bytes> {
@@ -17,7 +17,7 @@
 //! Test implementation of SyncProvider.
 
 use util::U256;
-use ethsync::{SyncProvider, SyncStatus, SyncState};
+use ethsync::{SyncProvider, SyncStatus, SyncState, EthPeerInfo};
 use std::sync::RwLock;
 
 /// TestSyncProvider config.
@@ -65,5 +65,9 @@ impl SyncProvider for TestSyncProvider {
 
 	fn stop_network(&self) {
 	}
+
+	fn peers(&self) -> Vec<EthPeerInfo> {
+		Vec::new()
+	}
 }
 
@@ -17,6 +17,7 @@
 use std::str::FromStr;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::sync::atomic::Ordering as AtomicOrdering;
 use jsonrpc_core::IoHandler;
 use util::hash::{Address, H256, FixedHash};
 use util::numbers::{Uint, U256};
@@ -608,6 +609,99 @@ fn rpc_eth_send_transaction() {
 	assert_eq!(tester.io.handle_request(&request), Some(response));
 }
 
+#[test]
+fn rpc_eth_send_transaction_while_state_is_syncing() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account("").unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into()).unwrap();
+	tester.client.state_pending.store(true, AtomicOrdering::Relaxed);
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a"
+		}],
+		"id": 1
+	}"#;
+
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"State of the latest block is still being downloaded. Try again once the node is synced.","data":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	assert!(tester.miner.imported_transactions.lock().unwrap().is_empty());
+}
+
+#[test]
+fn rpc_eth_send_transaction_with_condition() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account("").unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into()).unwrap();
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a",
+			"condition": { "block": 1000 }
+		}],
+		"id": 1
+	}"#;
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x9184e72a000u64),
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		value: U256::from(0x9184e72au64),
+		data: vec![]
+	};
+	let signature = tester.accounts_provider.sign(address, t.hash()).unwrap();
+	let t = t.with_signature(signature);
+
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(&request), Some(response));
+	assert_eq!(tester.miner.imported_transactions.lock().unwrap().len(), 0);
+	let scheduled = tester.miner.scheduled_transactions();
+	assert_eq!(scheduled.len(), 1);
+	assert_eq!(scheduled[0].transaction, t);
+	assert_eq!(scheduled[0].condition.block, Some(1000));
+}
+
+#[test]
+fn rpc_eth_send_transaction_after_scheduled_one_uses_next_nonce() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account("").unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into()).unwrap();
+	let request = |condition: &str| r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a""# + condition + r#"
+		}],
+		"id": 1
+	}"#;
+
+	tester.io.handle_request(&request(r#", "condition": { "block": 1000 }"#)).unwrap();
+	tester.io.handle_request(&request("")).unwrap();
+
+	let scheduled = tester.miner.scheduled_transactions();
+	let imported = tester.miner.imported_transactions.lock().unwrap();
+	assert_eq!(scheduled[0].transaction.nonce, U256::zero());
+	assert_eq!(imported.len(), 1);
+	assert_eq!(imported[0].nonce, U256::one());
+}
+
 #[test]
 fn rpc_eth_send_raw_transaction() {
 	let tester = EthTester::default();
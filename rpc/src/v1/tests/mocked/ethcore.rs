@@ -17,9 +17,12 @@
 use std::sync::Arc;
 use jsonrpc_core::IoHandler;
 use v1::{Ethcore, EthcoreClient};
-use v1::tests::helpers::TestMinerService;
+use v1::tests::helpers::{TestMinerService, TestSyncProvider, Config};
 use v1::helpers::ConfirmationsQueue;
 use ethcore::client::{TestBlockChainClient};
+use ethcore::service::SyncMessage;
+use util::numbers::U256;
+use util::network::{NetworkConfiguration, NetworkService};
 use util::log::RotatingLogger;
 use util::network_settings::NetworkSettings;
 
@@ -47,16 +50,30 @@ fn settings() -> Arc<NetworkSettings> {
 	})
 }
 
-fn ethcore_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>) -> EthcoreClient<TestBlockChainClient, TestMinerService> {
-	EthcoreClient::new(client, miner, logger(), settings(), None)
+fn sync_provider() -> Arc<TestSyncProvider> {
+	Arc::new(TestSyncProvider::new(Config {
+		network_id: U256::from(3),
+		num_peers: 120,
+	}))
+}
+
+fn network_service() -> Arc<NetworkService<SyncMessage>> {
+	Arc::new(NetworkService::new(NetworkConfiguration::new()).unwrap())
+}
+
+fn ethcore_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>, sync: &Arc<TestSyncProvider>, net: &Arc<NetworkService<SyncMessage>>)
+	-> EthcoreClient<TestBlockChainClient, TestMinerService, TestSyncProvider> {
+	EthcoreClient::new(client, miner, sync, net, logger(), settings(), None)
 }
 
 #[test]
 fn rpc_ethcore_extra_data() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_extraData", "params": [], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"0x01020304","id":1}"#;
@@ -71,8 +88,10 @@ fn rpc_ethcore_default_extra_data() {
 
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_defaultExtraData", "params": [], "id": 1}"#;
 	let response = format!(r#"{{"jsonrpc":"2.0","result":"0x{}","id":1}}"#, misc::version_data().to_hex());
@@ -84,8 +103,10 @@ fn rpc_ethcore_default_extra_data() {
 fn rpc_ethcore_gas_floor_target() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_gasFloorTarget", "params": [], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"0x3039","id":1}"#;
@@ -93,12 +114,29 @@ fn rpc_ethcore_gas_floor_target() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_ethcore_gas_ceil_target() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_gasCeilTarget", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0xd431","id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_ethcore_min_gas_price() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_minGasPrice", "params": [], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"0x01312d00","id":1}"#;
@@ -110,10 +148,12 @@ fn rpc_ethcore_min_gas_price() {
 fn rpc_ethcore_dev_logs() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let logger = logger();
 	logger.append("a".to_owned());
 	logger.append("b".to_owned());
-	let ethcore = EthcoreClient::new(&client, &miner, logger.clone(), settings(), None).to_delegate();
+	let ethcore = EthcoreClient::new(&client, &miner, &sync, &net, logger.clone(), settings(), None).to_delegate();
 	let io = IoHandler::new();
 	io.add_delegate(ethcore);
 
@@ -127,8 +167,10 @@ fn rpc_ethcore_dev_logs() {
 fn rpc_ethcore_dev_logs_levels() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_devLogsLevels", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"rpc=trace","id":1}"#;
@@ -140,8 +182,10 @@ fn rpc_ethcore_dev_logs_levels() {
 fn rpc_ethcore_transactions_limit() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_transactionsLimit", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":1024,"id":1}"#;
@@ -153,8 +197,10 @@ fn rpc_ethcore_transactions_limit() {
 fn rpc_ethcore_net_chain() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_netChain", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"testchain","id":1}"#;
@@ -166,8 +212,10 @@ fn rpc_ethcore_net_chain() {
 fn rpc_ethcore_net_max_peers() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_netMaxPeers", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":25,"id":1}"#;
@@ -179,8 +227,10 @@ fn rpc_ethcore_net_max_peers() {
 fn rpc_ethcore_net_port() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_netPort", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":30303,"id":1}"#;
@@ -192,8 +242,10 @@ fn rpc_ethcore_net_port() {
 fn rpc_ethcore_rpc_settings() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_rpcSettings", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":{"enabled":true,"interface":"all","port":8545},"id":1}"#;
@@ -205,8 +257,10 @@ fn rpc_ethcore_rpc_settings() {
 fn rpc_ethcore_node_name() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_nodeName", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"mynode","id":1}"#;
@@ -218,9 +272,11 @@ fn rpc_ethcore_node_name() {
 fn rpc_ethcore_unsigned_transactions_count() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
 	let queue = Arc::new(ConfirmationsQueue::default());
-	let ethcore = EthcoreClient::new(&client, &miner, logger(), settings(), Some(queue)).to_delegate();
+	let ethcore = EthcoreClient::new(&client, &miner, &sync, &net, logger(), settings(), Some(queue)).to_delegate();
 	io.add_delegate(ethcore);
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_unsignedTransactionsCount", "params":[], "id": 1}"#;
@@ -233,11 +289,66 @@ fn rpc_ethcore_unsigned_transactions_count() {
 fn rpc_ethcore_unsigned_transactions_count_when_signer_disabled() {
 	let miner = miner_service();
 	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
 	let io = IoHandler::new();
-	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_unsignedTransactionsCount", "params":[], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","error":{"code":-32030,"message":"Trusted Signer is disabled. This API is not available.","data":null},"id":1}"#;
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_ethcore_encrypt_message() {
+	use rustc_serialize::hex::{ToHex, FromHex};
+	use ethcore::ethstore::ethkey::{Generator, Random, ecies};
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+	let kp = Random.generate().unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_encryptMessage", "params":["0x"#.to_owned() + &kp.public().to_hex() + r#"", "0x"# + &b"private data".to_hex() + r#""], "id": 1}"#;
+	let response = io.handle_request(&request).unwrap();
+
+	let prefix = r#"{"jsonrpc":"2.0","result":"0x"#;
+	let suffix = r#"","id":1}"#;
+	assert!(response.starts_with(prefix) && response.ends_with(suffix));
+	let encrypted = response[prefix.len()..(response.len() - suffix.len())].from_hex().unwrap();
+	assert_eq!(ecies::decrypt(kp.secret(), &[], &encrypted).unwrap(), b"private data".to_vec());
+}
+
+#[test]
+fn rpc_ethcore_net_peers() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_netPeers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":0,"max":25,"peers":[]},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_banned_peers() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_bannedPeers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
@@ -64,6 +64,20 @@ fn rpc_ethcore_set_gas_floor_target() {
 	assert_eq!(miner.gas_floor_target(), U256::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap());
 }
 
+#[test]
+fn rpc_ethcore_set_gas_ceil_target() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setGasCeilTarget", "params":["0x5b8d80"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(miner.gas_ceil_target(), U256::from(6_000_000));
+}
+
 #[test]
 fn rpc_ethcore_set_extra_data() {
 	let miner = miner_service();
@@ -105,3 +119,33 @@ fn rpc_ethcore_set_transactions_limit() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
+
+#[test]
+fn rpc_ethcore_remove_scheduled_transaction() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_removeScheduledTransaction", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_ban_and_unban_peer() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+
+	// network is not started so nothing is banned
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_banPeer", "params":["0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_unbanPeer", "params":["0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
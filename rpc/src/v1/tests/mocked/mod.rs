@@ -26,3 +26,4 @@ mod personal_signer;
 mod ethcore;
 mod ethcore_set;
 mod rpc;
+mod shh;
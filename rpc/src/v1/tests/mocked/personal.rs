@@ -17,12 +17,17 @@
 use std::sync::Arc;
 use std::str::FromStr;
 use jsonrpc_core::IoHandler;
+use rustc_serialize::hex::ToHex;
 use util::numbers::*;
 use ethcore::account_provider::AccountProvider;
 use v1::{PersonalClient, Personal};
 use v1::tests::helpers::TestMinerService;
 use ethcore::client::TestBlockChainClient;
 use ethcore::transaction::{Action, Transaction};
+use ethcore::ethstore::ethkey::{Generator, Random, ecies};
+use ethcore::ethstore::EthStore;
+use ethcore::ethstore::dir::DiskDirectory;
+use devtools::RandomTempPath;
 
 struct PersonalTester {
 	accounts: Arc<AccountProvider>,
@@ -47,10 +52,13 @@ fn miner_service() -> Arc<TestMinerService> {
 }
 
 fn setup(signer: Option<u16>) -> PersonalTester {
-	let accounts = accounts_provider();
+	setup_with_accounts(accounts_provider(), signer)
+}
+
+fn setup_with_accounts(accounts: Arc<AccountProvider>, signer: Option<u16>) -> PersonalTester {
 	let client = blockchain_client();
 	let miner = miner_service();
-	let personal = PersonalClient::new(&accounts, &client, &miner, signer);
+	let personal = PersonalClient::new(&accounts, &client, &miner, signer, None);
 
 	let io = IoHandler::new();
 	io.add_delegate(personal.to_delegate());
@@ -103,6 +111,184 @@ fn accounts() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn should_set_account_name_and_meta() {
+	// given
+	let tester = setup(None);
+	let address = tester.accounts.new_account("").unwrap();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_setAccountName", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "Savings"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_setAccountMeta", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "{\"tags\":[]}"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+
+	// then
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_accountsInfo", "params": [], "id": 1}"#;
+	let timestamp = tester.accounts.account_meta(address).unwrap().timestamp.unwrap();
+	let response = r#"{"jsonrpc":"2.0","result":{""#.to_owned() + &format!("0x{:?}", address) + r#"":{"name":"Savings","meta":"{\"tags\":[]}","timestamp":"#
+		+ &timestamp.to_string() + r#"}},"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response));
+}
+
+#[test]
+fn should_create_open_and_close_vaults() {
+	// given
+	let path = RandomTempPath::create_dir();
+	let store = EthStore::open(Box::new(DiskDirectory::at(path.as_path()))).unwrap();
+	let tester = setup_with_accounts(Arc::new(AccountProvider::new(Box::new(store))), None);
+	let address = tester.accounts.new_account("").unwrap();
+	let ok = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_newVault", "params": ["vault1", "password"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(ok.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_changeVault", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "vault1"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(ok.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_setVaultMeta", "params": ["vault1", "{\"name\":\"Savings\"}"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(ok.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_closeVault", "params": ["vault1"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(ok.to_owned()));
+
+	// then
+	assert!(tester.accounts.accounts().is_empty());
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_listVaults", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["vault1"],"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_listOpenedVaults", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_openVault", "params": ["vault1", "wrong"], "id": 1}"#;
+	assert!(tester.io.handle_request(request).unwrap().contains(r#""code":-32021"#));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_openVault", "params": ["vault1", "password"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(ok.to_owned()));
+	assert_eq!(tester.accounts.accounts(), vec![address]);
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_getVaultMeta", "params": ["vault1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"{\"name\":\"Savings\"}","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn should_change_password() {
+	// given
+	let tester = setup(None);
+	let address = tester.accounts.new_account("old").unwrap();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_changePassword", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "old", "new"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+
+	// then
+	assert!(tester.accounts.sign_with_password(address, "old".into(), [0u8; 32]).is_err());
+	assert!(tester.accounts.sign_with_password(address, "new".into(), [0u8; 32]).is_ok());
+}
+
+#[test]
+fn should_export_account() {
+	// given
+	let tester = setup(None);
+	let address = tester.accounts.new_account("old").unwrap();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_exportAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "wrong"], "id": 1}"#;
+	let res = tester.io.handle_request(&request).unwrap();
+	assert!(res.contains(r#""code":-32021"#));
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_exportAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "old", "new", 1024], "id": 1}"#;
+	let res = tester.io.handle_request(&request).unwrap();
+
+	// then
+	assert!(res.contains(&format!(r#""address":"{:?}""#, address)));
+	assert!(res.contains(r#""c":1024"#));
+	assert!(tester.accounts.sign_with_password(address, "old".into(), [0u8; 32]).is_ok());
+}
+
+#[test]
+fn should_reject_export_with_invalid_iterations() {
+	// given
+	let tester = setup(None);
+	let address = tester.accounts.new_account("old").unwrap();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_exportAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "old", null, 0], "id": 1}"#;
+	let zero = tester.io.handle_request(&request).unwrap();
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_exportAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "old", null, 4294967295], "id": 1}"#;
+	let huge = tester.io.handle_request(&request).unwrap();
+
+	// then
+	assert!(zero.contains(r#""code":-32602"#));
+	assert!(huge.contains(r#""code":-32602"#));
+}
+
+#[test]
+fn should_return_error_when_setting_name_of_unknown_account() {
+	// given
+	let tester = setup(None);
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_setAccountName", "params": ["0x0000000000000000000000000000000000000001", "Savings"], "id": 1}"#;
+	let res = tester.io.handle_request(request).unwrap();
+
+	// then
+	assert!(res.contains(r#""code":-32021"#));
+}
+
+#[test]
+fn should_unlock_account_for_given_duration_and_lock_it() {
+	// given
+	let tester = setup(None);
+	let address = tester.accounts.new_account("password123").unwrap();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_unlockAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "password123", 600], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+
+	// then
+	assert!(tester.accounts.sign(address, H256::zero()).is_ok());
+	assert!(tester.accounts.sign(address, H256::zero()).is_ok());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_lockAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#""], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	assert!(tester.accounts.sign(address, H256::zero()).is_err());
+}
+
+#[test]
+fn should_reject_too_long_unlock_duration() {
+	// given
+	let tester = setup(None);
+	let address = tester.accounts.new_account("password123").unwrap();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_unlockAccount", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "password123", 18446744073709551615], "id": 1}"#;
+	let res = tester.io.handle_request(&request).unwrap();
+
+	// then
+	assert!(res.contains(r#""code":-32602"#));
+	assert!(tester.accounts.sign(address, H256::zero()).is_err());
+}
+
+#[test]
+fn should_decrypt_message_with_unlocked_account() {
+	// given
+	let tester = setup(None);
+	let kp = Random.generate().unwrap();
+	let address = tester.accounts.insert_account(kp.secret().clone(), "password123").unwrap();
+	let encrypted = ecies::encrypt(kp.public(), &[], b"private data").unwrap();
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_decrypt", "params": [""#.to_owned() + &format!("0x{:?}", address) + r#"", "0x"# + &encrypted.to_hex() + r#""], "id": 1}"#;
+
+	// when
+	let locked = tester.io.handle_request(&request).unwrap();
+	tester.accounts.unlock_account_temporarily(address, "password123".into()).unwrap();
+	let unlocked = tester.io.handle_request(&request);
+
+	// then
+	assert!(locked.contains(r#""code":-32020"#));
+	let response = r#"{"jsonrpc":"2.0","result":"0x"#.to_owned() + &b"private data".to_hex() + r#"","id":1}"#;
+	assert_eq!(unlocked, Some(response));
+}
+
 #[test]
 fn new_account() {
 	let tester = setup(None);
@@ -118,6 +304,37 @@ fn new_account() {
 	assert_eq!(res, Some(response));
 }
 
+#[test]
+fn new_account_from_secret() {
+	let tester = setup(None);
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_newAccountFromSecret", "params": ["0x17d08f5fe8c77af811caa0c9a187e668ce3b74a99acc3f6d976f075fa8e0be55", "pass"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x26d1ec50b4e62c1d1a40d16e7cacc6a6580757d5","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(tester.accounts.accounts().len(), 1);
+}
+
+#[test]
+fn new_account_from_phrase() {
+	let tester = setup(None);
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_newAccountFromPhrase", "params": ["this is sparta", "pass"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x26d1ec50b4e62c1d1a40d16e7cacc6a6580757d5","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(tester.accounts.accounts().len(), 1);
+}
+
+#[test]
+fn new_account_from_wallet() {
+	let tester = setup(None);
+	let wallet = r#"{\"encseed\":\"137103c28caeebbcea5d7f95edb97a289ded151b72159137cb7b2671f394f54cff8c121589dcb373e267225547b3c71cbdb54f6e48ec85cd549f96cf0dedb3bc0a9ac6c79b9c426c5878ca2c9d06ff42a23cb648312fc32ba83649de0928e066\",\"ethaddr\":\"ede84640d1a1d3e06902048e67aa7db8d52c2ce1\"}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_newAccountFromWallet", "params": [""#.to_owned() + wallet + r#"", "123"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0xede84640d1a1d3e06902048e67aa7db8d52c2ce1","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	assert_eq!(tester.accounts.accounts().len(), 1);
+}
+
 #[test]
 fn sign_and_send_transaction_with_invalid_password() {
 	let tester = setup(None);
@@ -17,14 +17,18 @@
 use std::sync::Arc;
 use std::str::FromStr;
 use jsonrpc_core::IoHandler;
+use rustc_serialize::hex::{ToHex, FromHex};
 use util::numbers::*;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::TestBlockChainClient;
 use ethcore::transaction::{Transaction, Action};
+use ethcore::log_entry::{LogEntry, LocalizedLogEntry};
+use ethcore::ethstore::ethkey::{Generator, Random, ecies};
 use v1::{SignerClient, PersonalSigner};
 use v1::tests::helpers::TestMinerService;
 use v1::helpers::{SigningQueue, ConfirmationsQueue};
-use v1::types::TransactionRequest;
+use v1::helpers::abi;
+use v1::types::{TransactionRequest, DecryptRequest, Bytes};
 
 
 struct PersonalSignerTester {
@@ -32,9 +36,8 @@ struct PersonalSignerTester {
 	accounts: Arc<AccountProvider>,
 	io: IoHandler,
 	miner: Arc<TestMinerService>,
-	// these unused fields are necessary to keep the data alive
-	// as the handler has only weak pointers.
-	_client: Arc<TestBlockChainClient>,
+	// the handler has only weak pointers, so the client has to be kept alive here
+	client: Arc<TestBlockChainClient>,
 }
 
 fn blockchain_client() -> Arc<TestBlockChainClient> {
@@ -64,7 +67,7 @@ fn signer_tester() -> PersonalSignerTester {
 		accounts: accounts,
 		io: io,
 		miner: miner,
-		_client: client,
+		client: client,
 	}
 }
 
@@ -81,11 +84,12 @@ fn should_return_list_of_transactions_in_queue() {
 		value: Some(U256::from(1)),
 		data: None,
 		nonce: None,
-	});
+		condition: None,
+	}.into());
 
 	// when
 	let request = r#"{"jsonrpc":"2.0","method":"personal_transactionsToConfirm","params":[],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x01","transaction":{"data":null,"from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x01"}}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x01","transaction":{"condition":null,"data":null,"from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x01"}}],"id":1}"#;
 
 	// then
 	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
@@ -104,7 +108,8 @@ fn should_reject_transaction_from_queue_without_dispatching() {
 		value: Some(U256::from(1)),
 		data: None,
 		nonce: None,
-	});
+		condition: None,
+	}.into());
 	assert_eq!(tester.queue.requests().len(), 1);
 
 	// when
@@ -129,7 +134,8 @@ fn should_not_remove_transaction_if_password_is_invalid() {
 		value: Some(U256::from(1)),
 		data: None,
 		nonce: None,
-	});
+		condition: None,
+	}.into());
 	assert_eq!(tester.queue.requests().len(), 1);
 
 	// when
@@ -155,7 +161,8 @@ fn should_confirm_transaction_and_dispatch() {
 		value: Some(U256::from(1)),
 		data: None,
 		nonce: None,
-	});
+		condition: None,
+	}.into());
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -178,7 +185,7 @@ fn should_confirm_transaction_and_dispatch() {
 		"params":["0x01", {"gasPrice":"0x1000"}, "test"],
 		"id":1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"hash":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","modification":{"gasPrice":"0x1000","gas":null,"nonce":null}},"id":1}"#;
 
 	// then
 	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
@@ -186,3 +193,173 @@ fn should_confirm_transaction_and_dispatch() {
 	assert_eq!(tester.miner.imported_transactions.lock().unwrap().len(), 1);
 }
 
+#[test]
+fn should_confirm_transaction_with_modified_gas_and_nonce() {
+	// given
+	let tester = signer_tester();
+	let address = tester.accounts.new_account("test").unwrap();
+	let recipient = Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap();
+	tester.queue.add_request(TransactionRequest {
+		from: address,
+		to: Some(recipient),
+		gas_price: Some(U256::from(10_000)),
+		gas: Some(U256::from(10_000_000)),
+		value: Some(U256::from(1)),
+		data: None,
+		nonce: None,
+		condition: None,
+	}.into());
+
+	let t = Transaction {
+		nonce: U256::from(5),
+		gas_price: U256::from(10_000),
+		gas: U256::from(0x5208),
+		action: Action::Call(recipient),
+		value: U256::from(0x1),
+		data: vec![]
+	};
+	tester.accounts.unlock_account_temporarily(address, "test".into()).unwrap();
+	let signature = tester.accounts.sign(address, t.hash()).unwrap();
+	let t = t.with_signature(signature);
+
+	// when
+	let request = r#"{
+		"jsonrpc":"2.0",
+		"method":"personal_confirmTransaction",
+		"params":["0x01", {"gas":"0x5208","nonce":"0x05"}, "test"],
+		"id":1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"hash":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","modification":{"gasPrice":null,"gas":"0x5208","nonce":"0x05"}},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	assert_eq!(tester.queue.requests().len(), 0);
+	assert_eq!(tester.miner.imported_transactions.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn should_not_modify_transaction_with_invalid_password() {
+	// given
+	let tester = signer_tester();
+	let address = tester.accounts.new_account("test").unwrap();
+	let request = TransactionRequest {
+		from: address,
+		to: Some(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		gas_price: Some(U256::from(10_000)),
+		gas: Some(U256::from(10_000_000)),
+		value: Some(U256::from(1)),
+		data: None,
+		nonce: None,
+		condition: None,
+	};
+	tester.queue.add_request(request.clone().into());
+
+	// when
+	let rpc = r#"{"jsonrpc":"2.0","method":"personal_confirmTransaction","params":["0x01",{"gasPrice":"0x01"},"xxx"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&rpc), Some(response.to_owned()));
+	assert_eq!(tester.queue.peek(&U256::from(1)).unwrap().payload, request.into());
+	assert_eq!(tester.miner.imported_transactions.lock().unwrap().len(), 0);
+}
+
+#[test]
+fn should_reject_modification_with_used_nonce() {
+	// given
+	let tester = signer_tester();
+	let address = tester.accounts.new_account("test").unwrap();
+	tester.client.set_nonce(address, U256::from(3));
+	tester.queue.add_request(TransactionRequest {
+		from: address,
+		to: Some(Address::from(2)),
+		gas_price: Some(U256::from(10_000)),
+		gas: Some(U256::from(10_000_000)),
+		value: Some(U256::from(1)),
+		data: None,
+		nonce: None,
+		condition: None,
+	}.into());
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"personal_confirmTransaction","params":["0x01",{"nonce":"0x02"},"test"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	assert_eq!(tester.queue.requests().len(), 1);
+	assert_eq!(tester.queue.peek(&U256::from(1)).unwrap().payload, TransactionRequest {
+		from: address,
+		to: Some(Address::from(2)),
+		gas_price: Some(U256::from(10_000)),
+		gas: Some(U256::from(10_000_000)),
+		value: Some(U256::from(1)),
+		data: None,
+		nonce: None,
+		condition: None,
+	}.into());
+	assert_eq!(tester.miner.imported_transactions.lock().unwrap().len(), 0);
+}
+
+#[test]
+fn should_confirm_decryption_request() {
+	// given
+	let tester = signer_tester();
+	let kp = Random.generate().unwrap();
+	let address = tester.accounts.insert_account(kp.secret().clone(), "test").unwrap();
+	let encrypted = ecies::encrypt(kp.public(), &[], b"private data").unwrap();
+	tester.queue.add_request(DecryptRequest {
+		address: address,
+		msg: Bytes::new(encrypted),
+	}.into());
+	assert_eq!(tester.queue.requests().len(), 1);
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"personal_confirmTransaction","params":["0x01",{},"test"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x"#.to_owned() + &b"private data".to_hex() + r#"","id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&request), Some(response));
+	assert_eq!(tester.queue.requests().len(), 0);
+	assert_eq!(tester.miner.imported_transactions.lock().unwrap().len(), 0);
+}
+
+#[test]
+fn should_return_pending_multisig_operations() {
+	// given
+	let tester = signer_tester();
+	let account = tester.accounts.new_account("test").unwrap();
+	let wallet = Address::from(10);
+	let operation = |n: u8| format!("{:064x}", n);
+	let owner = format!("{:0>64}", "1");
+	let ours = format!("{:0>64}", account.hex());
+	let submitted = |op: &str| -> String {
+		// operation, initiator, value, to, data offset, data length
+		op.to_owned() + &owner + &format!("{:064x}", 5) + &format!("{:064x}", 3) + &format!("{:064x}", 0xa0) + &format!("{:064x}", 0)
+	};
+	let log = |event: &str, data: String| LocalizedLogEntry {
+		entry: LogEntry {
+			address: wallet,
+			topics: vec![abi::event_topic(event)],
+			data: data.from_hex().unwrap(),
+		},
+		block_hash: H256::default(),
+		block_number: 1,
+		transaction_hash: H256::default(),
+		transaction_index: 0,
+		log_index: 0,
+	};
+	tester.client.set_logs(vec![
+		log("ConfirmationNeeded(bytes32,address,uint256,address,bytes)", submitted(&operation(1))),
+		log("ConfirmationNeeded(bytes32,address,uint256,address,bytes)", submitted(&operation(2))),
+		// operation 2 is already confirmed by our account
+		log("Confirmation(address,bytes32)", ours + &operation(2)),
+	]);
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"personal_multisigPending","params":["0x000000000000000000000000000000000000000a"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"confirmedBy":[],"data":"0x","initiator":"0x0000000000000000000000000000000000000001","operation":"0x0000000000000000000000000000000000000000000000000000000000000001","to":"0x0000000000000000000000000000000000000003","value":"0x05"}],"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+}
@@ -0,0 +1,116 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use jsonrpc_core::IoHandler;
+use ethcore_whisper::{Whisper, WhisperConfig};
+use v1::{Shh, ShhClient};
+
+struct ShhTester {
+	whisper: Arc<Whisper>,
+	io: IoHandler,
+}
+
+fn shh_tester() -> ShhTester {
+	let whisper = Whisper::new(WhisperConfig::default());
+	let io = IoHandler::new();
+	io.add_delegate(ShhClient::new(&whisper).to_delegate());
+	ShhTester {
+		whisper: whisper,
+		io: io,
+	}
+}
+
+#[test]
+fn rpc_shh_version() {
+	let tester = shh_tester();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_version", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"2","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_new_identity() {
+	// given
+	let tester = shh_tester();
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_newIdentity", "params": [], "id": 1}"#;
+	let res = tester.io.handle_request(request).unwrap();
+
+	// then
+	let identity = tester.whisper.new_identity().unwrap();
+	assert!(res.starts_with(r#"{"jsonrpc":"2.0","result":"0x"#));
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_hasIdentity", "params": [""#.to_owned() + &format!("0x{:?}", identity) + r#""], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_has_unknown_identity() {
+	let tester = shh_tester();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_hasIdentity", "params": ["0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_post_and_get_filter_changes() {
+	// given
+	let tester = shh_tester();
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_newFilter", "params": [{"topics":["0x6578616d706c65"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x00","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"topics":["0x6578616d706c65"],"payload":"0x1234","priority":"0x01","ttl":"0x64"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	// then
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_getFilterChanges", "params": ["0x00"], "id": 1}"#;
+	let res = tester.io.handle_request(request).unwrap();
+	assert!(res.contains(r#""payload":"0x1234""#));
+	assert!(res.contains(r#""from":null"#));
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_uninstallFilter", "params": ["0x00"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_post_rejects_too_long_ttl() {
+	// given
+	let tester = shh_tester();
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"topics":["0x6578616d706c65"],"payload":"0x1234","priority":"0xffffffffffffffffff","ttl":"0x0100000000"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	let just_over_max = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"topics":["0x6578616d706c65"],"payload":"0x1234","priority":"0x01","ttl":"0x2a301"}], "id": 1}"#;
+
+	// when
+	let res = tester.io.handle_request(request);
+	let res_just_over_max = tester.io.handle_request(just_over_max);
+
+	// then
+	assert_eq!(res, Some(response.to_owned()));
+	assert_eq!(res_just_over_max, Some(response.to_owned()));
+}
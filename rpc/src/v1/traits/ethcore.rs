@@ -30,6 +30,9 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns mining gas floor target.
 	fn gas_floor_target(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns mining gas ceiling target.
+	fn gas_ceil_target(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns minimal gas price for transaction to be included in queue.
 	fn min_gas_price(&self, _: Params) -> Result<Value, Error>;
 
@@ -48,6 +51,12 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns network port
 	fn net_port(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns details of connected peers.
+	fn net_peers(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns banned nodes.
+	fn banned_peers(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns rpc settings
 	fn rpc_settings(&self, _: Params) -> Result<Value, Error>;
 
@@ -64,12 +73,19 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns error when signer is disabled
 	fn unsigned_transactions_count(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns own transactions waiting for their conditions to be met.
+	fn scheduled_transactions(&self, _: Params) -> Result<Value, Error>;
+
+	/// Encrypts a message with given public key using ECIES.
+	fn encrypt_message(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 
 		delegate.add_method("ethcore_extraData", Ethcore::extra_data);
 		delegate.add_method("ethcore_gasFloorTarget", Ethcore::gas_floor_target);
+		delegate.add_method("ethcore_gasCeilTarget", Ethcore::gas_ceil_target);
 		delegate.add_method("ethcore_minGasPrice", Ethcore::min_gas_price);
 		delegate.add_method("ethcore_transactionsLimit", Ethcore::transactions_limit);
 		delegate.add_method("ethcore_devLogs", Ethcore::dev_logs);
@@ -77,11 +93,15 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_netChain", Ethcore::net_chain);
 		delegate.add_method("ethcore_netMaxPeers", Ethcore::net_max_peers);
 		delegate.add_method("ethcore_netPort", Ethcore::net_port);
+		delegate.add_method("ethcore_netPeers", Ethcore::net_peers);
+		delegate.add_method("ethcore_bannedPeers", Ethcore::banned_peers);
 		delegate.add_method("ethcore_rpcSettings", Ethcore::rpc_settings);
 		delegate.add_method("ethcore_nodeName", Ethcore::node_name);
 		delegate.add_method("ethcore_defaultExtraData", Ethcore::default_extra_data);
 		delegate.add_method("ethcore_gasPriceStatistics", Ethcore::gas_price_statistics);
 		delegate.add_method("ethcore_unsignedTransactionsCount", Ethcore::unsigned_transactions_count);
+		delegate.add_method("ethcore_scheduledTransactions", Ethcore::scheduled_transactions);
+		delegate.add_method("ethcore_encryptMessage", Ethcore::encrypt_message);
 
 		delegate
 	}
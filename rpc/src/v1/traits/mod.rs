@@ -24,6 +24,7 @@ pub mod ethcore;
 pub mod ethcore_set;
 pub mod traces;
 pub mod rpc;
+pub mod shh;
 
 pub use self::web3::Web3;
 pub use self::eth::{Eth, EthFilter, EthSigning};
@@ -33,5 +34,6 @@ pub use self::ethcore::Ethcore;
 pub use self::ethcore_set::EthcoreSet;
 pub use self::traces::Traces;
 pub use self::rpc::Rpc;
+pub use self::shh::Shh;
 
 
@@ -27,23 +27,98 @@ pub trait Personal: Sized + Send + Sync + 'static {
 	/// Creates new account (it becomes new current unlocked account)
 	fn new_account(&self, _: Params) -> Result<Value, Error>;
 
-	/// Unlocks specified account for use (can only be one unlocked account at one moment)
+	/// Creates new account from the given presale wallet JSON.
+	fn new_account_from_wallet(&self, _: Params) -> Result<Value, Error>;
+
+	/// Creates new account from the given raw secret.
+	fn new_account_from_secret(&self, _: Params) -> Result<Value, Error>;
+
+	/// Creates new account from the given brain wallet phrase.
+	fn new_account_from_phrase(&self, _: Params) -> Result<Value, Error>;
+
+	/// Changes password of given account.
+	fn change_password(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns encrypted JSON key file of given account.
+	/// Optional new password and number of KDF iterations re-encrypt the exported key.
+	fn export_account(&self, _: Params) -> Result<Value, Error>;
+
+	/// Unlocks specified account for use for given number of seconds
+	/// (or for a single signature if the duration is zero).
 	fn unlock_account(&self, _: Params) -> Result<Value, Error>;
 
+	/// Locks specified account.
+	fn lock_account(&self, _: Params) -> Result<Value, Error>;
+
 	/// Sends transaction and signs it in single call. The account is not unlocked in such case.
 	fn sign_and_send_transaction(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns `true` if Trusted Signer is enabled, `false` otherwise.
 	fn signer_enabled(&self, _: Params) -> Result<Value, Error>;
 
+	/// Set an account's name.
+	fn set_account_name(&self, _: Params) -> Result<Value, Error>;
+
+	/// Set an account's metadata string.
+	fn set_account_meta(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns accounts information (names and metadata).
+	fn accounts_info(&self, _: Params) -> Result<Value, Error>;
+
+	/// Decrypts ECIES-encrypted message with account's secret.
+	/// Requires confirmation in Trusted Signer if enabled, otherwise the account must be unlocked.
+	fn decrypt(&self, _: Params) -> Result<Value, Error>;
+
+	/// Creates new vault protected with given password. Vault is opened after creation.
+	fn new_vault(&self, _: Params) -> Result<Value, Error>;
+
+	/// Opens vault, making its accounts available.
+	fn open_vault(&self, _: Params) -> Result<Value, Error>;
+
+	/// Closes vault, hiding its accounts.
+	fn close_vault(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns names of all vaults.
+	fn list_vaults(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns names of opened vaults.
+	fn list_opened_vaults(&self, _: Params) -> Result<Value, Error>;
+
+	/// Moves account to given opened vault. Empty vault name moves it back to the main keys directory.
+	fn change_vault(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns metadata string of opened vault.
+	fn get_vault_meta(&self, _: Params) -> Result<Value, Error>;
+
+	/// Sets metadata string of opened vault.
+	fn set_vault_meta(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 		delegate.add_method("personal_signerEnabled", Personal::signer_enabled);
 		delegate.add_method("personal_listAccounts", Personal::accounts);
 		delegate.add_method("personal_newAccount", Personal::new_account);
+		delegate.add_method("personal_newAccountFromWallet", Personal::new_account_from_wallet);
+		delegate.add_method("personal_newAccountFromSecret", Personal::new_account_from_secret);
+		delegate.add_method("personal_newAccountFromPhrase", Personal::new_account_from_phrase);
+		delegate.add_method("personal_changePassword", Personal::change_password);
+		delegate.add_method("personal_exportAccount", Personal::export_account);
 		delegate.add_method("personal_unlockAccount", Personal::unlock_account);
+		delegate.add_method("personal_lockAccount", Personal::lock_account);
 		delegate.add_method("personal_signAndSendTransaction", Personal::sign_and_send_transaction);
+		delegate.add_method("personal_setAccountName", Personal::set_account_name);
+		delegate.add_method("personal_setAccountMeta", Personal::set_account_meta);
+		delegate.add_method("personal_accountsInfo", Personal::accounts_info);
+		delegate.add_method("personal_decrypt", Personal::decrypt);
+		delegate.add_method("personal_newVault", Personal::new_vault);
+		delegate.add_method("personal_openVault", Personal::open_vault);
+		delegate.add_method("personal_closeVault", Personal::close_vault);
+		delegate.add_method("personal_listVaults", Personal::list_vaults);
+		delegate.add_method("personal_listOpenedVaults", Personal::list_opened_vaults);
+		delegate.add_method("personal_changeVault", Personal::change_vault);
+		delegate.add_method("personal_getVaultMeta", Personal::get_vault_meta);
+		delegate.add_method("personal_setVaultMeta", Personal::set_vault_meta);
 		delegate
 	}
 }
@@ -51,21 +126,26 @@ pub trait Personal: Sized + Send + Sync + 'static {
 /// Personal extension for transactions confirmations rpc interface.
 pub trait PersonalSigner: Sized + Send + Sync + 'static {
 
-	/// Returns a list of transactions to confirm.
+	/// Returns a list of requests (transactions, decryptions) to confirm.
 	fn transactions_to_confirm(&self, _: Params) -> Result<Value, Error>;
 
-	/// Confirm and send a specific transaction.
+	/// Confirm a specific request (send the transaction or decrypt the message).
+	/// Confirmed transactions return their hash together with the applied modification.
 	fn confirm_transaction(&self, _: Params) -> Result<Value, Error>;
 
-	/// Reject the transaction request.
+	/// Reject the request.
 	fn reject_transaction(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns operations of given multisig wallet which still wait for confirmation by any of our accounts.
+	fn multisig_pending(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 		delegate.add_method("personal_transactionsToConfirm", PersonalSigner::transactions_to_confirm);
 		delegate.add_method("personal_confirmTransaction", PersonalSigner::confirm_transaction);
 		delegate.add_method("personal_rejectTransaction", PersonalSigner::reject_transaction);
+		delegate.add_method("personal_multisigPending", PersonalSigner::multisig_pending);
 		delegate
 	}
 }
@@ -0,0 +1,60 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper rpc interface.
+use std::sync::Arc;
+use jsonrpc_core::*;
+
+/// Whisper rpc interface.
+pub trait Shh: Sized + Send + Sync + 'static {
+	/// Returns whisper protocol version.
+	fn version(&self, _: Params) -> Result<Value, Error>;
+
+	/// Seals a message into an envelope and sends it to the network.
+	fn post(&self, _: Params) -> Result<Value, Error>;
+
+	/// Generates a new identity and returns its public key.
+	fn new_identity(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns true if the private key of given identity is known.
+	fn has_identity(&self, _: Params) -> Result<Value, Error>;
+
+	/// Creates a new message filter and returns its id.
+	fn new_filter(&self, _: Params) -> Result<Value, Error>;
+
+	/// Uninstalls a message filter.
+	fn uninstall_filter(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns messages received by the filter since the last poll.
+	fn filter_changes(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns all known messages matching the filter.
+	fn messages(&self, _: Params) -> Result<Value, Error>;
+
+	/// Should be used to convert object to io delegate.
+	fn to_delegate(self) -> IoDelegate<Self> {
+		let mut delegate = IoDelegate::new(Arc::new(self));
+		delegate.add_method("shh_version", Shh::version);
+		delegate.add_method("shh_post", Shh::post);
+		delegate.add_method("shh_newIdentity", Shh::new_identity);
+		delegate.add_method("shh_hasIdentity", Shh::has_identity);
+		delegate.add_method("shh_newFilter", Shh::new_filter);
+		delegate.add_method("shh_uninstallFilter", Shh::uninstall_filter);
+		delegate.add_method("shh_getFilterChanges", Shh::filter_changes);
+		delegate.add_method("shh_getMessages", Shh::messages);
+		delegate
+	}
+}
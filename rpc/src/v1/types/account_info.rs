@@ -0,0 +1,55 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::account_provider::AccountMeta;
+
+/// Account information.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct AccountInfo {
+	/// Account name
+	pub name: String,
+	/// Account metadata (free-form JSON)
+	pub meta: String,
+	/// UNIX time the account was created at, if known
+	pub timestamp: Option<u64>,
+}
+
+impl From<AccountMeta> for AccountInfo {
+	fn from(meta: AccountMeta) -> Self {
+		AccountInfo {
+			name: meta.name,
+			meta: meta.meta,
+			timestamp: meta.timestamp,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::AccountInfo;
+
+	#[test]
+	fn account_info_serialization() {
+		let info = AccountInfo {
+			name: "Savings".into(),
+			meta: "{}".into(),
+			timestamp: Some(1469000000),
+		};
+		let serialized = serde_json::to_string(&info).unwrap();
+		assert_eq!(serialized, r#"{"name":"Savings","meta":"{}","timestamp":1469000000}"#);
+	}
+}
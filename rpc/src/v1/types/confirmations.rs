@@ -0,0 +1,218 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types used in the Trusted Signer confirmations queue.
+
+use serde::{Serialize, Serializer};
+use util::hash::{Address, H256};
+use util::numbers::U256;
+use v1::types::{Bytes, TransactionRequest, TransactionConfirmation, TransactionModification, MultisigCall};
+use v1::helpers::multisig::WalletCall;
+
+/// Request to decrypt a message with account's secret.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize)]
+pub struct DecryptRequest {
+	/// Account which secret should be used
+	pub address: Address,
+	/// ECIES-encrypted message
+	pub msg: Bytes,
+}
+
+/// Decryption request waiting in a queue
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize)]
+pub struct DecryptConfirmation {
+	/// Id of this confirmation
+	pub id: U256,
+	/// Decryption request
+	pub decrypt: DecryptRequest,
+}
+
+/// Transaction confirmation calling multisig wallet contract.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize)]
+pub struct MultisigTransactionConfirmation {
+	/// Id of this confirmation
+	pub id: U256,
+	/// TransactionRequest
+	pub transaction: TransactionRequest,
+	/// Decoded wallet call
+	pub multisig: MultisigCall,
+}
+
+/// Payload of the request awaiting confirmation.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ConfirmationPayload {
+	/// Transaction to sign and dispatch
+	Transaction(TransactionRequest),
+	/// Message to decrypt
+	Decrypt(DecryptRequest),
+}
+
+impl From<TransactionRequest> for ConfirmationPayload {
+	fn from(request: TransactionRequest) -> Self {
+		ConfirmationPayload::Transaction(request)
+	}
+}
+
+impl From<DecryptRequest> for ConfirmationPayload {
+	fn from(request: DecryptRequest) -> Self {
+		ConfirmationPayload::Decrypt(request)
+	}
+}
+
+/// Request waiting in a confirmation queue
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ConfirmationRequest {
+	/// Id of this confirmation
+	pub id: U256,
+	/// Payload of the request
+	pub payload: ConfirmationPayload,
+}
+
+impl Serialize for ConfirmationRequest {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match self.payload {
+			ConfirmationPayload::Transaction(ref transaction) => {
+				let call = match (transaction.to, transaction.data.as_ref()) {
+					(Some(_), Some(data)) => WalletCall::decode(&data.0),
+					_ => None,
+				};
+
+				match call {
+					Some(call) => MultisigTransactionConfirmation {
+						id: self.id,
+						transaction: transaction.clone(),
+						multisig: call.into(),
+					}.serialize(serializer),
+					None => TransactionConfirmation {
+						id: self.id,
+						transaction: transaction.clone(),
+					}.serialize(serializer),
+				}
+			},
+			ConfirmationPayload::Decrypt(ref decrypt) => DecryptConfirmation {
+				id: self.id,
+				decrypt: decrypt.clone(),
+			}.serialize(serializer),
+		}
+	}
+}
+
+/// Event in the confirmations queue sent to `SignerUI`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueNotification {
+	/// Event type: `newRequest`, `requestRejected`, `requestConfirmed` or `requestModified`
+	pub event: String,
+	/// Id of the request
+	pub id: U256,
+	/// All requests awaiting confirmation
+	pub pending: Vec<ConfirmationRequest>,
+}
+
+/// Transaction confirmed in `SignerUI`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfirmedTransaction {
+	/// Hash of the dispatched transaction
+	pub hash: H256,
+	/// Modification applied before signing, if any
+	pub modification: Option<TransactionModification>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::hash::{Address, H256};
+	use util::numbers::U256;
+	use v1::types::{Bytes, TransactionRequest, TransactionModification};
+	use super::*;
+
+	#[test]
+	fn should_serialize_transaction_confirmation() {
+		// given
+		let request = ConfirmationRequest {
+			id: U256::from(1),
+			payload: TransactionRequest {
+				from: Address::from(1),
+				..Default::default()
+			}.into(),
+		};
+
+		// when
+		let serialized = serde_json::to_string(&request).unwrap();
+
+		// then
+		assert_eq!(serialized, r#"{"id":"0x01","transaction":{"from":"0x0000000000000000000000000000000000000001","to":null,"gasPrice":null,"gas":null,"value":null,"data":null,"nonce":null,"condition":null}}"#);
+	}
+
+	#[test]
+	fn should_serialize_multisig_transaction_confirmation() {
+		// given
+		let mut data = vec![0x79, 0x7a, 0xf6, 0x27];
+		data.extend_from_slice(&[0u8; 31]);
+		data.push(1);
+		let request = ConfirmationRequest {
+			id: U256::from(1),
+			payload: TransactionRequest {
+				from: Address::from(1),
+				to: Some(Address::from(2)),
+				data: Some(Bytes::new(data)),
+				..Default::default()
+			}.into(),
+		};
+
+		// when
+		let serialized = serde_json::to_string(&request).unwrap();
+
+		// then
+		assert!(serialized.ends_with(r#""multisig":{"method":"confirm","operation":"0x0000000000000000000000000000000000000000000000000000000000000001","to":null,"value":null,"data":null}}"#));
+	}
+
+	#[test]
+	fn should_serialize_confirmed_transaction() {
+		// given
+		let confirmed = ConfirmedTransaction {
+			hash: H256::from(1),
+			modification: Some(TransactionModification {
+				gas_price: Some(U256::from(2)),
+				..Default::default()
+			}),
+		};
+
+		// when
+		let serialized = serde_json::to_string(&confirmed).unwrap();
+
+		// then
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","modification":{"gasPrice":"0x02","gas":null,"nonce":null}}"#);
+	}
+
+	#[test]
+	fn should_serialize_decrypt_confirmation() {
+		// given
+		let request = ConfirmationRequest {
+			id: U256::from(2),
+			payload: DecryptRequest {
+				address: Address::from(1),
+				msg: Bytes::new(vec![1, 2]),
+			}.into(),
+		};
+
+		// when
+		let serialized = serde_json::to_string(&request).unwrap();
+
+		// then
+		assert_eq!(serialized, r#"{"id":"0x02","decrypt":{"address":"0x0000000000000000000000000000000000000001","msg":"0x0102"}}"#);
+	}
+}
@@ -0,0 +1,112 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Multisig wallet types.
+
+use util::hash::{Address, H256};
+use util::numbers::U256;
+use v1::types::Bytes;
+use v1::helpers::multisig::{WalletCall, PendingOperation};
+
+/// Decoded call to the multisig wallet contract.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize)]
+pub struct MultisigCall {
+	/// Wallet method: `submit`, `confirm` or `revoke`
+	pub method: String,
+	/// Operation to confirm or revoke
+	pub operation: Option<H256>,
+	/// Recipient of submitted transaction
+	pub to: Option<Address>,
+	/// Value of submitted transaction
+	pub value: Option<U256>,
+	/// Data of submitted transaction
+	pub data: Option<Bytes>,
+}
+
+impl From<WalletCall> for MultisigCall {
+	fn from(call: WalletCall) -> Self {
+		match call {
+			WalletCall::Submit { to, value, data } => MultisigCall {
+				method: "submit".into(),
+				to: Some(to),
+				value: Some(value),
+				data: Some(Bytes::new(data)),
+				..Default::default()
+			},
+			WalletCall::Confirm(operation) => MultisigCall {
+				method: "confirm".into(),
+				operation: Some(operation),
+				..Default::default()
+			},
+			WalletCall::Revoke(operation) => MultisigCall {
+				method: "revoke".into(),
+				operation: Some(operation),
+				..Default::default()
+			},
+		}
+	}
+}
+
+/// Multisig wallet operation waiting for confirmations.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+pub struct MultisigOperation {
+	/// Operation hash
+	pub operation: H256,
+	/// Owner who submitted the operation
+	pub initiator: Address,
+	/// Value
+	pub value: U256,
+	/// Recipient
+	pub to: Address,
+	/// Call data
+	pub data: Bytes,
+	/// Owners who already confirmed the operation
+	#[serde(rename="confirmedBy")]
+	pub confirmed_by: Vec<Address>,
+}
+
+impl From<PendingOperation> for MultisigOperation {
+	fn from(op: PendingOperation) -> Self {
+		MultisigOperation {
+			operation: op.operation,
+			initiator: op.initiator,
+			value: op.value,
+			to: op.to,
+			data: Bytes::new(op.data),
+			confirmed_by: op.confirmed_by,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::hash::H256;
+	use v1::helpers::multisig::WalletCall;
+	use super::MultisigCall;
+
+	#[test]
+	fn should_serialize_multisig_call() {
+		// given
+		let call: MultisigCall = WalletCall::Confirm(H256::from(1)).into();
+
+		// when
+		let serialized = serde_json::to_string(&call).unwrap();
+
+		// then
+		assert_eq!(serialized, r#"{"method":"confirm","operation":"0x0000000000000000000000000000000000000000000000000000000000000001","to":null,"value":null,"data":null}"#);
+	}
+}
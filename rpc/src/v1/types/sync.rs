@@ -16,6 +16,9 @@
 
 use serde::{Serialize, Serializer};
 use util::numbers::*;
+use util::hash::H512;
+use util::network::PeerSessionInfo;
+use ethsync::{EthPeerInfo, PeerAsking};
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -50,9 +53,145 @@ impl Serialize for SyncStatus {
 	}
 }
 
+/// Connected peers
+#[derive(Default, Debug, Serialize)]
+pub struct Peers {
+	/// Number of peers taking part in sync
+	pub active: usize,
+	/// Number of connected peers
+	pub connected: usize,
+	/// Maximal number of peers
+	pub max: u32,
+	/// Details of connected peers
+	pub peers: Vec<PeerInfo>,
+}
+
+/// Connected peer details
+#[derive(Default, Debug, Serialize)]
+pub struct PeerInfo {
+	/// Public node id
+	pub id: Option<H512>,
+	/// Peer client ID
+	pub name: String,
+	/// Negotiated capabilities
+	pub caps: Vec<String>,
+	/// Network connection details
+	pub network: PeerNetworkInfo,
+	/// Sub-protocols details
+	pub protocols: PeerProtocolsInfo,
+}
+
+/// Peer network connection details
+#[derive(Default, Debug, Serialize)]
+pub struct PeerNetworkInfo {
+	/// Remote endpoint address
+	#[serde(rename="remoteAddress")]
+	pub remote_address: String,
+	/// Local endpoint address
+	#[serde(rename="localAddress")]
+	pub local_address: String,
+	/// True if the connection was originated by us
+	pub originated: bool,
+	/// Ping delay in milliseconds
+	pub ping: Option<u64>,
+	/// Connection duration in seconds
+	pub duration: u64,
+	/// Bytes received from the peer
+	#[serde(rename="bytesReceived")]
+	pub bytes_received: usize,
+	/// Bytes sent to the peer
+	#[serde(rename="bytesSent")]
+	pub bytes_sent: usize,
+}
+
+/// Peer sub-protocols details
+#[derive(Default, Debug, Serialize)]
+pub struct PeerProtocolsInfo {
+	/// Ethereum protocol details
+	pub eth: Option<PeerEthereumProtocolInfo>,
+}
+
+/// Peer Ethereum protocol details
+#[derive(Default, Debug, Serialize)]
+pub struct PeerEthereumProtocolInfo {
+	/// Negotiated eth protocol version
+	pub version: u32,
+	/// Peer best block hash
+	pub head: H256,
+	/// Peer best block number if known
+	#[serde(rename="bestNumber")]
+	pub best_number: Option<U256>,
+	/// Peer total difficulty if known
+	pub difficulty: Option<U256>,
+	/// Data currently requested from the peer: `nothing`, `blockHeaders`, `blockBodies`, `heads`, `blockReceipts`, `nodeData` or `forkHeader`
+	pub asking: String,
+}
+
+impl From<PeerSessionInfo> for PeerInfo {
+	fn from(info: PeerSessionInfo) -> Self {
+		PeerInfo {
+			id: info.id,
+			name: info.client_version,
+			caps: info.capabilities.into_iter().map(|c| format!("{}/{}", c.protocol, c.version)).collect(),
+			network: PeerNetworkInfo {
+				remote_address: info.remote_address,
+				local_address: info.local_address,
+				originated: info.originated,
+				ping: info.ping_ms,
+				duration: info.duration,
+				bytes_received: info.bytes_received,
+				bytes_sent: info.bytes_sent,
+			},
+			protocols: PeerProtocolsInfo::default(),
+		}
+	}
+}
+
+impl From<EthPeerInfo> for PeerEthereumProtocolInfo {
+	fn from(info: EthPeerInfo) -> Self {
+		PeerEthereumProtocolInfo {
+			version: info.protocol_version,
+			head: info.head,
+			best_number: info.best_number.map(U256::from),
+			difficulty: info.difficulty,
+			asking: match info.asking {
+				PeerAsking::Nothing => "nothing",
+				PeerAsking::BlockHeaders => "blockHeaders",
+				PeerAsking::BlockBodies => "blockBodies",
+				PeerAsking::Heads => "heads",
+				PeerAsking::BlockReceipts => "blockReceipts",
+				PeerAsking::NodeData => "nodeData",
+				PeerAsking::ForkHeader => "forkHeader",
+			}.into(),
+		}
+	}
+}
+
+/// Banned node
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BannedPeer {
+	/// Public node id
+	pub id: H512,
+	/// Unix timestamp the ban expires at, `null` if the ban is permanent
+	pub until: Option<u64>,
+}
+
+impl From<(H512, Option<u64>)> for BannedPeer {
+	fn from(banned: (H512, Option<u64>)) -> Self {
+		BannedPeer {
+			id: banned.0,
+			until: banned.1,
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use util::numbers::*;
+	use util::hash::H512;
+	use util::network::{PeerSessionInfo, PeerCapabilityInfo};
+	use ethsync::{EthPeerInfo, PeerAsking};
 	use super::*;
 
 	#[test]
@@ -72,4 +211,45 @@ mod tests {
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"startingBlock":"0x00","currentBlock":"0x00","highestBlock":"0x00"}"#);
 	}
+
+	#[test]
+	fn test_serialize_banned_peer() {
+		let t = BannedPeer::from((H512::from(1), Some(1469000000)));
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"id":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001","until":1469000000}"#);
+
+		let t = BannedPeer::from((H512::from(1), None));
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"id":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001","until":null}"#);
+	}
+
+	#[test]
+	fn test_serialize_peer_info() {
+		let session = PeerSessionInfo {
+			id: None,
+			client_version: "Parity/v1.2.0".into(),
+			protocol_version: 4,
+			capabilities: vec![PeerCapabilityInfo { protocol: "eth".into(), version: 63 }],
+			remote_address: "127.0.0.1:30303".into(),
+			local_address: "127.0.0.1:30304".into(),
+			originated: true,
+			compression: false,
+			ping_ms: Some(20),
+			duration: 60,
+			bytes_received: 1024,
+			bytes_sent: 512,
+		};
+		let mut t = PeerInfo::from(session);
+		t.protocols.eth = Some(EthPeerInfo {
+			id: 1,
+			protocol_version: 63,
+			head: H256::from(2),
+			best_number: Some(10),
+			difficulty: None,
+			asking: PeerAsking::BlockBodies,
+		}.into());
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"id":null,"name":"Parity/v1.2.0","caps":["eth/63"],"network":{"remoteAddress":"127.0.0.1:30303","localAddress":"127.0.0.1:30304","originated":true,"ping":20,"duration":60,"bytesReceived":1024,"bytesSent":512},"protocols":{"eth":{"version":63,"head":"0x0000000000000000000000000000000000000000000000000000000000000002","bestNumber":"0x0a","difficulty":null,"asking":"blockBodies"}}}"#);
+	}
 }
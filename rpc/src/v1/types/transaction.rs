@@ -17,7 +17,8 @@
 use util::numbers::*;
 use ethcore::contract_address;
 use ethcore::transaction::{LocalizedTransaction, Action, SignedTransaction};
-use v1::types::{Bytes, OptionalValue};
+use ethcore::miner::ScheduledTransaction as EthScheduledTransaction;
+use v1::types::{Bytes, OptionalValue, TransactionCondition};
 
 /// Transaction
 #[derive(Debug, Default, Serialize)]
@@ -102,6 +103,27 @@ impl From<SignedTransaction> for Transaction {
 	}
 }
 
+/// Transaction held by the node until its condition is met
+#[derive(Debug, Serialize)]
+pub struct ScheduledTransaction {
+	/// Transaction
+	pub transaction: Transaction,
+	/// Condition
+	pub condition: TransactionCondition,
+	/// Reason why the transaction was not imported once the condition was met
+	pub error: Option<String>,
+}
+
+impl From<EthScheduledTransaction> for ScheduledTransaction {
+	fn from(t: EthScheduledTransaction) -> ScheduledTransaction {
+		ScheduledTransaction {
+			transaction: t.transaction.into(),
+			condition: t.condition.into(),
+			error: t.error,
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
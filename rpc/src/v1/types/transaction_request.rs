@@ -18,8 +18,36 @@
 
 use util::hash::Address;
 use util::numbers::U256;
+use ethcore::miner::TransactionCondition as EthTransactionCondition;
 use v1::types::bytes::Bytes;
 
+/// Condition that has to be met before the transaction is sent to the network.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TransactionCondition {
+	/// Not before block with given number
+	pub block: Option<u64>,
+	/// Not before given UNIX timestamp
+	pub time: Option<u64>,
+}
+
+impl Into<EthTransactionCondition> for TransactionCondition {
+	fn into(self) -> EthTransactionCondition {
+		EthTransactionCondition {
+			block: self.block,
+			timestamp: self.time,
+		}
+	}
+}
+
+impl From<EthTransactionCondition> for TransactionCondition {
+	fn from(c: EthTransactionCondition) -> Self {
+		TransactionCondition {
+			block: c.block,
+			time: c.timestamp,
+		}
+	}
+}
+
 /// Transaction request coming from RPC
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct TransactionRequest {
@@ -38,6 +66,8 @@ pub struct TransactionRequest {
 	pub data: Option<Bytes>,
 	/// Transaction's nonce
 	pub nonce: Option<U256>,
+	/// Delay the transaction until the condition is met
+	pub condition: Option<TransactionCondition>,
 }
 
 /// Transaction confirmation waiting in a queue
@@ -50,11 +80,35 @@ pub struct TransactionConfirmation {
 }
 
 /// Possible modifications to the confirmed transaction sent by `SignerUI`
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionModification {
 	/// Modified gas price
 	#[serde(rename="gasPrice")]
 	pub gas_price: Option<U256>,
+	/// Modified gas limit
+	pub gas: Option<U256>,
+	/// Modified nonce
+	pub nonce: Option<U256>,
+}
+
+impl TransactionModification {
+	/// Returns true if nothing is modified.
+	pub fn is_empty(&self) -> bool {
+		self.gas_price.is_none() && self.gas.is_none() && self.nonce.is_none()
+	}
+
+	/// Applies modifications to given request.
+	pub fn apply(&self, request: &mut TransactionRequest) {
+		if let Some(gas_price) = self.gas_price {
+			request.gas_price = Some(gas_price);
+		}
+		if let Some(gas) = self.gas {
+			request.gas = Some(gas);
+		}
+		if let Some(nonce) = self.nonce {
+			request.nonce = Some(nonce);
+		}
+	}
 }
 
 
@@ -89,6 +143,7 @@ mod tests {
 			value: Some(U256::from(3)),
 			data: Some(Bytes::new(vec![0x12, 0x34, 0x56])),
 			nonce: Some(U256::from(4)),
+			condition: None,
 		});
 	}
 
@@ -111,7 +166,8 @@ mod tests {
 			gas: Some(U256::from_str("76c0").unwrap()),
 			value: Some(U256::from_str("9184e72a").unwrap()),
 			data: Some(Bytes::new("d46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675".from_hex().unwrap())),
-			nonce: None
+			nonce: None,
+			condition: None,
 		});
 	}
 
@@ -128,6 +184,7 @@ mod tests {
 			value: None,
 			data: None,
 			nonce: None,
+			condition: None,
 		});
 	}
 
@@ -151,9 +208,24 @@ mod tests {
 			value: None,
 			data: Some(Bytes::new(vec![0x85, 0x95, 0xba, 0xb1])),
 			nonce: None,
+			condition: None,
 		});
 	}
 
+	#[test]
+	fn transaction_request_deserialize_condition() {
+		let s = r#"{
+			"from":"0x0000000000000000000000000000000000000001",
+			"condition":{"block":1000}
+		}"#;
+		let deserialized: TransactionRequest = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized.condition, Some(TransactionCondition {
+			block: Some(1000),
+			time: None,
+		}));
+	}
+
 	#[test]
 	fn should_deserialize_modification() {
 		// given
@@ -161,17 +233,32 @@ mod tests {
 			"gasPrice":"0x0ba43b7400"
 		}"#;
 		let s2 = r#"{}"#;
+		let s3 = r#"{
+			"gas":"0x5208",
+			"nonce":"0x01"
+		}"#;
 
 		// when
 		let res1: TransactionModification = serde_json::from_str(s1).unwrap();
 		let res2: TransactionModification = serde_json::from_str(s2).unwrap();
+		let res3: TransactionModification = serde_json::from_str(s3).unwrap();
 
 		// then
 		assert_eq!(res1, TransactionModification {
 			gas_price: Some(U256::from_str("0ba43b7400").unwrap()),
+			gas: None,
+			nonce: None,
 		});
 		assert_eq!(res2, TransactionModification {
 			gas_price: None,
+			gas: None,
+			nonce: None,
+		});
+		assert!(res2.is_empty());
+		assert_eq!(res3, TransactionModification {
+			gas_price: None,
+			gas: Some(U256::from(21_000)),
+			nonce: Some(U256::from(1)),
 		});
 	}
 }
@@ -0,0 +1,114 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use util::numbers::*;
+use ethcore_whisper::ReceivedMessage;
+use v1::types::Bytes;
+
+/// Whisper message to post.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct WhisperPost {
+	/// Identity to sign the message with.
+	pub from: Option<H512>,
+	/// Identity to encrypt the message to.
+	pub to: Option<H512>,
+	/// Symmetric key to encrypt the message with.
+	pub key: Option<H256>,
+	/// Message topics.
+	pub topics: Vec<Bytes>,
+	/// Message payload.
+	pub payload: Bytes,
+	/// Time to spend on proof of work in milliseconds.
+	pub priority: U256,
+	/// Time to live in seconds.
+	pub ttl: U256,
+}
+
+/// Whisper message filter.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct WhisperFilter {
+	/// Identity the messages are encrypted to.
+	pub to: Option<H512>,
+	/// Only accept messages signed by this identity.
+	pub from: Option<H512>,
+	/// Symmetric key the messages are encrypted with.
+	pub key: Option<H256>,
+	/// Topics that must all be present.
+	pub topics: Option<Vec<Bytes>>,
+}
+
+/// Received whisper message.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct WhisperMessage {
+	/// Envelope hash
+	pub hash: H256,
+	/// Signer
+	pub from: Option<H512>,
+	/// Recipient identity
+	pub to: Option<H512>,
+	/// Expiry time
+	pub expiry: U256,
+	/// Time to live
+	pub ttl: U256,
+	/// Send time
+	pub sent: U256,
+	/// Abridged topics
+	pub topics: Vec<Bytes>,
+	/// Payload
+	pub payload: Bytes,
+	/// Proof of work (leading zero bits)
+	#[serde(rename="workProved")]
+	pub work_proved: U256,
+}
+
+impl From<ReceivedMessage> for WhisperMessage {
+	fn from(m: ReceivedMessage) -> WhisperMessage {
+		WhisperMessage {
+			hash: m.hash,
+			from: m.from,
+			to: m.to,
+			expiry: U256::from(m.expiry),
+			ttl: U256::from(m.ttl),
+			sent: U256::from(m.sent),
+			topics: m.topics.into_iter().map(|t| Bytes::new(t.to_vec())).collect(),
+			payload: Bytes::new(m.payload),
+			work_proved: U256::from(m.work),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::*;
+	use v1::types::Bytes;
+	use super::*;
+
+	#[test]
+	fn post_deserialization() {
+		let s = r#"{"topics":["0x6578616d706c65"],"payload":"0x1234","priority":"0x32","ttl":"0x64"}"#;
+		let post: WhisperPost = serde_json::from_str(s).unwrap();
+		assert_eq!(post, WhisperPost {
+			from: None,
+			to: None,
+			key: None,
+			topics: vec![Bytes::new(b"example".to_vec())],
+			payload: Bytes::new(vec![0x12, 0x34]),
+			priority: U256::from(50),
+			ttl: U256::from(100),
+		});
+	}
+}
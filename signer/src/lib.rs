@@ -50,6 +50,7 @@ extern crate rand;
 extern crate ethcore_util as util;
 extern crate ethcore_rpc as rpc;
 extern crate jsonrpc_core;
+extern crate serde_json;
 extern crate ws;
 extern crate parity_minimal_sysui as sysui;
 
@@ -22,7 +22,8 @@ use std::thread;
 use std::path::PathBuf;
 use std::default::Default;
 use std::ops::Drop;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use util::panics::{PanicHandler, OnPanicListener, MayPanic};
 use jsonrpc_core::{IoHandler, IoDelegate};
@@ -102,7 +103,9 @@ impl Server {
 
 		// Create WebSocket
 		let origin = format!("{}", addr);
-		let ws = try!(ws::Builder::new().with_settings(config).build(session::Factory::new(handler, origin, authcodes_path)));
+		let subscribers: session::Subscribers = Arc::new(Mutex::new(HashMap::new()));
+		let factory = session::Factory::new(handler, origin, authcodes_path, subscribers.clone());
+		let ws = try!(ws::Builder::new().with_settings(config).build(factory));
 
 		let panic_handler = PanicHandler::new_in_arc();
 		let ph = panic_handler.clone();
@@ -120,9 +123,20 @@ impl Server {
 		let q = queue.clone();
 		let broadcaster_handle = thread::spawn(move || {
 			ph.catch_panic(move || {
-				q.start_listening(|_message| {
+				q.start_listening(|message| {
 					// TODO [ToDr] Some better structure here for messages.
 					broadcaster.send("new_message").unwrap();
+
+					// details are sent only to sessions that asked for them, older UIs don't understand them
+					let subscribers = subscribers.lock().unwrap();
+					if subscribers.is_empty() {
+						return;
+					}
+					if let Some(notification) = q.notification(&message) {
+						for out in subscribers.values() {
+							let _ = out.send(notification.clone());
+						}
+					}
 				}).expect("It's the only place we are running start_listening. It shouldn't fail.");
 				broadcaster.shutdown().expect("Broadcaster should close gently.")
 			}).unwrap()
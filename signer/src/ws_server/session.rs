@@ -20,11 +20,35 @@ use ws;
 use sysui;
 use authcode_store::AuthCodes;
 use std::path::{PathBuf, Path};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
 use std::str::FromStr;
 use jsonrpc_core::IoHandler;
+use serde_json::{self, Value};
 use util::H256;
 
+/// Method `SignerUI` calls to receive `personal_signerPending` notifications.
+/// Sessions which don't subscribe only get a bare `new_message` on queue changes.
+pub const SUBSCRIBE_PENDING_METHOD: &'static str = "personal_signerSubscribePending";
+
+/// Sessions subscribed to `personal_signerPending` notifications, by session id.
+pub type Subscribers = Arc<Mutex<HashMap<usize, ws::Sender>>>;
+
+/// Returns serialized id of the request if it's a subscription request.
+fn subscription_id(req: &str) -> Option<String> {
+	let req: Value = match serde_json::from_str(req) {
+		Ok(req) => req,
+		Err(_) => return None,
+	};
+
+	match req.find("method").and_then(Value::as_string) {
+		Some(method) if method == SUBSCRIBE_PENDING_METHOD => {
+			req.find("id").and_then(|id| serde_json::to_string(id).ok())
+		},
+		_ => None,
+	}
+}
+
 fn origin_is_allowed(self_origin: &str, header: Option<&Vec<u8>>) -> bool {
 	match header {
 		None => false,
@@ -63,10 +87,12 @@ fn auth_is_valid(codes: &Path, protocols: ws::Result<Vec<&str>>) -> bool {
 }
 
 pub struct Session {
+	id: usize,
 	out: ws::Sender,
 	self_origin: String,
 	authcodes_path: PathBuf,
 	handler: Arc<IoHandler>,
+	subscribers: Subscribers,
 }
 
 impl ws::Handler for Session {
@@ -122,25 +148,38 @@ impl ws::Handler for Session {
 
 	fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
 		let req = try!(msg.as_text());
+		if let Some(id) = subscription_id(req) {
+			self.subscribers.lock().unwrap().insert(self.id, self.out.clone());
+			return self.out.send(format!(r#"{{"jsonrpc":"2.0","result":true,"id":{}}}"#, id));
+		}
+
 		match self.handler.handle_request(req) {
 			Some(res) => self.out.send(res),
 			None => Ok(()),
 		}
 	}
+
+	fn on_close(&mut self, _code: ws::CloseCode, _reason: &str) {
+		self.subscribers.lock().unwrap().remove(&self.id);
+	}
 }
 
 pub struct Factory {
 	handler: Arc<IoHandler>,
 	self_origin: String,
 	authcodes_path: PathBuf,
+	subscribers: Subscribers,
+	next_id: usize,
 }
 
 impl Factory {
-	pub fn new(handler: Arc<IoHandler>, self_origin: String, authcodes_path: PathBuf) -> Self {
+	pub fn new(handler: Arc<IoHandler>, self_origin: String, authcodes_path: PathBuf, subscribers: Subscribers) -> Self {
 		Factory {
 			handler: handler,
 			self_origin: self_origin,
 			authcodes_path: authcodes_path,
+			subscribers: subscribers,
+			next_id: 0,
 		}
 	}
 }
@@ -149,11 +188,26 @@ impl ws::Factory for Factory {
 	type Handler = Session;
 
 	fn connection_made(&mut self, sender: ws::Sender) -> Self::Handler {
+		self.next_id += 1;
 		Session {
+			id: self.next_id,
 			out: sender,
 			handler: self.handler.clone(),
 			self_origin: self.self_origin.clone(),
 			authcodes_path: self.authcodes_path.clone(),
+			subscribers: self.subscribers.clone(),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::subscription_id;
+
+	#[test]
+	fn should_detect_subscription_request() {
+		assert_eq!(subscription_id(r#"{"jsonrpc":"2.0","method":"personal_signerSubscribePending","params":[],"id":5}"#), Some("5".to_owned()));
+		assert_eq!(subscription_id(r#"{"jsonrpc":"2.0","method":"personal_requestsToConfirm","params":[],"id":5}"#), None);
+		assert_eq!(subscription_id("new_message"), None);
+	}
+}
@@ -19,10 +19,17 @@ use ethcore::header::{ Header as BlockHeader};
 
 known_heap_size!(0, HeaderId, SyncBlock);
 
-/// Block data with optional body.
+/// Block data with optional body and receipts.
 struct SyncBlock {
 	header: Bytes,
 	body: Option<Bytes>,
+	receipts: Option<Bytes>,
+}
+
+impl SyncBlock {
+	fn is_complete(&self, need_receipts: bool) -> bool {
+		self.body.is_some() && (!need_receipts || self.receipts.is_some())
+	}
 }
 
 /// Used to identify header by transactions and uncles hashes
@@ -51,6 +58,12 @@ pub struct BlockCollection {
 	downloading_headers: HashSet<H256>,
 	/// Set of block bodies being downloaded identified by block hash.
 	downloading_bodies: HashSet<H256>,
+	/// Download block receipts along with bodies.
+	need_receipts: bool,
+	/// Used to map receipts to header by receipts root.
+	receipt_ids: HashMap<H256, H256>,
+	/// Set of block receipts being downloaded identified by block hash.
+	downloading_receipts: HashSet<H256>,
 }
 
 impl BlockCollection {
@@ -64,6 +77,9 @@ impl BlockCollection {
 			head: None,
 			downloading_headers: HashSet::new(),
 			downloading_bodies: HashSet::new(),
+			need_receipts: false,
+			receipt_ids: HashMap::new(),
+			downloading_receipts: HashSet::new(),
 		}
 	}
 
@@ -76,6 +92,8 @@ impl BlockCollection {
 		self.head = None;
 		self.downloading_headers.clear();
 		self.downloading_bodies.clear();
+		self.receipt_ids.clear();
+		self.downloading_receipts.clear();
 	}
 
 	/// Reset collection for a new sync round with given subchain block hashes.
@@ -84,6 +102,11 @@ impl BlockCollection {
 		self.heads = hashes;
 	}
 
+	/// Enable or disable receipts download. Takes effect for headers inserted afterwards.
+	pub fn set_download_receipts(&mut self, download: bool) {
+		self.need_receipts = download;
+	}
+
 	/// Insert a set of headers into collection and advance subchain head pointers.
 	pub fn insert_headers(&mut self, headers: Vec<Bytes>) {
 		for h in headers.into_iter() {
@@ -108,6 +131,20 @@ impl BlockCollection {
 		inserted
 	}
 
+	/// Insert a collection of block receipts for previously downloaded headers.
+	pub fn insert_receipts(&mut self, receipts: Vec<Bytes>) -> usize {
+		let mut inserted = 0;
+		for r in receipts.into_iter() {
+			if let Err(e) = self.insert_receipt(r) {
+				trace!(target: "sync", "Ignored invalid receipts: {:?}", e);
+			}
+			else {
+				inserted += 1;
+			}
+		}
+		inserted
+	}
+
 	/// Returns a set of block hashes that require a body download. The returned set is marked as being downloaded.
 	pub fn needed_bodies(&mut self, count: usize, _ignore_downloading: bool) -> Vec<H256> {
 		if self.head.is_none() {
@@ -130,6 +167,28 @@ impl BlockCollection {
 		needed_bodies
 	}
 
+	/// Returns a set of block hashes that require a receipts download. The returned set is marked as being downloaded.
+	pub fn needed_receipts(&mut self, count: usize, _ignore_downloading: bool) -> Vec<H256> {
+		if self.head.is_none() || !self.need_receipts {
+			return Vec::new();
+		}
+		let mut needed_receipts: Vec<H256> = Vec::new();
+		let mut head = self.head;
+		while head.is_some() && needed_receipts.len() < count {
+			head = self.parents.get(&head.unwrap()).cloned();
+			if let Some(head) = head {
+				match self.blocks.get(&head) {
+					Some(block) if block.receipts.is_none() && !self.downloading_receipts.contains(&head) => {
+						needed_receipts.push(head.clone());
+					}
+					_ => (),
+				}
+			}
+		}
+		self.downloading_receipts.extend(needed_receipts.iter());
+		needed_receipts
+	}
+
 	/// Returns a set of block hashes that require a header download. The returned set is marked as being downloaded.
 	pub fn needed_headers(&mut self, count: usize, ignore_downloading: bool) -> Option<(H256, usize)> {
 		// find subchain to download
@@ -156,8 +215,18 @@ impl BlockCollection {
 		self.downloading_bodies.remove(hash);
 	}
 
+	/// Unmark block receipts as being downloaded.
+	pub fn clear_receipt_download(&mut self, hash: &H256) {
+		self.downloading_receipts.remove(hash);
+	}
+
 	/// Get a valid chain of blocks ordered in descending order and ready for importing into blockchain.
 	pub fn drain(&mut self) -> Vec<Bytes> {
+		self.drain_with_receipts().into_iter().map(|(block, _)| block).collect()
+	}
+
+	/// Get a valid chain of blocks along with their receipts, if requested, ordered in descending order and ready for importing into blockchain.
+	pub fn drain_with_receipts(&mut self) -> Vec<(Bytes, Option<Bytes>)> {
 		if self.blocks.is_empty() || self.head.is_none() {
 			return Vec::new();
 		}
@@ -171,7 +240,7 @@ impl BlockCollection {
 				head = self.parents.get(&head.unwrap()).cloned();
 				if let Some(head) = head {
 					match self.blocks.get(&head) {
-						Some(block) if block.body.is_some() => {
+						Some(block) if block.is_complete(self.need_receipts) => {
 							blocks.push(block);
 							hashes.push(head);
 							self.head = Some(head);
@@ -187,7 +256,7 @@ impl BlockCollection {
 				let body = Rlp::new(block.body.as_ref().unwrap()); // incomplete blocks are filtered out in the loop above
 				block_rlp.append_raw(body.at(0).as_raw(), 1);
 				block_rlp.append_raw(body.at(1).as_raw(), 1);
-				drained.push(block_rlp.out());
+				drained.push((block_rlp.out(), block.receipts.clone()));
 			}
 		}
 		for h in hashes {
@@ -216,7 +285,33 @@ impl BlockCollection {
 
 	/// Check if given block hash is marked as being downloaded.
 	pub fn is_downloading(&self, hash: &H256) -> bool {
-		self.downloading_headers.contains(hash) || self.downloading_bodies.contains(hash)
+		self.downloading_headers.contains(hash) || self.downloading_bodies.contains(hash) || self.downloading_receipts.contains(hash)
+	}
+
+	fn insert_receipt(&mut self, r: Bytes) -> Result<(), UtilError> {
+		let receipts = UntrustedRlp::new(&r);
+		let receipts_root = ordered_trie_root(receipts.iter().map(|r| r.as_raw().to_vec()).collect());
+		match self.receipt_ids.get(&receipts_root).cloned() {
+			Some(h) => {
+				self.receipt_ids.remove(&receipts_root);
+				self.downloading_receipts.remove(&h);
+				match self.blocks.get_mut(&h) {
+					Some(ref mut block) => {
+						trace!(target: "sync", "Got receipts {}", h);
+						block.receipts = Some(receipts.as_raw().to_vec());
+						Ok(())
+					},
+					None => {
+						warn!("Got receipts with no header {}", h);
+						Err(UtilError::Network(NetworkError::BadProtocol))
+					}
+				}
+			}
+			None => {
+				trace!(target: "sync", "Ignored unknown/stale block receipts");
+				Err(UtilError::Network(NetworkError::BadProtocol))
+			}
+		}
 	}
 
 	fn insert_body(&mut self, b: Bytes) -> Result<(), UtilError> {
@@ -268,6 +363,7 @@ impl BlockCollection {
 		let mut block = SyncBlock {
 			header: header,
 			body: None,
+			receipts: None,
 		};
 		let header_id = HeaderId {
 			transactions_root: info.transactions_root,
@@ -283,6 +379,13 @@ impl BlockCollection {
 		else {
 			self.header_ids.insert(header_id, hash.clone());
 		}
+		if self.need_receipts {
+			if info.receipts_root == rlp::SHA3_NULL_RLP {
+				block.receipts = Some(rlp::EMPTY_LIST_RLP.to_vec());
+			} else {
+				self.receipt_ids.insert(info.receipts_root.clone(), hash.clone());
+			}
+		}
 
 		self.parents.insert(info.parent_hash.clone(), hash.clone());
 		self.blocks.insert(hash.clone(), block);
@@ -324,7 +427,8 @@ mod test {
 	use super::BlockCollection;
 	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockID, BlockChainClient};
 	use ethcore::views::HeaderView;
-	use ethcore::header::BlockNumber;
+	use ethcore::header::{BlockNumber, Header};
+	use ethcore::receipt::Receipt;
 	use util::*;
 
 	fn is_empty(bc: &BlockCollection) -> bool {
@@ -334,7 +438,9 @@ mod test {
 		bc.header_ids.is_empty() &&
 		bc.head.is_none() &&
 		bc.downloading_headers.is_empty() &&
-		bc.downloading_bodies.is_empty()
+		bc.downloading_bodies.is_empty() &&
+		bc.receipt_ids.is_empty() &&
+		bc.downloading_receipts.is_empty()
 	}
 
 	#[test]
@@ -443,5 +549,43 @@ mod test {
 		bc.insert_headers(headers[0..1].to_vec());
 		assert_eq!(bc.drain().len(), 2);
 	}
-}
 
+	#[test]
+	fn insert_receipts() {
+		let mut bc = BlockCollection::new();
+		bc.set_download_receipts(true);
+		let client = TestBlockChainClient::new();
+		let nblocks = 4;
+		client.add_blocks(nblocks, EachBlockWith::Nothing);
+		let receipts: Vec<_> = (0 .. nblocks).map(|i| {
+			let mut stream = RlpStream::new_list(1);
+			stream.append(&Receipt::new(H256::from(i as u64 + 1), U256::from(21000), vec![]));
+			stream.out()
+		}).collect();
+		let mut headers = Vec::new();
+		let mut parent_hash = H256::new();
+		for i in 0 .. nblocks {
+			let block = (&client as &BlockChainClient).block(BlockID::Number(i as BlockNumber)).unwrap();
+			let mut header: Header = Rlp::new(&block).val_at(0);
+			header.parent_hash = parent_hash;
+			header.receipts_root = ordered_trie_root(Rlp::new(&receipts[i]).iter().map(|r| r.as_raw().to_vec()).collect());
+			let header = encode(&header).to_vec();
+			parent_hash = HeaderView::new(&header).sha3();
+			headers.push(header);
+		}
+		let hashes: Vec<_> = headers.iter().map(|h| HeaderView::new(h).sha3()).collect();
+		bc.reset_to(vec![hashes[0].clone()]);
+
+		bc.insert_headers(headers[0..2].to_vec());
+		assert!(bc.drain_with_receipts().is_empty());
+		assert_eq!(bc.needed_receipts(4, false), vec![hashes[0].clone(), hashes[1].clone()]);
+		assert!(bc.is_downloading(&hashes[1]));
+		assert_eq!(bc.insert_receipts(vec![receipts[0].clone(), receipts[2].clone()]), 1);
+
+		let drained = bc.drain_with_receipts();
+		assert_eq!(drained.len(), 1);
+		assert_eq!(HeaderView::new(Rlp::new(&drained[0].0).at(0).as_raw()).sha3(), hashes[0]);
+		assert_eq!(drained[0].1, Some(receipts[0].clone()));
+		assert!(bc.needed_receipts(4, false).is_empty());
+	}
+}
@@ -23,6 +23,8 @@
 ///
 /// Syncing strategy summary.
 /// Split the chain into ranges of N blocks each. Download ranges sequentially. Split each range into subchains of M blocks. Download subchains in parallel.
+/// Subchain heads (the skeleton) are downloaded from the peer with the highest total difficulty. Subchain headers and block bodies are then
+/// downloaded from all peers. The number of headers and bodies requested from a peer adapts to the peer response time.
 /// State.
 /// Sync state consists of the following data:
 /// - s: State enum which can be one of the following values: `ChainHead`, `Blocks`, `Idle`
@@ -39,7 +41,7 @@
 /// Workflow for `ChainHead` state.
 /// In this state we try to get subchain headers with a single `GetBlockHeaders` request.
 /// On `NewPeer` / On `Restart`:
-/// 	If peer's total difficulty is higher, request N/M headers with interval M+1 starting from l
+/// 	If peer's total difficulty is higher and no other active peer has a higher total difficulty, request N/M headers with interval M+1 starting from l
 /// On `BlockHeaders(R)`:
 /// 	If R is empty:
 /// If l is equal to genesis block hash or l is more than 1000 blocks behind our best hash:
@@ -64,6 +66,7 @@
 ///
 /// On `BlockHeaders(R)`:
 /// If R is empty remove current peer from P and restart.
+/// If R is not a continuous chain starting from the requested header remove current peer from P and restart.
 /// 	Validate received headers. For each header find a parent in H or R or the blockchain. Restart if there is a block with unknown parent.
 /// Go to `CollectBlocks`.
 ///
@@ -80,6 +83,28 @@
 /// Restart.
 ///
 /// All other messages are ignored.
+/// Workflow for fast sync.
+/// Blocks are downloaded in `ChainHead` and `Blocks` states as above along with their receipts and imported without execution.
+/// When the subchain heads response is shorter than N/M headers the best peer head is less than M blocks away. Select pivot block
+/// 64 blocks behind the last subchain head. Once the pivot block is imported set s to `StateDownload`.
+/// Workflow for `StateDownload` state.
+/// In this state we download the state trie of the pivot block from multiple peers.
+/// On `NewPeer` / On `Restart`:
+/// 	For all idle peers request up to 384 trie nodes that are not in the state database and not being downloaded by other peers.
+/// On `NodeData(R)`:
+/// If R is empty remove current peer from P and restart.
+/// 	Verify each node against the requested hashes and write it into the state database. Add referenced nodes to the download set.
+/// 	If there are no nodes left to download, disable fast sync and restart.
+/// Nodes that are already in the state database are not downloaded again so the download resumes after restart.
+///
+/// All other messages are ignored.
+/// Workflow for fork checkpoint.
+/// If the chain spec lists fork checkpoints, the header of the latest checkpoint block is requested from every new peer before it is added to P.
+/// On `BlockHeaders(R)`:
+/// If R is empty the peer has not reached the fork yet. It stays connected but is not used for sync or propagation.
+/// If R does not match the checkpoint the peer is on the other side of the chain split and is disconnected.
+/// 	Else add the peer to P and continue sync.
+///
 /// Workflow for Idle state.
 /// On `NewBlock`:
 /// 	Import the block. If the block is unknown set s to `ChainHead` and restart.
@@ -96,14 +121,15 @@ use ethcore::header::{BlockNumber, Header as BlockHeader};
 use ethcore::client::{BlockChainClient, BlockStatus, BlockID, BlockChainInfo};
 use ethcore::error::*;
 use ethcore::block::Block;
+use ethcore::spec::ForkCheckpoint;
 use io::SyncIo;
 use time;
 use super::SyncConfig;
 use blocks::BlockCollection;
+use state::StateDownload;
+use lru_set::LruSet;
 use rand::{thread_rng, Rng};
 
-known_heap_size!(0, PeerInfo);
-
 type PacketDecodeError = DecoderError;
 
 const PROTOCOL_VERSION: u8 = 63u8;
@@ -113,7 +139,10 @@ const MAX_NODE_DATA_TO_SEND: usize = 1024;
 const MAX_RECEIPTS_TO_SEND: usize = 1024;
 const MAX_RECEIPTS_HEADERS_TO_SEND: usize = 256;
 const MAX_HEADERS_TO_REQUEST: usize = 128;
-const MAX_BODIES_TO_REQUEST: usize = 64;
+const MIN_HEADERS_TO_REQUEST: usize = 16;
+const MAX_BODIES_TO_REQUEST: usize = 128;
+const MIN_BODIES_TO_REQUEST: usize = 8;
+const INITIAL_BODIES_TO_REQUEST: usize = 64;
 const MIN_PEERS_PROPAGATION: usize = 4;
 const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
@@ -121,6 +150,10 @@ const SUBCHAIN_SIZE: usize = 64;
 const MAX_ROUND_PARENTS: usize = 32;
 const MAX_NEW_HASHES: usize = 64;
 const MAX_TX_TO_IMPORT: usize = 512;
+const MAX_KNOWN_TRANSACTIONS: usize = 4096;
+const MAX_NODES_TO_REQUEST: usize = 384;
+/// Fast sync pivot block distance from the best peer head.
+const FAST_SYNC_PIVOT_DISTANCE: BlockNumber = 64;
 
 const STATUS_PACKET: u8 = 0x00;
 const NEW_BLOCK_HASHES_PACKET: u8 = 0x01;
@@ -136,7 +169,18 @@ const NODE_DATA_PACKET: u8 = 0x0e;
 const GET_RECEIPTS_PACKET: u8 = 0x0f;
 const RECEIPTS_PACKET: u8 = 0x10;
 
+/// Number of packet IDs used by eth/62.
+pub const ETH62_PACKET_COUNT: u8 = 0x08;
+/// Number of packet IDs used by eth/63.
+pub const ETH63_PACKET_COUNT: u8 = 0x11;
+
 const CONNECTION_TIMEOUT_SEC: f64 = 15f64;
+/// Longest time to wait for a response from a peer throttled by our bandwidth limit.
+const THROTTLED_TIMEOUT_SEC: f64 = 60f64;
+/// Peers responding faster than this get larger requests.
+const FAST_RESPONSE_SEC: f64 = 1f64;
+/// Peers responding slower than this get smaller requests and lose reputation.
+const SLOW_RESPONSE_SEC: f64 = 5f64;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -151,6 +195,8 @@ pub enum SyncState {
 	Blocks,
 	/// Downloading blocks learned from `NewHashes` packet
 	NewBlocks,
+	/// Downloading state of the fast sync pivot block
+	StateDownload,
 }
 
 /// Syncing status and statistics
@@ -182,11 +228,49 @@ pub struct SyncStatus {
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 /// Peer data type requested
-enum PeerAsking {
+pub enum PeerAsking {
+	/// Peer is idle
 	Nothing,
+	/// Block headers are requested
 	BlockHeaders,
+	/// Block bodies are requested
 	BlockBodies,
+	/// Chain head is requested
 	Heads,
+	/// Block receipts are requested
+	BlockReceipts,
+	/// State trie nodes are requested
+	NodeData,
+	/// Fork checkpoint header is requested
+	ForkHeader,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+/// Peer agreement with the fork checkpoint
+enum ForkConfirmation {
+	/// Fork checkpoint header has not been received yet
+	Unconfirmed,
+	/// Peer chain does not reach the fork checkpoint
+	TooShort,
+	/// Peer is on our side of the fork
+	Confirmed,
+}
+
+/// Eth protocol details of a connected peer.
+#[derive(Debug, Clone)]
+pub struct EthPeerInfo {
+	/// Network session id
+	pub id: PeerId,
+	/// eth protocol version
+	pub protocol_version: u32,
+	/// Peer best block hash
+	pub head: H256,
+	/// Peer best block number if known
+	pub best_number: Option<BlockNumber>,
+	/// Peer total difficulty if known
+	pub difficulty: Option<U256>,
+	/// Type of data currently being requested from peer
+	pub asking: PeerAsking,
 }
 
 #[derive(Clone)]
@@ -212,6 +296,27 @@ struct PeerInfo {
 	asking_hash: Option<H256>,
 	/// Request timestamp
 	ask_time: f64,
+	/// Hashes of transactions known to the peer (sent by us or received from it)
+	known_transactions: LruSet<H256>,
+	/// Number of headers to request, adapted to the peer response time
+	headers_limit: usize,
+	/// Number of block bodies to request, adapted to the peer response time
+	bodies_limit: usize,
+	/// Fork checkpoint status
+	confirmation: ForkConfirmation,
+}
+
+impl HeapSizeOf for PeerInfo {
+	fn heap_size_of_children(&self) -> usize {
+		self.asking_blocks.heap_size_of_children() + self.known_transactions.heap_size_of_children()
+	}
+}
+
+impl PeerInfo {
+	/// Peer may be used for sync and block propagation.
+	fn is_confirmed(&self) -> bool {
+		self.confirmation == ForkConfirmation::Confirmed
+	}
 }
 
 /// Blockchain sync handler.
@@ -245,6 +350,14 @@ pub struct ChainSync {
 	round_parents: VecDeque<(H256, H256)>,
 	/// Network ID
 	network_id: U256,
+	/// Import blocks without execution until the pivot block state is downloaded
+	fast_sync: bool,
+	/// Fast sync pivot block number, once known
+	fast_sync_pivot: Option<BlockNumber>,
+	/// Pivot block state being downloaded
+	state_download: StateDownload,
+	/// Latest fork checkpoint peers have to agree with
+	fork_checkpoint: Option<ForkCheckpoint>,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
@@ -252,22 +365,30 @@ type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError
 impl ChainSync {
 	/// Create a new instance of syncing strategy.
 	pub fn new(config: SyncConfig, chain: &BlockChainClient) -> ChainSync {
-		let chain = chain.chain_info();
+		let chain_info = chain.chain_info();
+		// Resume fast sync if the best block state has not been downloaded completely
+		let fast_sync = (config.fast_sync && chain_info.best_block_number == 0) || chain.is_state_pending();
+		let mut blocks = BlockCollection::new();
+		blocks.set_download_receipts(fast_sync);
 		let mut sync = ChainSync {
 			state: SyncState::ChainHead,
-			starting_block: chain.best_block_number,
+			starting_block: chain_info.best_block_number,
 			highest_block: None,
-			last_imported_block: chain.best_block_number,
-			last_imported_hash: chain.best_block_hash,
+			last_imported_block: chain_info.best_block_number,
+			last_imported_hash: chain_info.best_block_hash,
 			peers: HashMap::new(),
 			active_peers: HashSet::new(),
-			blocks: BlockCollection::new(),
+			blocks: blocks,
 			syncing_difficulty: U256::from(0u64),
 			last_sent_block_number: 0,
 			imported_this_round: None,
 			round_parents: VecDeque::new(),
 			_max_download_ahead_blocks: max(MAX_HEADERS_TO_REQUEST, config.max_download_ahead_blocks),
 			network_id: config.network_id,
+			fast_sync: fast_sync,
+			fast_sync_pivot: None,
+			state_download: StateDownload::new(),
+			fork_checkpoint: config.fork_checkpoints.into_iter().max_by_key(|c| c.number),
 		};
 		sync.reset();
 		sync
@@ -293,6 +414,18 @@ impl ChainSync {
 		}
 	}
 
+	/// Returns eth protocol details of all connected peers.
+	pub fn peers(&self) -> Vec<EthPeerInfo> {
+		self.peers.iter().map(|(id, peer)| EthPeerInfo {
+			id: *id,
+			protocol_version: peer.protocol_version,
+			head: peer.latest_hash,
+			best_number: peer.latest_number,
+			difficulty: peer.difficulty,
+			asking: peer.asking.clone(),
+		}).collect()
+	}
+
 	/// Abort all sync activity
 	pub fn abort(&mut self, io: &mut SyncIo) {
 		self.restart(io);
@@ -309,7 +442,7 @@ impl ChainSync {
 		}
 		self.syncing_difficulty = From::from(0u64);
 		self.state = SyncState::Idle;
-		self.active_peers = self.peers.keys().cloned().collect();
+		self.active_peers = self.peers.iter().filter(|&(_, p)| p.is_confirmed()).map(|(id, _)| *id).collect();
 	}
 
 	/// Restart sync
@@ -356,6 +489,10 @@ impl ChainSync {
 			asking_blocks: Vec::new(),
 			asking_hash: None,
 			ask_time: 0f64,
+			known_transactions: LruSet::new(MAX_KNOWN_TRANSACTIONS),
+			headers_limit: MAX_HEADERS_TO_REQUEST,
+			bodies_limit: INITIAL_BODIES_TO_REQUEST,
+			confirmation: if self.fork_checkpoint.is_some() { ForkConfirmation::Unconfirmed } else { ForkConfirmation::Confirmed },
 		};
 
 		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{})", peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis);
@@ -381,15 +518,72 @@ impl ChainSync {
 		}
 
 		self.peers.insert(peer_id.clone(), peer);
-		self.active_peers.insert(peer_id.clone());
 		debug!(target: "sync", "Connected {}:{}", peer_id, io.peer_info(peer_id));
+		if let Some(number) = self.fork_checkpoint.as_ref().map(|c| c.number) {
+			// Peer is used for sync once it agrees with the fork checkpoint
+			self.request_fork_header(io, peer_id, number);
+			return Ok(());
+		}
+		self.active_peers.insert(peer_id.clone());
 		self.sync_peer(io, peer_id, false);
 		Ok(())
 	}
 
+	/// Called by peer with the fork checkpoint block header
+	fn on_peer_fork_header(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.reset_peer_asking(peer_id, PeerAsking::ForkHeader);
+		let checkpoint = self.fork_checkpoint.clone().expect("fork header is only requested when a fork checkpoint is configured. qed");
+		let confirmation = if r.item_count() == 0 {
+			trace!(target: "sync", "{} -> Fork checkpoint block #{} is not known to the peer", peer_id, checkpoint.number);
+			ForkConfirmation::TooShort
+		} else {
+			let header: BlockHeader = try!(r.val_at(0));
+			if !checkpoint.matches(&header) {
+				debug!(target: "sync", "{} -> Fork checkpoint mismatch at #{} ({}): {}", peer_id, header.number(), header.hash(), io.peer_info(peer_id));
+				io.disable_peer(peer_id);
+				return Ok(());
+			}
+			trace!(target: "sync", "{} -> Fork checkpoint confirmed", peer_id);
+			ForkConfirmation::Confirmed
+		};
+		let confirmed = confirmation == ForkConfirmation::Confirmed;
+		self.peers.get_mut(&peer_id).expect("peer_id is validated in on_packet. qed").confirmation = confirmation;
+		if confirmed {
+			self.active_peers.insert(peer_id);
+			self.sync_peer(io, peer_id, false);
+		}
+		Ok(())
+	}
+
+	/// Request the fork checkpoint header again from a peer that did not have it, once the peer announces
+	/// a block number or total difficulty past the checkpoint.
+	fn recheck_fork_checkpoint(&mut self, io: &mut SyncIo, peer_id: PeerId, number: BlockNumber, difficulty: Option<U256>) {
+		let checkpoint = match self.fork_checkpoint {
+			Some(ref checkpoint) => checkpoint.number,
+			None => return,
+		};
+		if !self.peers.get(&peer_id).map_or(false, |p| p.confirmation == ForkConfirmation::TooShort) {
+			return;
+		}
+		let checkpoint_difficulty = io.chain().block_total_difficulty(BlockID::Number(checkpoint));
+		let passed = number >= checkpoint || match (difficulty, checkpoint_difficulty) {
+			(Some(difficulty), Some(checkpoint_difficulty)) => difficulty >= checkpoint_difficulty,
+			_ => false,
+		};
+		if passed {
+			trace!(target: "sync", "{} passed fork checkpoint #{}, asking again", peer_id, checkpoint);
+			self.peers.get_mut(&peer_id).expect("peer_id is validated above. qed").confirmation = ForkConfirmation::Unconfirmed;
+			self.request_fork_header(io, peer_id, checkpoint);
+		}
+	}
+
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
 	/// Called by peer once it has new block headers during sync
 	fn on_peer_block_headers(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		if self.peers.get(&peer_id).map_or(false, |p| p.asking == PeerAsking::ForkHeader) {
+			return self.on_peer_fork_header(io, peer_id, r);
+		}
+		self.on_peer_response(io, peer_id, PeerAsking::BlockHeaders, r.item_count());
 		self.clear_peer_download(peer_id);
 		let expected_hash = self.peers.get(&peer_id).and_then(|p| p.asking_hash);
 		let expected_asking = if self.state == SyncState::ChainHead { PeerAsking::Heads } else { PeerAsking::BlockHeaders };
@@ -419,9 +613,23 @@ impl ChainSync {
 		let mut headers = Vec::new();
 		let mut hashes = Vec::new();
 		let mut valid_response = item_count == 0; //empty response is valid
+		let mut parent: Option<(H256, BlockNumber)> = None;
 		for i in 0..item_count {
 			let info: BlockHeader = try!(r.val_at(i));
 			let number = BlockNumber::from(info.number);
+			// Subchain headers must form a continuous chain. Subchain heads are requested with gaps.
+			if self.state != SyncState::ChainHead {
+				if let Some((parent_hash, parent_number)) = parent {
+					if info.parent_hash != parent_hash || number != parent_number + 1 {
+						trace!(target: "sync", "{} Deactivated for broken headers chain at {}", peer_id, number);
+						io.report_peer(peer_id, ReputationEvent::InvalidPacket);
+						self.deactivate_peer(io, peer_id);
+						self.continue_sync(io);
+						return Ok(());
+					}
+				}
+				parent = Some((info.hash(), number));
+			}
 			// Check if any of the headers matches the hash we requested
 			if !valid_response {
 				if let Some(expected) = expected_hash {
@@ -448,6 +656,7 @@ impl ChainSync {
 				},
 				BlockStatus::Bad => {
 					warn!(target: "sync", "Bad header {} ({}) from {}: {}, state = {:?}", number, hash, peer_id, io.peer_info(peer_id), self.state);
+					io.report_peer(peer_id, ReputationEvent::BadBlock);
 					io.disable_peer(peer_id);
 					return Ok(());
 				},
@@ -461,6 +670,7 @@ impl ChainSync {
 		// Disable the peer for this syncing round if it gives invalid chain
 		if !valid_response {
 			trace!(target: "sync", "{} Deactivated for invalid headers response", peer_id);
+			io.report_peer(peer_id, ReputationEvent::InvalidPacket);
 			self.deactivate_peer(io, peer_id);
 		}
 
@@ -468,6 +678,8 @@ impl ChainSync {
 			// Peer does not have any new subchain heads, deactivate it nd try with another
 			trace!(target: "sync", "{} Deactivated for no data", peer_id);
 			self.deactivate_peer(io, peer_id);
+		} else if valid_response {
+			io.report_peer(peer_id, ReputationEvent::UsefulData);
 		}
 		match self.state {
 			SyncState::ChainHead => {
@@ -478,9 +690,19 @@ impl ChainSync {
 					self.start_sync_round(io);
 				} else {
 					// TODO: validate heads better. E.g. check that there is enough distance between blocks.
-					trace!(target: "sync", "Received {} subchain heads, proceeding to download", headers.len());
-					self.blocks.reset_to(hashes);
-					self.state = SyncState::Blocks;
+					if self.fast_sync && self.fast_sync_pivot.is_none() && item_count < SUBCHAIN_SIZE {
+						// The best peer head is within the last subchain
+						let pivot = max(self.highest_block.unwrap_or(0).saturating_sub(FAST_SYNC_PIVOT_DISTANCE), self.last_imported_block);
+						debug!(target: "sync", "Fast sync pivot block #{}", pivot);
+						self.fast_sync_pivot = Some(pivot);
+					}
+					if self.fast_sync_pivot_reached() {
+						self.start_state_download(io);
+					} else {
+						trace!(target: "sync", "Received {} subchain heads, proceeding to download", headers.len());
+						self.blocks.reset_to(hashes);
+						self.state = SyncState::Blocks;
+					}
 				}
 			},
 			SyncState::Blocks | SyncState::NewBlocks | SyncState::Waiting => {
@@ -497,6 +719,7 @@ impl ChainSync {
 
 	/// Called by peer once it has new block bodies
 	fn on_peer_block_bodies(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.on_peer_response(io, peer_id, PeerAsking::BlockBodies, r.item_count());
 		self.clear_peer_download(peer_id);
 		self.reset_peer_asking(peer_id, PeerAsking::BlockBodies);
 		let item_count = r.item_count();
@@ -518,7 +741,10 @@ impl ChainSync {
 			}
 			if self.blocks.insert_bodies(bodies) != item_count {
 				trace!(target: "sync", "Deactivating peer for giving invalid block bodies");
+				io.report_peer(peer_id, ReputationEvent::InvalidPacket);
 				self.deactivate_peer(io, peer_id);
+			} else {
+				io.report_peer(peer_id, ReputationEvent::UsefulData);
 			}
 			self.collect_blocks(io);
 		}
@@ -526,6 +752,73 @@ impl ChainSync {
 		Ok(())
 	}
 
+	/// Called by peer once it has new block receipts
+	fn on_peer_block_receipts(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.on_peer_response(io, peer_id, PeerAsking::BlockReceipts, r.item_count());
+		self.clear_peer_download(peer_id);
+		self.reset_peer_asking(peer_id, PeerAsking::BlockReceipts);
+		let item_count = r.item_count();
+		trace!(target: "sync", "{} -> Receipts ({} entries)", peer_id, item_count);
+		if item_count == 0 {
+			self.deactivate_peer(io, peer_id);
+		}
+		else if self.state != SyncState::Blocks && self.state != SyncState::Waiting {
+			trace!(target: "sync", "Ignored unexpected block receipts");
+		}
+		else if self.state == SyncState::Waiting {
+			trace!(target: "sync", "Ignored block receipts while waiting");
+		}
+		else
+		{
+			let mut receipts = Vec::with_capacity(item_count);
+			for i in 0..item_count {
+				receipts.push(try!(r.at(i)).as_raw().to_vec());
+			}
+			if self.blocks.insert_receipts(receipts) != item_count {
+				trace!(target: "sync", "Deactivating peer for giving invalid block receipts");
+				io.report_peer(peer_id, ReputationEvent::InvalidPacket);
+				self.deactivate_peer(io, peer_id);
+			} else {
+				io.report_peer(peer_id, ReputationEvent::UsefulData);
+			}
+			self.collect_blocks(io);
+		}
+		self.continue_sync(io);
+		Ok(())
+	}
+
+	/// Called by peer once it has new state trie nodes
+	fn on_peer_node_data(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.clear_peer_download(peer_id);
+		self.reset_peer_asking(peer_id, PeerAsking::NodeData);
+		let item_count = r.item_count();
+		trace!(target: "sync", "{} -> NodeData ({} entries)", peer_id, item_count);
+		if item_count == 0 {
+			self.deactivate_peer(io, peer_id);
+		}
+		else if self.state != SyncState::StateDownload {
+			trace!(target: "sync", "Ignored unexpected node data");
+		}
+		else
+		{
+			let mut nodes = Vec::with_capacity(item_count);
+			for i in 0..item_count {
+				nodes.push(try!(try!(r.at(i)).data()).to_vec());
+			}
+			let nodes = self.state_download.insert_nodes(nodes);
+			if nodes.len() != item_count {
+				trace!(target: "sync", "Deactivating peer for giving invalid node data");
+				io.report_peer(peer_id, ReputationEvent::InvalidPacket);
+				self.deactivate_peer(io, peer_id);
+			} else {
+				io.report_peer(peer_id, ReputationEvent::UsefulData);
+			}
+			io.chain().import_state_data(nodes);
+		}
+		self.continue_sync(io);
+		Ok(())
+	}
+
 	/// Called by peer once it has new block bodies
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
 	fn on_peer_new_block(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
@@ -534,12 +827,22 @@ impl ChainSync {
 		let h = header_rlp.as_raw().sha3();
 		trace!(target: "sync", "{} -> NewBlock ({})", peer_id, h);
 		let header: BlockHeader = try!(header_rlp.as_val());
+		if !self.peers.get(&peer_id).map_or(false, |p| p.is_confirmed()) {
+			trace!(target: "sync", "NewBlock ignored from unconfirmed peer {}", peer_id);
+			let difficulty: U256 = try!(r.val_at(1));
+			self.recheck_fork_checkpoint(io, peer_id, header.number(), Some(difficulty));
+			return Ok(());
+		}
 		let mut unknown = false;
 		{
 			let peer = self.peers.get_mut(&peer_id).unwrap();
 			peer.latest_hash = header.hash();
 			peer.latest_number = Some(header.number());
 		}
+		if self.fast_sync {
+			trace!(target: "sync", "NewBlock ignored during fast sync");
+			return Ok(());
+		}
 		match io.chain().import_block(block_rlp.as_raw().to_vec()) {
 			Err(Error::Import(ImportError::AlreadyInChain)) => {
 				trace!(target: "sync", "New block already in chain {:?}", h);
@@ -560,6 +863,7 @@ impl ChainSync {
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
+				io.report_peer(peer_id, ReputationEvent::BadBlock);
 				io.disable_peer(peer_id);
 			}
 		};
@@ -570,7 +874,7 @@ impl ChainSync {
 				trace!(target: "sync", "New unknown block {:?}", h);
 				//TODO: handle too many unknown blocks
 				let difficulty: U256 = try!(r.val_at(1));
-				if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
+				if let Some(peer) = self.peers.get_mut(&peer_id) {
 					if peer.difficulty.map_or(true, |pd| difficulty > pd) {
 						//self.state = SyncState::ChainHead;
 						peer.difficulty = Some(difficulty);
@@ -585,10 +889,22 @@ impl ChainSync {
 
 	/// Handles `NewHashes` packet. Initiates headers download for any unknown hashes.
 	fn on_peer_new_hashes(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
-		if self.state != SyncState::Idle {
+		if self.peers.get(&peer_id).map_or(false, |p| p.confirmation == ForkConfirmation::TooShort) {
+			let mut max_height: BlockNumber = 0;
+			for item in r.iter().take(MAX_NEW_HASHES) {
+				max_height = max(max_height, try!(item.val_at::<BlockNumber>(1)));
+			}
+			self.recheck_fork_checkpoint(io, peer_id, max_height, None);
+			return Ok(());
+		}
+		if self.state != SyncState::Idle || self.fast_sync {
 			trace!(target: "sync", "Ignoring new hashes since we're already downloading.");
 			return Ok(());
 		}
+		if !self.peers.get(&peer_id).map_or(false, |p| p.is_confirmed()) {
+			trace!(target: "sync", "Ignoring new hashes from unconfirmed peer {}", peer_id);
+			return Ok(());
+		}
 		trace!(target: "sync", "{} -> NewHashes ({} entries)", peer_id, r.item_count());
 		let hashes = r.iter().take(MAX_NEW_HASHES).map(|item| (item.val_at::<H256>(0), item.val_at::<BlockNumber>(1)));
 		let mut max_height: BlockNumber = 0;
@@ -618,6 +934,7 @@ impl ChainSync {
 				},
 				BlockStatus::Bad => {
 					debug!(target: "sync", "Bad new block hash {:?}", h);
+					io.report_peer(peer_id, ReputationEvent::BadBlock);
 					io.disable_peer(peer_id);
 					return Ok(());
 				}
@@ -663,7 +980,7 @@ impl ChainSync {
 				self.sync_peer(io, p, false);
 			}
 		}
-		if self.state != SyncState::Waiting && !self.peers.values().any(|p| p.asking != PeerAsking::Nothing) {
+		if self.state != SyncState::Waiting && self.state != SyncState::StateDownload && !self.peers.values().any(|p| p.asking != PeerAsking::Nothing) {
 			self.complete_sync();
 		}
 	}
@@ -714,6 +1031,18 @@ impl ChainSync {
 					self.sync_peer(io, peer_id, force);
 				},
 				SyncState::ChainHead => {
+					if self.peers.values().any(|p| p.asking == PeerAsking::Heads) {
+						trace!(target: "sync", "Subchain heads are already being downloaded");
+						return;
+					}
+					// Subchain heads are requested from the idle peer with the best chain
+					match self.best_idle_peer(io, td) {
+						Some(best) if best != peer_id => {
+							self.sync_peer(io, best, force);
+							return;
+						},
+						_ => (),
+					}
 					// Request subchain headers
 					trace!(target: "sync", "Starting sync with better chain");
 					let last = self.last_imported_hash.clone();
@@ -724,12 +1053,17 @@ impl ChainSync {
 						self.request_blocks(io, peer_id, false);
 					}
 				}
+				SyncState::StateDownload => self.request_state(io, peer_id),
 				SyncState::Waiting => ()
 			}
 		}
 	}
 
 	fn start_sync_round(&mut self, io: &mut SyncIo) {
+		if self.fast_sync_pivot_reached() {
+			self.start_state_download(io);
+			return;
+		}
 		self.state = SyncState::ChainHead;
 		trace!(target: "sync", "Starting round (last imported count = {:?}, block = {:?}", self.imported_this_round, self.last_imported_block);
 		// Check if need to retract to find the common block. The problem is that the peers still return headers by hash even
@@ -760,6 +1094,88 @@ impl ChainSync {
 		self.imported_this_round = None;
 	}
 
+	/// Active peer that is not busy or throttled with the highest total difficulty above `difficulty`.
+	fn best_idle_peer(&self, io: &SyncIo, difficulty: U256) -> Option<PeerId> {
+		self.active_peers.iter()
+			.filter(|&&id| !io.is_throttled(id))
+			.filter_map(|id| self.peers.get(id).and_then(|p| match p.difficulty {
+				Some(d) if p.asking == PeerAsking::Nothing && d > difficulty => Some((*id, d)),
+				_ => None,
+			}))
+			.max_by_key(|&(_, difficulty)| difficulty)
+			.map(|(id, _)| id)
+	}
+
+	/// Adapt peer request size to the time it took to respond with `item_count` items. Slow peers lose reputation.
+	fn on_peer_response(&mut self, io: &mut SyncIo, peer_id: PeerId, asking: PeerAsking, item_count: usize) {
+		let elapsed = {
+			let peer = match self.peers.get_mut(&peer_id) {
+				Some(peer) if peer.asking == asking => peer,
+				_ => return,
+			};
+			let elapsed = time::precise_time_s() - peer.ask_time;
+			match asking {
+				PeerAsking::BlockHeaders => {
+					let full = item_count >= peer.headers_limit;
+					peer.headers_limit = adapt_request_size(peer.headers_limit, MIN_HEADERS_TO_REQUEST, MAX_HEADERS_TO_REQUEST, elapsed, full);
+				},
+				PeerAsking::BlockBodies | PeerAsking::BlockReceipts => {
+					let full = item_count >= peer.bodies_limit;
+					peer.bodies_limit = adapt_request_size(peer.bodies_limit, MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST, elapsed, full);
+				},
+				_ => (),
+			}
+			elapsed
+		};
+		if elapsed > SLOW_RESPONSE_SEC && !io.is_throttled(peer_id) {
+			trace!(target: "sync", "Slow response from {}: {:.1}s", peer_id, elapsed);
+			io.report_peer(peer_id, ReputationEvent::Timeout);
+		}
+	}
+
+	/// Check if fast sync has imported all blocks up to the pivot block.
+	fn fast_sync_pivot_reached(&self) -> bool {
+		self.fast_sync && self.fast_sync_pivot.map_or(false, |pivot| self.last_imported_block >= pivot)
+	}
+
+	/// Stop downloading blocks and download the state of the best block. Keeps the download progress if it is already in progress.
+	fn start_state_download(&mut self, io: &mut SyncIo) {
+		let chain_info = io.chain().chain_info();
+		let header = io.chain().block_header(BlockID::Hash(chain_info.best_block_hash.clone())).expect("Best block header is always in the chain; qed");
+		let root = HeaderView::new(&header).state_root();
+		if self.state_download.root() != Some(root) {
+			info!(target: "sync", "Downloading state of block #{} ({})", chain_info.best_block_number, chain_info.best_block_hash);
+			self.state_download.reset_to(root);
+		}
+		self.blocks.clear();
+		self.last_imported_block = chain_info.best_block_number;
+		self.last_imported_hash = chain_info.best_block_hash;
+		self.state = SyncState::StateDownload;
+	}
+
+	/// Called once all state trie nodes are downloaded. Switches to full sync.
+	fn complete_state_download(&mut self, io: &mut SyncIo) {
+		info!(target: "sync", "State download complete, {} nodes downloaded", self.state_download.downloaded());
+		io.chain().complete_state_import();
+		self.state_download.clear();
+		self.fast_sync = false;
+		self.fast_sync_pivot = None;
+		self.blocks.set_download_receipts(false);
+		self.restart(io);
+	}
+
+	/// Find some state trie nodes to download for a peer.
+	fn request_state(&mut self, io: &mut SyncIo, peer_id: PeerId) {
+		self.clear_peer_download(peer_id);
+		let needed_nodes = self.state_download.needed_nodes(io.chain(), MAX_NODES_TO_REQUEST);
+		if !needed_nodes.is_empty() {
+			replace(&mut self.peers.get_mut(&peer_id).unwrap().asking_blocks, needed_nodes.clone());
+			self.request_node_data(io, peer_id, needed_nodes);
+		} else if self.state_download.is_complete() {
+			self.complete_state_download(io);
+		}
+	}
+
 	/// Find some headers or blocks to download for a peer.
 	fn request_blocks(&mut self, io: &mut SyncIo, peer_id: PeerId, ignore_others: bool) {
 		self.clear_peer_download(peer_id);
@@ -768,16 +1184,29 @@ impl ChainSync {
 			return;
 		}
 
+		let (headers_limit, bodies_limit) = {
+			let peer = self.peers.get(&peer_id).expect("peer_id may originate either from on_packet, where it is already validated or from enumerating self.peers. qed");
+			(peer.headers_limit, peer.bodies_limit)
+		};
+
 		// check to see if we need to download any block bodies first
-		let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, ignore_others);
+		let needed_bodies = self.blocks.needed_bodies(bodies_limit, ignore_others);
 		if !needed_bodies.is_empty() {
 			replace(&mut self.peers.get_mut(&peer_id).unwrap().asking_blocks, needed_bodies.clone());
 			self.request_bodies(io, peer_id, needed_bodies);
 			return;
 		}
 
+		// receipts are only downloaded by fast sync
+		let needed_receipts = self.blocks.needed_receipts(bodies_limit, ignore_others);
+		if !needed_receipts.is_empty() {
+			replace(&mut self.peers.get_mut(&peer_id).unwrap().asking_blocks, needed_receipts.clone());
+			self.request_receipts(io, peer_id, needed_receipts);
+			return;
+		}
+
 		// find subchain to download
-		if let Some((h, count)) = self.blocks.needed_headers(MAX_HEADERS_TO_REQUEST, ignore_others) {
+		if let Some((h, count)) = self.blocks.needed_headers(headers_limit, ignore_others) {
 			replace(&mut self.peers.get_mut(&peer_id).unwrap().asking_blocks, vec![h.clone()]);
 			self.request_headers_by_hash(io, peer_id, &h, count, 0, false, PeerAsking::BlockHeaders);
 		}
@@ -797,6 +1226,16 @@ impl ChainSync {
 					self.blocks.clear_body_download(b);
 				}
 			},
+			PeerAsking::BlockReceipts => {
+				for b in &peer.asking_blocks {
+					self.blocks.clear_receipt_download(b);
+				}
+			},
+			PeerAsking::NodeData => {
+				for n in &peer.asking_blocks {
+					self.state_download.clear_node_download(n);
+				}
+			},
 			_ => (),
 		}
 		peer.asking_blocks.clear();
@@ -815,9 +1254,9 @@ impl ChainSync {
 	fn collect_blocks(&mut self, io: &mut SyncIo) {
 		let mut restart = false;
 		let mut imported = HashSet::new();
-		let blocks = self.blocks.drain();
+		let blocks = self.blocks.drain_with_receipts();
 		let count = blocks.len();
-		for block in blocks {
+		for (block, receipts) in blocks {
 			let (h, number, parent) = {
 				let header = BlockView::new(&block).header_view();
 				(header.sha3(), header.number(), header.parent_hash())
@@ -830,7 +1269,18 @@ impl ChainSync {
 				break;
 			}
 
-			match io.chain().import_block(block) {
+			if self.fast_sync && self.fast_sync_pivot.map_or(false, |pivot| number > pivot) {
+				// Blocks after the pivot are downloaded again once the pivot state is available
+				trace!(target: "sync", "Fast sync pivot block reached");
+				self.start_state_download(io);
+				return;
+			}
+
+			let result = match (self.fast_sync, receipts) {
+				(true, Some(receipts)) => io.chain().import_block_with_receipts(block, receipts),
+				_ => io.chain().import_block(block),
+			};
+			match result {
 				Err(Error::Import(ImportError::AlreadyInChain)) => {
 					trace!(target: "sync", "Block already in chain {:?}", h);
 					self.block_imported(&h, number, &parent);
@@ -885,6 +1335,17 @@ impl ChainSync {
 			.asking_hash = Some(h.clone());
 	}
 
+	/// Request fork checkpoint block header from a peer
+	fn request_fork_header(&mut self, sync: &mut SyncIo, peer_id: PeerId, number: BlockNumber) {
+		trace!(target: "sync", "{} <- GetForkHeader: #{}", peer_id, number);
+		let mut rlp = RlpStream::new_list(4);
+		rlp.append(&number);
+		rlp.append(&1u32);
+		rlp.append(&0u32);
+		rlp.append(&0u32);
+		self.send_request(sync, peer_id, PeerAsking::ForkHeader, GET_BLOCK_HEADERS_PACKET, rlp.out());
+	}
+
 	/// Request block bodies from a peer
 	fn request_bodies(&mut self, sync: &mut SyncIo, peer_id: PeerId, hashes: Vec<H256>) {
 		let mut rlp = RlpStream::new_list(hashes.len());
@@ -895,6 +1356,26 @@ impl ChainSync {
 		self.send_request(sync, peer_id, PeerAsking::BlockBodies, GET_BLOCK_BODIES_PACKET, rlp.out());
 	}
 
+	/// Request block receipts from a peer
+	fn request_receipts(&mut self, sync: &mut SyncIo, peer_id: PeerId, hashes: Vec<H256>) {
+		let mut rlp = RlpStream::new_list(hashes.len());
+		trace!(target: "sync", "{} <- GetReceipts: {} entries starting from {:?}", peer_id, hashes.len(), hashes.first());
+		for h in hashes {
+			rlp.append(&h);
+		}
+		self.send_request(sync, peer_id, PeerAsking::BlockReceipts, GET_RECEIPTS_PACKET, rlp.out());
+	}
+
+	/// Request state trie nodes from a peer
+	fn request_node_data(&mut self, sync: &mut SyncIo, peer_id: PeerId, hashes: Vec<H256>) {
+		let mut rlp = RlpStream::new_list(hashes.len());
+		trace!(target: "sync", "{} <- GetNodeData: {} entries starting from {:?}", peer_id, hashes.len(), hashes.first());
+		for h in hashes {
+			rlp.append(&h);
+		}
+		self.send_request(sync, peer_id, PeerAsking::NodeData, GET_NODE_DATA_PACKET, rlp.out());
+	}
+
 	/// Reset peer status after request is complete.
 	fn reset_peer_asking(&mut self, peer_id: PeerId, asking: PeerAsking) -> bool {
 		let peer = self.peers.get_mut(&peer_id).unwrap();
@@ -943,8 +1424,11 @@ impl ChainSync {
 		item_count = min(item_count, MAX_TX_TO_IMPORT);
 		let mut transactions = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
-			let tx = try!(r.at(i)).as_raw().to_vec();
-			transactions.push(tx);
+			let tx = try!(r.at(i));
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.known_transactions.insert(tx.as_raw().sha3());
+			}
+			transactions.push(tx.as_raw().to_vec());
 		}
 		let _ = io.chain().queue_transactions(transactions);
 		Ok(())
@@ -1049,6 +1533,9 @@ impl ChainSync {
 	}
 
 	/// Respond to GetNodeData request
+	///
+	/// Per eth/63 NodeData is a list of trie nodes, each one an RLP byte string,
+	/// so every node is appended as a string instead of being spliced in raw.
 	fn return_node_data(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
 		let mut count = r.item_count();
 		trace!(target: "sync", "{} -> GetNodeData: {} entries", peer_id, count);
@@ -1057,17 +1544,17 @@ impl ChainSync {
 			return Ok(None);
 		}
 		count = min(count, MAX_NODE_DATA_TO_SEND);
-		let mut added = 0usize;
-		let mut data = Bytes::new();
+		let mut data = Vec::new();
 		for i in 0..count {
-			if let Some(mut hdr) = io.chain().state_data(&try!(r.val_at::<H256>(i))) {
-				data.append(&mut hdr);
-				added += 1;
+			if let Some(node) = io.chain().state_data(&try!(r.val_at::<H256>(i))) {
+				data.push(node);
 			}
 		}
-		trace!(target: "sync", "{} -> GetNodeData: return {} entries", peer_id, added);
-		let mut rlp = RlpStream::new_list(added);
-		rlp.append_raw(&data, added);
+		trace!(target: "sync", "{} -> GetNodeData: return {} entries", peer_id, data.len());
+		let mut rlp = RlpStream::new_list(data.len());
+		for node in &data {
+			rlp.append(node);
+		}
 		Ok(Some((NODE_DATA_PACKET, rlp)))
 	}
 
@@ -1152,6 +1639,8 @@ impl ChainSync {
 			TRANSACTIONS_PACKET => self.on_peer_transactions(io, peer, &rlp),
 			BLOCK_HEADERS_PACKET => self.on_peer_block_headers(io, peer, &rlp),
 			BLOCK_BODIES_PACKET => self.on_peer_block_bodies(io, peer, &rlp),
+			RECEIPTS_PACKET => self.on_peer_block_receipts(io, peer, &rlp),
+			NODE_DATA_PACKET => self.on_peer_node_data(io, peer, &rlp),
 			NEW_BLOCK_PACKET => self.on_peer_new_block(io, peer, &rlp),
 			NEW_BLOCK_HASHES_PACKET => self.on_peer_new_hashes(io, peer, &rlp),
 			_ => {
@@ -1161,15 +1650,22 @@ impl ChainSync {
 		};
 		result.unwrap_or_else(|e| {
 			debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+			io.report_peer(peer, ReputationEvent::InvalidPacket);
 		})
 	}
 
 	pub fn maintain_peers(&mut self, io: &mut SyncIo) {
 		let tick = time::precise_time_s();
 		let mut aborting = Vec::new();
-		for (peer_id, peer) in &self.peers {
+		for (peer_id, peer) in &mut self.peers {
 			if peer.asking != PeerAsking::Nothing && (tick - peer.ask_time) > CONNECTION_TIMEOUT_SEC {
+				if io.is_throttled(*peer_id) && (tick - peer.ask_time) <= THROTTLED_TIMEOUT_SEC {
+					// Response is delayed by our own bandwidth limit, give the peer more time.
+					trace!(target:"sync", "Extending timeout for throttled peer {}", peer_id);
+					continue;
+				}
 				trace!(target:"sync", "Timeout {}", peer_id);
+				io.report_peer(*peer_id, ReputationEvent::Timeout);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
 			}
@@ -1225,7 +1721,7 @@ impl ChainSync {
 	fn get_lagging_peers(&mut self, chain_info: &BlockChainInfo, io: &SyncIo) -> Vec<(PeerId, BlockNumber)> {
 		let latest_hash = chain_info.best_block_hash;
 		let latest_number = chain_info.best_block_number;
-		self.peers.iter_mut().filter_map(|(&id, ref mut peer_info)|
+		self.peers.iter_mut().filter(|&(id, ref peer_info)| peer_info.is_confirmed() && !io.is_throttled(*id)).filter_map(|(&id, ref mut peer_info)|
 			match io.chain().block_status(BlockID::Hash(peer_info.latest_hash.clone())) {
 				BlockStatus::InChain => {
 					if peer_info.latest_number.is_none() {
@@ -1297,6 +1793,8 @@ impl ChainSync {
 	}
 
 	/// propagates new transactions to all peers
+	/// Sends transactions not yet known to the peers. Local transactions are sent to all peers,
+	/// others only to a random subset. Returns number of peers that received any transactions.
 	fn propagate_new_transactions(&mut self, io: &mut SyncIo) -> usize {
 
 		// Early out of nobody to send to.
@@ -1304,35 +1802,63 @@ impl ChainSync {
 			return 0;
 		}
 
-		let mut transactions = io.chain().all_transactions();
+		let transactions = io.chain().all_transactions();
 		if transactions.is_empty() {
 			return 0;
 		}
 
-		let mut packet = RlpStream::new_list(transactions.len());
-		let tx_count = transactions.len();
-		for tx in transactions.drain(..) {
-			packet.append(&tx);
-		}
-		let rlp = packet.out();
+		let local: HashSet<H256> = io.chain().local_transactions().iter().map(|tx| tx.hash()).collect();
+		let transactions = transactions.into_iter()
+			.map(|tx| {
+				let hash = tx.hash();
+				(hash, local.contains(&hash), tx)
+			})
+			.collect::<Vec<_>>();
 
 		let lucky_peers = {
 			// sqrt(x)/x scaled to max u32
 			let fraction = (self.peers.len() as f64).powf(-0.5).mul(u32::max_value() as f64).round() as u32;
 			let small = self.peers.len() < MIN_PEERS_PROPAGATION;
 			let lucky_peers = self.peers.iter()
+				.filter(|&(_, peer)| peer.is_confirmed())
 				.filter_map(|(&p, _)| if small || ::rand::random::<u32>() < fraction { Some(p.clone()) } else { None })
 				.collect::<Vec<_>>();
 
 			// taking at max of MAX_PEERS_PROPAGATION
-			lucky_peers.iter().cloned().take(min(lucky_peers.len(), MAX_PEERS_PROPAGATION)).collect::<Vec<PeerId>>()
+			lucky_peers.iter().cloned().take(min(lucky_peers.len(), MAX_PEERS_PROPAGATION)).collect::<HashSet<PeerId>>()
 		};
 
-		let sent = lucky_peers.len();
-		for peer_id in lucky_peers {
-			self.send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp.clone());
+		let mut packets = Vec::new();
+		for (peer_id, peer) in &mut self.peers {
+			if !peer.is_confirmed() {
+				continue;
+			}
+			if io.is_throttled(*peer_id) {
+				// Transactions are not marked as known, so they are sent once the peer catches up.
+				continue;
+			}
+			let lucky = lucky_peers.contains(peer_id);
+			let to_send = transactions.iter()
+				.filter(|&&(ref hash, is_local, _)| (lucky || is_local) && !peer.known_transactions.contains(hash))
+				.collect::<Vec<_>>();
+			if to_send.is_empty() {
+				continue;
+			}
+
+			let mut packet = RlpStream::new_list(to_send.len());
+			for &&(ref hash, _, ref tx) in &to_send {
+				packet.append(tx);
+				peer.known_transactions.insert(hash.clone());
+			}
+			packets.push((*peer_id, to_send.len(), packet.out()));
 		}
-		trace!(target: "sync", "Sent {} transactions to {} peers.", tx_count, sent);
+
+		let sent = packets.len();
+		for (peer_id, tx_count, rlp) in packets {
+			trace!(target: "sync", "{} <- Transactions ({} entries)", peer_id, tx_count);
+			self.send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp);
+		}
+		trace!(target: "sync", "Sent transactions to {} peers.", sent);
 		sent
 	}
 
@@ -1352,6 +1878,10 @@ impl ChainSync {
 	/// Maintain other peers. Send out any new blocks and transactions
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
 		self.check_resume(io);
+		if self.state == SyncState::StateDownload && !self.peers.values().any(|p| p.asking != PeerAsking::Nothing) {
+			// Local state nodes are walked in bounded steps, keep going while nothing is being downloaded
+			self.restart(io);
+		}
 	}
 
 	/// called when block is imported to chain, updates transactions queue and propagates the blocks
@@ -1367,6 +1897,17 @@ impl ChainSync {
 	}
 }
 
+/// New request size given the time the last request took. Halved for slow responses, doubled for fast complete responses.
+fn adapt_request_size(current: usize, min_size: usize, max_size: usize, elapsed: f64, full: bool) -> usize {
+	if elapsed > SLOW_RESPONSE_SEC {
+		max(current / 2, min_size)
+	} else if elapsed < FAST_RESPONSE_SEC && full {
+		min(current * 2, max_size)
+	} else {
+		current
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use tests::helpers::*;
@@ -1377,7 +1918,15 @@ mod tests {
 	use ethcore::views::BlockView;
 	use ethcore::header::*;
 	use ethcore::client::*;
-	use ethcore::miner::MinerService;
+	use ethcore::miner::{MinerService, AccountDetails};
+	use ethcore::transaction::{Transaction, SignedTransaction, Action};
+	use lru_set::LruSet;
+	use super::{MAX_KNOWN_TRANSACTIONS, NEW_BLOCK_PACKET, BLOCK_BODIES_PACKET};
+	use super::{MAX_HEADERS_TO_REQUEST, MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST, INITIAL_BODIES_TO_REQUEST, adapt_request_size};
+	use super::{ForkConfirmation, STATUS_PACKET, BLOCK_HEADERS_PACKET, GET_BLOCK_HEADERS_PACKET, NEW_BLOCK_HASHES_PACKET};
+	use ethcore::spec::ForkCheckpoint;
+	use super::{CONNECTION_TIMEOUT_SEC, THROTTLED_TIMEOUT_SEC};
+	use time;
 
 	fn get_dummy_block(order: u32, parent_hash: H256) -> Bytes {
 		let mut header = Header::new();
@@ -1542,8 +2091,9 @@ mod tests {
 		let rlp_result = result.unwrap();
 		assert!(rlp_result.is_some());
 
-		// the length of one rlp-encoded hashe
-		assert_eq!(34, rlp_result.unwrap().1.out().len());
+		// the test client returns the 33-byte rlp of the hash as the node, which is wrapped
+		// into a byte string (1 + 33) inside the response list (1 + 34)
+		assert_eq!(35, rlp_result.unwrap().1.out().len());
 
 		io.sender = Some(2usize);
 
@@ -1551,24 +2101,45 @@ mod tests {
 		assert_eq!(1, io.queue.len());
 	}
 
+	fn dummy_peer(peer_latest_hash: H256) -> PeerInfo {
+		PeerInfo {
+			protocol_version: 0,
+			genesis: H256::zero(),
+			network_id: U256::zero(),
+			latest_hash: peer_latest_hash,
+			latest_number: None,
+			difficulty: None,
+			asking: PeerAsking::Nothing,
+			asking_blocks: Vec::new(),
+			asking_hash: None,
+			ask_time: 0f64,
+			known_transactions: LruSet::new(MAX_KNOWN_TRANSACTIONS),
+			headers_limit: MAX_HEADERS_TO_REQUEST,
+			bodies_limit: INITIAL_BODIES_TO_REQUEST,
+			confirmation: ForkConfirmation::Confirmed,
+		}
+	}
+
 	fn dummy_sync_with_peer(peer_latest_hash: H256, client: &BlockChainClient) -> ChainSync {
 		let mut sync = ChainSync::new(SyncConfig::default(), client);
-		sync.peers.insert(0,
-			PeerInfo {
-				protocol_version: 0,
-				genesis: H256::zero(),
-				network_id: U256::zero(),
-				latest_hash: peer_latest_hash,
-				latest_number: None,
-				difficulty: None,
-				asking: PeerAsking::Nothing,
-				asking_blocks: Vec::new(),
-				asking_hash: None,
-				ask_time: 0f64,
-			});
+		sync.peers.insert(0, dummy_peer(peer_latest_hash));
 		sync
 	}
 
+	fn dummy_transaction(client: &TestBlockChainClient) -> SignedTransaction {
+		let keypair = KeyPair::create().unwrap();
+		client.set_balance(keypair.address(), U256::from(1_000_000_000));
+		client.set_nonce(keypair.address(), U256::zero());
+		Transaction {
+			action: Action::Create,
+			value: U256::from(100),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::one(),
+			nonce: U256::zero(),
+		}.sign(&keypair.secret())
+	}
+
 	#[test]
 	fn finds_lagging_peers() {
 		let mut client = TestBlockChainClient::new();
@@ -1833,4 +2404,444 @@ mod tests {
 		assert_eq!(status.transactions_in_pending_queue, 0);
 		assert_eq!(status.transactions_in_future_queue, 0);
 	}
+
+	#[test]
+	fn should_return_peers_info() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+
+		// when
+		let peers = sync.peers();
+
+		// then
+		assert_eq!(peers.len(), 1);
+		assert_eq!(peers[0].id, 0);
+		assert_eq!(peers[0].head, client.block_hash_delta_minus(2));
+		assert_eq!(peers[0].asking, PeerAsking::Nothing);
+	}
+
+	#[test]
+	fn should_propagate_transaction_to_peer_only_once() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx = dummy_transaction(&client);
+		client.import_transactions(vec![tx]);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		let first = sync.propagate_new_transactions(&mut io);
+		let second = sync.propagate_new_transactions(&mut io);
+
+		// then
+		assert_eq!(first, 1);
+		assert_eq!(second, 0);
+		assert_eq!(io.queue.len(), 1);
+		assert_eq!(io.queue[0].packet_id, super::TRANSACTIONS_PACKET);
+	}
+
+	#[test]
+	fn should_not_propagate_transaction_back_to_sender() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx = dummy_transaction(&client);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+		let mut rlp = RlpStream::new_list(1);
+		rlp.append(&tx);
+		let data = rlp.out();
+
+		// when
+		sync.on_peer_transactions(&mut io, 0, &UntrustedRlp::new(&data)).unwrap();
+		let sent = sync.propagate_new_transactions(&mut io);
+
+		// then
+		assert_eq!(io.chain.miner.status().transactions_in_pending_queue, 1);
+		assert_eq!(sent, 0);
+		assert_eq!(io.queue.len(), 0);
+	}
+
+	#[test]
+	fn should_propagate_local_transactions_to_all_peers() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx = dummy_transaction(&client);
+		{
+			let fetch_account = |a: &Address| AccountDetails {
+				nonce: client.latest_nonce(a).unwrap(),
+				balance: client.latest_balance(a).unwrap(),
+			};
+			client.miner.import_own_transaction(&client, tx, fetch_account).unwrap();
+		}
+		let peers = super::MAX_PEERS_PROPAGATION + 10;
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		for peer_id in 1..peers {
+			sync.peers.insert(peer_id, dummy_peer(client.block_hash_delta_minus(2)));
+		}
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		let sent = sync.propagate_new_transactions(&mut io);
+
+		// then
+		assert_eq!(sent, peers);
+		assert_eq!(io.queue.len(), peers);
+	}
+
+	#[test]
+	fn should_not_propagate_transactions_to_throttled_peer() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx = dummy_transaction(&client);
+		client.import_transactions(vec![tx]);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+		io.throttled.insert(0);
+
+		// when
+		let throttled = sync.propagate_new_transactions(&mut io);
+		io.throttled.clear();
+		let resumed = sync.propagate_new_transactions(&mut io);
+
+		// then
+		assert_eq!(throttled, 0);
+		assert_eq!(resumed, 1);
+		assert_eq!(io.queue.len(), 1);
+	}
+
+	#[test]
+	fn should_not_timeout_throttled_peer() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::BlockHeaders;
+			peer.ask_time = time::precise_time_s() - CONNECTION_TIMEOUT_SEC - 1f64;
+		}
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+		io.throttled.insert(0);
+
+		// when
+		sync.maintain_peers(&mut io);
+
+		// then
+		assert!(io.reported.is_empty());
+		assert_eq!(sync.peers[&0].asking, PeerAsking::BlockHeaders);
+	}
+
+	#[test]
+	fn should_deactivate_peer_with_broken_headers_chain() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		sync.peers.insert(1, dummy_peer(client.block_hash_delta_minus(2)));
+		sync.active_peers = vec![0, 1].into_iter().collect();
+		sync.state = SyncState::Blocks;
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::BlockHeaders;
+			peer.asking_hash = client.block_hash(BlockID::Number(3));
+			peer.ask_time = time::precise_time_s();
+		}
+		// block 4 is missing
+		let mut headers = RlpStream::new_list(2);
+		headers.append_raw(&client.block_header(BlockID::Number(3)).unwrap(), 1);
+		headers.append_raw(&client.block_header(BlockID::Number(5)).unwrap(), 1);
+		let headers = headers.out();
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.on_packet(&mut io, 0, BLOCK_HEADERS_PACKET, &headers);
+
+		// then
+		assert_eq!(io.reported, vec![(0, ReputationEvent::InvalidPacket)]);
+		assert!(!sync.active_peers.contains(&0));
+		assert!(sync.active_peers.contains(&1));
+	}
+
+	#[test]
+	fn should_timeout_throttled_peer_eventually() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::BlockHeaders;
+			peer.ask_time = time::precise_time_s() - THROTTLED_TIMEOUT_SEC - 1f64;
+		}
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+		io.throttled.insert(0);
+
+		// when
+		sync.maintain_peers(&mut io);
+
+		// then
+		assert_eq!(io.reported, vec![(0, ReputationEvent::Timeout)]);
+		assert!(!sync.peers.contains_key(&0));
+	}
+
+	#[test]
+	fn should_report_malformed_packet() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.on_packet(&mut io, 0, NEW_BLOCK_PACKET, &[0xc0]);
+
+		// then
+		assert_eq!(io.reported, vec![(0, ReputationEvent::InvalidPacket)]);
+	}
+
+	#[test]
+	fn adapts_request_size_to_response_time() {
+		// slow response
+		assert_eq!(adapt_request_size(64, 8, 128, 6f64, true), 32);
+		assert_eq!(adapt_request_size(8, 8, 128, 6f64, true), 8);
+		// fast response
+		assert_eq!(adapt_request_size(64, 8, 128, 0.5f64, true), 128);
+		assert_eq!(adapt_request_size(128, 8, 128, 0.5f64, true), 128);
+		assert_eq!(adapt_request_size(64, 8, 128, 0.5f64, false), 64);
+		// normal response
+		assert_eq!(adapt_request_size(64, 8, 128, 2f64, true), 64);
+	}
+
+	#[test]
+	fn should_request_heads_from_best_peer() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let td = client.chain_info().total_difficulty;
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		sync.peers.get_mut(&0).unwrap().difficulty = Some(td + U256::from(1));
+		let mut best = dummy_peer(client.block_hash_delta_minus(1));
+		best.difficulty = Some(td + U256::from(2));
+		sync.peers.insert(1, best);
+		sync.active_peers.insert(0);
+		sync.active_peers.insert(1);
+		sync.state = SyncState::ChainHead;
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.sync_peer(&mut io, 0, false);
+
+		// then
+		assert_eq!(io.queue.len(), 1);
+		assert_eq!(io.queue[0].recipient, 1);
+		assert_eq!(sync.peers[&0].asking, PeerAsking::Nothing);
+		assert_eq!(sync.peers[&1].asking, PeerAsking::Heads);
+	}
+
+	#[test]
+	fn should_request_heads_from_next_best_peer_if_best_is_busy_or_throttled() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let td = client.chain_info().total_difficulty;
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		sync.peers.get_mut(&0).unwrap().difficulty = Some(td + U256::from(1));
+		for (id, difficulty) in vec![(1, 2u64), (2, 4), (3, 3)] {
+			let mut peer = dummy_peer(client.block_hash_delta_minus(1));
+			peer.difficulty = Some(td + U256::from(difficulty));
+			sync.peers.insert(id, peer);
+		}
+		sync.peers.get_mut(&2).unwrap().asking = PeerAsking::BlockBodies;
+		for id in 0..4 {
+			sync.active_peers.insert(id);
+		}
+		sync.state = SyncState::ChainHead;
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+		io.throttled.insert(3);
+
+		// when
+		sync.sync_peer(&mut io, 0, false);
+
+		// then
+		assert_eq!(io.queue.len(), 1);
+		assert_eq!(io.queue[0].recipient, 1);
+		assert_eq!(sync.peers[&1].asking, PeerAsking::Heads);
+		assert_eq!(sync.peers[&3].asking, PeerAsking::Nothing);
+	}
+
+	#[test]
+	fn should_shrink_requests_for_slow_peer() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(2), &client);
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::BlockBodies;
+			peer.ask_time = 0f64;
+		}
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.on_packet(&mut io, 0, BLOCK_BODIES_PACKET, &[0xc0]);
+
+		// then
+		assert_eq!(sync.peers[&0].bodies_limit, INITIAL_BODIES_TO_REQUEST / 2);
+		assert!(sync.peers[&0].bodies_limit >= MIN_BODIES_TO_REQUEST && sync.peers[&0].bodies_limit < MAX_BODIES_TO_REQUEST);
+		assert!(io.reported.contains(&(0, ReputationEvent::Timeout)));
+	}
+
+	#[test]
+	fn should_resume_fast_sync_while_state_is_pending() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		client.state_pending.store(true, atomic::Ordering::Relaxed);
+
+		// when
+		let pending_sync = ChainSync::new(SyncConfig::default(), &client);
+		client.complete_state_import();
+		let complete_sync = ChainSync::new(SyncConfig::default(), &client);
+
+		// then
+		assert!(pending_sync.fast_sync);
+		assert!(!complete_sync.fast_sync);
+	}
+
+	fn dummy_sync_with_fork_checkpoint(client: &BlockChainClient, checkpoint: ForkCheckpoint) -> ChainSync {
+		let mut config = SyncConfig::default();
+		config.fork_checkpoints = vec![checkpoint];
+		let mut sync = ChainSync::new(config, client);
+		let mut peer = dummy_peer(client.chain_info().best_block_hash);
+		peer.asking = PeerAsking::ForkHeader;
+		peer.confirmation = ForkConfirmation::Unconfirmed;
+		sync.peers.insert(0, peer);
+		sync
+	}
+
+	fn fork_header_rlp(client: &BlockChainClient, number: BlockNumber) -> Bytes {
+		let mut rlp = RlpStream::new_list(1);
+		rlp.append_raw(&client.block_header(BlockID::Number(number)).unwrap(), 1);
+		rlp.out()
+	}
+
+	#[test]
+	fn should_request_fork_header_on_status() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let checkpoint = ForkCheckpoint { number: 5, hash: client.block_hash(BlockID::Number(5)), extra_data: None };
+		let mut config = SyncConfig::default();
+		config.fork_checkpoints = vec![checkpoint];
+		let mut sync = ChainSync::new(config, &client);
+		let chain_info = client.chain_info();
+		let mut status = RlpStream::new_list(5);
+		status.append(&63u32);
+		status.append(&sync.network_id);
+		status.append(&chain_info.total_difficulty);
+		status.append(&chain_info.best_block_hash);
+		status.append(&chain_info.genesis_hash);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.on_packet(&mut io, 0, STATUS_PACKET, &status.out());
+
+		// then
+		assert_eq!(io.queue.len(), 1);
+		assert_eq!(io.queue[0].packet_id, GET_BLOCK_HEADERS_PACKET);
+		assert_eq!(sync.peers[&0].asking, PeerAsking::ForkHeader);
+		assert!(!sync.active_peers.contains(&0));
+	}
+
+	#[test]
+	fn should_confirm_peer_with_fork_header() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let checkpoint = ForkCheckpoint { number: 5, hash: client.block_hash(BlockID::Number(5)), extra_data: None };
+		let mut sync = dummy_sync_with_fork_checkpoint(&client, checkpoint);
+		let headers = fork_header_rlp(&client, 5);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.on_packet(&mut io, 0, BLOCK_HEADERS_PACKET, &headers);
+
+		// then
+		assert_eq!(sync.peers[&0].confirmation, ForkConfirmation::Confirmed);
+		assert!(sync.active_peers.contains(&0));
+	}
+
+	#[test]
+	fn should_not_use_peer_on_other_side_of_fork() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let checkpoint = ForkCheckpoint { number: 5, hash: None, extra_data: Some(b"fork".to_vec()) };
+		let mut sync = dummy_sync_with_fork_checkpoint(&client, checkpoint.clone());
+		let mut short_sync = dummy_sync_with_fork_checkpoint(&client, checkpoint);
+		let headers = fork_header_rlp(&client, 5);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+
+		// when
+		sync.on_packet(&mut io, 0, BLOCK_HEADERS_PACKET, &headers);
+		let disabled: Vec<_> = io.disabled.drain().collect();
+		short_sync.on_packet(&mut io, 0, BLOCK_HEADERS_PACKET, &[0xc0]);
+
+		// then
+		assert_eq!(disabled, vec![0]);
+		assert_eq!(sync.peers[&0].confirmation, ForkConfirmation::Unconfirmed);
+		assert!(!sync.active_peers.contains(&0));
+		assert_eq!(short_sync.peers[&0].confirmation, ForkConfirmation::TooShort);
+		assert!(!short_sync.active_peers.contains(&0));
+		assert!(io.disabled.is_empty());
+		assert!(io.queue.is_empty());
+	}
+
+	#[test]
+	fn should_ask_too_short_peer_again_once_it_passes_fork_checkpoint() {
+		// given
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let checkpoint = ForkCheckpoint { number: 5, hash: client.block_hash(BlockID::Number(5)), extra_data: None };
+		let mut sync = dummy_sync_with_fork_checkpoint(&client, checkpoint);
+		let mut short_hashes = RlpStream::new_list(1);
+		short_hashes.begin_list(2).append(&H256::random()).append(&4u64);
+		let mut hashes = RlpStream::new_list(1);
+		hashes.begin_list(2).append(&H256::random()).append(&6u64);
+		let mut queue = VecDeque::new();
+		let mut io = TestIo::new(&mut client, &mut queue, None);
+		sync.on_packet(&mut io, 0, BLOCK_HEADERS_PACKET, &[0xc0]);
+
+		// when
+		sync.on_packet(&mut io, 0, NEW_BLOCK_HASHES_PACKET, &short_hashes.out());
+		let short_confirmation = sync.peers[&0].confirmation.clone();
+		sync.on_packet(&mut io, 0, NEW_BLOCK_HASHES_PACKET, &hashes.out());
+
+		// then
+		assert_eq!(short_confirmation, ForkConfirmation::TooShort);
+		assert_eq!(sync.peers[&0].confirmation, ForkConfirmation::Unconfirmed);
+		assert_eq!(sync.peers[&0].asking, PeerAsking::ForkHeader);
+		assert_eq!(io.queue.len(), 1);
+		assert_eq!(io.queue[0].packet_id, GET_BLOCK_HEADERS_PACKET);
+	}
 }
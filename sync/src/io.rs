@@ -14,19 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use util::{NetworkContext, PeerId, PacketId,};
+use util::{NetworkContext, PeerId, PacketId, ReputationEvent};
 use util::error::UtilError;
 use ethcore::service::SyncMessage;
 use ethcore::client::BlockChainClient;
 
 /// IO interface for the syning handler.
 /// Provides peer connection management and an interface to the blockchain client.
-// TODO: ratings
 pub trait SyncIo {
 	/// Disable a peer
 	fn disable_peer(&mut self, peer_id: PeerId);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
+	/// Adjust peer reputation
+	fn report_peer(&mut self, peer_id: PeerId, event: ReputationEvent);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), UtilError>;
 	/// Send a packet to a peer.
@@ -37,6 +38,10 @@ pub trait SyncIo {
 	fn peer_info(&self, peer_id: PeerId) -> String {
 		peer_id.to_string()
 	}
+	/// Check if IO with the peer is paused by the bandwidth limit
+	fn is_throttled(&self, _peer_id: PeerId) -> bool {
+		false
+	}
 	/// Returns if the chain block queue empty
 	fn is_chain_queue_empty(&self) -> bool {
 		self.chain().queue_info().is_empty()
@@ -70,6 +75,14 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 		self.network.disconnect_peer(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, event: ReputationEvent) {
+		self.network.report_peer(peer_id, event);
+	}
+
+	fn is_throttled(&self, peer_id: PeerId) -> bool {
+		self.network.is_throttled(peer_id)
+	}
+
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), UtilError>{
 		self.network.respond(packet_id, data)
 	}
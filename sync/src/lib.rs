@@ -45,7 +45,7 @@
 //! 	service.start().unwrap();
 //! 	let dir = env::temp_dir();
 //! 	let client = Client::new(ClientConfig::default(), ethereum::new_frontier(true), &dir, Arc::new(Miner::default()), service.io().channel()).unwrap();
-//! 	let miner = Miner::new(false, ethereum::new_frontier(true));
+//! 	let miner = Miner::new(Default::default(), ethereum::new_frontier(true));
 //! 	let sync = EthSync::new(SyncConfig::default(), client);
 //! 	EthSync::register(&mut service, sync);
 //! }
@@ -68,15 +68,18 @@ use util::network::{NetworkProtocolHandler, NetworkService, NetworkContext, Peer
 use util::TimerToken;
 use util::{U256, ONE_U256};
 use ethcore::client::Client;
+use ethcore::spec::ForkCheckpoint;
 use ethcore::service::{SyncMessage, NetSyncMessage};
 use io::NetSyncIo;
 use util::io::IoChannel;
 use util::{NetworkIoMessage, NetworkError};
-use chain::ChainSync;
+use chain::{ChainSync, ETH62_PACKET_COUNT, ETH63_PACKET_COUNT};
 
 mod chain;
 mod blocks;
+mod state;
 mod io;
+mod lru_set;
 
 #[cfg(test)]
 mod tests;
@@ -87,6 +90,10 @@ pub struct SyncConfig {
 	pub max_download_ahead_blocks: usize,
 	/// Network ID
 	pub network_id: U256,
+	/// Download state of a recent block instead of executing all blocks
+	pub fast_sync: bool,
+	/// Peers disagreeing with these blocks are disconnected
+	pub fork_checkpoints: Vec<ForkCheckpoint>,
 }
 
 impl Default for SyncConfig {
@@ -94,6 +101,8 @@ impl Default for SyncConfig {
 		SyncConfig {
 			max_download_ahead_blocks: 20000,
 			network_id: ONE_U256,
+			fast_sync: false,
+			fork_checkpoints: Vec::new(),
 		}
 	}
 }
@@ -106,6 +115,8 @@ pub trait SyncProvider: Send + Sync {
 	fn start_network(&self);
 	/// Stop the network
 	fn stop_network(&self);
+	/// Get eth protocol details of connected peers
+	fn peers(&self) -> Vec<EthPeerInfo>;
 }
 
 /// Ethereum network protocol handler
@@ -118,7 +129,7 @@ pub struct EthSync {
 	io_channel: RwLock<IoChannel<NetSyncMessage>>,
 }
 
-pub use self::chain::{SyncStatus, SyncState};
+pub use self::chain::{SyncStatus, SyncState, EthPeerInfo, PeerAsking};
 
 impl EthSync {
 	/// Creates and register protocol with the network service
@@ -133,7 +144,7 @@ impl EthSync {
 
 	/// Register protocol with the network service
 	pub fn register(service: &NetworkService<SyncMessage>, sync: Arc<EthSync>) -> Result<(), NetworkError> {
-		service.register_protocol(sync.clone(), "eth", &[62u8, 63u8])
+		service.register_protocol(sync.clone(), "eth", &[(62u8, ETH62_PACKET_COUNT), (63u8, ETH63_PACKET_COUNT)])
 	}
 
 	/// Stop sync
@@ -160,6 +171,10 @@ impl SyncProvider for EthSync {
 	fn stop_network(&self) {
 		self.io_channel.read().unwrap().send(NetworkIoMessage::User(SyncMessage::StopNetwork)).expect("Error sending IO notification");
 	}
+
+	fn peers(&self) -> Vec<EthPeerInfo> {
+		self.sync.read().unwrap().peers()
+	}
 }
 
 impl NetworkProtocolHandler<SyncMessage> for EthSync {
@@ -0,0 +1,111 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bounded set evicting least recently used items.
+
+use std::mem;
+use std::hash::Hash;
+use std::collections::{HashMap, BTreeMap};
+use heapsize::HeapSizeOf;
+
+/// A set holding at most `capacity` items.
+/// Inserting into a full set removes the item that was inserted or touched least recently.
+#[derive(Clone)]
+pub struct LruSet<T> where T: Hash + Eq + Clone {
+	capacity: usize,
+	counter: u64,
+	items: HashMap<T, u64>,
+	by_age: BTreeMap<u64, T>,
+}
+
+impl<T> LruSet<T> where T: Hash + Eq + Clone {
+	/// Creates empty set with given capacity.
+	pub fn new(capacity: usize) -> Self {
+		LruSet {
+			capacity: capacity,
+			counter: 0,
+			items: HashMap::new(),
+			by_age: BTreeMap::new(),
+		}
+	}
+
+	/// Inserts an item or marks existing one as most recently used.
+	pub fn insert(&mut self, item: T) {
+		self.counter += 1;
+		if let Some(age) = self.items.insert(item.clone(), self.counter) {
+			self.by_age.remove(&age);
+		}
+		self.by_age.insert(self.counter, item);
+
+		while self.items.len() > self.capacity {
+			let oldest = *self.by_age.keys().next().expect("by_age has the same number of items as items; qed");
+			let item = self.by_age.remove(&oldest).expect("key was just taken from the map; qed");
+			self.items.remove(&item);
+		}
+	}
+
+	/// Returns true if the set contains given item.
+	pub fn contains(&self, item: &T) -> bool {
+		self.items.contains_key(item)
+	}
+}
+
+impl<T> HeapSizeOf for LruSet<T> where T: Hash + Eq + Clone + HeapSizeOf {
+	fn heap_size_of_children(&self) -> usize {
+		// `BTreeMap` nodes are approximated by the size of their entries
+		self.items.heap_size_of_children()
+			+ self.by_age.len() * (mem::size_of::<u64>() + mem::size_of::<T>())
+			+ self.by_age.values().fold(0, |acc, item| acc + item.heap_size_of_children())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use heapsize::HeapSizeOf;
+	use super::LruSet;
+
+	#[test]
+	fn should_evict_least_recently_used() {
+		// given
+		let mut set = LruSet::new(2);
+		set.insert(1);
+		set.insert(2);
+
+		// when
+		set.insert(1);
+		set.insert(3);
+
+		// then
+		assert!(set.contains(&1));
+		assert!(!set.contains(&2));
+		assert!(set.contains(&3));
+	}
+
+	#[test]
+	fn should_account_for_items_in_heap_size() {
+		// given
+		let mut set = LruSet::new(1024);
+		let empty = set.heap_size_of_children();
+
+		// when
+		for i in 0..1024u64 {
+			set.insert(i);
+		}
+
+		// then
+		assert!(set.heap_size_of_children() >= empty + 1024 * 2 * 8);
+	}
+}
@@ -0,0 +1,362 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use util::*;
+use ethcore::client::BlockChainClient;
+
+/// Maximum number of nodes looked up in the local database by a single `needed_nodes` call.
+const MAX_LOCAL_LOOKUPS: usize = 1024;
+
+/// Trie a state node belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum NodeKind {
+	/// Account trie node.
+	Account,
+	/// Account storage trie node.
+	Storage,
+	/// Contract code.
+	Code,
+}
+
+/// State of a single block being downloaded node by node. Keeps track of which nodes need
+/// to be downloaded and which are being downloaded. Nodes that are already in the local
+/// database are not downloaded, which allows to resume an interrupted download.
+#[derive(Default)]
+pub struct StateDownload {
+	/// State root being downloaded.
+	root: Option<H256>,
+	/// Nodes to download.
+	pending: VecDeque<H256>,
+	/// All nodes that are not yet imported with their kind.
+	unknown: HashMap<H256, NodeKind>,
+	/// Set of nodes being downloaded.
+	downloading: HashSet<H256>,
+	/// Number of nodes downloaded so far.
+	downloaded: usize,
+}
+
+impl StateDownload {
+	/// Create a new instance.
+	pub fn new() -> StateDownload {
+		StateDownload {
+			root: None,
+			pending: VecDeque::new(),
+			unknown: HashMap::new(),
+			downloading: HashSet::new(),
+			downloaded: 0,
+		}
+	}
+
+	/// Clear everything.
+	pub fn clear(&mut self) {
+		self.root = None;
+		self.pending.clear();
+		self.unknown.clear();
+		self.downloading.clear();
+		self.downloaded = 0;
+	}
+
+	/// Reset collection to download the state trie with the given root.
+	pub fn reset_to(&mut self, root: H256) {
+		self.clear();
+		self.root = Some(root.clone());
+		if root != SHA3_NULL_RLP {
+			self.enqueue(root, NodeKind::Account);
+		}
+	}
+
+	/// State root being downloaded, if any.
+	pub fn root(&self) -> Option<H256> {
+		self.root
+	}
+
+	/// Check if all nodes have been imported.
+	pub fn is_complete(&self) -> bool {
+		self.unknown.is_empty()
+	}
+
+	/// Number of nodes downloaded so far.
+	pub fn downloaded(&self) -> usize {
+		self.downloaded
+	}
+
+	/// Returns a set of node hashes that require a download. The returned set is marked as being downloaded.
+	/// Nodes found in the local database are walked without downloading, at most `MAX_LOCAL_LOOKUPS` per call.
+	/// May return an empty set before the download is complete.
+	pub fn needed_nodes(&mut self, chain: &BlockChainClient, count: usize) -> Vec<H256> {
+		let mut needed = Vec::new();
+		let mut lookups = 0;
+		while needed.len() < count && lookups < MAX_LOCAL_LOOKUPS {
+			let hash = match self.pending.pop_front() {
+				Some(hash) => hash,
+				None => break,
+			};
+			let kind = match self.unknown.get(&hash) {
+				Some(kind) if !self.downloading.contains(&hash) => *kind,
+				_ => continue,
+			};
+			lookups += 1;
+			match chain.state_data(&hash) {
+				Some(data) => {
+					if let Err(e) = self.import_node(&hash, kind, &data) {
+						// Should never happen for nodes that were verified before importing
+						warn!(target: "sync", "Invalid local state node {}: {:?}", hash, e);
+					}
+				},
+				None => needed.push(hash),
+			}
+		}
+		self.downloading.extend(needed.iter().cloned());
+		needed
+	}
+
+	/// Unmark a node as being downloaded. It is requested again if it has not been imported.
+	pub fn clear_node_download(&mut self, hash: &H256) {
+		if self.downloading.remove(hash) && self.unknown.contains_key(hash) {
+			self.pending.push_back(hash.clone());
+		}
+	}
+
+	/// Insert a collection of downloaded nodes. Returns nodes verified against the requested hashes
+	/// that should be written into the state database. Unexpected or malformed nodes are ignored.
+	pub fn insert_nodes(&mut self, nodes: Vec<Bytes>) -> Vec<Bytes> {
+		let mut verified = Vec::with_capacity(nodes.len());
+		for node in nodes {
+			let hash = node.sha3();
+			let kind = match self.unknown.get(&hash) {
+				Some(kind) => *kind,
+				None => {
+					trace!(target: "sync", "Ignored unexpected state node {}", hash);
+					continue;
+				}
+			};
+			if let Err(e) = self.import_node(&hash, kind, &node) {
+				trace!(target: "sync", "Ignored invalid state node {}: {:?}", hash, e);
+				continue;
+			}
+			self.downloading.remove(&hash);
+			self.downloaded += 1;
+			verified.push(node);
+		}
+		verified
+	}
+
+	fn enqueue(&mut self, hash: H256, kind: NodeKind) {
+		if !self.unknown.contains_key(&hash) {
+			self.unknown.insert(hash.clone(), kind);
+			self.pending.push_back(hash);
+		}
+	}
+
+	fn import_node(&mut self, hash: &H256, kind: NodeKind, data: &[u8]) -> Result<(), DecoderError> {
+		let mut children = Vec::new();
+		if kind != NodeKind::Code {
+			try!(trie_node_children(&UntrustedRlp::new(data), kind, &mut children));
+		}
+		self.unknown.remove(hash);
+		for (child, kind) in children {
+			self.enqueue(child, kind);
+		}
+		Ok(())
+	}
+}
+
+/// Collect hashes of nodes referenced by a trie node. Inline nodes are walked recursively.
+fn trie_node_children(node: &UntrustedRlp, kind: NodeKind, children: &mut Vec<(H256, NodeKind)>) -> Result<(), DecoderError> {
+	match node.item_count() {
+		17 => {
+			for i in 0..16 {
+				try!(trie_child(&try!(node.at(i)), kind, children));
+			}
+		},
+		2 => {
+			let path = try!(try!(node.at(0)).data());
+			let is_leaf = path.first().map_or(false, |b| b & 0x20 != 0);
+			if !is_leaf {
+				try!(trie_child(&try!(node.at(1)), kind, children));
+			} else if kind == NodeKind::Account {
+				try!(account_children(&UntrustedRlp::new(try!(try!(node.at(1)).data())), children));
+			}
+		},
+		_ => return Err(DecoderError::RlpIncorrectListLen),
+	}
+	Ok(())
+}
+
+fn trie_child(child: &UntrustedRlp, kind: NodeKind, children: &mut Vec<(H256, NodeKind)>) -> Result<(), DecoderError> {
+	if child.is_empty() {
+		Ok(())
+	} else if child.is_list() {
+		trie_node_children(child, kind, children)
+	} else {
+		children.push((try!(child.as_val()), kind));
+		Ok(())
+	}
+}
+
+/// Account storage trie and code referenced by an account trie leaf.
+fn account_children(account: &UntrustedRlp, children: &mut Vec<(H256, NodeKind)>) -> Result<(), DecoderError> {
+	let storage_root: H256 = try!(account.val_at(2));
+	let code_hash: H256 = try!(account.val_at(3));
+	if storage_root != SHA3_NULL_RLP {
+		children.push((storage_root, NodeKind::Storage));
+	}
+	if code_hash != SHA3_EMPTY {
+		children.push((code_hash, NodeKind::Code));
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use super::{StateDownload, MAX_LOCAL_LOOKUPS};
+	use ethcore::client::{TestBlockChainClient, BlockChainClient};
+	use util::*;
+
+	fn account(storage_root: &H256, code_hash: &H256) -> Bytes {
+		let mut stream = RlpStream::new_list(4);
+		stream.append(&U256::zero());
+		stream.append(&U256::from(1000));
+		stream.append(storage_root);
+		stream.append(code_hash);
+		stream.out()
+	}
+
+	fn large_state(db: &mut MemoryDB) -> H256 {
+		let mut root = H256::new();
+		{
+			let mut accounts = SecTrieDBMut::new(db, &mut root);
+			for i in 1..(MAX_LOCAL_LOOKUPS as u64 * 2) {
+				accounts.insert(&Address::from(i), &account(&SHA3_NULL_RLP, &SHA3_EMPTY));
+			}
+		}
+		root
+	}
+
+	fn test_state(db: &mut MemoryDB) -> H256 {
+		let code = vec![0x60u8, 0x00, 0x60, 0x00, 0xf3];
+		let code_hash = db.insert(&code);
+		let mut storage_root = H256::new();
+		{
+			let mut storage = SecTrieDBMut::new(db, &mut storage_root);
+			for i in 1..20u64 {
+				storage.insert(&H256::from(i), &encode(&U256::from(i)));
+			}
+		}
+		let mut root = H256::new();
+		{
+			let mut accounts = SecTrieDBMut::new(db, &mut root);
+			for i in 1..50u64 {
+				accounts.insert(&Address::from(i), &account(&SHA3_NULL_RLP, &SHA3_EMPTY));
+			}
+			accounts.insert(&Address::from(100), &account(&storage_root, &code_hash));
+		}
+		root
+	}
+
+	#[test]
+	fn downloads_state() {
+		// given
+		let mut db = MemoryDB::new();
+		let root = test_state(&mut db);
+		let client = TestBlockChainClient::new();
+		let mut state = StateDownload::new();
+		state.reset_to(root);
+
+		// when
+		let mut requests = 0;
+		while !state.is_complete() {
+			let needed = state.needed_nodes(&client, 8);
+			assert!(!needed.is_empty());
+			let nodes = needed.iter().map(|h| db.lookup(h).unwrap().to_vec()).collect();
+			let verified = state.insert_nodes(nodes);
+			assert_eq!(verified.len(), needed.len());
+			client.import_state_data(verified);
+			requests += 1;
+		}
+
+		// then
+		assert!(requests > 1);
+		let mut check = StateDownload::new();
+		check.reset_to(root);
+		assert!(check.needed_nodes(&client, 8).is_empty());
+		assert!(check.is_complete());
+	}
+
+	#[test]
+	fn resumes_from_local_nodes() {
+		// given
+		let mut db = MemoryDB::new();
+		let root = test_state(&mut db);
+		let client = TestBlockChainClient::new();
+		client.import_state_data(db.keys().into_iter().filter(|&(_, refs)| refs > 0).map(|(h, _)| db.lookup(&h).unwrap().to_vec()).collect());
+		let mut state = StateDownload::new();
+		state.reset_to(root);
+
+		// when
+		let needed = state.needed_nodes(&client, 8);
+
+		// then
+		assert!(needed.is_empty());
+		assert!(state.is_complete());
+		assert_eq!(state.downloaded(), 0);
+	}
+
+	#[test]
+	fn bounds_local_lookups() {
+		// given
+		let mut db = MemoryDB::new();
+		let root = large_state(&mut db);
+		let client = TestBlockChainClient::new();
+		client.import_state_data(db.keys().into_iter().filter(|&(_, refs)| refs > 0).map(|(h, _)| db.lookup(&h).unwrap().to_vec()).collect());
+		let mut state = StateDownload::new();
+		state.reset_to(root);
+
+		// when
+		let needed = state.needed_nodes(&client, 8);
+
+		// then
+		assert!(needed.is_empty());
+		assert!(!state.is_complete());
+		let mut calls = 1;
+		while !state.is_complete() {
+			assert!(state.needed_nodes(&client, 8).is_empty());
+			calls += 1;
+		}
+		assert!(calls > 1);
+		assert_eq!(state.downloaded(), 0);
+	}
+
+	#[test]
+	fn rejects_unexpected_nodes() {
+		// given
+		let mut db = MemoryDB::new();
+		let root = test_state(&mut db);
+		let client = TestBlockChainClient::new();
+		let mut state = StateDownload::new();
+		state.reset_to(root);
+		let needed = state.needed_nodes(&client, 8);
+		assert_eq!(needed, vec![root]);
+
+		// when
+		let verified = state.insert_nodes(vec![vec![0xc0]]);
+
+		// then
+		assert!(verified.is_empty());
+		state.clear_node_download(&root);
+		assert_eq!(state.needed_nodes(&client, 8), vec![root]);
+	}
+}
@@ -24,6 +24,9 @@ pub struct TestIo<'p> {
 	pub chain: &'p mut TestBlockChainClient,
 	pub queue: &'p mut VecDeque<TestPacket>,
 	pub sender: Option<PeerId>,
+	pub reported: Vec<(PeerId, ReputationEvent)>,
+	pub throttled: HashSet<PeerId>,
+	pub disabled: HashSet<PeerId>,
 }
 
 impl<'p> TestIo<'p> {
@@ -31,22 +34,34 @@ impl<'p> TestIo<'p> {
 		TestIo {
 			chain: chain,
 			queue: queue,
-			sender: sender
+			sender: sender,
+			reported: Vec::new(),
+			throttled: HashSet::new(),
+			disabled: HashSet::new(),
 		}
 	}
 }
 
 impl<'p> SyncIo for TestIo<'p> {
-	fn disable_peer(&mut self, _peer_id: PeerId) {
+	fn disable_peer(&mut self, peer_id: PeerId) {
+		self.disabled.insert(peer_id);
 	}
 
 	fn disconnect_peer(&mut self, _peer_id: PeerId) {
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, event: ReputationEvent) {
+		self.reported.push((peer_id, event));
+	}
+
 	fn is_expired(&self) -> bool {
 		false
 	}
 
+	fn is_throttled(&self, peer_id: PeerId) -> bool {
+		self.throttled.contains(&peer_id)
+	}
+
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), UtilError> {
 		self.queue.push_back(TestPacket {
 			data: data,
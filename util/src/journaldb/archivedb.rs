@@ -169,6 +169,19 @@ impl JournalDB for ArchiveDB {
 		Ok((inserts + deletes) as u32)
 	}
 
+	fn inject(&mut self) -> Result<u32, UtilError> {
+		let batch = DBTransaction::new();
+		let mut inserts = 0usize;
+		for (key, (value, rc)) in self.overlay.drain() {
+			if rc > 0 {
+				try!(batch.put(&key.bytes(), &value));
+				inserts += 1;
+			}
+		}
+		try!(self.backing.write(batch));
+		Ok(inserts as u32)
+	}
+
 	fn latest_era(&self) -> Option<u64> { self.latest_era }
 
 	fn state(&self, id: &H256) -> Option<Bytes> {
@@ -423,4 +436,23 @@ mod tests {
 			assert!(state.is_some());
 		}
 	}
+
+	#[test]
+	fn inject() {
+		let mut dir = ::std::env::temp_dir();
+		dir.push(H32::random().hex());
+
+		let foo = {
+			let mut jdb = ArchiveDB::new(dir.to_str().unwrap(), None);
+			jdb.commit(0, &b"0".sha3(), None).unwrap();
+			let foo = jdb.insert(b"foo");
+			jdb.inject().unwrap();
+			foo
+		};
+
+		{
+			let jdb = ArchiveDB::new(dir.to_str().unwrap(), None);
+			assert!(jdb.exists(&foo));
+		}
+	}
 }
@@ -344,6 +344,30 @@ impl JournalDB for EarlyMergeDB {
  	}
 
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
+	fn inject(&mut self) -> Result<u32, UtilError> {
+		let batch = DBTransaction::new();
+		let mut inserts = 0usize;
+		{
+			let mut refs = self.refs.as_ref().unwrap().write().unwrap();
+			for (key, (value, rc)) in self.overlay.drain() {
+				if rc <= 0 {
+					continue;
+				}
+				match refs.get_mut(&key) {
+					Some(info) => if !info.in_archive {
+						// already in the backing DB, but would be deleted once its journal entry is reverted.
+						info.in_archive = true;
+						Self::set_already_in(&batch, &key);
+					},
+					None => try!(batch.put(&key.bytes(), &value)),
+				}
+				inserts += 1;
+			}
+		}
+		try!(self.backing.write(batch));
+		Ok(inserts as u32)
+	}
+
 	fn commit(&mut self, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError> {
 		// journal format:
 		// [era, 0] => [ id, [insert_0, ...], [remove_0, ...] ]
@@ -1064,4 +1088,22 @@ mod tests {
 			assert!(!jdb.exists(&bar));
 		}
 	}
+
+	#[test]
+	fn inject_keeps_reverted_key() {
+		let mut jdb = EarlyMergeDB::new_temp();
+		jdb.commit(0, &b"0".sha3(), None).unwrap();
+		let foo = jdb.insert(b"foo");
+		jdb.commit(1, &b"1a".sha3(), None).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+
+		jdb.insert(b"foo");
+		jdb.inject().unwrap();
+		assert!(jdb.can_reconstruct_refs());
+
+		jdb.commit(1, &b"1b".sha3(), None).unwrap();
+		jdb.commit(2, &b"2b".sha3(), Some((1, b"1b".sha3()))).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert!(jdb.exists(&foo));
+	}
 }
@@ -216,6 +216,19 @@ impl JournalDB for OverlayRecentDB {
 
 	fn latest_era(&self) -> Option<u64> { self.journal_overlay.read().unwrap().latest_era }
 
+	fn inject(&mut self) -> Result<u32, UtilError> {
+		let batch = DBTransaction::new();
+		let mut inserts = 0usize;
+		for (key, (value, rc)) in self.transaction_overlay.drain() {
+			if rc > 0 {
+				try!(batch.put(&key.bytes(), &value));
+				inserts += 1;
+			}
+		}
+		try!(self.backing.write(batch));
+		Ok(inserts as u32)
+	}
+
 	fn commit(&mut self, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError> {
 		// record new commit's details.
 		trace!("commit: #{} ({}), end era: {:?}", now, id, end);
@@ -896,4 +909,24 @@ mod tests {
 		assert!(jdb.exists(&foo));
 		assert!(jdb.exists(&bar));
 	}
+
+	#[test]
+	fn inject() {
+		let mut dir = ::std::env::temp_dir();
+		dir.push(H32::random().hex());
+
+		let foo = {
+			let mut jdb = OverlayRecentDB::new(dir.to_str().unwrap(), None);
+			jdb.commit(0, &b"0".sha3(), None).unwrap();
+			let foo = jdb.insert(b"foo");
+			jdb.inject().unwrap();
+			assert!(jdb.can_reconstruct_refs());
+			foo
+		};
+
+		{
+			let jdb = OverlayRecentDB::new(dir.to_str().unwrap(), None);
+			assert!(jdb.exists(&foo));
+		}
+	}
 }
@@ -116,6 +116,12 @@ impl JournalDB for RefCountedDB {
 
 	fn latest_era(&self) -> Option<u64> { self.latest_era }
 
+	fn inject(&mut self) -> Result<u32, UtilError> {
+		self.inserts.clear();
+		self.removes.clear();
+		self.forward.commit()
+	}
+
 	fn commit(&mut self, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError> {
 		// journal format:
 		// [era, 0] => [ id, [insert_0, ...], [remove_0, ...] ]
@@ -308,4 +314,23 @@ mod tests {
 		assert!(!jdb.exists(&baz));
 		assert!(!jdb.exists(&bar));
 	}
+
+	#[test]
+	fn inject() {
+		let mut dir = ::std::env::temp_dir();
+		dir.push(H32::random().hex());
+
+		let foo = {
+			let mut jdb = RefCountedDB::new(dir.to_str().unwrap(), None);
+			jdb.commit(0, &b"0".sha3(), None).unwrap();
+			let foo = jdb.insert(b"foo");
+			jdb.inject().unwrap();
+			foo
+		};
+
+		{
+			let jdb = RefCountedDB::new(dir.to_str().unwrap(), None);
+			assert!(jdb.exists(&foo));
+		}
+	}
 }
@@ -38,6 +38,10 @@ pub trait JournalDB : HashDB + Send + Sync {
 	/// old era to the backing database, reverting any non-canonical historical commit's inserts.
 	fn commit(&mut self, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError>;
 
+	/// Write all recent insert operations straight to the backing database, bypassing the journal.
+	/// Injected data is never reverted or pruned. Recent removals are discarded.
+	fn inject(&mut self) -> Result<u32, UtilError>;
+
 	/// State data query
 	fn state(&self, _id: &H256) -> Option<Bytes> {
 		None
@@ -133,6 +133,7 @@ pub mod using_queue;
 mod json_aid;
 pub mod vector;
 pub mod sha3;
+pub mod snappy;
 pub mod hashdb;
 pub mod memorydb;
 pub mod migration;
@@ -0,0 +1,237 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Network bandwidth limiting.
+
+use std::cmp;
+use std::sync::Mutex;
+use time;
+
+/// Upload and download rate limits in bytes per second. `None` stands for no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BandwidthLimit {
+	/// Upload limit.
+	pub upload: Option<usize>,
+	/// Download limit.
+	pub download: Option<usize>,
+}
+
+impl BandwidthLimit {
+	/// Returns true if either direction is limited.
+	pub fn is_limited(&self) -> bool {
+		self.upload.is_some() || self.download.is_some()
+	}
+}
+
+/// Token bucket refilled at a constant rate. Allows bursts of up to one second worth of tokens.
+#[derive(Debug)]
+pub struct TokenBucket {
+	/// Tokens added per second.
+	rate: usize,
+	/// Tokens currently available.
+	tokens: usize,
+	/// Last refill time in nanoseconds.
+	updated_ns: u64,
+}
+
+impl TokenBucket {
+	/// Create a full bucket with given rate.
+	pub fn new(rate: usize, now_ns: u64) -> TokenBucket {
+		TokenBucket {
+			rate: rate,
+			tokens: rate,
+			updated_ns: now_ns,
+		}
+	}
+
+	/// Number of tokens available at given time.
+	pub fn available(&mut self, now_ns: u64) -> usize {
+		if now_ns > self.updated_ns {
+			let refill = (now_ns - self.updated_ns).saturating_mul(self.rate as u64) / 1_000_000_000;
+			if refill > 0 {
+				let tokens = self.tokens as u64 + refill;
+				if tokens >= self.rate as u64 {
+					self.tokens = self.rate;
+					self.updated_ns = now_ns;
+				} else {
+					// Only advance by the time accounted for so that fractional tokens are not lost.
+					self.tokens = tokens as usize;
+					self.updated_ns += refill * 1_000_000_000 / self.rate as u64;
+				}
+			}
+		}
+		self.tokens
+	}
+
+	/// Take tokens out of the bucket.
+	pub fn consume(&mut self, tokens: usize) {
+		self.tokens = self.tokens.saturating_sub(tokens);
+	}
+}
+
+/// Upload and download token buckets.
+#[derive(Debug)]
+pub struct Throttle {
+	upload: Option<TokenBucket>,
+	download: Option<TokenBucket>,
+}
+
+impl Throttle {
+	/// Create a new throttle enforcing given limit.
+	pub fn new(limit: &BandwidthLimit) -> Throttle {
+		let now = time::precise_time_ns();
+		Throttle {
+			upload: limit.upload.map(|rate| TokenBucket::new(rate, now)),
+			download: limit.download.map(|rate| TokenBucket::new(rate, now)),
+		}
+	}
+
+	/// Number of bytes that may be sent at given time.
+	pub fn upload_allowance(&mut self, now_ns: u64) -> usize {
+		self.upload.as_mut().map_or(usize::max_value(), |b| b.available(now_ns))
+	}
+
+	/// Number of bytes that may be received at given time.
+	pub fn download_allowance(&mut self, now_ns: u64) -> usize {
+		self.download.as_mut().map_or(usize::max_value(), |b| b.available(now_ns))
+	}
+
+	/// Account for sent bytes.
+	pub fn consume_upload(&mut self, size: usize) {
+		if let Some(ref mut b) = self.upload {
+			b.consume(size);
+		}
+	}
+
+	/// Account for received bytes.
+	pub fn consume_download(&mut self, size: usize) {
+		if let Some(ref mut b) = self.download {
+			b.consume(size);
+		}
+	}
+}
+
+/// Bandwidth limits shared by all connections.
+pub struct BandwidthLimiter {
+	/// Throttle for all traffic.
+	global: Mutex<Throttle>,
+	/// Limit for each peer connection.
+	peer_limit: BandwidthLimit,
+	/// Set if any limit is configured.
+	limited: bool,
+}
+
+impl BandwidthLimiter {
+	/// Create a new limiter with given global and per-peer limits.
+	pub fn new(global: BandwidthLimit, peer: BandwidthLimit) -> BandwidthLimiter {
+		BandwidthLimiter {
+			global: Mutex::new(Throttle::new(&global)),
+			peer_limit: peer,
+			limited: global.is_limited() || peer.is_limited(),
+		}
+	}
+
+	/// Create a limiter that does not limit anything.
+	pub fn unlimited() -> BandwidthLimiter {
+		BandwidthLimiter::new(BandwidthLimit::default(), BandwidthLimit::default())
+	}
+
+	/// Returns true if any limit is configured.
+	pub fn is_limited(&self) -> bool {
+		self.limited
+	}
+
+	/// Create a throttle for a new peer connection.
+	pub fn peer_throttle(&self) -> Throttle {
+		Throttle::new(&self.peer_limit)
+	}
+
+	/// Number of bytes that may be sent by a connection with given throttle.
+	pub fn upload_allowance(&self, peer: &mut Throttle, now_ns: u64) -> usize {
+		if !self.limited {
+			return usize::max_value();
+		}
+		cmp::min(peer.upload_allowance(now_ns), self.global.lock().unwrap().upload_allowance(now_ns))
+	}
+
+	/// Number of bytes that may be received by a connection with given throttle.
+	pub fn download_allowance(&self, peer: &mut Throttle, now_ns: u64) -> usize {
+		if !self.limited {
+			return usize::max_value();
+		}
+		cmp::min(peer.download_allowance(now_ns), self.global.lock().unwrap().download_allowance(now_ns))
+	}
+
+	/// Account for bytes sent by a connection with given throttle.
+	pub fn consume_upload(&self, peer: &mut Throttle, size: usize) {
+		if self.limited {
+			peer.consume_upload(size);
+			self.global.lock().unwrap().consume_upload(size);
+		}
+	}
+
+	/// Account for bytes received by a connection with given throttle.
+	pub fn consume_download(&self, peer: &mut Throttle, size: usize) {
+		if self.limited {
+			peer.consume_download(size);
+			self.global.lock().unwrap().consume_download(size);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn token_bucket_refills_over_time() {
+		// given
+		let mut bucket = TokenBucket::new(1000, 0);
+
+		// when
+		bucket.consume(1000);
+
+		// then
+		assert_eq!(bucket.available(0), 0);
+		assert_eq!(bucket.available(500_000_000), 500);
+		assert_eq!(bucket.available(10_000_000_000), 1000);
+	}
+
+	#[test]
+	fn limiter_enforces_smallest_limit() {
+		// given
+		let limiter = BandwidthLimiter::new(
+			BandwidthLimit { upload: Some(1000), download: None },
+			BandwidthLimit { upload: Some(5000), download: Some(100) });
+		let mut peer = limiter.peer_throttle();
+
+		// when
+		limiter.consume_upload(&mut peer, 400);
+
+		// then (time before bucket creation, so nothing is refilled)
+		assert_eq!(limiter.upload_allowance(&mut peer, 0), 600);
+		assert_eq!(limiter.download_allowance(&mut peer, 0), 100);
+	}
+
+	#[test]
+	fn unlimited_limiter_allows_everything() {
+		let limiter = BandwidthLimiter::unlimited();
+		let mut peer = limiter.peer_throttle();
+		assert!(!limiter.is_limited());
+		assert_eq!(limiter.upload_allowance(&mut peer, 0), usize::max_value());
+		assert_eq!(limiter.download_allowance(&mut peer, 0), usize::max_value());
+	}
+}
@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::cmp;
 use std::collections::VecDeque;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
@@ -30,15 +31,20 @@ use io::{IoContext, StreamToken};
 use network::error::NetworkError;
 use network::handshake::Handshake;
 use network::stats::NetworkStats;
+use network::bandwidth::{BandwidthLimiter, Throttle};
 use crypto;
+use snappy;
 use rcrypto::blockmodes::*;
 use rcrypto::aessafe::*;
 use rcrypto::symmetriccipher::*;
 use rcrypto::buffer::*;
 use tiny_keccak::Keccak;
+use time;
 
 const ENCRYPTED_HEADER_LEN: usize = 32;
 const RECIEVE_PAYLOAD_TIMEOUT: u64 = 30000;
+/// Maximum packet payload size. Frame header length field is 3 bytes.
+const MAX_PAYLOAD_SIZE: usize = (1 << 24) - 1;
 
 pub trait GenericSocket : Read + Write {
 }
@@ -63,6 +69,18 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	stats: Arc<NetworkStats>,
 	/// Registered flag
 	registered: AtomicBool,
+	/// Bytes received over this connection
+	bytes_received: usize,
+	/// Bytes sent over this connection
+	bytes_sent: usize,
+	/// Shared bandwidth limits
+	limiter: Arc<BandwidthLimiter>,
+	/// Bandwidth limits for this connection, shared with its clones
+	throttle: Arc<Mutex<Throttle>>,
+	/// Reading is paused until more bandwidth is available
+	read_throttled: bool,
+	/// Writing is paused until more bandwidth is available
+	write_throttled: bool,
 }
 
 impl<Socket: GenericSocket> GenericConnection<Socket> {
@@ -81,10 +99,18 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		}
 		let sock_ref = <Socket as Read>::by_ref(&mut self.socket);
 		loop {
-			let max = self.rec_size - self.rec_buf.len();
+			let allowance = self.limiter.download_allowance(&mut self.throttle.lock().unwrap(), time::precise_time_ns());
+			self.read_throttled = allowance == 0;
+			if self.read_throttled {
+				trace!(target:"network", "{}: Download throttled", self.token);
+				return Ok(None);
+			}
+			let max = cmp::min(self.rec_size - self.rec_buf.len(), allowance);
 			match sock_ref.take(max as u64).try_read_buf(&mut self.rec_buf) {
 				Ok(Some(size)) if size != 0  => {
 					self.stats.inc_recv(size);
+					self.bytes_received += size;
+					self.limiter.consume_download(&mut self.throttle.lock().unwrap(), size);
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
@@ -120,33 +146,66 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		self.interest.is_writable()
 	}
 
+	/// Get number of bytes received over this connection.
+	pub fn bytes_received(&self) -> usize {
+		self.bytes_received
+	}
+
+	/// Get number of bytes sent over this connection.
+	pub fn bytes_sent(&self) -> usize {
+		self.bytes_sent
+	}
+
+	/// Check if reading has been paused by the bandwidth limit.
+	pub fn is_read_throttled(&self) -> bool {
+		self.read_throttled
+	}
+
+	/// Check if writing has been paused by the bandwidth limit.
+	pub fn is_write_throttled(&self) -> bool {
+		self.write_throttled && !self.send_queue.is_empty()
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, UtilError> where Message: Send + Clone {
 		if self.send_queue.is_empty() {
 			return Ok(WriteStatus::Complete)
 		}
-		{
+		let allowance = self.limiter.upload_allowance(&mut self.throttle.lock().unwrap(), time::precise_time_ns());
+		self.write_throttled = allowance == 0;
+		if self.write_throttled {
+			trace!(target:"network", "{}: Upload throttled", self.token);
+			return Ok(WriteStatus::Ongoing)
+		}
+		let (sent, status) = {
 			let buf = self.send_queue.front_mut().unwrap();
 			let send_size = buf.get_ref().len();
-			if (buf.position() as usize) >= send_size {
+			let position = buf.position() as usize;
+			if position >= send_size {
 				warn!(target:"net", "Unexpected connection data");
 				return Ok(WriteStatus::Complete)
 			}
-			match self.socket.try_write_buf(buf) {
-				Ok(Some(size)) if (buf.position() as usize) < send_size => {
-					self.stats.inc_send(size);
-					Ok(WriteStatus::Ongoing)
-				},
-				Ok(Some(size)) if (buf.position() as usize) == send_size => {
-					self.stats.inc_send(size);
-					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
-					Ok(WriteStatus::Complete)
+			let end = position + cmp::min(send_size - position, allowance);
+			match self.socket.try_write(&buf.get_ref()[position..end]) {
+				Ok(Some(size)) => {
+					buf.set_position((position + size) as u64);
+					if position + size < send_size {
+						(size, WriteStatus::Ongoing)
+					} else {
+						trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
+						(size, WriteStatus::Complete)
+					}
 				},
-				Ok(Some(_)) => { panic!("Wrote past buffer");},
-				Ok(None) => Ok(WriteStatus::Ongoing),
+				Ok(None) => (0, WriteStatus::Ongoing),
 				Err(e) => try!(Err(e))
 			}
-		}.and_then(|r| {
+		};
+		self.stats.inc_send(sent);
+		self.bytes_sent += sent;
+		self.limiter.consume_upload(&mut self.throttle.lock().unwrap(), sent);
+		// Wait for the next bandwidth refill if the whole allowance has been used.
+		self.write_throttled = sent == allowance;
+		Ok(status).and_then(|r| {
 			if r == WriteStatus::Complete {
 				self.send_queue.pop_front();
 			}
@@ -164,7 +223,8 @@ pub type Connection = GenericConnection<TcpStream>;
 
 impl Connection {
 	/// Create a new connection with given id and socket.
-	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>) -> Connection {
+	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>, limiter: Arc<BandwidthLimiter>) -> Connection {
+		let throttle = Arc::new(Mutex::new(limiter.peer_throttle()));
 		Connection {
 			token: token,
 			socket: socket,
@@ -174,6 +234,12 @@ impl Connection {
 			interest: EventSet::hup() | EventSet::readable(),
 			stats: stats,
 			registered: AtomicBool::new(false),
+			bytes_received: 0,
+			bytes_sent: 0,
+			limiter: limiter,
+			throttle: throttle,
+			read_throttled: false,
+			write_throttled: false,
 		}
 	}
 
@@ -187,6 +253,11 @@ impl Connection {
 		self.socket.peer_addr()
 	}
 
+	/// Get local address of the connection
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.socket.local_addr()
+	}
+
 	/// Get remote peer address string
 	pub fn remote_addr_str(&self) -> String {
 		self.socket.peer_addr().map(|a| a.to_string()).unwrap_or_else(|_| "Unknown".to_owned())
@@ -203,6 +274,12 @@ impl Connection {
 			interest: EventSet::hup(),
 			stats: self.stats.clone(),
 			registered: AtomicBool::new(false),
+			bytes_received: self.bytes_received,
+			bytes_sent: self.bytes_sent,
+			limiter: self.limiter.clone(),
+			throttle: self.throttle.clone(),
+			read_throttled: false,
+			write_throttled: false,
 		})
 	}
 
@@ -284,6 +361,8 @@ pub struct EncryptedConnection {
 	protocol_id: u16,
 	/// Payload expected to be received for the last header.
 	payload_len: usize,
+	/// Packet data is Snappy compressed.
+	compression: bool,
 }
 
 impl EncryptedConnection {
@@ -334,14 +413,30 @@ impl EncryptedConnection {
 			ingress_mac: ingress_mac,
 			read_state: EncryptedConnectionState::Header,
 			protocol_id: 0,
-			payload_len: 0
+			payload_len: 0,
+			compression: false,
 		};
 		enc.connection.expect(ENCRYPTED_HEADER_LEN);
 		Ok(enc)
 	}
 
+	/// Enable or disable Snappy compression of packet data. Takes effect for the next packet sent or received.
+	pub fn set_compression(&mut self, compression: bool) {
+		self.compression = compression;
+	}
+
 	/// Send a packet
 	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, payload: &[u8]) -> Result<(), UtilError> where Message: Send + Clone {
+		let compressed;
+		let payload = if self.compression {
+			compressed = try!(compress_packet(payload));
+			&compressed[..]
+		} else {
+			payload
+		};
+		if payload.len() > MAX_PAYLOAD_SIZE {
+			return Err(From::from(NetworkError::OversizedPacket));
+		}
 		let mut header = RlpStream::new();
 		let len = payload.len() as usize;
 		header.append_raw(&[(len >> 16) as u8, (len >> 8) as u8, len as u8], 1);
@@ -417,6 +512,9 @@ impl EncryptedConnection {
 		self.decoder.decrypt(&mut RefReadBuffer::new(&payload[0..self.payload_len]), &mut RefWriteBuffer::new(&mut packet), false).expect("Invalid length or padding");
 		let mut pad_buf = [0u8; 16];
 		self.decoder.decrypt(&mut RefReadBuffer::new(&payload[self.payload_len..(payload.len() - 16)]), &mut RefWriteBuffer::new(&mut pad_buf), false).expect("Invalid length or padding");
+		if self.compression {
+			packet = try!(decompress_packet(&packet));
+		}
 		Ok(Packet {
 			protocol: self.protocol_id,
 			data: packet
@@ -465,6 +563,44 @@ impl EncryptedConnection {
 	}
 }
 
+/// Length of the RLP encoded packet id at the start of the packet.
+fn packet_id_len(payload: &[u8]) -> Result<usize, UtilError> {
+	match payload.first() {
+		// packet id is an integer, anything but a short string is invalid
+		Some(&b) if b < 0xb8 => {
+			let len = try!(PayloadInfo::from(payload)).total();
+			if len > payload.len() {
+				return Err(From::from(NetworkError::BadProtocol));
+			}
+			Ok(len)
+		},
+		_ => Err(From::from(NetworkError::BadProtocol)),
+	}
+}
+
+/// Compress packet data. The RLP encoded packet id is left as is.
+fn compress_packet(payload: &[u8]) -> Result<Bytes, UtilError> {
+	let id_len = try!(packet_id_len(payload));
+	let mut packet = Vec::with_capacity(id_len + snappy::max_compressed_len(payload.len() - id_len));
+	packet.extend_from_slice(&payload[..id_len]);
+	packet.extend_from_slice(&snappy::compress(&payload[id_len..]));
+	Ok(packet)
+}
+
+/// Decompress packet data, refusing anything that would expand over `MAX_PAYLOAD_SIZE`.
+fn decompress_packet(payload: &[u8]) -> Result<Bytes, UtilError> {
+	let id_len = try!(packet_id_len(payload));
+	let len = try!(snappy::decompressed_len(&payload[id_len..]).map_err(|_| NetworkError::BadProtocol));
+	if len > MAX_PAYLOAD_SIZE {
+		return Err(From::from(NetworkError::OversizedPacket));
+	}
+	let data = try!(snappy::decompress(&payload[id_len..]).map_err(|_| NetworkError::BadProtocol));
+	let mut packet = Vec::with_capacity(id_len + data.len());
+	packet.extend_from_slice(&payload[..id_len]);
+	packet.extend_from_slice(&data);
+	Ok(packet)
+}
+
 #[test]
 pub fn test_encryption() {
 	use hash::*;
@@ -499,6 +635,7 @@ mod tests {
 	use bytes::*;
 	use devtools::*;
 	use io::*;
+	use network::bandwidth::{BandwidthLimiter, BandwidthLimit};
 
 	impl GenericSocket for TestSocket {}
 
@@ -543,6 +680,12 @@ mod tests {
 				interest: EventSet::hup() | EventSet::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
 				registered: AtomicBool::new(false),
+				bytes_received: 0,
+				bytes_sent: 0,
+				limiter: Arc::new(BandwidthLimiter::unlimited()),
+				throttle: Arc::new(Mutex::new(BandwidthLimiter::unlimited().peer_throttle())),
+				read_throttled: false,
+				write_throttled: false,
 			}
 		}
 	}
@@ -566,6 +709,12 @@ mod tests {
 				interest: EventSet::hup() | EventSet::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
 				registered: AtomicBool::new(false),
+				bytes_received: 0,
+				bytes_sent: 0,
+				limiter: Arc::new(BandwidthLimiter::unlimited()),
+				throttle: Arc::new(Mutex::new(BandwidthLimiter::unlimited().peer_throttle())),
+				read_throttled: false,
+				write_throttled: false,
 			}
 		}
 	}
@@ -640,6 +789,65 @@ mod tests {
 		assert_eq!(1024, connection.socket.cursor);
 	}
 
+	#[test]
+	fn connection_counts_bytes() {
+		let mut connection = TestConnection::new();
+		connection.socket = TestSocket::new_buf(1024);
+		connection.send_queue.push_back(Cursor::new(vec![0; 10240]));
+		connection.writable(&test_io()).unwrap();
+		connection.rec_size = 512;
+		connection.socket.read_buffer = vec![99; 512];
+
+		connection.readable().unwrap();
+
+		assert_eq!(1024, connection.bytes_sent());
+		assert_eq!(512, connection.bytes_received());
+	}
+
+	#[test]
+	fn connection_write_is_throttled() {
+		let mut connection = TestConnection::new();
+		let limiter = Arc::new(BandwidthLimiter::new(BandwidthLimit { upload: Some(1000), download: None }, BandwidthLimit::default()));
+		connection.throttle = Arc::new(Mutex::new(limiter.peer_throttle()));
+		connection.limiter = limiter;
+		connection.send_queue.push_back(Cursor::new(vec![0; 10240]));
+
+		let status = connection.writable(&test_io());
+
+		assert!(WriteStatus::Ongoing == status.unwrap());
+		assert!(connection.socket.write_buffer.len() <= 1000);
+		assert!(connection.is_write_throttled());
+	}
+
+	#[test]
+	fn connection_clone_shares_throttle() {
+		let listener = ::mio::tcp::TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+		let socket = ::mio::tcp::TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+		let limiter = Arc::new(BandwidthLimiter::new(BandwidthLimit::default(), BandwidthLimit { upload: Some(1000), download: None }));
+		let connection = super::Connection::new(0, socket, Arc::new(NetworkStats::new()), limiter.clone());
+
+		let clone = connection.try_clone().unwrap();
+		limiter.consume_upload(&mut clone.throttle.lock().unwrap(), 1000);
+
+		assert_eq!(limiter.upload_allowance(&mut connection.throttle.lock().unwrap(), 0), 0);
+	}
+
+	#[test]
+	fn connection_read_is_throttled() {
+		let mut connection = TestConnection::new();
+		let limiter = Arc::new(BandwidthLimiter::new(BandwidthLimit::default(), BandwidthLimit { upload: None, download: Some(512) }));
+		connection.throttle = Arc::new(Mutex::new(limiter.peer_throttle()));
+		connection.limiter = limiter;
+		connection.rec_size = 2048;
+		connection.socket.read_buffer = vec![99; 2048];
+
+		let status = connection.readable();
+
+		assert!(status.unwrap().is_none());
+		assert!(connection.rec_buf.len() <= 512);
+		assert!(connection.is_read_throttled());
+	}
+
 	#[test]
 	fn connection_read_from_broken() {
 		let mut connection = TestBrokenConnection::new();
@@ -672,4 +880,44 @@ mod tests {
 		assert!(status.is_ok());
 		assert_eq!(0, connection.socket.cursor);
 	}
+
+	#[test]
+	fn compressed_packet_roundtrip() {
+		let mut payload = vec![0x10u8];
+		payload.extend_from_slice(&[0x42u8; 4096]);
+
+		let compressed = super::compress_packet(&payload).unwrap();
+		assert_eq!(compressed[0], 0x10);
+		assert!(compressed.len() < payload.len());
+		assert_eq!(super::decompress_packet(&compressed).unwrap(), payload);
+	}
+
+	#[test]
+	fn compressed_packet_keeps_multibyte_id() {
+		// packet id 0 is encoded as an empty string, 0x80 as a single byte string
+		for id in &[vec![0x80u8], vec![0x81u8, 0x80]] {
+			let mut payload = id.clone();
+			payload.extend_from_slice(&[0x42u8; 4096]);
+
+			let compressed = super::compress_packet(&payload).unwrap();
+			assert_eq!(&compressed[..id.len()], &id[..]);
+			assert_eq!(super::decompress_packet(&compressed).unwrap(), payload);
+		}
+		// lists and truncated ids are not valid packet ids
+		assert!(super::compress_packet(&[0xc0u8, 0x00]).is_err());
+		assert!(super::compress_packet(&[0x82u8, 0x01]).is_err());
+		assert!(super::decompress_packet(&[]).is_err());
+	}
+
+	#[test]
+	fn compressed_packet_size_is_limited() {
+		// packet id followed by a snappy preamble declaring 2^24 bytes of data
+		let bomb = vec![0x10u8, 0x80, 0x80, 0x80, 0x08];
+
+		match super::decompress_packet(&bomb) {
+			Err(::error::UtilError::Network(::network::error::NetworkError::OversizedPacket)) => {},
+			_ => panic!("Oversized packet should be rejected"),
+		}
+		assert!(super::decompress_packet(&[0x10u8, 0x05, 0x00]).is_err());
+	}
 }
@@ -28,10 +28,10 @@ use crypto::*;
 use rlp::*;
 use network::node_table::*;
 use network::error::NetworkError;
+use network::ip_filter::IpFilter;
 use io::StreamToken;
 
-use network::PROTOCOL_VERSION;
-
+const PROTOCOL_VERSION: u32 = 4;							// Discovery protocol version.
 const ADDRESS_BYTES_SIZE: u32 = 32;							// Size of address type in bytes.
 const ADDRESS_BITS: u32 = 8 * ADDRESS_BYTES_SIZE;			// Denoted by n in [Kademlia].
 const NODE_BINS: u32 = ADDRESS_BITS - 1;					// Size of m_state (excludes root, which is us).
@@ -93,6 +93,7 @@ pub struct Discovery {
 	node_buckets: Vec<NodeBucket>,
 	send_queue: VecDeque<Datagramm>,
 	check_timestamps: bool,
+	ip_filter: IpFilter,
 }
 
 pub struct TableUpdates {
@@ -101,7 +102,7 @@ pub struct TableUpdates {
 }
 
 impl Discovery {
-	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken) -> Discovery {
+	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, ip_filter: IpFilter) -> Discovery {
 		let socket = UdpSocket::bound(&listen).expect("Error binding UDP socket");
 		Discovery {
 			id: key.public().clone(),
@@ -115,6 +116,7 @@ impl Discovery {
 			udp_socket: socket,
 			send_queue: VecDeque::new(),
 			check_timestamps: true,
+			ip_filter: ip_filter,
 		}
 	}
 
@@ -368,10 +370,13 @@ impl Discovery {
 		try!(self.check_timestamp(timestamp));
 		let mut added_map = HashMap::new();
 		let entry = NodeEntry { id: node.clone(), endpoint: source.clone() };
-		if !entry.endpoint.is_valid() || !entry.endpoint.is_global() {
+		if !entry.endpoint.is_valid() {
 			debug!(target: "discovery", "Got bad address: {:?}", entry);
-		}
-		else {
+		} else if !entry.endpoint.is_allowed(&self.ip_filter) {
+			debug!(target: "discovery", "Address not allowed: {:?}", entry);
+		} else if !entry.endpoint.is_global() && !self.ip_filter.allows_non_global(&entry.endpoint.address) {
+			debug!(target: "discovery", "Got non-global address: {:?}", entry);
+		} else {
 			self.update_node(entry.clone());
 			added_map.insert(node.clone(), entry);
 		}
@@ -444,6 +449,10 @@ impl Discovery {
 				debug!(target: "discovery", "Bad address: {:?}", endpoint);
 				continue;
 			}
+			if !endpoint.is_allowed(&self.ip_filter) {
+				debug!(target: "discovery", "Address not allowed: {:?}", endpoint);
+				continue;
+			}
 			let node_id: NodeId = try!(r.val_at(3));
 			if node_id == self.id {
 				continue;
@@ -511,6 +520,7 @@ mod tests {
 	use std::net::*;
 	use network::node_table::*;
 	use crypto::KeyPair;
+	use network::ip_filter::IpFilter;
 	use std::str::FromStr;
 	use rustc_serialize::hex::FromHex;
 
@@ -537,8 +547,8 @@ mod tests {
 		let key2 = KeyPair::create().unwrap();
 		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40444").unwrap(), udp_port: 40444 };
 		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40445").unwrap(), udp_port: 40445 };
-		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0);
-		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0);
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default());
 
 		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7770").unwrap();
 		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7771").unwrap();
@@ -570,7 +580,7 @@ mod tests {
 	fn removes_expired() {
 		let key = KeyPair::create().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40446").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0);
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
 		for _ in 0..1200 {
 			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
 		}
@@ -579,11 +589,36 @@ mod tests {
 		assert!(removed > 0);
 	}
 
+	#[test]
+	fn ignores_non_global_ping_unless_allowed() {
+		let key = KeyPair::create().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40448").unwrap(), udp_port: 40448 };
+		let from = SocketAddr::from_str("99.99.99.99:40445").unwrap();
+		// ping from 127.0.0.1:3322
+		let packet = "\
+		e9614ccfd9fc3e74360018522d30e1419a143407ffcce748de3e22116b7e8dc92ff74788c0b6663a\
+		aa3d67d641936511c8f8d6ad8698b820a7cf9e1be7155e9a241f556658c55428ec0563514365799a\
+		4be2be5a685a80971ddcfa80cb422cdd0101ec04cb847f000001820cfa8215a8d790000000000000\
+		000000000000000000018208ae820d058443b9a3550102\
+		".from_hex().unwrap();
+
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
+		discovery.check_timestamps = false;
+		let updates = discovery.on_packet(&packet, from.clone()).unwrap().unwrap();
+		assert!(updates.added.is_empty());
+
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40449").unwrap(), udp_port: 40449 };
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::parse("private").unwrap());
+		discovery.check_timestamps = false;
+		let updates = discovery.on_packet(&packet, from.clone()).unwrap().unwrap();
+		assert_eq!(updates.added.len(), 1);
+	}
+
 	#[test]
 	fn packets() {
 		let key = KeyPair::create().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0);
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
 		discovery.check_timestamps = false;
 		let from = SocketAddr::from_str("99.99.99.99:40445").unwrap();
 
@@ -92,6 +92,8 @@ pub enum NetworkError {
 	Expired,
 	/// Peer not found.
 	PeerNotFound,
+	/// Packet size is over the protocol limit.
+	OversizedPacket,
 	/// Peer is diconnected.
 	Disconnect(DisconnectReason),
 	/// Socket IO error.
@@ -107,6 +109,7 @@ impl fmt::Display for NetworkError {
 			BadProtocol => "Bad protocol".into(),
 			Expired => "Expired message".into(),
 			PeerNotFound => "Peer not found".into(),
+			OversizedPacket => "Packet is too large".into(),
 			Disconnect(ref reason) => format!("Peer disconnected: {}", reason),
 			Io(ref err) => format!("Socket I/O error: {}", err),
 		};
@@ -29,6 +29,7 @@ use network::node_table::NodeId;
 use error::*;
 use network::error::NetworkError;
 use network::stats::NetworkStats;
+use network::bandwidth::BandwidthLimiter;
 use io::{IoContext, StreamToken};
 
 #[derive(PartialEq, Eq, Debug)]
@@ -84,10 +85,10 @@ const ECIES_OVERHEAD: usize = 113;
 
 impl Handshake {
 	/// Create a new handshake object
-	pub fn new(token: StreamToken, id: Option<&NodeId>, socket: TcpStream, nonce: &H256, stats: Arc<NetworkStats>) -> Result<Handshake, UtilError> {
+	pub fn new(token: StreamToken, id: Option<&NodeId>, socket: TcpStream, nonce: &H256, stats: Arc<NetworkStats>, limiter: Arc<BandwidthLimiter>) -> Result<Handshake, UtilError> {
 		Ok(Handshake {
 			id: if let Some(id) = id { id.clone()} else { NodeId::new() },
-			connection: Connection::new(token, socket, stats),
+			connection: Connection::new(token, socket, stats, limiter),
 			originated: false,
 			state: HandshakeState::New,
 			ecdhe: try!(KeyPair::create()),
@@ -345,6 +346,7 @@ mod test {
 	use std::net::SocketAddr;
 	use mio::tcp::TcpStream;
 	use network::stats::NetworkStats;
+	use network::bandwidth::BandwidthLimiter;
 
 	fn check_auth(h: &Handshake, version: u64) {
 		assert_eq!(h.id, Public::from_str("fda1cff674c90c9a197539fe3dfb53086ace64f83ed7c6eabec741f7f381cc803e52ab2cd55d5569bce4347107a310dfd5f88a010cd2ffd1005ca406f1842877").unwrap());
@@ -363,7 +365,7 @@ mod test {
 		let addr = SocketAddr::from_str("127.0.0.1:50556").unwrap();
 		let socket = TcpStream::connect(&addr).unwrap();
 		let nonce = H256::new();
-		Handshake::new(0, to, socket, &nonce, Arc::new(NetworkStats::new())).unwrap()
+		Handshake::new(0, to, socket, &nonce, Arc::new(NetworkStats::new()), Arc::new(BandwidthLimiter::unlimited())).unwrap()
 	}
 
 	fn test_io() -> IoContext<i32> {
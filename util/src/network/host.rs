@@ -32,16 +32,19 @@ use misc::version;
 use crypto::*;
 use sha3::Hashable;
 use rlp::*;
-use network::session::{Session, SessionData};
+use network::session::{Session, SessionData, PeerSessionInfo};
 use error::*;
 use io::*;
-use network::{NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION};
+use network::{NetworkProtocolHandler, NonReservedPeerMode, ReputationEvent, PROTOCOL_VERSION};
 use network::node_table::*;
 use network::stats::NetworkStats;
 use network::error::{NetworkError, DisconnectReason};
 use network::discovery::{Discovery, TableUpdates, NodeEntry};
 use network::ip_utils::{map_external_address, select_public_address};
+use network::ip_filter::IpFilter;
+use network::bandwidth::{BandwidthLimit, BandwidthLimiter};
 use path::restrict_permissions_owner;
+use time;
 
 type Slab<T> = ::slab::Slab<T, usize>;
 
@@ -49,6 +52,7 @@ const MAX_SESSIONS: usize = 1024 + MAX_HANDSHAKES;
 const MAX_HANDSHAKES: usize = 80;
 const MAX_HANDSHAKES_PER_ROUND: usize = 32;
 const MAINTENANCE_TIMEOUT: u64 = 1000;
+const BANDWIDTH_TIMEOUT: u64 = 100;
 
 #[derive(Debug, Clone)]
 /// Network service configuration
@@ -75,6 +79,14 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
+	/// IP filter applied to incoming connections, dialed nodes and discovered addresses.
+	pub ip_filter: IpFilter,
+	/// Upload and download limits for all connections combined.
+	pub global_bandwidth: BandwidthLimit,
+	/// Upload and download limits for each peer connection.
+	pub peer_bandwidth: BandwidthLimit,
+	/// Compress packets with Snappy when the peer supports it.
+	pub compression: bool,
 }
 
 impl Default for NetworkConfiguration {
@@ -98,6 +110,10 @@ impl NetworkConfiguration {
 			ideal_peers: 25,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			ip_filter: IpFilter::default(),
+			global_bandwidth: BandwidthLimit::default(),
+			peer_bandwidth: BandwidthLimit::default(),
+			compression: true,
 		}
 	}
 
@@ -125,6 +141,7 @@ const DISCOVERY_REFRESH: usize = SYS_TIMER + 4;
 const DISCOVERY_ROUND: usize = SYS_TIMER + 5;
 const INIT_PUBLIC: usize = SYS_TIMER + 6;
 const NODE_TABLE: usize = SYS_TIMER + 7;
+const BANDWIDTH: usize = SYS_TIMER + 8;
 const FIRST_SESSION: usize = 0;
 const LAST_SESSION: usize = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: usize = LAST_SESSION + 256;
@@ -144,8 +161,8 @@ pub enum NetworkIoMessage<Message> where Message: Send + Sync + Clone {
 		handler: Arc<NetworkProtocolHandler<Message> + Sync>,
 		/// Protocol Id.
 		protocol: ProtocolId,
-		/// Supported protocol versions.
-		versions: Vec<u8>,
+		/// Supported protocol versions and number of packet IDs reserved by each version.
+		versions: Vec<(u8, u8)>,
 	},
 	/// Register a new protocol timer
 	AddTimer {
@@ -160,6 +177,8 @@ pub enum NetworkIoMessage<Message> where Message: Send + Sync + Clone {
 	Disconnect(PeerId),
 	/// Disconnect and temporary disable peer.
 	DisablePeer(PeerId),
+	/// Adjust peer reputation.
+	ReportPeer(PeerId, ReputationEvent),
 	/// User message
 	User(Message),
 }
@@ -256,6 +275,19 @@ impl<'s, Message> NetworkContext<'s, Message> where Message: Send + Sync + Clone
 		self.io.message(NetworkIoMessage::Disconnect(peer));
 	}
 
+	/// Report peer behaviour. Peers with bad reputation get disconnected and banned for a while.
+	pub fn report_peer(&self, peer: PeerId, event: ReputationEvent) {
+		self.io.message(NetworkIoMessage::ReportPeer(peer, event));
+	}
+
+	/// Check if sending to or receiving from the peer is paused by the bandwidth limit.
+	pub fn is_throttled(&self, peer: PeerId) -> bool {
+		self.resolve_session(peer).map_or(false, |s| {
+			let s = s.lock().unwrap();
+			s.is_read_throttled() || s.is_write_throttled()
+		})
+	}
+
 	/// Check if the session is still active.
 	pub fn is_expired(&self) -> bool {
 		self.session.as_ref().map_or(false, |s| s.lock().unwrap().expired())
@@ -317,6 +349,11 @@ impl HostInfo {
 		self.nonce = self.nonce.sha3();
 		self.nonce.clone()
 	}
+
+	/// Returns true if packets should be compressed for peers supporting it.
+	pub fn compression(&self) -> bool {
+		self.config.compression
+	}
 }
 
 type SharedSession = Arc<Mutex<Session>>;
@@ -338,6 +375,9 @@ pub struct Host<Message> where Message: Send + Sync + Clone {
 	timers: RwLock<HashMap<TimerToken, ProtocolTimer>>,
 	timer_counter: RwLock<usize>,
 	stats: Arc<NetworkStats>,
+	bandwidth: Arc<BandwidthLimiter>,
+	/// Time and total bytes sent and received at the last rate update.
+	rate_sample: Mutex<(u64, usize, usize)>,
 	reserved_nodes: RwLock<HashSet<NodeId>>,
 	num_sessions: AtomicUsize,
 	stopping: AtomicBool,
@@ -373,6 +413,7 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let bandwidth = Arc::new(BandwidthLimiter::new(config.global_bandwidth, config.peer_bandwidth));
 
 		let mut host = Host::<Message> {
 			info: RwLock::new(HostInfo {
@@ -393,6 +434,8 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			timers: RwLock::new(HashMap::new()),
 			timer_counter: RwLock::new(USER_TIMER),
 			stats: stats,
+			bandwidth: bandwidth,
+			rate_sample: Mutex::new((time::precise_time_ns(), 0, 0)),
 			reserved_nodes: RwLock::new(HashSet::new()),
 			num_sessions: AtomicUsize::new(0),
 			stopping: AtomicBool::new(false),
@@ -474,10 +517,54 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		Ok(())
 	}
 
+	/// Ban a node and its address until unbanned. Disconnects it if connected.
+	pub fn ban_node(&self, id: &NodeId, io: &IoContext<NetworkIoMessage<Message>>) {
+		let ip = self.sessions.read().unwrap().iter()
+			.map(|e| e.lock().unwrap())
+			.find(|s| s.id() == Some(id))
+			.and_then(|s| s.remote_addr().ok())
+			.map(|a| a.ip());
+		self.nodes.write().unwrap().ban(id, ip, None);
+		let mut to_kill = Vec::new();
+		for e in self.sessions.write().unwrap().iter_mut() {
+			let mut s = e.lock().unwrap();
+			if s.id() == Some(id) {
+				s.disconnect(io, DisconnectReason::DisconnectRequested);
+				to_kill.push(s.token());
+			}
+		}
+		for p in to_kill {
+			trace!(target: "network", "Disconnecting banned peer: {}", p);
+			self.kill_connection(p, io, false);
+		}
+	}
+
+	/// Remove a node from the ban list. Returns false if the node was not banned.
+	pub fn unban_node(&self, id: &NodeId) -> bool {
+		self.nodes.write().unwrap().unban(id)
+	}
+
+	/// Returns banned nodes with ban expiration time.
+	pub fn banned_nodes(&self) -> Vec<(NodeId, Option<u64>)> {
+		self.nodes.read().unwrap().banned_nodes()
+	}
+
 	pub fn client_version() -> String {
 		version()
 	}
 
+	/// Returns details of all established sessions.
+	pub fn sessions_info(&self) -> Vec<(PeerId, PeerSessionInfo)> {
+		let sessions = self.sessions.read().unwrap();
+		let info = sessions.iter()
+			.filter_map(|s| {
+				let s = s.lock().unwrap();
+				if s.is_ready() && !s.expired() { Some((s.token(), s.peer_info())) } else { None }
+			})
+			.collect();
+		info
+	}
+
 	pub fn external_url(&self) -> Option<String> {
 		self.info.read().unwrap().public_endpoint.as_ref().map(|e| format!("{}", Node::new(self.info.read().unwrap().id().clone(), e.clone())))
 	}
@@ -537,7 +624,7 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		let discovery = {
 			let info = self.info.read().unwrap();
 			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
-				Some(Discovery::new(&info.keys, public_endpoint.address.clone(), public_endpoint, DISCOVERY))
+				Some(Discovery::new(&info.keys, public_endpoint.address.clone(), public_endpoint, DISCOVERY, info.config.ip_filter.clone()))
 			} else { None }
 		};
 
@@ -559,6 +646,41 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage<Message>>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
+		self.update_rates();
+	}
+
+	/// Update current upload and download rates in network stats.
+	fn update_rates(&self) {
+		let now = time::precise_time_ns();
+		let (send, recv) = (self.stats.send(), self.stats.recv());
+		let mut sample = self.rate_sample.lock().unwrap();
+		let elapsed_ms = (now - sample.0) / 1_000_000;
+		if elapsed_ms > 0 {
+			let rate = |current: usize, previous: usize| ((current - previous) as u64 * 1000 / elapsed_ms) as usize;
+			self.stats.set_rates(rate(send, sample.1), rate(recv, sample.2));
+			*sample = (now, send, recv);
+		}
+	}
+
+	/// Resume IO on connections paused by the bandwidth limit.
+	fn resume_throttled(&self, io: &IoContext<NetworkIoMessage<Message>>) {
+		let mut to_read = Vec::new();
+		let mut to_write = Vec::new();
+		for e in self.sessions.read().unwrap().iter() {
+			let s = e.lock().unwrap();
+			if s.is_read_throttled() {
+				to_read.push(s.token());
+			}
+			if s.is_write_throttled() {
+				to_write.push(s.token());
+			}
+		}
+		for token in to_write {
+			self.session_writable(token, io);
+		}
+		for token in to_read {
+			self.session_readable(token, io);
+		}
 	}
 
 	fn have_session(&self, id: &NodeId) -> bool {
@@ -593,14 +715,14 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 	}
 
 	fn connect_peers(&self, io: &IoContext<NetworkIoMessage<Message>>) {
-		let (ideal_peers, mut pin) = {
+		let (ideal_peers, mut pin, ip_filter) = {
 			let info = self.info.read().unwrap();
 			if info.capabilities.is_empty() {
 				return;
 			}
 			let config = &info.config;
 
-			(config.ideal_peers, config.non_reserved_mode == NonReservedPeerMode::Deny)
+			(config.ideal_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.ip_filter.clone())
 		};
 
 		let session_count = self.session_count();
@@ -625,13 +747,13 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		// iterate over all nodes, reserved ones coming first.
 		// if we are pinned to only reserved nodes, ignore all others.
 		let nodes = reserved_nodes.iter().cloned().chain(if !pin {
-			self.nodes.read().unwrap().nodes()
+			self.nodes.read().unwrap().nodes(&ip_filter)
 		} else {
 			Vec::new()
 		});
 
 		let mut started: usize = 0;
-		for id in nodes.filter(|ref id| !self.have_session(id) && !self.connecting_to(id))
+		for id in nodes.filter(|ref id| !self.have_session(id) && !self.connecting_to(id) && !self.nodes.read().unwrap().is_banned(id))
 			.take(min(MAX_HANDSHAKES_PER_ROUND, handshake_limit - handshake_count)) {
 			self.connect_peer(&id, io);
 			started += 1;
@@ -654,14 +776,23 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		let socket = {
 			let address = {
 				let mut nodes = self.nodes.write().unwrap();
-				if let Some(node) = nodes.get_mut(id) {
+				if nodes.is_banned(id) {
+					debug!(target: "network", "Connection to banned node aborted");
+					return;
+				}
+				let address = if let Some(node) = nodes.get_mut(id) {
 					node.last_attempted = Some(::time::now());
 					node.endpoint.address
 				}
 				else {
 					debug!(target: "network", "Connection to expired node aborted");
 					return;
+				};
+				if nodes.is_banned_ip(&address.ip()) {
+					debug!(target: "network", "Connection to banned address {:?} aborted", address);
+					return;
 				}
+				address
 			};
 			match TcpStream::connect(&address) {
 				Ok(socket) => socket,
@@ -681,7 +812,7 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		let nonce = self.info.write().unwrap().next_nonce();
 		let mut sessions = self.sessions.write().unwrap();
 		let token = sessions.insert_with_opt(|token| {
-			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), &self.info.read().unwrap()) {
+			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), self.bandwidth.clone(), &self.info.read().unwrap()) {
 				Ok(s) => Some(Arc::new(Mutex::new(s))),
 				Err(e) => {
 					debug!(target: "network", "Session create error: {:?}", e);
@@ -704,7 +835,17 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		loop {
 			let socket = match self.tcp_listener.lock().unwrap().accept() {
 				Ok(None) => break,
-				Ok(Some((sock, _addr))) => sock,
+				Ok(Some((sock, addr))) => {
+					if !self.info.read().unwrap().config.ip_filter.allows(&addr) {
+						debug!(target: "network", "Rejected incoming connection from {:?}: address is not allowed", addr);
+						continue;
+					}
+					if self.nodes.read().unwrap().is_banned_ip(&addr.ip()) {
+						debug!(target: "network", "Rejected incoming connection from {:?}: address is banned", addr);
+						continue;
+					}
+					sock
+				},
 				Err(e) => {
 					warn!("Error accepting connection: {:?}", e);
 					break
@@ -756,6 +897,15 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 					},
 					Ok(SessionData::Ready) => {
 						self.num_sessions.fetch_add(1, AtomicOrdering::SeqCst);
+						let banned = {
+							let nodes = self.nodes.read().unwrap();
+							nodes.is_banned(s.id().unwrap()) || s.remote_addr().map_or(false, |a| nodes.is_banned_ip(&a.ip()))
+						};
+						if banned {
+							trace!(target: "network", "Disconnecting banned peer: {}", token);
+							s.disconnect(io, DisconnectReason::UselessPeer);
+							return;
+						}
 						if !s.info.originated {
 							let session_count = self.session_count();
 							let reserved_nodes = self.reserved_nodes.read().unwrap();
@@ -891,6 +1041,9 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 	fn initialize(&self, io: &IoContext<NetworkIoMessage<Message>>) {
 		io.register_timer(IDLE, MAINTENANCE_TIMEOUT).expect("Error registering Network idle timer");
 		io.register_timer(INIT_PUBLIC, 0).expect("Error registering initialization timer");
+		if self.bandwidth.is_limited() {
+			io.register_timer(BANDWIDTH, BANDWIDTH_TIMEOUT).expect("Error registering bandwidth timer");
+		}
 		self.maintain_network(io)
 	}
 
@@ -956,8 +1109,11 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 			},
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
-				self.nodes.write().unwrap().clear_useless();
+				let mut nodes = self.nodes.write().unwrap();
+				nodes.clear_useless();
+				nodes.clear_expired_bans();
 			},
+			BANDWIDTH => self.resume_throttled(io),
 			_ => match self.timers.read().unwrap().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().unwrap().get(timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -986,8 +1142,8 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 				h.initialize(&NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved));
 				self.handlers.write().unwrap().insert(protocol, h);
 				let mut info = self.info.write().unwrap();
-				for v in versions {
-					info.capabilities.push(CapabilityInfo { protocol: protocol, version: *v, packet_count:0 });
+				for &(version, packet_count) in versions {
+					info.capabilities.push(CapabilityInfo { protocol: protocol, version: version, packet_count: packet_count });
 				}
 			},
 			NetworkIoMessage::AddTimer {
@@ -1024,6 +1180,22 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::ReportPeer(ref peer, ref event) => {
+				let session = { self.sessions.read().unwrap().get(*peer).cloned() };
+				if let Some(session) = session {
+					let (id, ip) = {
+						let s = session.lock().unwrap();
+						(s.id().cloned(), s.remote_addr().ok().map(|a| a.ip()))
+					};
+					if let Some(id) = id {
+						if self.nodes.write().unwrap().note_event(&id, ip, *event) {
+							debug!(target: "network", "Banning peer {} for bad reputation", peer);
+							session.lock().unwrap().disconnect(io, DisconnectReason::UselessPeer);
+							self.kill_connection(*peer, io, false);
+						}
+					}
+				}
+			},
 			NetworkIoMessage::User(ref message) => {
 				let reserved = self.reserved_nodes.read().unwrap();
 				for (p, h) in self.handlers.read().unwrap().iter() {
@@ -0,0 +1,255 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! IP address filtering for peer connections.
+
+use std::net::{SocketAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use network::ip_utils::SocketAddrExt;
+
+/// Predefined class of allowed addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllowIP {
+	/// Connect to any address.
+	All,
+	/// Connect to private network addresses only.
+	Private,
+	/// Connect to public network addresses only.
+	Public,
+	/// Block all addresses not explicitly allowed.
+	None,
+}
+
+/// IP network given in CIDR notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpNetwork {
+	/// IPv4 network with prefix length.
+	V4(Ipv4Addr, u8),
+	/// IPv6 network with prefix length.
+	V6(Ipv6Addr, u8),
+}
+
+impl IpNetwork {
+	/// Returns true if given socket address belongs to this network.
+	pub fn contains(&self, address: &SocketAddr) -> bool {
+		match (self, address) {
+			(&IpNetwork::V4(ref network, prefix), &SocketAddr::V4(ref a)) => {
+				let mask = if prefix == 0 { 0 } else { !0u32 << (32 - prefix as u32) };
+				(ipv4_to_u32(network) & mask) == (ipv4_to_u32(a.ip()) & mask)
+			},
+			(&IpNetwork::V6(ref network, prefix), &SocketAddr::V6(ref a)) => {
+				let network = network.segments();
+				let ip = a.ip().segments();
+				let mut bits = prefix as u32;
+				for i in 0..8 {
+					if bits == 0 {
+						break;
+					}
+					let mask = if bits >= 16 { 0xffffu16 } else { !0u16 << (16 - bits) };
+					if network[i] & mask != ip[i] & mask {
+						return false;
+					}
+					bits = bits.saturating_sub(16);
+				}
+				true
+			},
+			_ => false,
+		}
+	}
+}
+
+fn ipv4_to_u32(ip: &Ipv4Addr) -> u32 {
+	let o = ip.octets();
+	((o[0] as u32) << 24) | ((o[1] as u32) << 16) | ((o[2] as u32) << 8) | (o[3] as u32)
+}
+
+impl FromStr for IpNetwork {
+	type Err = ();
+
+	/// Parses `address/prefix`. A bare address is treated as a single host network.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, '/');
+		let address = parts.next().unwrap_or("");
+		let prefix = match parts.next() {
+			Some(p) => Some(try!(u8::from_str(p).map_err(|_| ()))),
+			None => None,
+		};
+		if let Ok(ip) = Ipv4Addr::from_str(address) {
+			let prefix = prefix.unwrap_or(32);
+			if prefix > 32 {
+				return Err(());
+			}
+			return Ok(IpNetwork::V4(ip, prefix));
+		}
+		if let Ok(ip) = Ipv6Addr::from_str(address) {
+			let prefix = prefix.unwrap_or(128);
+			if prefix > 128 {
+				return Err(());
+			}
+			return Ok(IpNetwork::V6(ip, prefix));
+		}
+		Err(())
+	}
+}
+
+/// Filter deciding which peer addresses may be connected to or accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpFilter {
+	/// Predefined class of allowed addresses.
+	pub predefined: AllowIP,
+	/// Networks allowed in addition to the predefined class.
+	pub custom_allow: Vec<IpNetwork>,
+	/// Networks always blocked.
+	pub custom_block: Vec<IpNetwork>,
+}
+
+impl Default for IpFilter {
+	fn default() -> Self {
+		IpFilter {
+			predefined: AllowIP::All,
+			custom_allow: Vec::new(),
+			custom_block: Vec::new(),
+		}
+	}
+}
+
+impl IpFilter {
+	/// Attempt to parse the filter from a space separated list of rules.
+	/// Each rule is one of `all`, `public`, `private`, `none`, a network
+	/// in CIDR notation to allow or a network prefixed with `-` to block.
+	pub fn parse(s: &str) -> Option<Self> {
+		let mut filter = IpFilter::default();
+		for rule in s.split_whitespace() {
+			match rule {
+				"all" => filter.predefined = AllowIP::All,
+				"public" => filter.predefined = AllowIP::Public,
+				"private" => filter.predefined = AllowIP::Private,
+				"none" => filter.predefined = AllowIP::None,
+				custom => {
+					if custom.starts_with('-') {
+						filter.custom_block.push(match IpNetwork::from_str(&custom[1..]) {
+							Ok(network) => network,
+							Err(_) => return None,
+						});
+					} else {
+						filter.custom_allow.push(match IpNetwork::from_str(custom) {
+							Ok(network) => network,
+							Err(_) => return None,
+						});
+					}
+				}
+			}
+		}
+		Some(filter)
+	}
+
+	/// Returns true if the filter allows given address.
+	pub fn allows(&self, address: &SocketAddr) -> bool {
+		if self.custom_block.iter().any(|n| n.contains(address)) {
+			return false;
+		}
+		if self.custom_allow.iter().any(|n| n.contains(address)) {
+			return true;
+		}
+		let is_global = match *address {
+			SocketAddr::V4(ref a) => a.ip().is_global_s(),
+			SocketAddr::V6(ref a) => a.ip().is_global_s(),
+		};
+		match self.predefined {
+			AllowIP::All => true,
+			AllowIP::Public => is_global,
+			AllowIP::Private => !is_global,
+			AllowIP::None => false,
+		}
+	}
+
+	/// Returns true if non-global `address` was explicitly allowed by the user,
+	/// either with the `private` class or with a network containing it.
+	/// Discovery ignores non-global addresses otherwise.
+	pub fn allows_non_global(&self, address: &SocketAddr) -> bool {
+		self.predefined == AllowIP::Private || self.custom_allow.iter().any(|n| n.contains(address))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::SocketAddr;
+	use std::str::FromStr;
+
+	fn addr(s: &str) -> SocketAddr {
+		SocketAddr::from_str(s).unwrap()
+	}
+
+	#[test]
+	fn parses_ip_filter() {
+		assert_eq!(IpFilter::parse(""), Some(IpFilter::default()));
+		assert_eq!(IpFilter::parse("public").unwrap().predefined, AllowIP::Public);
+		assert_eq!(IpFilter::parse("none 10.0.0.0/8 -10.1.0.0/16"), Some(IpFilter {
+			predefined: AllowIP::None,
+			custom_allow: vec![IpNetwork::V4("10.0.0.0".parse().unwrap(), 8)],
+			custom_block: vec![IpNetwork::V4("10.1.0.0".parse().unwrap(), 16)],
+		}));
+		assert_eq!(IpFilter::parse("fe80::/10").unwrap().custom_allow, vec![IpNetwork::V6("fe80::".parse().unwrap(), 10)]);
+		assert_eq!(IpFilter::parse("10.0.0.0/33"), None);
+		assert_eq!(IpFilter::parse("foo"), None);
+	}
+
+	#[test]
+	fn network_contains_address() {
+		let v4 = IpNetwork::from_str("192.168.0.0/16").unwrap();
+		assert!(v4.contains(&addr("192.168.10.1:30303")));
+		assert!(!v4.contains(&addr("192.169.0.1:30303")));
+		assert!(!v4.contains(&addr("[::1]:30303")));
+
+		let host = IpNetwork::from_str("1.2.3.4").unwrap();
+		assert!(host.contains(&addr("1.2.3.4:1")));
+		assert!(!host.contains(&addr("1.2.3.5:1")));
+
+		let v6 = IpNetwork::from_str("2001:db8::/33").unwrap();
+		assert!(v6.contains(&addr("[2001:db8:7fff::1]:30303")));
+		assert!(!v6.contains(&addr("[2001:db8:8000::1]:30303")));
+
+		assert!(IpNetwork::from_str("0.0.0.0/0").unwrap().contains(&addr("8.8.8.8:53")));
+	}
+
+	#[test]
+	fn filter_allows_addresses() {
+		let public = addr("22.99.55.44:30303");
+		let private = addr("10.0.0.1:30303");
+
+		let filter = IpFilter::default();
+		assert!(filter.allows(&public) && filter.allows(&private));
+
+		let filter = IpFilter::parse("public").unwrap();
+		assert!(filter.allows(&public) && !filter.allows(&private));
+
+		let filter = IpFilter::parse("private").unwrap();
+		assert!(!filter.allows(&public) && filter.allows(&private));
+
+		let filter = IpFilter::parse("public 10.0.0.0/24 -22.99.0.0/16").unwrap();
+		assert!(!filter.allows(&public) && filter.allows(&private));
+
+		let filter = IpFilter::parse("none").unwrap();
+		assert!(!filter.allows(&public) && !filter.allows(&private));
+
+		assert!(!IpFilter::default().allows_non_global(&private));
+		assert!(!IpFilter::parse("public").unwrap().allows_non_global(&private));
+		assert!(IpFilter::parse("private").unwrap().allows_non_global(&private));
+		assert!(IpFilter::parse("none 10.0.0.0/8").unwrap().allows_non_global(&private));
+		assert!(!IpFilter::parse("all 192.168.0.0/16").unwrap().allows_non_global(&private));
+	}
+}
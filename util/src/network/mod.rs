@@ -57,7 +57,7 @@
 //!
 //! fn main () {
 //! 	let mut service = NetworkService::<MyMessage>::new(NetworkConfiguration::new_local()).expect("Error creating network service");
-//! 	service.register_protocol(Arc::new(MyHandler), "myproto", &[1u8]);
+//! 	service.register_protocol(Arc::new(MyHandler), "myproto", &[(1u8, 1u8)]);
 //! 	service.start().expect("Error starting service");
 //!
 //! 	// Wait for quit condition
@@ -75,6 +75,8 @@ mod error;
 mod node_table;
 mod stats;
 mod ip_utils;
+mod ip_filter;
+mod bandwidth;
 
 #[cfg(test)]
 mod tests;
@@ -88,11 +90,19 @@ pub use network::host::NetworkIoMessage::User as UserMessage;
 pub use network::error::NetworkError;
 pub use network::host::NetworkConfiguration;
 pub use network::stats::NetworkStats;
+pub use network::session::{PeerSessionInfo, PeerCapabilityInfo};
+pub use network::ip_filter::{IpFilter, IpNetwork, AllowIP};
+pub use network::bandwidth::BandwidthLimit;
 
 use io::TimerToken;
 pub use network::node_table::is_valid_node_url;
 
+/// Advertised p2p protocol version. Older peers disconnect anyone advertising a different one,
+/// so packet compression is negotiated with `COMPRESSION_CAPABILITY` instead of a version bump.
 const PROTOCOL_VERSION: u32 = 4;
+/// Hello capability advertising support for Snappy compressed packets. It has no packets of its own.
+const COMPRESSION_CAPABILITY: &'static str = "snappy";
+const COMPRESSION_CAPABILITY_VERSION: u8 = 1;
 
 /// Network IO protocol handler. This needs to be implemented for each new subprotocol.
 /// All the handler function are called from within IO event loop.
@@ -130,4 +140,29 @@ impl NonReservedPeerMode {
 			_ => None,
 		}
 	}
-}
\ No newline at end of file
+}
+
+/// Peer behaviour reported by protocol handlers. Adjusts reputation of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReputationEvent {
+	/// Peer sent an invalid block or header.
+	BadBlock,
+	/// Peer sent a malformed or unexpected packet.
+	InvalidPacket,
+	/// Peer did not respond in time.
+	Timeout,
+	/// Peer served useful data.
+	UsefulData,
+}
+
+impl ReputationEvent {
+	/// Reputation change caused by the event.
+	pub fn score(&self) -> i32 {
+		match *self {
+			ReputationEvent::BadBlock => -50,
+			ReputationEvent::InvalidPacket => -20,
+			ReputationEvent::Timeout => -10,
+			ReputationEvent::UsefulData => 1,
+		}
+	}
+}
@@ -16,10 +16,11 @@
 
 use std::mem;
 use std::slice::from_raw_parts;
-use std::net::{SocketAddr, ToSocketAddrs, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
+use std::net::{SocketAddr, ToSocketAddrs, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr, IpAddr};
 use std::hash::{Hash, Hasher};
 use std::str::{FromStr};
 use std::collections::{HashMap, HashSet};
+use std::cmp::min;
 use std::fmt::{Display, Formatter};
 use std::path::{PathBuf};
 use std::fmt;
@@ -31,11 +32,21 @@ use time::Tm;
 use error::*;
 use network::discovery::{TableUpdates, NodeEntry};
 use network::ip_utils::*;
+use network::ip_filter::IpFilter;
+use network::ReputationEvent;
 pub use rustc_serialize::json::Json;
+use rustc_serialize::json::Object as JsonObject;
 
 /// Node public key
 pub type NodeId = H512;
 
+/// Reputation at or below which a node gets banned.
+const BAN_THRESHOLD: i32 = -100;
+/// Highest reputation a node can earn.
+const MAX_REPUTATION: i32 = 100;
+/// Duration of automatic bans in seconds.
+const BAN_DURATION_SEC: u64 = 60 * 60;
+
 #[derive(Debug, Clone)]
 /// Node address info
 pub struct NodeEndpoint {
@@ -104,6 +115,10 @@ impl NodeEndpoint {
 			SocketAddr::V6(a) => a.ip().is_global_s()
 		}
 	}
+
+	pub fn is_allowed(&self, filter: &IpFilter) -> bool {
+		filter.allows(&self.address)
+	}
 }
 
 impl FromStr for NodeEndpoint {
@@ -193,19 +208,38 @@ impl Hash for Node {
 	}
 }
 
+/// Ban list entry.
+#[derive(Debug, Clone, PartialEq)]
+struct Ban {
+	/// Address the node was seen at, banned together with the node.
+	ip: Option<IpAddr>,
+	/// Ban expiration time. `None` means banned until removed from the list.
+	until: Option<u64>,
+}
+
+impl Ban {
+	fn is_active(&self) -> bool {
+		self.until.map_or(true, |until| until > now_sec())
+	}
+}
+
 /// Node table backed by disk file.
 pub struct NodeTable {
 	nodes: HashMap<NodeId, Node>,
 	useless_nodes: HashSet<NodeId>,
+	reputation: HashMap<NodeId, i32>,
+	banned: HashMap<NodeId, Ban>,
 	path: Option<String>,
 }
 
 impl NodeTable {
 	pub fn new(path: Option<String>) -> NodeTable {
 		NodeTable {
-			path: path.clone(),
-			nodes: NodeTable::load(path),
+			nodes: NodeTable::load(&path),
 			useless_nodes: HashSet::new(),
+			reputation: NodeTable::load_reputation(&path),
+			banned: NodeTable::load_bans(&path),
+			path: path,
 		}
 	}
 
@@ -217,9 +251,11 @@ impl NodeTable {
 		self.nodes.insert(node.id.clone(), node);
 	}
 
-	/// Returns node ids sorted by number of failures
-	pub fn nodes(&self) -> Vec<NodeId> {
-		let mut refs: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id)).collect();
+	/// Returns ids of nodes allowed by the filter sorted by number of failures
+	pub fn nodes(&self, filter: &IpFilter) -> Vec<NodeId> {
+		let mut refs: Vec<&Node> = self.nodes.values()
+			.filter(|n| !self.useless_nodes.contains(&n.id) && !self.is_banned(&n.id) && n.endpoint.is_allowed(filter))
+			.collect();
 		refs.sort_by(|a, b| a.failures.cmp(&b.failures));
 		refs.iter().map(|n| n.id.clone()).collect()
 	}
@@ -263,74 +299,200 @@ impl NodeTable {
 		self.useless_nodes.clear();
 	}
 
+	/// Adjust node reputation. Bans the node and the address it's connected from for a while
+	/// once the reputation drops to the threshold. Returns true if the node got banned.
+	pub fn note_event(&mut self, id: &NodeId, ip: Option<IpAddr>, event: ReputationEvent) -> bool {
+		let ban = {
+			let reputation = self.reputation.entry(id.clone()).or_insert(0);
+			*reputation = min(*reputation + event.score(), MAX_REPUTATION);
+			*reputation <= BAN_THRESHOLD
+		};
+		if ban {
+			self.reputation.remove(id);
+			if !self.is_banned(id) {
+				self.ban(id, ip, Some(BAN_DURATION_SEC));
+			}
+		}
+		ban
+	}
+
+	/// Current reputation of a node.
+	pub fn reputation(&self, id: &NodeId) -> i32 {
+		self.reputation.get(id).cloned().unwrap_or(0)
+	}
+
+	/// Ban a node and the address it's connected from for given number of seconds
+	/// or until unbanned if no duration is given.
+	pub fn ban(&mut self, id: &NodeId, ip: Option<IpAddr>, duration_sec: Option<u64>) {
+		let ip = ip.or_else(|| self.nodes.get(id).map(|n| n.endpoint.address.ip()));
+		self.banned.insert(id.clone(), Ban { ip: ip, until: duration_sec.map(|d| now_sec() + d) });
+		self.save_bans();
+	}
+
+	/// Remove a node from the ban list. Returns false if the node was not banned.
+	pub fn unban(&mut self, id: &NodeId) -> bool {
+		let removed = self.banned.remove(id).is_some();
+		if removed {
+			self.save_bans();
+		}
+		removed
+	}
+
+	/// Check if the node is currently banned.
+	pub fn is_banned(&self, id: &NodeId) -> bool {
+		self.banned.get(id).map_or(false, Ban::is_active)
+	}
+
+	/// Check if the address belongs to a currently banned node.
+	pub fn is_banned_ip(&self, ip: &IpAddr) -> bool {
+		self.banned.values().any(|ban| ban.ip.as_ref() == Some(ip) && ban.is_active())
+	}
+
+	/// Returns currently banned nodes with ban expiration time.
+	pub fn banned_nodes(&self) -> Vec<(NodeId, Option<u64>)> {
+		self.banned.iter()
+			.filter(|&(_, ban)| ban.is_active())
+			.map(|(id, ban)| (id.clone(), ban.until))
+			.collect()
+	}
+
+	/// Remove expired bans.
+	pub fn clear_expired_bans(&mut self) {
+		let expired: Vec<NodeId> = self.banned.keys().filter(|id| !self.is_banned(id)).cloned().collect();
+		for id in &expired {
+			self.banned.remove(id);
+		}
+		if !expired.is_empty() {
+			self.save_bans();
+		}
+	}
+
 	fn save(&self) {
+		self.save_nodes();
+		self.save_bans();
+		self.save_reputation();
+	}
+
+	fn save_nodes(&self) {
+		let node_ids = self.nodes(&IpFilter::default());
+		let entries = node_ids.iter().map(|id| {
+			let node = self.nodes.get(id).unwrap();
+			format!("{{ \"url\": \"{}\", \"failures\": {} }}", node, node.failures)
+		}).collect::<Vec<_>>();
+		self.write_list("nodes.json", "nodes", &entries);
+	}
+
+	fn save_bans(&self) {
+		let entries = self.banned.iter().filter(|&(_, ban)| ban.is_active()).map(|(id, ban)| {
+			let ip = ban.ip.map_or_else(|| "null".to_owned(), |ip| format!("\"{}\"", ip));
+			let until = ban.until.map_or_else(|| "null".to_owned(), |u| u.to_string());
+			format!("{{ \"id\": \"{}\", \"ip\": {}, \"until\": {} }}", id.hex(), ip, until)
+		}).collect::<Vec<_>>();
+		self.write_list("banned.json", "banned", &entries);
+	}
+
+	fn save_reputation(&self) {
+		let entries = self.reputation.iter().filter(|&(_, score)| *score != 0).map(|(id, score)| {
+			format!("{{ \"id\": \"{}\", \"score\": {} }}", id.hex(), score)
+		}).collect::<Vec<_>>();
+		self.write_list("reputation.json", "reputation", &entries);
+	}
+
+	/// Writes `{ "<name>": [<entries>] }` to `file_name` in the node table directory.
+	fn write_list(&self, file_name: &str, name: &str, entries: &[String]) {
 		if let Some(ref path) = self.path {
 			let mut path_buf = PathBuf::from(path);
 			if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
 				warn!("Error creating node table directory: {:?}", e);
 				return;
 			};
-			path_buf.push("nodes.json");
+			path_buf.push(file_name);
 			let mut json = String::new();
 			json.push_str("{\n");
-			json.push_str("\"nodes\": [\n");
-			let node_ids = self.nodes();
-			for i in 0 .. node_ids.len() {
-				let node = self.nodes.get(&node_ids[i]).unwrap();
-				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == node_ids.len() - 1 {""} else {","}))
+			json.push_str(&format!("\"{}\": [\n", name));
+			for i in 0 .. entries.len() {
+				json.push_str(&format!("\t{}{}\n", entries[i], if i == entries.len() - 1 {""} else {","}))
 			}
 			json.push_str("]\n");
 			json.push_str("}");
 			let mut file = match fs::File::create(path_buf.as_path()) {
 				Ok(file) => file,
 				Err(e) => {
-					warn!("Error creating node table file: {:?}", e);
+					warn!("Error creating {}: {:?}", file_name, e);
 					return;
 				}
 			};
 			if let Err(e) = file.write(&json.into_bytes()) {
-					warn!("Error writing node table file: {:?}", e);
+				warn!("Error writing {}: {:?}", file_name, e);
 			}
 		}
 	}
 
-	fn load(path: Option<String>) -> HashMap<NodeId, Node> {
-		let mut nodes: HashMap<NodeId, Node> = HashMap::new();
-		if let Some(path) = path {
+	/// Reads objects of list `name` from `file_name` in the node table directory.
+	fn read_list(path: &Option<String>, file_name: &str, name: &str) -> Vec<JsonObject> {
+		if let Some(ref path) = *path {
 			let mut path_buf = PathBuf::from(path);
-			path_buf.push("nodes.json");
+			path_buf.push(file_name);
 			let mut file = match fs::File::open(path_buf.as_path()) {
 				Ok(file) => file,
 				Err(e) => {
-					debug!("Error opening node table file: {:?}", e);
-					return nodes;
+					debug!("Error opening {}: {:?}", file_name, e);
+					return Vec::new();
 				}
 			};
 			let mut buf = String::new();
-			match file.read_to_string(&mut buf) {
-				Ok(_) => {},
-				Err(e) => {
-					warn!("Error reading node table file: {:?}", e);
-					return nodes;
-				}
+			if let Err(e) = file.read_to_string(&mut buf) {
+				warn!("Error reading {}: {:?}", file_name, e);
+				return Vec::new();
 			}
 			let json = match Json::from_str(&buf) {
 				Ok(json) => json,
 				Err(e) => {
-					warn!("Error parsing node table file: {:?}", e);
-					return nodes;
+					warn!("Error parsing {}: {:?}", file_name, e);
+					return Vec::new();
 				}
 			};
-			if let Some(list) = json.as_object().and_then(|o| o.get("nodes")).and_then(|n| n.as_array()) {
-				for n in list.iter().filter_map(|n| n.as_object()) {
-					if let Some(url) = n.get("url").and_then(|u| u.as_string()) {
-						if let Ok(mut node) = Node::from_str(url) {
-							if let Some(failures) = n.get("failures").and_then(|f| f.as_u64()) {
-								node.failures = failures as u32;
-							}
-							nodes.insert(node.id.clone(), node);
-						}
+			if let Some(list) = json.as_object().and_then(|o| o.get(name)).and_then(|n| n.as_array()) {
+				return list.iter().filter_map(|n| n.as_object()).cloned().collect();
+			}
+		}
+		Vec::new()
+	}
+
+	fn load_bans(path: &Option<String>) -> HashMap<NodeId, Ban> {
+		let mut banned = HashMap::new();
+		for n in NodeTable::read_list(path, "banned.json", "banned") {
+			if let Some(Ok(id)) = n.get("id").and_then(|i| i.as_string()).map(NodeId::from_str) {
+				banned.insert(id, Ban {
+					ip: n.get("ip").and_then(|ip| ip.as_string()).and_then(|ip| IpAddr::from_str(ip).ok()),
+					until: n.get("until").and_then(|u| u.as_u64()),
+				});
+			}
+		}
+		banned
+	}
+
+	fn load_reputation(path: &Option<String>) -> HashMap<NodeId, i32> {
+		let mut reputation = HashMap::new();
+		for n in NodeTable::read_list(path, "reputation.json", "reputation") {
+			if let Some(Ok(id)) = n.get("id").and_then(|i| i.as_string()).map(NodeId::from_str) {
+				if let Some(score) = n.get("score").and_then(|s| s.as_i64()) {
+					reputation.insert(id, score as i32);
+				}
+			}
+		}
+		reputation
+	}
+
+	fn load(path: &Option<String>) -> HashMap<NodeId, Node> {
+		let mut nodes: HashMap<NodeId, Node> = HashMap::new();
+		for n in NodeTable::read_list(path, "nodes.json", "nodes") {
+			if let Some(url) = n.get("url").and_then(|u| u.as_string()) {
+				if let Ok(mut node) = Node::from_str(url) {
+					if let Some(failures) = n.get("failures").and_then(|f| f.as_u64()) {
+						node.failures = failures as u32;
 					}
+					nodes.insert(node.id.clone(), node);
 				}
 			}
 		}
@@ -344,6 +506,10 @@ impl Drop for NodeTable {
 	}
 }
 
+fn now_sec() -> u64 {
+	::time::get_time().sec as u64
+}
+
 /// Check if node url is valid
 pub fn is_valid_node_url(url: &str) -> bool {
 	use std::str::FromStr;
@@ -357,6 +523,9 @@ mod tests {
 	use std::net::*;
 	use hash::*;
 	use devtools::*;
+	use network::ip_filter::IpFilter;
+	use network::ReputationEvent;
+	use super::Ban;
 
 	#[test]
 	fn endpoint_parse() {
@@ -402,12 +571,29 @@ mod tests {
 		table.note_failure(&id1);
 		table.note_failure(&id2);
 
-		let r = table.nodes();
+		let r = table.nodes(&IpFilter::default());
 		assert_eq!(r[0][..], id3[..]);
 		assert_eq!(r[1][..], id2[..]);
 		assert_eq!(r[2][..], id1[..]);
 	}
 
+	#[test]
+	fn table_filters_nodes() {
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@10.0.0.1:7770").unwrap();
+		let id1 = H512::from_str("a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c").unwrap();
+		let id2 = H512::from_str("b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c").unwrap();
+		let mut table = NodeTable::new(None);
+		table.add_node(node1);
+		table.add_node(node2);
+
+		assert_eq!(table.nodes(&IpFilter::default()).len(), 2);
+		assert_eq!(table.nodes(&IpFilter::parse("public").unwrap()), vec![id1]);
+		assert_eq!(table.nodes(&IpFilter::parse("private").unwrap()), vec![id2.clone()]);
+		assert_eq!(table.nodes(&IpFilter::parse("all -10.0.0.0/8").unwrap()).len(), 1);
+		assert_eq!(table.nodes(&IpFilter::parse("none 10.0.0.1").unwrap()), vec![id2]);
+	}
+
 	#[test]
 	fn table_save_load() {
 		let temp_path = RandomTempPath::create_dir();
@@ -424,9 +610,83 @@ mod tests {
 
 		{
 			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
-			let r = table.nodes();
+			let r = table.nodes(&IpFilter::default());
 			assert_eq!(r[0][..], id1[..]);
 			assert_eq!(r[1][..], id2[..]);
 		}
 	}
+
+	#[test]
+	fn table_bans_nodes_with_bad_reputation() {
+		let node = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id = node.id.clone();
+		let mut table = NodeTable::new(None);
+		table.add_node(node);
+
+		assert!(!table.note_event(&id, None, ReputationEvent::UsefulData));
+		assert!(!table.note_event(&id, None, ReputationEvent::BadBlock));
+		assert!(!table.note_event(&id, None, ReputationEvent::BadBlock));
+		assert_eq!(table.reputation(&id), -99);
+		assert!(!table.is_banned(&id));
+
+		assert!(table.note_event(&id, None, ReputationEvent::Timeout));
+		assert!(table.is_banned(&id));
+		assert!(table.is_banned_ip(&IpAddr::from_str("22.99.55.44").unwrap()));
+		assert_eq!(table.reputation(&id), 0);
+		assert!(table.nodes(&IpFilter::default()).is_empty());
+		assert_eq!(table.banned_nodes().len(), 1);
+
+		assert!(table.unban(&id));
+		assert!(!table.unban(&id));
+		assert!(!table.is_banned_ip(&IpAddr::from_str("22.99.55.44").unwrap()));
+		assert_eq!(table.nodes(&IpFilter::default()), vec![id]);
+	}
+
+	#[test]
+	fn table_expires_bans() {
+		let id = NodeId::random();
+		let mut table = NodeTable::new(None);
+		table.ban(&id, IpAddr::from_str("10.0.0.1").ok(), Some(0));
+		assert!(!table.is_banned_ip(&IpAddr::from_str("10.0.0.1").unwrap()));
+		assert!(!table.is_banned(&id));
+		assert!(table.banned_nodes().is_empty());
+		table.clear_expired_bans();
+		assert!(table.banned.is_empty());
+	}
+
+	#[test]
+	fn table_save_load_bans() {
+		let temp_path = RandomTempPath::create_dir();
+		let id1 = NodeId::random();
+		let id2 = NodeId::random();
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			table.ban(&id1, IpAddr::from_str("10.0.0.1").ok(), None);
+			table.ban(&id2, None, Some(3600));
+		}
+
+		{
+			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert!(table.is_banned(&id1));
+			assert!(table.is_banned(&id2));
+			assert!(table.is_banned_ip(&IpAddr::from_str("10.0.0.1").unwrap()));
+			assert_eq!(table.banned.get(&id1), Some(&Ban { ip: IpAddr::from_str("10.0.0.1").ok(), until: None }));
+			assert_eq!(table.banned.get(&id2).unwrap().ip, None);
+		}
+	}
+
+	#[test]
+	fn table_save_load_reputation() {
+		let temp_path = RandomTempPath::create_dir();
+		let id = NodeId::random();
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			table.note_event(&id, None, ReputationEvent::BadBlock);
+		}
+
+		{
+			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert_eq!(table.reputation(&id), ReputationEvent::BadBlock.score());
+		}
+	}
 }
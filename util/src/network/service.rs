@@ -19,7 +19,9 @@ use error::*;
 use panics::*;
 use network::{NetworkProtocolHandler, NetworkConfiguration};
 use network::error::NetworkError;
-use network::host::{Host, NetworkIoMessage, ProtocolId};
+use network::host::{Host, NetworkIoMessage, ProtocolId, PeerId};
+use network::session::PeerSessionInfo;
+use network::node_table::NodeId;
 use network::stats::NetworkStats;
 use io::*;
 
@@ -54,7 +56,8 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 	}
 
 	/// Regiter a new protocol handler with the event loop.
-	pub fn register_protocol(&self, handler: Arc<NetworkProtocolHandler<Message>+Send + Sync>, protocol: ProtocolId, versions: &[u8]) -> Result<(), NetworkError> {
+	/// `versions` lists supported protocol versions with the number of packet IDs used by each.
+	pub fn register_protocol(&self, handler: Arc<NetworkProtocolHandler<Message>+Send + Sync>, protocol: ProtocolId, versions: &[(u8, u8)]) -> Result<(), NetworkError> {
 		try!(self.io_service.send_message(NetworkIoMessage::AddHandler {
 			handler: handler,
 			protocol: protocol,
@@ -90,6 +93,12 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 		host.as_ref().map(|h| h.local_url())
 	}
 
+	/// Returns details of all connected peer sessions.
+	pub fn sessions_info(&self) -> Vec<(PeerId, PeerSessionInfo)> {
+		let host = self.host.read().unwrap();
+		host.as_ref().map_or_else(Vec::new, |h| h.sessions_info())
+	}
+
 	/// Start network IO
 	pub fn start(&self) -> Result<(), UtilError> {
 		let mut host = self.host.write().unwrap();
@@ -132,6 +141,32 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 		}
 	}
 
+	/// Ban a node until it's unbanned. Disconnects it if connected.
+	/// Returns false if the network is not started and nothing was banned.
+	pub fn ban_peer(&self, id: &NodeId) -> bool {
+		let host = self.host.read().unwrap();
+		match *host {
+			Some(ref host) => {
+				let io_ctxt = IoContext::new(self.io_service.channel(), 0);
+				host.ban_node(id, &io_ctxt);
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Remove a node from the ban list. Returns false if the node was not banned.
+	pub fn unban_peer(&self, id: &NodeId) -> bool {
+		let host = self.host.read().unwrap();
+		host.as_ref().map_or(false, |h| h.unban_node(id))
+	}
+
+	/// Returns banned nodes with ban expiration time as a unix timestamp. `None` means the ban does not expire.
+	pub fn banned_peers(&self) -> Vec<(NodeId, Option<u64>)> {
+		let host = self.host.read().unwrap();
+		host.as_ref().map_or_else(Vec::new, |h| h.banned_nodes())
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: ::network::NonReservedPeerMode) {
 		let host = self.host.read().unwrap();
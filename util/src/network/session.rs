@@ -29,6 +29,8 @@ use network::error::{NetworkError, DisconnectReason};
 use network::host::*;
 use network::node_table::NodeId;
 use network::stats::NetworkStats;
+use network::bandwidth::BandwidthLimiter;
+use network::{PROTOCOL_VERSION, COMPRESSION_CAPABILITY, COMPRESSION_CAPABILITY_VERSION};
 use time;
 
 const PING_TIMEOUT_SEC: u64 = 30;
@@ -46,6 +48,8 @@ pub struct Session {
 	expired: bool,
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
+	/// Session creation time.
+	start_time_ns: u64,
 	state: State,
 }
 
@@ -86,9 +90,11 @@ pub struct SessionInfo {
 	pub ping_ms: Option<u64>,
 	/// True if this session was originated by us.
 	pub originated: bool,
+	/// True if packets are Snappy compressed.
+	pub compression: bool,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PeerCapabilityInfo {
 	pub protocol: String,
 	pub version: u8,
@@ -104,6 +110,35 @@ impl Decodable for PeerCapabilityInfo {
 	}
 }
 
+/// Connected peer session details.
+#[derive(Debug, Clone)]
+pub struct PeerSessionInfo {
+	/// Peer public key
+	pub id: Option<NodeId>,
+	/// Peer client ID
+	pub client_version: String,
+	/// Peer RLPx protocol version
+	pub protocol_version: u32,
+	/// Capabilities negotiated with the peer
+	pub capabilities: Vec<PeerCapabilityInfo>,
+	/// Remote socket address
+	pub remote_address: String,
+	/// Local socket address
+	pub local_address: String,
+	/// True if this session was originated by us.
+	pub originated: bool,
+	/// True if packets are Snappy compressed.
+	pub compression: bool,
+	/// Peer ping delay in milliseconds
+	pub ping_ms: Option<u64>,
+	/// Time since the session was created in seconds
+	pub duration: u64,
+	/// Bytes received from the peer
+	pub bytes_received: usize,
+	/// Bytes sent to the peer
+	pub bytes_sent: usize,
+}
+
 #[derive(Debug)]
 struct SessionCapabilityInfo {
 	pub protocol: &'static str,
@@ -125,10 +160,10 @@ impl Session {
 	/// Create a new session out of comepleted handshake. This clones the handshake connection object
 	/// and leaves the handhsake in limbo to be deregistered from the event loop.
 	pub fn new<Message>(io: &IoContext<Message>, socket: TcpStream, token: StreamToken, id: Option<&NodeId>,
-		nonce: &H256, stats: Arc<NetworkStats>, host: &HostInfo) -> Result<Session, UtilError>
+		nonce: &H256, stats: Arc<NetworkStats>, limiter: Arc<BandwidthLimiter>, host: &HostInfo) -> Result<Session, UtilError>
 		where Message: Send + Clone {
 		let originated = id.is_some();
-		let mut handshake = Handshake::new(token, id, socket, &nonce, stats).expect("Can't create handshake");
+		let mut handshake = Handshake::new(token, id, socket, &nonce, stats, limiter).expect("Can't create handshake");
 		try!(handshake.start(io, host, originated));
 		Ok(Session {
 			state: State::Handshake(handshake),
@@ -140,9 +175,11 @@ impl Session {
 				capabilities: Vec::new(),
 				ping_ms: None,
 				originated: originated,
+				compression: false,
 			},
 			ping_time_ns: 0,
 			pong_time_ns: None,
+			start_time_ns: time::precise_time_ns(),
 			expired: false,
 		})
 	}
@@ -167,6 +204,16 @@ impl Session {
 		}
 	}
 
+	/// Check if reading from the peer is paused by the bandwidth limit.
+	pub fn is_read_throttled(&self) -> bool {
+		self.connection().is_read_throttled()
+	}
+
+	/// Check if sending to the peer is paused by the bandwidth limit.
+	pub fn is_write_throttled(&self) -> bool {
+		self.connection().is_write_throttled()
+	}
+
 	/// Get id of the remote peer
 	pub fn id(&self) -> Option<&NodeId> {
 		self.info.id.as_ref()
@@ -200,6 +247,28 @@ impl Session {
 		self.connection().remote_addr()
 	}
 
+	/// Get session details for reporting.
+	pub fn peer_info(&self) -> PeerSessionInfo {
+		let connection = self.connection();
+		PeerSessionInfo {
+			id: self.info.id.clone(),
+			client_version: self.info.client_version.clone(),
+			protocol_version: self.info.protocol_version,
+			capabilities: self.info.capabilities.iter().map(|c| PeerCapabilityInfo {
+				protocol: c.protocol.to_owned(),
+				version: c.version,
+			}).collect(),
+			remote_address: connection.remote_addr_str(),
+			local_address: connection.local_addr().map(|a| a.to_string()).unwrap_or_else(|_| "Unknown".to_owned()),
+			originated: self.info.originated,
+			compression: self.info.compression,
+			ping_ms: self.info.ping_ms,
+			duration: (time::precise_time_ns() - self.start_time_ns) / 1000_000_000,
+			bytes_received: connection.bytes_received(),
+			bytes_sent: connection.bytes_sent(),
+		}
+	}
+
 	/// Readable IO handler. Returns packet data if available.
 	pub fn readable<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<SessionData, UtilError>  where Message: Send + Sync + Clone {
 		if self.expired() {
@@ -348,18 +417,16 @@ impl Session {
 			PACKET_GET_PEERS => Ok(SessionData::None), //TODO;
 			PACKET_PEERS => Ok(SessionData::None),
 			PACKET_USER ... PACKET_LAST => {
-				let mut i = 0usize;
-				while packet_id < self.info.capabilities[i].id_offset {
-					i += 1;
-					if i == self.info.capabilities.len() {
+				// map to protocol
+				let cap = match self.info.capabilities.iter().find(|c| packet_id >= c.id_offset && packet_id - c.id_offset < c.packet_count) {
+					Some(cap) => cap,
+					None => {
 						debug!(target: "network", "Unknown packet: {:?}", packet_id);
 						return Ok(SessionData::Continue)
 					}
-				}
-
-				// map to protocol
-				let protocol = self.info.capabilities[i].protocol;
-				let pid = packet_id - self.info.capabilities[i].id_offset;
+				};
+				let protocol = cap.protocol;
+				let pid = packet_id - cap.id_offset;
 				Ok(SessionData::Packet { data: packet.data, protocol: protocol, packet_id: pid } )
 			},
 			_ => {
@@ -374,9 +441,19 @@ impl Session {
 		rlp.append_raw(&[PACKET_HELLO as u8], 0);
 		rlp.begin_list(5)
 			.append(&host.protocol_version)
-			.append(&host.client_version)
-			.append(&host.capabilities)
-			.append(&host.local_endpoint.address.port())
+			.append(&host.client_version);
+		// Compression capability is ignored by peers that don't know it.
+		let compression = host.compression();
+		rlp.begin_list(host.capabilities.len() + if compression { 1 } else { 0 });
+		for c in &host.capabilities {
+			rlp.append(c);
+		}
+		if compression {
+			rlp.begin_list(2)
+				.append(&COMPRESSION_CAPABILITY)
+				.append(&COMPRESSION_CAPABILITY_VERSION);
+		}
+		rlp.append(&host.local_endpoint.address.port())
 			.append(host.id());
 		self.send(io, rlp)
 	}
@@ -413,6 +490,8 @@ impl Session {
 			}
 		}
 
+		// Packet ID ranges are assigned in the alphabetical order of capability names.
+		caps.sort_by(|a, b| a.protocol.cmp(b.protocol));
 		i = 0;
 		let mut offset: u8 = PACKET_USER;
 		while i < caps.len() {
@@ -427,10 +506,19 @@ impl Session {
 			trace!(target: "network", "No common capabilities with peer.");
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
-		if protocol != host.protocol_version {
+		if protocol < PROTOCOL_VERSION {
 			trace!(target: "network", "Peer protocol version mismatch: {}", protocol);
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
+		self.info.protocol_version = protocol;
+		// Both sides have sent hello uncompressed, everything after it is compressed if supported by both.
+		let compression = host.compression() && peer_caps.iter().any(|c| c.protocol == COMPRESSION_CAPABILITY && c.version == COMPRESSION_CAPABILITY_VERSION);
+		if compression {
+			if let State::Session(ref mut connection) = self.state {
+				connection.set_compression(true);
+			}
+		}
+		self.info.compression = compression;
 		self.had_hello = true;
 		Ok(())
 	}
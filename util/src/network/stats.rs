@@ -26,6 +26,10 @@ pub struct NetworkStats {
 	send: AtomicUsize,
 	/// Total number of sessions created
 	sessions: AtomicUsize,
+	/// Current download rate in bytes per second
+	recv_rate: AtomicUsize,
+	/// Current upload rate in bytes per second
+	send_rate: AtomicUsize,
 }
 
 impl NetworkStats {
@@ -47,6 +51,13 @@ impl NetworkStats {
 		self.sessions.fetch_add(1, Ordering::Relaxed);
 	}
 
+	/// Set current upload and download rates.
+	#[inline]
+	pub fn set_rates(&self, send_rate: usize, recv_rate: usize) {
+		self.send_rate.store(send_rate, Ordering::Relaxed);
+		self.recv_rate.store(recv_rate, Ordering::Relaxed);
+	}
+
 	/// Get bytes sent.
 	#[inline]
 	pub fn send(&self) -> usize {
@@ -65,12 +76,26 @@ impl NetworkStats {
 		self.sessions.load(Ordering::Relaxed)
 	}
 
+	/// Get current upload rate in bytes per second.
+	#[inline]
+	pub fn send_rate(&self) -> usize {
+		self.send_rate.load(Ordering::Relaxed)
+	}
+
+	/// Get current download rate in bytes per second.
+	#[inline]
+	pub fn recv_rate(&self) -> usize {
+		self.recv_rate.load(Ordering::Relaxed)
+	}
+
 	/// Create a new empty instance.
 	pub fn new() -> NetworkStats {
 		NetworkStats {
 			recv: AtomicUsize::new(0),
 			send: AtomicUsize::new(0),
 			sessions: AtomicUsize::new(0),
+			recv_rate: AtomicUsize::new(0),
+			send_rate: AtomicUsize::new(0),
 		}
 	}
 }
@@ -46,7 +46,7 @@ impl TestProtocol {
 	/// Creates and register protocol with the network service
 	pub fn register(service: &mut NetworkService<TestProtocolMessage>, drop_session: bool) -> Arc<TestProtocol> {
 		let handler = Arc::new(TestProtocol::new(drop_session));
-		service.register_protocol(handler.clone(), "test", &[42u8, 43u8]).expect("Error registering test protocol handler");
+		service.register_protocol(handler.clone(), "test", &[(42u8, 34u8), (43u8, 34u8)]).expect("Error registering test protocol handler");
 		handler
 	}
 
@@ -99,7 +99,7 @@ impl NetworkProtocolHandler<TestProtocolMessage> for TestProtocol {
 fn net_service() {
 	let service = NetworkService::<TestProtocolMessage>::new(NetworkConfiguration::new_local()).expect("Error creating network service");
 	service.start().unwrap();
-	service.register_protocol(Arc::new(TestProtocol::new(false)), "myproto", &[1u8]).unwrap();
+	service.register_protocol(Arc::new(TestProtocol::new(false)), "myproto", &[(1u8, 1u8)]).unwrap();
 }
 
 #[test]
@@ -125,6 +125,82 @@ fn net_connect() {
 	assert!(service2.stats().sessions() >= 1);
 }
 
+#[test]
+fn net_sessions_info() {
+	let key1 = KeyPair::create().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.use_secret = Some(key1.secret().clone());
+	config1.boot_nodes = vec![ ];
+	let mut service1 = NetworkService::<TestProtocolMessage>::new(config1).unwrap();
+	service1.start().unwrap();
+	let handler1 = TestProtocol::register(&mut service1, false);
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	let mut service2 = NetworkService::<TestProtocolMessage>::new(config2).unwrap();
+	service2.start().unwrap();
+	let handler2 = TestProtocol::register(&mut service2, false);
+	while !(handler1.got_packet() && handler2.got_packet()) {
+		thread::sleep(Duration::from_millis(50));
+	}
+
+	let sessions = service2.sessions_info();
+	assert_eq!(sessions.len(), 1);
+	let (_, ref info) = sessions[0];
+	assert_eq!(info.id, Some(key1.public().clone()));
+	assert!(info.client_version.contains("Parity"));
+	assert!(info.originated);
+	assert!(info.capabilities.contains(&PeerCapabilityInfo { protocol: "test".to_owned(), version: 43 }));
+	assert!(info.bytes_received > 0);
+	assert!(info.bytes_sent > 0);
+	// older peers disconnect anyone whose hello version differs from their own
+	assert_eq!(info.protocol_version, 4);
+	assert!(info.compression);
+}
+
+#[test]
+fn net_connect_without_compression() {
+	// peer without compression capability gets uncompressed packets
+	let key1 = KeyPair::create().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.use_secret = Some(key1.secret().clone());
+	config1.boot_nodes = vec![ ];
+	config1.compression = false;
+	let mut service1 = NetworkService::<TestProtocolMessage>::new(config1).unwrap();
+	service1.start().unwrap();
+	let handler1 = TestProtocol::register(&mut service1, false);
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	let mut service2 = NetworkService::<TestProtocolMessage>::new(config2).unwrap();
+	service2.start().unwrap();
+	let handler2 = TestProtocol::register(&mut service2, false);
+	while !(handler1.got_packet() && handler2.got_packet()) {
+		thread::sleep(Duration::from_millis(50));
+	}
+
+	let sessions = service2.sessions_info();
+	assert_eq!(sessions.len(), 1);
+	assert!(!sessions[0].1.compression);
+	let sessions = service1.sessions_info();
+	assert_eq!(sessions.len(), 1);
+	assert!(!sessions[0].1.compression);
+	assert_eq!(sessions[0].1.protocol_version, 4);
+}
+
+#[test]
+fn net_ban_peer() {
+	let service = NetworkService::<TestProtocolMessage>::new(NetworkConfiguration::new_local()).unwrap();
+	let id = H512::random();
+	assert!(!service.ban_peer(&id));
+	service.start().unwrap();
+
+	assert!(service.ban_peer(&id));
+	assert_eq!(service.banned_peers(), vec![(id.clone(), None)]);
+
+	assert!(service.unban_peer(&id));
+	assert!(service.banned_peers().is_empty());
+	assert!(!service.unban_peer(&id));
+}
+
 #[test]
 fn net_start_stop() {
 	let config = NetworkConfiguration::new_local();
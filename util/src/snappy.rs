@@ -0,0 +1,319 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snappy compression in the raw (unframed) format.
+//! https://github.com/google/snappy/blob/master/format_description.txt
+
+use std::fmt;
+
+/// Input is compressed in independent blocks of this size so that copy offsets fit into 16 bits.
+const BLOCK_SIZE: usize = 1 << 16;
+const HASH_TABLE_BITS: u32 = 14;
+const MIN_MATCH: usize = 4;
+
+const TAG_LITERAL: u8 = 0x00;
+const TAG_COPY_1: u8 = 0x01;
+const TAG_COPY_2: u8 = 0x02;
+const TAG_COPY_4: u8 = 0x03;
+
+/// Attempted to decompress malformed input.
+#[derive(Debug, PartialEq)]
+pub struct InvalidInput;
+
+impl fmt::Display for InvalidInput {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("Attempted snappy decompression with invalid input")
+	}
+}
+
+/// Maximum size of compressed data for an input of given size.
+pub fn max_compressed_len(len: usize) -> usize {
+	32 + len + len / 6
+}
+
+/// Size of the data once decompressed, read from the compressed data preamble.
+/// Use it to check the size before calling `decompress` on untrusted input.
+pub fn decompressed_len(compressed: &[u8]) -> Result<usize, InvalidInput> {
+	read_varint(compressed).map(|(len, _)| len)
+}
+
+/// Compress a buffer.
+pub fn compress(input: &[u8]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(max_compressed_len(input.len()));
+	write_varint(&mut out, input.len() as u64);
+	for block in input.chunks(BLOCK_SIZE) {
+		compress_block(block, &mut out);
+	}
+	out
+}
+
+/// Decompress a buffer. Allocates as much memory as declared in the compressed data preamble.
+pub fn decompress(compressed: &[u8]) -> Result<Vec<u8>, InvalidInput> {
+	let (len, mut i) = try!(read_varint(compressed));
+	let mut out = Vec::with_capacity(len);
+	while i < compressed.len() {
+		let tag = compressed[i];
+		i += 1;
+		match tag & 0x03 {
+			TAG_LITERAL => {
+				let mut literal_len = (tag >> 2) as usize;
+				if literal_len >= 60 {
+					let bytes = literal_len - 59;
+					if i + bytes > compressed.len() {
+						return Err(InvalidInput);
+					}
+					literal_len = read_le(&compressed[i..i + bytes]);
+					i += bytes;
+				}
+				literal_len += 1;
+				if i + literal_len > compressed.len() || out.len() + literal_len > len {
+					return Err(InvalidInput);
+				}
+				out.extend_from_slice(&compressed[i..i + literal_len]);
+				i += literal_len;
+			},
+			TAG_COPY_1 => {
+				if i + 1 > compressed.len() {
+					return Err(InvalidInput);
+				}
+				let copy_len = ((tag >> 2) & 0x07) as usize + 4;
+				let offset = (((tag >> 5) as usize) << 8) | compressed[i] as usize;
+				i += 1;
+				try!(copy_back(&mut out, offset, copy_len, len));
+			},
+			TAG_COPY_2 => {
+				if i + 2 > compressed.len() {
+					return Err(InvalidInput);
+				}
+				let copy_len = (tag >> 2) as usize + 1;
+				let offset = read_le(&compressed[i..i + 2]);
+				i += 2;
+				try!(copy_back(&mut out, offset, copy_len, len));
+			},
+			_ => {
+				if i + 4 > compressed.len() {
+					return Err(InvalidInput);
+				}
+				let copy_len = (tag >> 2) as usize + 1;
+				let offset = read_le(&compressed[i..i + 4]);
+				i += 4;
+				try!(copy_back(&mut out, offset, copy_len, len));
+			},
+		}
+	}
+	if out.len() != len {
+		return Err(InvalidInput);
+	}
+	Ok(out)
+}
+
+fn compress_block(block: &[u8], out: &mut Vec<u8>) {
+	let mut table = vec![0usize; 1 << HASH_TABLE_BITS];
+	let mut literal_start = 0;
+	let mut i = 0;
+	while i + MIN_MATCH <= block.len() {
+		let current = load_u32(block, i);
+		let hash = (current.wrapping_mul(0x1e35a7bd) >> (32 - HASH_TABLE_BITS)) as usize;
+		let candidate = table[hash];
+		table[hash] = i;
+		if candidate < i && load_u32(block, candidate) == current {
+			let mut match_len = MIN_MATCH;
+			while i + match_len < block.len() && block[candidate + match_len] == block[i + match_len] {
+				match_len += 1;
+			}
+			emit_literal(&block[literal_start..i], out);
+			emit_copy(i - candidate, match_len, out);
+			i += match_len;
+			literal_start = i;
+		} else {
+			i += 1;
+		}
+	}
+	emit_literal(&block[literal_start..], out);
+}
+
+fn emit_literal(literal: &[u8], out: &mut Vec<u8>) {
+	if literal.is_empty() {
+		return;
+	}
+	let n = literal.len() - 1;
+	if n < 60 {
+		out.push(TAG_LITERAL | ((n as u8) << 2));
+	} else {
+		let bytes = if n < 1 << 8 { 1 } else if n < 1 << 16 { 2 } else if n < 1 << 24 { 3 } else { 4 };
+		out.push(TAG_LITERAL | ((59 + bytes as u8) << 2));
+		for b in 0..bytes {
+			out.push((n >> (8 * b)) as u8);
+		}
+	}
+	out.extend_from_slice(literal);
+}
+
+fn emit_copy(offset: usize, mut len: usize, out: &mut Vec<u8>) {
+	// Split long matches so that the remainder is never shorter than `MIN_MATCH`.
+	while len >= 68 {
+		emit_copy_2(offset, 64, out);
+		len -= 64;
+	}
+	if len > 64 {
+		emit_copy_2(offset, 60, out);
+		len -= 60;
+	}
+	emit_copy_2(offset, len, out);
+}
+
+fn emit_copy_2(offset: usize, len: usize, out: &mut Vec<u8>) {
+	out.push(TAG_COPY_2 | (((len - 1) as u8) << 2));
+	out.push(offset as u8);
+	out.push((offset >> 8) as u8);
+}
+
+fn copy_back(out: &mut Vec<u8>, offset: usize, len: usize, max_len: usize) -> Result<(), InvalidInput> {
+	if offset == 0 || offset > out.len() || out.len() + len > max_len {
+		return Err(InvalidInput);
+	}
+	// Byte by byte since the source range may overlap with the bytes being written.
+	let start = out.len() - offset;
+	for i in 0..len {
+		let b = out[start + i];
+		out.push(b);
+	}
+	Ok(())
+}
+
+fn load_u32(data: &[u8], i: usize) -> u32 {
+	(data[i] as u32) | ((data[i + 1] as u32) << 8) | ((data[i + 2] as u32) << 16) | ((data[i + 3] as u32) << 24)
+}
+
+fn read_le(data: &[u8]) -> usize {
+	data.iter().rev().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+}
+
+fn read_varint(data: &[u8]) -> Result<(usize, usize), InvalidInput> {
+	let mut result = 0u64;
+	for i in 0..5 {
+		if i >= data.len() {
+			return Err(InvalidInput);
+		}
+		result |= ((data[i] & 0x7f) as u64) << (7 * i);
+		if data[i] & 0x80 == 0 {
+			if result > ::std::u32::MAX as u64 {
+				return Err(InvalidInput);
+			}
+			return Ok((result as usize, i + 1));
+		}
+	}
+	Err(InvalidInput)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+	while value >= 0x80 {
+		out.push((value as u8) | 0x80);
+		value >>= 7;
+	}
+	out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn roundtrip(data: &[u8]) {
+		let compressed = compress(data);
+		assert!(compressed.len() <= max_compressed_len(data.len()));
+		assert_eq!(decompressed_len(&compressed), Ok(data.len()));
+		assert_eq!(decompress(&compressed).unwrap(), data);
+	}
+
+	#[test]
+	fn should_compress_and_decompress() {
+		roundtrip(b"");
+		roundtrip(b"a");
+		roundtrip(b"abcdabcdabcdabcdabcdabcd");
+		roundtrip(&[42u8; 1000]);
+		roundtrip(&(0..200_000).map(|i| (i % 251) as u8).collect::<Vec<_>>());
+		roundtrip(&(0..5000u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn should_shrink_repetitive_data() {
+		let data = vec![7u8; 4096];
+		assert!(compress(&data).len() < 300);
+	}
+
+	#[test]
+	fn should_decompress_reference_encoding() {
+		// literal "abcd" followed by a 1-byte offset copy of 6 bytes at distance 4
+		let compressed = [0x0a, 0x0c, b'a', b'b', b'c', b'd', 0x09, 0x04];
+		assert_eq!(decompress(&compressed).unwrap(), b"abcdabcdab".to_vec());
+	}
+
+	#[test]
+	fn should_match_reference_implementation() {
+		// vectors from the golang/snappy test suite used by geth
+		let valid: &[(&[u8], &[u8])] = &[
+			(b"\x00", b""),
+			(b"\x03\x08\xff\xff\xff", b"\xff\xff\xff"),
+			(b"\x03\xf0\x02\xff\xff\xff", b"\xff\xff\xff"),
+			(b"\x03\xf4\x02\x00\xff\xff\xff", b"\xff\xff\xff"),
+			(b"\x03\xf8\x02\x00\x00\xff\xff\xff", b"\xff\xff\xff"),
+			(b"\x03\xfc\x02\x00\x00\x00\xff\xff\xff", b"\xff\xff\xff"),
+			(b"\x04\x0cabcd", b"abcd"),
+			(b"\x0d\x0cabcd\x15\x04", b"abcdabcdabcda"),
+			(b"\x08\x0cabcd\x01\x04", b"abcdabcd"),
+			(b"\x08\x0cabcd\x01\x02", b"abcdcdcd"),
+			(b"\x08\x0cabcd\x01\x01", b"abcddddd"),
+			(b"\x06\x0cabcd\x06\x03\x00", b"abcdbc"),
+			(b"\x06\x0cabcd\x07\x03\x00\x00\x00", b"abcdbc"),
+		];
+		for &(compressed, data) in valid {
+			assert_eq!(decompress(compressed).unwrap(), data.to_vec());
+		}
+
+		let corrupt: &[&[u8]] = &[
+			b"\x02\x08\xff\xff\xff",
+			b"\x03\x08\xff\xff",
+			b"\x01\xf0",
+			b"\x04\x01",
+			b"\x04\x02\x00",
+			b"\x04\x03\x00\x00\x00",
+			b"\x08\x0cabcd\x01\x00",
+			b"\x09\x0cabcd\x01\x04",
+			b"\x08\x0cabcd\x01\x05",
+			b"\x07\x0cabcd\x01\x04",
+		];
+		for compressed in corrupt {
+			assert_eq!(decompress(compressed), Err(InvalidInput));
+		}
+
+		// inputs too short for a match are emitted as a single literal by every encoder
+		assert_eq!(compress(b"hello"), b"\x05\x10hello".to_vec());
+	}
+
+	#[test]
+	fn should_reject_invalid_input() {
+		// empty input
+		assert_eq!(decompress(&[]), Err(InvalidInput));
+		// copy before the beginning of output
+		assert_eq!(decompress(&[0x08, 0x0c, b'a', b'b', b'c', b'd', 0x01, 0x05]), Err(InvalidInput));
+		// truncated literal
+		assert_eq!(decompress(&[0x04, 0x0c, b'a', b'b']), Err(InvalidInput));
+		// output longer than declared
+		assert_eq!(decompress(&[0x02, 0x0c, b'a', b'b', b'c', b'd']), Err(InvalidInput));
+		// output shorter than declared
+		assert_eq!(decompress(&[0x08, 0x0c, b'a', b'b', b'c', b'd']), Err(InvalidInput));
+	}
+}
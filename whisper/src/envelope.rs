@@ -0,0 +1,298 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper envelope: the unit of propagation on the network.
+
+use util::*;
+use time;
+
+/// Abridged topic: the first four bytes of the SHA3 of the full topic.
+pub type Topic = H32;
+
+/// Bloom filter of envelope topics.
+pub type TopicBloom = H512;
+
+/// Maximum number of seconds the envelope send time may be ahead of the local clock.
+const MAX_CLOCK_DRIFT: u32 = 2;
+/// Maximum time to live in seconds (two days). Together with the clock drift check it bounds the expiry.
+pub const MAX_TTL: u32 = 2 * 24 * 60 * 60;
+
+/// Current time in seconds since the epoch.
+pub fn now() -> u32 {
+	time::get_time().sec as u32
+}
+
+/// Abridge a full topic.
+pub fn topic(data: &[u8]) -> Topic {
+	Topic::from_slice(&data.sha3()[0..4])
+}
+
+/// Bloom with three bits set for the topic. Bit `j` of the last topic byte selects
+/// the upper half of the filter for the bit derived from byte `j`.
+pub fn topic_bloom(topic: &Topic) -> TopicBloom {
+	let mut bloom = TopicBloom::new();
+	for j in 0..3 {
+		let mut index = topic[j] as usize;
+		if topic[3] & (1 << j) != 0 {
+			index += 256;
+		}
+		bloom[index / 8] |= 1 << (index % 8);
+	}
+	bloom
+}
+
+/// Bloom of a set of topics.
+pub fn topics_bloom(topics: &[Topic]) -> TopicBloom {
+	topics.iter().fold(TopicBloom::new(), |b, t| b | topic_bloom(t))
+}
+
+/// Envelope validation error.
+#[derive(Debug, PartialEq)]
+pub enum EnvelopeError {
+	/// Expiry time has passed.
+	Expired,
+	/// Envelope was sent in the future.
+	FromFuture,
+	/// Time to live is zero, larger than the expiry time or larger than `MAX_TTL`.
+	InvalidTtl,
+	/// Proof of work is below the required minimum.
+	InsufficientWork,
+}
+
+/// Whisper envelope. Wraps a possibly encrypted message with routing information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+	/// Time of expiry in seconds since the epoch.
+	pub expiry: u32,
+	/// Time to live in seconds.
+	pub ttl: u32,
+	/// Message topics.
+	pub topics: Vec<Topic>,
+	/// Message data.
+	pub data: Bytes,
+	/// Proof of work nonce.
+	pub nonce: u32,
+}
+
+impl Encodable for Envelope {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(5);
+		s.append(&self.expiry);
+		s.append(&self.ttl);
+		s.append(&self.topics);
+		s.append(&self.data);
+		s.append(&self.nonce);
+	}
+}
+
+impl Decodable for Envelope {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let d = decoder.as_rlp();
+		let res = Envelope {
+			expiry: try!(d.val_at(0)),
+			ttl: try!(d.val_at(1)),
+			topics: try!(d.val_at(2)),
+			data: try!(d.val_at(3)),
+			nonce: try!(d.val_at(4)),
+		};
+
+		Ok(res)
+	}
+}
+
+impl Envelope {
+	/// Create a new unsealed envelope expiring `ttl` seconds from now.
+	pub fn new(ttl: u32, topics: Vec<Topic>, data: Bytes) -> Envelope {
+		Envelope {
+			expiry: now().saturating_add(ttl),
+			ttl: ttl,
+			topics: topics,
+			data: data,
+			nonce: 0,
+		}
+	}
+
+	/// Envelope hash. Identifies the envelope on the network.
+	pub fn hash(&self) -> H256 {
+		encode(self).to_vec().sha3()
+	}
+
+	/// Time the envelope was sent at.
+	pub fn sent(&self) -> u32 {
+		self.expiry.saturating_sub(self.ttl)
+	}
+
+	/// Bloom of all envelope topics.
+	pub fn bloom(&self) -> TopicBloom {
+		topics_bloom(&self.topics)
+	}
+
+	/// Check if any of the envelope topics is in the bloom filter. Envelopes without topics always match.
+	pub fn matches_bloom(&self, bloom: &TopicBloom) -> bool {
+		self.topics.is_empty() || self.topics.iter().any(|t| bloom.contains(&topic_bloom(t)))
+	}
+
+	/// Proof of work as the number of leading zero bits of the work hash.
+	pub fn work(&self) -> u32 {
+		work(&self.rlp_without_nonce().sha3(), self.nonce)
+	}
+
+	/// Search for the best nonce for given number of milliseconds.
+	pub fn seal(&mut self, work_ms: u64) {
+		let hash = self.rlp_without_nonce().sha3();
+		let deadline = time::precise_time_ns().saturating_add(work_ms.saturating_mul(1_000_000));
+		let mut best = work(&hash, self.nonce);
+		let mut nonce = self.nonce;
+		loop {
+			// Check the clock every so often only
+			for _ in 0..1024 {
+				nonce = nonce.wrapping_add(1);
+				let w = work(&hash, nonce);
+				if w > best {
+					best = w;
+					self.nonce = nonce;
+				}
+			}
+			if time::precise_time_ns() >= deadline {
+				break;
+			}
+		}
+	}
+
+	/// Check that the envelope is alive at time `now` and proves at least `min_work`.
+	pub fn validate(&self, now: u32, min_work: u32) -> Result<(), EnvelopeError> {
+		if self.ttl == 0 || self.ttl > self.expiry || self.ttl > MAX_TTL {
+			return Err(EnvelopeError::InvalidTtl);
+		}
+		if self.expiry <= now {
+			return Err(EnvelopeError::Expired);
+		}
+		if self.sent() > now.saturating_add(MAX_CLOCK_DRIFT) {
+			return Err(EnvelopeError::FromFuture);
+		}
+		if self.work() < min_work {
+			return Err(EnvelopeError::InsufficientWork);
+		}
+		Ok(())
+	}
+
+	/// Pool priority. Work per byte of storage per second of lifetime.
+	pub fn priority(&self) -> f64 {
+		let size = encode(self).len() as f64;
+		2f64.powi(self.work() as i32) / (size * self.ttl as f64)
+	}
+
+	fn rlp_without_nonce(&self) -> Bytes {
+		let mut s = RlpStream::new_list(4);
+		s.append(&self.expiry);
+		s.append(&self.ttl);
+		s.append(&self.topics);
+		s.append(&self.data);
+		s.out()
+	}
+}
+
+fn work(hash: &H256, nonce: u32) -> u32 {
+	let mut d = [0u8; 64];
+	d[0..32].clone_from_slice(&hash[..]);
+	d[60] = (nonce >> 24) as u8;
+	d[61] = (nonce >> 16) as u8;
+	d[62] = (nonce >> 8) as u8;
+	d[63] = nonce as u8;
+	let mut zeros = 0;
+	for b in (&d[..]).sha3().iter() {
+		zeros += b.leading_zeros();
+		if *b != 0 {
+			break;
+		}
+	}
+	zeros
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use util::*;
+
+	#[test]
+	fn envelope_rlp_roundtrip() {
+		// given
+		let envelope = Envelope::new(50, vec![topic(b"test")], vec![1, 2, 3]);
+
+		// when
+		let decoded: Envelope = decode(&encode(&envelope));
+
+		// then
+		assert_eq!(decoded, envelope);
+		assert_eq!(decoded.hash(), envelope.hash());
+	}
+
+	#[test]
+	fn sealing_increases_work() {
+		// given
+		let mut envelope = Envelope::new(50, vec![topic(b"test")], vec![1, 2, 3]);
+		let initial = envelope.work();
+
+		// when
+		envelope.seal(20);
+
+		// then
+		assert!(envelope.work() >= initial);
+		assert!(envelope.work() >= 4);
+	}
+
+	#[test]
+	fn should_validate_expiry() {
+		let envelope = Envelope { expiry: 1000, ttl: 100, topics: vec![], data: vec![], nonce: 0 };
+		assert_eq!(envelope.validate(950, 0), Ok(()));
+		assert_eq!(envelope.validate(1000, 0), Err(EnvelopeError::Expired));
+		assert_eq!(envelope.validate(800, 0), Err(EnvelopeError::FromFuture));
+		assert_eq!(Envelope { ttl: 0, ..envelope.clone() }.validate(950, 0), Err(EnvelopeError::InvalidTtl));
+	}
+
+	#[test]
+	fn should_reject_too_long_ttl() {
+		let now = 1_000_000;
+		let envelope = Envelope { expiry: now + MAX_TTL, ttl: MAX_TTL, topics: vec![], data: vec![], nonce: 0 };
+		assert_eq!(envelope.validate(now, 0), Ok(()));
+		let envelope = Envelope { expiry: now + MAX_TTL + 1, ttl: MAX_TTL + 1, ..envelope };
+		assert_eq!(envelope.validate(now, 0), Err(EnvelopeError::InvalidTtl));
+		// expiry far ahead is either a long ttl or a send time in the future
+		let envelope = Envelope { expiry: now + 10 * MAX_TTL, ttl: MAX_TTL, ..envelope };
+		assert_eq!(envelope.validate(now, 0), Err(EnvelopeError::FromFuture));
+	}
+
+	#[test]
+	fn bloom_contains_topics() {
+		let t1 = topic(b"one");
+		let t2 = topic(b"two");
+		let envelope = Envelope::new(50, vec![t1.clone(), t2.clone()], vec![]);
+		let bloom = envelope.bloom();
+		assert!(bloom.contains(&topic_bloom(&t1)));
+		assert!(bloom.contains(&topic_bloom(&t2)));
+		assert!(!bloom.contains(&topic_bloom(&topic(b"three"))));
+		assert!(envelope.matches_bloom(&topic_bloom(&t1)));
+		assert!(!envelope.matches_bloom(&topic_bloom(&topic(b"three"))));
+	}
+
+	#[test]
+	fn should_not_overflow_expiry() {
+		let envelope = Envelope::new(u32::max_value(), vec![], vec![]);
+		assert_eq!(envelope.expiry, u32::max_value());
+		assert_eq!(envelope.validate(now(), 0), Err(EnvelopeError::InvalidTtl));
+		assert_eq!(Envelope { expiry: u32::max_value(), ttl: 1, ..envelope.clone() }.validate(u32::max_value() - 1, 0), Ok(()));
+	}
+}
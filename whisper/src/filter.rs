@@ -0,0 +1,68 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper message filters.
+
+use util::*;
+use envelope::{Envelope, Topic, TopicBloom, topics_bloom};
+
+/// Describes messages a client is interested in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+	/// All of these topics must be present in the envelope.
+	pub topics: Vec<Topic>,
+	/// Only accept messages addressed to this identity.
+	pub to: Option<Public>,
+	/// Only accept messages signed by this key.
+	pub from: Option<Public>,
+	/// Symmetric key to open messages with.
+	pub key: Option<H256>,
+}
+
+impl Filter {
+	/// Bloom of all filter topics.
+	pub fn bloom(&self) -> TopicBloom {
+		topics_bloom(&self.topics)
+	}
+
+	/// Check if the envelope topics match the filter.
+	pub fn matches(&self, envelope: &Envelope) -> bool {
+		envelope.bloom().contains(&self.bloom()) && self.topics.iter().all(|t| envelope.topics.contains(t))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use envelope::{Envelope, topic};
+
+	#[test]
+	fn filter_matches_all_topics() {
+		// given
+		let filter = Filter { topics: vec![topic(b"a"), topic(b"b")], to: None, from: None, key: None };
+
+		// then
+		assert!(filter.matches(&Envelope::new(10, vec![topic(b"b"), topic(b"c"), topic(b"a")], vec![])));
+		assert!(!filter.matches(&Envelope::new(10, vec![topic(b"a")], vec![])));
+		assert!(!filter.matches(&Envelope::new(10, vec![topic(b"c")], vec![])));
+	}
+
+	#[test]
+	fn empty_filter_matches_everything() {
+		let filter = Filter { topics: vec![], to: None, from: None, key: None };
+		assert!(filter.matches(&Envelope::new(10, vec![topic(b"a")], vec![])));
+	}
+}
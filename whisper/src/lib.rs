@@ -0,0 +1,55 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+#![warn(missing_docs)]
+#![cfg_attr(feature="dev", feature(plugin))]
+#![cfg_attr(feature="dev", plugin(clippy))]
+
+//! Whisper messaging protocol.
+//! Implements whisper protocol version 2 as specified here:
+//! https://github.com/ethereum/wiki/wiki/Whisper-Wire-Protocol
+//!
+//! Usage example:
+//!
+//! ```rust
+//! extern crate ethcore_util as util;
+//! extern crate ethcore_whisper as whisper;
+//! use util::network::{NetworkService, NetworkConfiguration};
+//! use whisper::{Whisper, WhisperConfig};
+//!
+//! fn main() {
+//! 	let service = NetworkService::<()>::new(NetworkConfiguration::new_local()).unwrap();
+//! 	service.start().unwrap();
+//! 	let whisper = Whisper::new(WhisperConfig::default());
+//! 	Whisper::register(&service, whisper.clone()).unwrap();
+//! }
+//! ```
+
+#[macro_use]
+extern crate log;
+extern crate ethcore_util as util;
+extern crate time;
+extern crate rand;
+
+mod envelope;
+mod message;
+mod filter;
+mod whisper;
+
+pub use envelope::{Envelope, EnvelopeError, Topic, TopicBloom, topic, MAX_TTL};
+pub use message::{Message, Encryption, Decryption};
+pub use filter::Filter;
+pub use whisper::{Whisper, WhisperConfig, Post, PostError, ReceivedMessage, PROTOCOL_VERSION, MAX_WORK_MS};
@@ -0,0 +1,214 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper message payload encoding, signing and encryption.
+//!
+//! Plain message layout is `flags (1) | signature (65, if flagged) | payload`.
+//! The signature covers `sha3(flags | payload)`.
+
+use util::*;
+use util::crypto::{ec, ecies, aes};
+use rand::{Rng, OsRng};
+
+/// Flag set when the message is signed.
+const SIGNATURE_FLAG: u8 = 0x80;
+const SIGNATURE_LEN: usize = 65;
+const IV_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+
+/// Key used to encrypt a message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Encryption {
+	/// Message is not encrypted.
+	None,
+	/// Message is encrypted to the public key of a recipient.
+	Asymmetric(Public),
+	/// Message is encrypted with a shared secret.
+	Symmetric(H256),
+}
+
+/// Key used to decrypt a message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decryption {
+	/// Message is not encrypted.
+	None,
+	/// Message is encrypted to the public key of this secret.
+	Asymmetric(Secret),
+	/// Message is encrypted with a shared secret.
+	Symmetric(H256),
+}
+
+/// Decoded whisper message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+	/// Message payload.
+	pub payload: Bytes,
+	/// Public key of the signer, if the message is signed.
+	pub from: Option<Public>,
+}
+
+impl Message {
+	/// Encode the message payload, optionally signing it with `signer` and encrypting it.
+	pub fn seal(payload: &[u8], signer: Option<&Secret>, encryption: &Encryption) -> Result<Bytes, CryptoError> {
+		let mut plain = Vec::with_capacity(1 + SIGNATURE_LEN + payload.len());
+		match signer {
+			Some(secret) => {
+				let signature = try!(ec::sign(secret, &signature_hash(SIGNATURE_FLAG, payload)));
+				plain.push(SIGNATURE_FLAG);
+				plain.extend_from_slice(&signature);
+			},
+			None => plain.push(0),
+		}
+		plain.extend_from_slice(payload);
+
+		match *encryption {
+			Encryption::None => Ok(plain),
+			Encryption::Asymmetric(ref public) => ecies::encrypt(public, &[], &plain),
+			Encryption::Symmetric(ref key) => symmetric_encrypt(key, &plain),
+		}
+	}
+
+	/// Decrypt and decode envelope data. Recovers the signer of signed messages.
+	pub fn open(data: &[u8], decryption: &Decryption) -> Result<Message, CryptoError> {
+		let plain = match *decryption {
+			Decryption::None => data.to_vec(),
+			Decryption::Asymmetric(ref secret) => try!(ecies::decrypt(secret, &[], data)),
+			Decryption::Symmetric(ref key) => try!(symmetric_decrypt(key, data)),
+		};
+		if plain.is_empty() {
+			return Err(CryptoError::InvalidMessage);
+		}
+
+		let flags = plain[0];
+		if flags & SIGNATURE_FLAG == 0 {
+			return Ok(Message {
+				payload: plain[1..].to_vec(),
+				from: None,
+			});
+		}
+		if plain.len() < 1 + SIGNATURE_LEN {
+			return Err(CryptoError::InvalidMessage);
+		}
+		let signature = Signature::from_slice(&plain[1..1 + SIGNATURE_LEN]);
+		let payload = &plain[1 + SIGNATURE_LEN..];
+		let from = try!(ec::recover(&signature, &signature_hash(flags, payload)));
+		Ok(Message {
+			payload: payload.to_vec(),
+			from: Some(from),
+		})
+	}
+}
+
+fn signature_hash(flags: u8, payload: &[u8]) -> H256 {
+	let mut data = Vec::with_capacity(1 + payload.len());
+	data.push(flags);
+	data.extend_from_slice(payload);
+	data.sha3()
+}
+
+/// Symmetric tag binding the cipher text to the key.
+fn tag(key: &H256, iv: &[u8], cipher: &[u8]) -> H256 {
+	let mut data = Vec::with_capacity(32 + iv.len() + cipher.len());
+	data.extend_from_slice(key);
+	data.extend_from_slice(iv);
+	data.extend_from_slice(cipher);
+	data.sha3()
+}
+
+/// AES-128-CTR with a random IV. Layout is `iv (16) | cipher | tag (32)`.
+fn symmetric_encrypt(key: &H256, plain: &[u8]) -> Result<Bytes, CryptoError> {
+	let mut iv = [0u8; IV_LEN];
+	let mut rng = try!(OsRng::new());
+	rng.fill_bytes(&mut iv);
+	let mut cipher = vec![0u8; plain.len()];
+	aes::encrypt(&key[0..16], &iv, plain, &mut cipher);
+
+	let mut out = Vec::with_capacity(IV_LEN + cipher.len() + TAG_LEN);
+	out.extend_from_slice(&iv);
+	out.extend_from_slice(&cipher);
+	out.extend_from_slice(&tag(key, &iv, &cipher));
+	Ok(out)
+}
+
+fn symmetric_decrypt(key: &H256, data: &[u8]) -> Result<Bytes, CryptoError> {
+	if data.len() < IV_LEN + TAG_LEN {
+		return Err(CryptoError::InvalidMessage);
+	}
+	let iv = &data[0..IV_LEN];
+	let cipher = &data[IV_LEN..data.len() - TAG_LEN];
+	if &data[data.len() - TAG_LEN..] != &tag(key, iv, cipher)[..] {
+		return Err(CryptoError::InvalidMessage);
+	}
+	let mut plain = vec![0u8; cipher.len()];
+	aes::decrypt(&key[0..16], iv, cipher, &mut plain);
+	Ok(plain)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use util::*;
+
+	#[test]
+	fn plain_message_roundtrip() {
+		let sealed = Message::seal(b"hello", None, &Encryption::None).unwrap();
+		let message = Message::open(&sealed, &Decryption::None).unwrap();
+		assert_eq!(message, Message { payload: b"hello".to_vec(), from: None });
+	}
+
+	#[test]
+	fn signed_message_recovers_signer() {
+		// given
+		let signer = KeyPair::create().unwrap();
+
+		// when
+		let sealed = Message::seal(b"hello", Some(signer.secret()), &Encryption::None).unwrap();
+		let message = Message::open(&sealed, &Decryption::None).unwrap();
+
+		// then
+		assert_eq!(message.payload, b"hello".to_vec());
+		assert_eq!(message.from, Some(signer.public().clone()));
+	}
+
+	#[test]
+	fn asymmetric_message_roundtrip() {
+		// given
+		let recipient = KeyPair::create().unwrap();
+		let other = KeyPair::create().unwrap();
+
+		// when
+		let sealed = Message::seal(b"secret", None, &Encryption::Asymmetric(recipient.public().clone())).unwrap();
+
+		// then
+		let message = Message::open(&sealed, &Decryption::Asymmetric(recipient.secret().clone())).unwrap();
+		assert_eq!(message.payload, b"secret".to_vec());
+		assert!(Message::open(&sealed, &Decryption::Asymmetric(other.secret().clone())).is_err());
+	}
+
+	#[test]
+	fn symmetric_message_roundtrip() {
+		// given
+		let key = H256::random();
+
+		// when
+		let sealed = Message::seal(b"secret", None, &Encryption::Symmetric(key.clone())).unwrap();
+
+		// then
+		let message = Message::open(&sealed, &Decryption::Symmetric(key)).unwrap();
+		assert_eq!(message.payload, b"secret".to_vec());
+		assert!(Message::open(&sealed, &Decryption::Symmetric(H256::random())).is_err());
+	}
+}
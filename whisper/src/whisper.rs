@@ -0,0 +1,655 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper protocol handler and local node state.
+
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use util::*;
+use envelope::{now, Envelope, EnvelopeError, Topic, TopicBloom};
+use message::{Message, Encryption, Decryption};
+use filter::Filter;
+
+/// Supported protocol version.
+pub const PROTOCOL_VERSION: u8 = 2;
+/// Number of packet IDs used by the protocol.
+pub const PACKET_COUNT: u8 = 3;
+
+const STATUS_PACKET: u8 = 0x00;
+const MESSAGES_PACKET: u8 = 0x01;
+const TOPIC_FILTER_PACKET: u8 = 0x02;
+
+const MAINTAIN_TIMER: TimerToken = 0;
+const MAINTAIN_INTERVAL_MS: u64 = 1000;
+/// Soft limit on the size of a single messages packet.
+const MAX_PACKET_SIZE: usize = 1024 * 1024;
+/// Maximum time to spend on proof of work for a posted message in milliseconds.
+pub const MAX_WORK_MS: u64 = 2000;
+/// Maximum number of envelope hashes remembered to drop duplicates. The oldest ones are forgotten first.
+const MAX_SEEN: usize = 100_000;
+/// Maximum number of messages kept for a filter between polls. The oldest ones are dropped first.
+const MAX_FILTER_PENDING: usize = 1024;
+
+/// Whisper configuration.
+#[derive(Debug, Clone)]
+pub struct WhisperConfig {
+	/// Maximum total size of envelopes kept in the pool, in bytes.
+	pub pool_size: usize,
+	/// Minimal proof of work (leading zero bits) required for an envelope to be accepted.
+	pub min_work: u32,
+}
+
+impl Default for WhisperConfig {
+	fn default() -> WhisperConfig {
+		WhisperConfig {
+			pool_size: 10 * 1024 * 1024,
+			min_work: 0,
+		}
+	}
+}
+
+/// Message to be posted.
+#[derive(Debug, Clone)]
+pub struct Post {
+	/// Identity to sign the message with.
+	pub from: Option<Public>,
+	/// Message encryption.
+	pub encryption: Encryption,
+	/// Message topics.
+	pub topics: Vec<Topic>,
+	/// Message payload.
+	pub payload: Bytes,
+	/// Time to live in seconds.
+	pub ttl: u32,
+	/// Time to spend on proof of work in milliseconds.
+	pub work_ms: u64,
+}
+
+/// Message received by a filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedMessage {
+	/// Envelope hash.
+	pub hash: H256,
+	/// Signer of the message.
+	pub from: Option<Public>,
+	/// Identity the message was decrypted with.
+	pub to: Option<Public>,
+	/// Time of expiry.
+	pub expiry: u32,
+	/// Time to live.
+	pub ttl: u32,
+	/// Time the message was sent at.
+	pub sent: u32,
+	/// Message topics.
+	pub topics: Vec<Topic>,
+	/// Message payload.
+	pub payload: Bytes,
+	/// Proof of work of the envelope.
+	pub work: u32,
+}
+
+/// Error posting a message.
+#[derive(Debug)]
+pub enum PostError {
+	/// Signing identity is not known.
+	UnknownIdentity,
+	/// Failed to sign or encrypt the message.
+	Crypto(CryptoError),
+	/// Sealed envelope is invalid.
+	Envelope(EnvelopeError),
+}
+
+impl From<CryptoError> for PostError {
+	fn from(err: CryptoError) -> PostError {
+		PostError::Crypto(err)
+	}
+}
+
+struct PoolEntry {
+	envelope: Envelope,
+	size: usize,
+	priority: f64,
+}
+
+#[derive(Default)]
+struct Pool {
+	entries: HashMap<H256, PoolEntry>,
+	size: usize,
+	/// Expiry of envelopes imported so far, including evicted ones. Bounded by `MAX_SEEN`.
+	seen: HashMap<H256, u32>,
+	/// Order in which envelopes were first seen.
+	seen_order: VecDeque<H256>,
+}
+
+impl Pool {
+	/// Insert an envelope, evicting the lowest priority envelopes if the pool is full.
+	/// Returns false if the envelope did not make it into the pool.
+	fn insert(&mut self, hash: H256, envelope: Envelope, limit: usize) -> bool {
+		self.remember(hash.clone(), envelope.expiry, MAX_SEEN);
+		let entry = PoolEntry {
+			size: encode(&envelope).len(),
+			priority: envelope.priority(),
+			envelope: envelope,
+		};
+		self.size += entry.size;
+		self.entries.insert(hash.clone(), entry);
+		while self.size > limit {
+			let lowest = self.entries.iter()
+				.fold(None, |lowest: Option<(&H256, f64)>, (h, e)| match lowest {
+					Some((_, p)) if p <= e.priority => lowest,
+					_ => Some((h, e.priority)),
+				})
+				.map(|(h, _)| h.clone())
+				.expect("pool size is positive, so there are entries; qed");
+			self.remove(&lowest);
+		}
+		self.entries.contains_key(&hash)
+	}
+
+	fn remove(&mut self, hash: &H256) {
+		if let Some(entry) = self.entries.remove(hash) {
+			self.size -= entry.size;
+		}
+	}
+
+	/// Remember an imported envelope, forgetting the oldest ones once there are more than `limit`.
+	fn remember(&mut self, hash: H256, expiry: u32, limit: usize) {
+		if self.seen.insert(hash.clone(), expiry).is_none() {
+			self.seen_order.push_back(hash);
+		}
+		while self.seen.len() > limit {
+			match self.seen_order.pop_front() {
+				Some(oldest) => { self.seen.remove(&oldest); },
+				None => break,
+			}
+		}
+	}
+
+	/// Check if the envelope was imported before.
+	fn contains(&self, hash: &H256) -> bool {
+		self.seen.contains_key(hash) || self.entries.contains_key(hash)
+	}
+
+	fn expire(&mut self, now: u32) {
+		let expired: Vec<H256> = self.entries.iter().filter(|&(_, e)| e.envelope.expiry <= now).map(|(h, _)| h.clone()).collect();
+		for hash in &expired {
+			self.remove(hash);
+		}
+		self.seen = self.seen.drain().filter(|&(_, expiry)| expiry > now).collect();
+		let seen = &self.seen;
+		self.seen_order.retain(|h| seen.contains_key(h));
+	}
+}
+
+#[derive(Default)]
+struct PeerInfo {
+	/// Envelopes the peer is known to have.
+	known: HashSet<H256>,
+	/// Topic bloom filter announced by the peer.
+	bloom: Option<TopicBloom>,
+	/// Our topic bloom filter last sent to the peer.
+	sent_bloom: Option<TopicBloom>,
+}
+
+struct InstalledFilter {
+	filter: Filter,
+	pending: Vec<ReceivedMessage>,
+}
+
+/// Whisper protocol handler. Keeps the envelope pool, identities and message filters.
+pub struct Whisper {
+	config: WhisperConfig,
+	pool: RwLock<Pool>,
+	peers: RwLock<HashMap<PeerId, PeerInfo>>,
+	identities: RwLock<HashMap<Public, KeyPair>>,
+	filters: RwLock<HashMap<usize, InstalledFilter>>,
+	next_filter_id: AtomicUsize,
+}
+
+impl Whisper {
+	/// Create a new handler.
+	pub fn new(config: WhisperConfig) -> Arc<Whisper> {
+		Arc::new(Whisper {
+			config: config,
+			pool: RwLock::new(Pool::default()),
+			peers: RwLock::new(HashMap::new()),
+			identities: RwLock::new(HashMap::new()),
+			filters: RwLock::new(HashMap::new()),
+			next_filter_id: AtomicUsize::new(0),
+		})
+	}
+
+	/// Register protocol with the network service.
+	pub fn register<M>(service: &NetworkService<M>, whisper: Arc<Whisper>) -> Result<(), NetworkError> where M: Send + Sync + Clone + 'static {
+		service.register_protocol(whisper, "shh", &[(PROTOCOL_VERSION, PACKET_COUNT)])
+	}
+
+	/// Generate a new identity key pair. Returns its public key.
+	pub fn new_identity(&self) -> Result<Public, CryptoError> {
+		let keypair = try!(KeyPair::create());
+		let public = keypair.public().clone();
+		self.identities.write().unwrap().insert(public.clone(), keypair);
+		Ok(public)
+	}
+
+	/// Check if the secret key for given identity is known.
+	pub fn has_identity(&self, identity: &Public) -> bool {
+		self.identities.read().unwrap().contains_key(identity)
+	}
+
+	/// Seal a message into an envelope and queue it for propagation. Returns the envelope hash.
+	pub fn post(&self, post: Post) -> Result<H256, PostError> {
+		let data = {
+			let identities = self.identities.read().unwrap();
+			let signer = match post.from {
+				Some(ref from) => Some(try!(identities.get(from).ok_or(PostError::UnknownIdentity)).secret()),
+				None => None,
+			};
+			try!(Message::seal(&post.payload, signer, &post.encryption))
+		};
+		let mut envelope = Envelope::new(post.ttl, post.topics, data);
+		envelope.seal(cmp::min(post.work_ms, MAX_WORK_MS));
+		let hash = envelope.hash();
+		try!(envelope.validate(now(), 0).map_err(PostError::Envelope));
+		self.import(envelope);
+		Ok(hash)
+	}
+
+	/// Install a new message filter. Returns its id.
+	pub fn add_filter(&self, filter: Filter) -> usize {
+		let id = self.next_filter_id.fetch_add(1, AtomicOrdering::Relaxed);
+		self.filters.write().unwrap().insert(id, InstalledFilter {
+			filter: filter,
+			pending: Vec::new(),
+		});
+		id
+	}
+
+	/// Uninstall a filter. Returns false if there was no such filter.
+	pub fn remove_filter(&self, id: usize) -> bool {
+		self.filters.write().unwrap().remove(&id).is_some()
+	}
+
+	/// Messages received by the filter since the last poll.
+	pub fn poll_filter(&self, id: usize) -> Option<Vec<ReceivedMessage>> {
+		self.filters.write().unwrap().get_mut(&id).map(|f| mem::replace(&mut f.pending, Vec::new()))
+	}
+
+	/// All messages in the pool matching the filter.
+	pub fn filter_messages(&self, id: usize) -> Option<Vec<ReceivedMessage>> {
+		let filter = match self.filters.read().unwrap().get(&id) {
+			Some(f) => f.filter.clone(),
+			None => return None,
+		};
+		let identities = self.identities.read().unwrap();
+		let pool = self.pool.read().unwrap();
+		Some(pool.entries.iter().filter_map(|(hash, e)| open(&filter, &identities, hash, &e.envelope)).collect())
+	}
+
+	/// Add an envelope to the pool and deliver it to matching filters.
+	/// Returns false if the envelope was seen before or got evicted immediately.
+	fn import(&self, envelope: Envelope) -> bool {
+		let hash = envelope.hash();
+		{
+			let mut pool = self.pool.write().unwrap();
+			if pool.contains(&hash) || !pool.insert(hash.clone(), envelope.clone(), self.config.pool_size) {
+				return false;
+			}
+		}
+		let identities = self.identities.read().unwrap();
+		for f in self.filters.write().unwrap().values_mut() {
+			if let Some(message) = open(&f.filter, &identities, &hash, &envelope) {
+				if f.pending.len() >= MAX_FILTER_PENDING {
+					f.pending.remove(0);
+				}
+				f.pending.push(message);
+			}
+		}
+		true
+	}
+
+	/// Bloom of topics we are interested in. Matches everything if any filter accepts all topics.
+	fn bloom(&self) -> TopicBloom {
+		let filters = self.filters.read().unwrap();
+		if filters.is_empty() || filters.values().any(|f| f.filter.topics.is_empty()) {
+			return TopicBloom::from_slice(&[0xffu8; 64]);
+		}
+		filters.values().fold(TopicBloom::new(), |b, f| b | f.filter.bloom())
+	}
+
+	fn on_status<M>(&self, io: &NetworkContext<M>, peer: PeerId, r: &UntrustedRlp) -> Result<(), DecoderError> where M: Send + Sync + Clone {
+		let version: u8 = try!(r.val_at(0));
+		if version != PROTOCOL_VERSION {
+			trace!(target: "whisper", "Peer {} protocol version mismatch: {}", peer, version);
+			io.disconnect_peer(peer);
+		}
+		Ok(())
+	}
+
+	fn on_messages(&self, peer: PeerId, r: &UntrustedRlp) -> Result<(), DecoderError> {
+		let time = now();
+		for item in r.iter() {
+			let envelope: Envelope = try!(item.as_val());
+			let hash = envelope.hash();
+			if let Some(info) = self.peers.write().unwrap().get_mut(&peer) {
+				info.known.insert(hash.clone());
+			}
+			if let Err(e) = envelope.validate(time, self.config.min_work) {
+				trace!(target: "whisper", "Peer {} sent invalid envelope {}: {:?}", peer, hash, e);
+				continue;
+			}
+			self.import(envelope);
+		}
+		Ok(())
+	}
+
+	fn on_topic_filter(&self, peer: PeerId, r: &UntrustedRlp) -> Result<(), DecoderError> {
+		let bloom: TopicBloom = try!(r.val_at(0));
+		if let Some(info) = self.peers.write().unwrap().get_mut(&peer) {
+			info.bloom = Some(bloom);
+		}
+		Ok(())
+	}
+
+	/// Drop expired envelopes, announce our topic bloom and send new envelopes to peers.
+	fn maintain<M>(&self, io: &NetworkContext<M>) where M: Send + Sync + Clone {
+		let bloom = self.bloom();
+		let mut pool = self.pool.write().unwrap();
+		pool.expire(now());
+		let mut peers = self.peers.write().unwrap();
+		for (peer, info) in peers.iter_mut() {
+			if info.sent_bloom.as_ref() != Some(&bloom) {
+				let mut s = RlpStream::new_list(1);
+				s.append(&bloom);
+				match io.send(*peer, TOPIC_FILTER_PACKET, s.out()) {
+					Ok(_) => info.sent_bloom = Some(bloom.clone()),
+					Err(e) => debug!(target: "whisper", "Error sending topic filter to {}: {:?}", peer, e),
+				}
+			}
+			info.known = info.known.drain().filter(|h| pool.seen.contains_key(h)).collect();
+			let mut size = 0;
+			let mut hashes = Vec::new();
+			for (hash, entry) in &pool.entries {
+				if size >= MAX_PACKET_SIZE {
+					break;
+				}
+				if info.known.contains(hash) || !info.bloom.as_ref().map_or(true, |b| entry.envelope.matches_bloom(b)) {
+					continue;
+				}
+				size += entry.size;
+				hashes.push(hash.clone());
+			}
+			if hashes.is_empty() {
+				continue;
+			}
+			let mut s = RlpStream::new_list(hashes.len());
+			for hash in &hashes {
+				s.append(&pool.entries[hash].envelope);
+			}
+			if let Err(e) = io.send(*peer, MESSAGES_PACKET, s.out()) {
+				debug!(target: "whisper", "Error sending messages to {}: {:?}", peer, e);
+				continue;
+			}
+			info.known.extend(hashes);
+		}
+	}
+}
+
+/// Open the envelope if it matches the filter.
+fn open(filter: &Filter, identities: &HashMap<Public, KeyPair>, hash: &H256, envelope: &Envelope) -> Option<ReceivedMessage> {
+	if !filter.matches(envelope) {
+		return None;
+	}
+	let decryption = match (filter.key.as_ref(), filter.to.as_ref()) {
+		(Some(key), _) => Decryption::Symmetric(key.clone()),
+		(None, Some(to)) => match identities.get(to) {
+			Some(keypair) => Decryption::Asymmetric(keypair.secret().clone()),
+			None => return None,
+		},
+		(None, None) => Decryption::None,
+	};
+	let message = match Message::open(&envelope.data, &decryption) {
+		Ok(message) => message,
+		Err(_) => return None,
+	};
+	if filter.from.is_some() && filter.from != message.from {
+		return None;
+	}
+	Some(ReceivedMessage {
+		hash: hash.clone(),
+		from: message.from,
+		to: filter.to.clone(),
+		expiry: envelope.expiry,
+		ttl: envelope.ttl,
+		sent: envelope.sent(),
+		topics: envelope.topics.clone(),
+		payload: message.payload,
+		work: envelope.work(),
+	})
+}
+
+impl<M> NetworkProtocolHandler<M> for Whisper where M: Send + Sync + Clone {
+	fn initialize(&self, io: &NetworkContext<M>) {
+		io.register_timer(MAINTAIN_TIMER, MAINTAIN_INTERVAL_MS).expect("Error registering whisper timer");
+	}
+
+	fn read(&self, io: &NetworkContext<M>, peer: &PeerId, packet_id: u8, data: &[u8]) {
+		let rlp = UntrustedRlp::new(data);
+		let result = match packet_id {
+			STATUS_PACKET => self.on_status(io, *peer, &rlp),
+			MESSAGES_PACKET => self.on_messages(*peer, &rlp),
+			TOPIC_FILTER_PACKET => self.on_topic_filter(*peer, &rlp),
+			_ => {
+				debug!(target: "whisper", "Unknown packet {}", packet_id);
+				Ok(())
+			}
+		};
+		if let Err(e) = result {
+			debug!(target: "whisper", "{} -> Malformed packet {}: {:?}", peer, packet_id, e);
+			io.report_peer(*peer, ReputationEvent::InvalidPacket);
+			io.disconnect_peer(*peer);
+		}
+	}
+
+	fn connected(&self, io: &NetworkContext<M>, peer: &PeerId) {
+		trace!(target: "whisper", "Connected {}", peer);
+		self.peers.write().unwrap().insert(*peer, PeerInfo::default());
+		let mut s = RlpStream::new_list(1);
+		s.append(&PROTOCOL_VERSION);
+		if let Err(e) = io.send(*peer, STATUS_PACKET, s.out()) {
+			debug!(target: "whisper", "Error sending status to {}: {:?}", peer, e);
+		}
+	}
+
+	fn disconnected(&self, _io: &NetworkContext<M>, peer: &PeerId) {
+		trace!(target: "whisper", "Disconnected {}", peer);
+		self.peers.write().unwrap().remove(peer);
+	}
+
+	fn timeout(&self, io: &NetworkContext<M>, _timer: TimerToken) {
+		self.maintain(io);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::{Pool, MAX_FILTER_PENDING};
+	use util::*;
+	use envelope::{Envelope, Topic, TopicBloom, topic, topic_bloom};
+	use message::{Message, Encryption};
+	use filter::Filter;
+
+	fn post(whisper: &Whisper, topics: Vec<Topic>, from: Option<Public>, encryption: Encryption) -> H256 {
+		whisper.post(Post {
+			from: from,
+			encryption: encryption,
+			topics: topics,
+			payload: b"hello".to_vec(),
+			ttl: 100,
+			work_ms: 1,
+		}).unwrap()
+	}
+
+	fn filter(topics: Vec<Topic>) -> Filter {
+		Filter { topics: topics, to: None, from: None, key: None }
+	}
+
+	#[test]
+	fn should_deliver_posted_message_to_filter() {
+		// given
+		let whisper = Whisper::new(WhisperConfig::default());
+		let id = whisper.add_filter(filter(vec![topic(b"test")]));
+		let other = whisper.add_filter(filter(vec![topic(b"other")]));
+
+		// when
+		let hash = post(&whisper, vec![topic(b"test")], None, Encryption::None);
+
+		// then
+		let messages = whisper.poll_filter(id).unwrap();
+		assert_eq!(messages.len(), 1);
+		assert_eq!(messages[0].hash, hash);
+		assert_eq!(messages[0].payload, b"hello".to_vec());
+		assert!(whisper.poll_filter(id).unwrap().is_empty());
+		assert!(whisper.poll_filter(other).unwrap().is_empty());
+		assert_eq!(whisper.filter_messages(id).unwrap().len(), 1);
+	}
+
+	#[test]
+	fn should_decrypt_messages_to_identity() {
+		// given
+		let whisper = Whisper::new(WhisperConfig::default());
+		let sender = whisper.new_identity().unwrap();
+		let recipient = whisper.new_identity().unwrap();
+		let id = whisper.add_filter(Filter { topics: vec![], to: Some(recipient.clone()), from: Some(sender.clone()), key: None });
+
+		// when
+		post(&whisper, vec![topic(b"test")], Some(sender.clone()), Encryption::Asymmetric(recipient.clone()));
+
+		// then
+		let messages = whisper.poll_filter(id).unwrap();
+		assert_eq!(messages.len(), 1);
+		assert_eq!(messages[0].from, Some(sender));
+		assert_eq!(messages[0].to, Some(recipient));
+		assert_eq!(messages[0].payload, b"hello".to_vec());
+	}
+
+	#[test]
+	fn should_reject_unknown_identity() {
+		let whisper = Whisper::new(WhisperConfig::default());
+		let result = whisper.post(Post {
+			from: Some(Public::random()),
+			encryption: Encryption::None,
+			topics: vec![],
+			payload: vec![],
+			ttl: 100,
+			work_ms: 1,
+		});
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn should_evict_lowest_priority_envelopes() {
+		// given
+		let whisper = Whisper::new(WhisperConfig { pool_size: 100, min_work: 0 });
+		let envelope = Envelope::new(100, vec![topic(b"test")], vec![0u8; 40]);
+		let mut short_lived = Envelope { ttl: 10, ..envelope.clone() };
+		short_lived.seal(1);
+
+		// when
+		assert!(whisper.import(envelope.clone()));
+		assert!(whisper.import(short_lived.clone()));
+
+		// then
+		let pool = whisper.pool.read().unwrap();
+		assert_eq!(pool.entries.len(), 1);
+		assert!(pool.entries.contains_key(&short_lived.hash()));
+	}
+
+	#[test]
+	fn should_not_deliver_evicted_envelopes() {
+		// given
+		let whisper = Whisper::new(WhisperConfig { pool_size: 100, min_work: 0 });
+		let id = whisper.add_filter(filter(vec![topic(b"test")]));
+		let envelope = Envelope::new(100, vec![topic(b"test")], Message::seal(&[0u8; 40], None, &Encryption::None).unwrap());
+		let mut short_lived = Envelope { ttl: 10, ..envelope.clone() };
+		short_lived.seal(1);
+		assert!(whisper.import(short_lived.clone()));
+		assert_eq!(whisper.poll_filter(id).unwrap().len(), 1);
+
+		// when
+		let imported = whisper.import(envelope.clone());
+		let reimported = whisper.import(envelope.clone());
+
+		// then
+		assert!(!imported);
+		assert!(!reimported);
+		assert!(whisper.poll_filter(id).unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_forget_oldest_seen_envelopes() {
+		// given
+		let mut pool = Pool::default();
+		let hashes: Vec<H256> = (0..4).map(|_| H256::random()).collect();
+
+		// when
+		for hash in &hashes {
+			pool.remember(hash.clone(), 100, 3);
+		}
+		pool.remember(hashes[3].clone(), 100, 3);
+
+		// then
+		assert_eq!(pool.seen.len(), 3);
+		assert!(!pool.contains(&hashes[0]));
+		assert!(hashes[1..].iter().all(|h| pool.contains(h)));
+		pool.expire(100);
+		assert!(pool.seen.is_empty());
+		assert!(pool.seen_order.is_empty());
+	}
+
+	#[test]
+	fn should_limit_messages_pending_for_filter() {
+		// given
+		let whisper = Whisper::new(WhisperConfig::default());
+		let id = whisper.add_filter(filter(vec![]));
+
+		// when
+		for i in 0..(MAX_FILTER_PENDING + 1) {
+			let envelope = Envelope::new(100, vec![], Message::seal(&[i as u8, (i >> 8) as u8], None, &Encryption::None).unwrap());
+			assert!(whisper.import(envelope));
+		}
+
+		// then
+		let messages = whisper.poll_filter(id).unwrap();
+		assert_eq!(messages.len(), MAX_FILTER_PENDING);
+		assert_eq!(messages[0].payload, vec![1u8, 0]);
+	}
+
+	#[test]
+	fn should_announce_filter_topics() {
+		let whisper = Whisper::new(WhisperConfig::default());
+		let all = TopicBloom::from_slice(&[0xffu8; 64]);
+		assert_eq!(whisper.bloom(), all);
+
+		let id = whisper.add_filter(filter(vec![topic(b"test")]));
+		assert_eq!(whisper.bloom(), topic_bloom(&topic(b"test")));
+
+		whisper.add_filter(filter(vec![]));
+		assert_eq!(whisper.bloom(), all);
+		whisper.remove_filter(id);
+		assert_eq!(whisper.bloom(), all);
+	}
+}